@@ -722,3 +722,27 @@ pub fn canonicalize_s(p: impl AsRef<Path>) -> PathBuf {
 pub async fn exists(p: impl AsRef<Path>) -> bool {
     tokio::fs::try_exists(p).await.is_ok_and(|n| n)
 }
+
+/// Returns the SHA-1 hash of the given bytes,
+/// as a lowercase hex string.
+#[must_use]
+pub fn get_sha1(bytes: &[u8]) -> String {
+    use sha1::Digest;
+    format!("{:x}", sha1::Sha1::digest(bytes))
+}
+
+/// Returns the SHA-256 hash of the given bytes,
+/// as a lowercase hex string.
+#[must_use]
+pub fn get_sha256(bytes: &[u8]) -> String {
+    use sha2::Digest;
+    format!("{:x}", sha2::Sha256::digest(bytes))
+}
+
+/// Returns the SHA-512 hash of the given bytes,
+/// as a lowercase hex string.
+#[must_use]
+pub fn get_sha512(bytes: &[u8]) -> String {
+    use sha2::Digest;
+    format!("{:x}", sha2::Sha512::digest(bytes))
+}
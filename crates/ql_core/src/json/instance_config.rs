@@ -90,6 +90,33 @@ pub struct InstanceConfigJson {
     /// An override for the main class when launching the game.
     /// Mainly only used for debugging purposes.
     pub main_class_override: Option<String>,
+    /// The Modrinth/CurseForge modpack this instance
+    /// was installed from (if any). Used to check for
+    /// and apply modpack updates.
+    pub modpack: Option<ModpackInfo>,
+    /// A built-in set of JVM tuning flags (garbage collector, etc.)
+    /// applied before the custom Java arguments.
+    /// See [`JvmPreset`] for the options.
+    ///
+    /// **Default: `None`** (only custom arguments)
+    pub jvm_preset: Option<JvmPreset>,
+    /// Pick the allocated memory automatically, based on
+    /// the system's RAM and the number of installed mods,
+    /// instead of using `ram_in_mb`.
+    ///
+    /// **Default: `false`**
+    pub auto_ram: Option<bool>,
+    /// The account to select when this instance is selected,
+    /// as shown in the launcher's account list
+    /// (eg: `Steve`, `Steve (elyby)`, `Steve (offline)`).
+    ///
+    /// **Default: `None`** (keep the current account)
+    pub account: Option<String>,
+    /// Joins a server or opens a world right after
+    /// the game starts, instead of the title screen.
+    ///
+    /// **Default: `None`**
+    pub quick_play: Option<QuickPlay>,
 }
 
 impl InstanceConfigJson {
@@ -289,11 +316,51 @@ pub struct GlobalSettings {
     pub pre_launch_prefix: Option<Vec<String>>,
 }
 
+/// Info about the modpack an instance was installed from.
+///
+/// The list of files installed by the pack is stored separately,
+/// in `modpack_files.json` (managed by `ql_mod_manager`).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ModpackInfo {
+    /// Project ID in the mod index format
+    /// (`CF:` prefix for CurseForge, see [`crate::ModId::get_index_str`])
+    pub project_id: String,
+    pub name: String,
+    pub installed_version: String,
+    /// RFC 3339 date of the installed version,
+    /// compared against the latest version when checking for updates
+    pub version_release_time: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VersionInfo {
     pub is_special_lwjgl3: bool,
 }
 
+/// Where to go right after the game starts,
+/// instead of the title screen.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum QuickPlay {
+    /// A multiplayer server, as `host` or `host:port`.
+    #[serde(rename = "server")]
+    Server(String),
+    /// A singleplayer world, by its folder name.
+    ///
+    /// Only supported in 1.20 (23w14a) and above.
+    #[serde(rename = "world")]
+    World(String),
+}
+
+impl QuickPlay {
+    /// The server address or world name
+    #[must_use]
+    pub fn target(&self) -> &str {
+        match self {
+            QuickPlay::Server(n) | QuickPlay::World(n) => n,
+        }
+    }
+}
+
 /// Defines how instance pre-launch prefix commands should interact with global pre-launch prefix commands
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub enum PreLaunchPrefixMode {
@@ -342,6 +409,74 @@ impl std::fmt::Display for PreLaunchPrefixMode {
     }
 }
 
+/// A built-in set of JVM tuning flags.
+///
+/// Flags that the instance's Java version doesn't
+/// support are left out when launching, so presets
+/// are safe to use on both old and new Java.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum JvmPreset {
+    /// Aikar's flags, the recommended G1 tuning for servers
+    #[serde(rename = "aikar")]
+    Aikar,
+    /// G1 garbage collector, tuned for the client
+    /// (similar to the official launcher)
+    #[serde(rename = "g1")]
+    G1,
+    /// Z garbage collector: very short pauses, but more
+    /// memory and CPU usage. Needs Java 15+
+    #[serde(rename = "zgc")]
+    Zgc,
+    /// Shenandoah garbage collector: short pauses.
+    /// Needs Java 12+, not available on Oracle Java
+    #[serde(rename = "shenandoah")]
+    Shenandoah,
+    /// Keeps memory usage down on low-end computers
+    #[serde(rename = "low_memory")]
+    LowMemory,
+    /// Only use the custom Java arguments
+    #[serde(rename = "none")]
+    #[default]
+    #[serde(other)]
+    None,
+}
+
+impl JvmPreset {
+    pub const ALL: &'static [Self] = &[
+        Self::None,
+        Self::Aikar,
+        Self::G1,
+        Self::Zgc,
+        Self::Shenandoah,
+        Self::LowMemory,
+    ];
+
+    #[must_use]
+    pub const fn get_description(self) -> &'static str {
+        match self {
+            JvmPreset::None => "Only use the Java arguments below",
+            JvmPreset::Aikar => "Recommended for servers (G1, tuned by Aikar)",
+            JvmPreset::G1 => "Balanced, good for most clients",
+            JvmPreset::Zgc => "Shortest lag spikes, needs more memory (Java 15+)",
+            JvmPreset::Shenandoah => "Short lag spikes (Java 12+, not Oracle Java)",
+            JvmPreset::LowMemory => "For computers with little RAM",
+        }
+    }
+}
+
+impl std::fmt::Display for JvmPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JvmPreset::None => write!(f, "None"),
+            JvmPreset::Aikar => write!(f, "Aikar's flags"),
+            JvmPreset::G1 => write!(f, "G1GC"),
+            JvmPreset::Zgc => write!(f, "ZGC"),
+            JvmPreset::Shenandoah => write!(f, "Shenandoah"),
+            JvmPreset::LowMemory => write!(f, "Low memory"),
+        }
+    }
+}
+
 /// Configuration for using a custom Minecraft JAR file
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
 pub struct CustomJarConfig {
@@ -1,16 +1,22 @@
 use std::sync::LazyLock;
 
-use crate::{err, file_utils, IntoJsonError, JsonDownloadError};
+use crate::{
+    err, file_utils, IntoIoError, IntoJsonError, JsonDownloadError, JsonFileError, LAUNCHER_DIR,
+};
 use cfg_if::cfg_if;
 use chrono::DateTime;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 static MANIFEST: LazyLock<tokio::sync::RwLock<Option<Manifest>>> =
     LazyLock::new(|| tokio::sync::RwLock::new(None));
 
+/// A copy of the last downloaded manifest,
+/// in `QuantumLauncher/downloads/`
+const CACHE_FILE: &str = "version_manifest.json";
+
 /// An official Minecraft version manifest
 /// (list of all versions and their download links)
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Manifest {
     latest: Latest,
     pub versions: Vec<Version>,
@@ -39,10 +45,34 @@ impl Manifest {
             return Ok(m);
         }
         let manifest = Self::load().await?;
+        if let Err(err) = manifest.save_to_cache().await {
+            err!("Couldn't cache version manifest: {err}");
+        }
         *MANIFEST.write().await = Some(manifest.clone());
         Ok(manifest)
     }
 
+    /// Loads the manifest saved by the last [`Manifest::download`]
+    /// (from any run of the launcher), without any network access.
+    ///
+    /// Meant for places where speed matters more than
+    /// being up to date, like shell completions.
+    /// Returns `None` if it was never downloaded.
+    pub async fn load_cached() -> Option<Manifest> {
+        let path = LAUNCHER_DIR.join("downloads").join(CACHE_FILE);
+        let json = tokio::fs::read_to_string(&path).await.ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    async fn save_to_cache(&self) -> Result<(), JsonFileError> {
+        let dir = LAUNCHER_DIR.join("downloads");
+        tokio::fs::create_dir_all(&dir).await.path(&dir)?;
+        let path = dir.join(CACHE_FILE);
+        let json = serde_json::to_string(self).json_to()?;
+        tokio::fs::write(&path, json).await.path(path)?;
+        Ok(())
+    }
+
     #[allow(unused)]
     async fn load() -> Result<Manifest, JsonDownloadError> {
         const ARM64: &str =
@@ -117,14 +147,14 @@ impl Manifest {
     }
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Latest {
     pub release: String,
     pub snapshot: String,
 }
 
 #[allow(non_snake_case)]
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Version {
     pub id: String,
     pub r#type: String,
@@ -11,9 +11,9 @@ pub use fabric::FabricJSON;
 pub use optifine::{JsonOptifine, OptifineArguments, OptifineLibrary};
 
 pub use asset_index::AssetIndex;
-pub use instance_config::{GlobalSettings, InstanceConfigJson};
+pub use instance_config::{GlobalSettings, InstanceConfigJson, ModpackInfo, QuickPlay};
 pub use manifest::Manifest;
 pub use version::{
     VersionDetails, V_1_12_2, V_1_5_2, V_OFFICIAL_FABRIC_SUPPORT, V_PAULSCODE_LAST,
-    V_PRECLASSIC_LAST,
+    V_PRECLASSIC_LAST, V_QUICK_PLAY_FIRST,
 };
@@ -14,6 +14,8 @@ pub const V_OFFICIAL_FABRIC_SUPPORT: &str = "2018-10-24T10:52:16+00:00";
 pub const V_1_5_2: &str = "2013-04-25T15:45:00+00:00";
 pub const V_1_12_2: &str = "2017-09-18T08:39:46+00:00";
 pub const V_PAULSCODE_LAST: &str = "2019-03-14T14:26:23+00:00";
+/// 23w14a, the first version with `--quickPlay*` arguments
+pub const V_QUICK_PLAY_FIRST: &str = "2023-04-05T11:05:17+00:00";
 
 #[allow(non_snake_case)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -59,6 +61,10 @@ pub struct VersionDetails {
 
     #[serde(skip)]
     pub q_patch_overrides: Vec<String>,
+    /// Extra Java arguments requested by patches
+    /// (MultiMC/PrismLauncher `+jvmArgs`).
+    #[serde(skip)]
+    pub q_patch_java_args: Vec<String>,
 }
 
 impl VersionDetails {
@@ -98,6 +104,7 @@ impl VersionDetails {
     /// to a `details.json` inside a `dir`.
     pub async fn save_to_dir(&self, dir: &Path) -> Result<(), JsonFileError> {
         debug_assert!(self.q_patch_overrides.is_empty());
+        debug_assert!(self.q_patch_java_args.is_empty());
 
         let text = serde_json::to_string(self).json_to()?;
         let path = dir.join("details.json");
@@ -145,14 +152,31 @@ impl VersionDetails {
         if let Some(args) = json.minecraftArguments {
             self.minecraftArguments = Some(args);
         }
+        if let Some(main_class) = json.mainClass {
+            self.mainClass = main_class;
+        }
         if let Some(mut libraries) = json.libraries {
             libraries.reverse();
             self.libraries.reverse();
             self.libraries.extend(libraries);
             self.libraries.reverse();
         }
+        if let Some(libraries) = json.plus_libraries {
+            self.libraries.extend(libraries);
+        }
+        for tweaker in json.plus_tweakers.into_iter().flatten() {
+            if let Some(args) = &mut self.minecraftArguments {
+                args.push_str(" --tweakClass ");
+                args.push_str(&tweaker);
+            } else if let Some(args) = &mut self.arguments {
+                args.game.push(Value::String("--tweakClass".to_owned()));
+                args.game.push(Value::String(tweaker));
+            }
+        }
+        if let Some(args) = json.plus_jvm_args {
+            self.q_patch_java_args.extend(args);
+        }
         self.q_patch_overrides.push(json.uid);
-        // TODO: More fields in the future
     }
 
     pub fn fix(&mut self) {
@@ -216,11 +240,23 @@ impl VersionDetails {
     }
 }
 
+/// A MultiMC/PrismLauncher-style patch (`patches/*.json`)
+/// applied on top of [`VersionDetails`] at launch.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[allow(non_snake_case)]
 pub struct VersionDetailsPatch {
+    /// Libraries loaded *before* the existing ones
     pub libraries: Option<Vec<Library>>,
+    /// Libraries loaded *after* the existing ones
+    #[serde(rename = "+libraries")]
+    pub plus_libraries: Option<Vec<Library>>,
+    pub mainClass: Option<String>,
     pub minecraftArguments: Option<String>,
+    /// LaunchWrapper tweak classes (`--tweakClass`)
+    #[serde(rename = "+tweakers")]
+    pub plus_tweakers: Option<Vec<String>>,
+    #[serde(rename = "+jvmArgs")]
+    pub plus_jvm_args: Option<Vec<String>>,
     pub uid: String,
 }
 
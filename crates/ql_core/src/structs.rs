@@ -25,9 +25,11 @@ pub enum Loader {
     #[serde(rename = "Paper")]
     Paper,
 
-    // The launcher doesn't currently support these:
+    // Installed as an instance patch (client only)
     #[serde(rename = "LiteLoader")]
     Liteloader,
+
+    // The launcher doesn't currently support these:
     #[serde(rename = "ModLoader")]
     Modloader,
     #[serde(rename = "Rift")]
@@ -107,7 +109,7 @@ impl Loader {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum JavaVersion {
     Java8 = 8,
     Java16 = 16,
@@ -19,7 +19,7 @@ impl std::fmt::Display for AccountResponseError {
     }
 }
 
-const AUTH_ERR_PREFIX: &str = "while logging into ely.by/littleskin account:\n";
+const AUTH_ERR_PREFIX: &str = "while logging into ely.by/littleskin/custom account:\n";
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("{AUTH_ERR_PREFIX}{0}")]
@@ -32,8 +32,12 @@ pub enum Error {
     KeyringError(#[from] KeyringError),
     #[error("{AUTH_ERR_PREFIX}Littleskin response:\n{0}")]
     LittleSkin(String),
-    #[error("incorrect password entered (ely.by/littleskin account)")]
+    #[error("incorrect password entered (ely.by/littleskin/custom account)")]
     IncorrectPassword,
+    #[error("{AUTH_ERR_PREFIX}invalid authentication server:\n{0}")]
+    InvalidServerUrl(String),
+    #[error("{AUTH_ERR_PREFIX}authentication server sent an invalid signature key")]
+    InvalidSignatureKey,
 
     #[error("{AUTH_ERR_PREFIX}while logging in through oauth:\n{0}")]
     Oauth(#[from] OauthError),
@@ -59,7 +63,7 @@ impl From<reqwest::Error> for Error {
 
 impl From<keyring::Error> for Error {
     fn from(err: keyring::Error) -> Self {
-        Self::KeyringError(KeyringError(err))
+        Self::KeyringError(KeyringError::Keyring(err))
     }
 }
 
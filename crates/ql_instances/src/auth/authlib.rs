@@ -1,4 +1,8 @@
-use ql_core::{file_utils, pt, DownloadFileError, IntoIoError, LAUNCHER_DIR};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ql_core::{err, file_utils, info, pt, DownloadFileError, IntoIoError, CLIENT, LAUNCHER_DIR};
+use serde::Deserialize;
+
+use super::{yggdrasil::Error, AccountType};
 
 /// Gets the java argument to start the authlib injector.
 ///
@@ -23,3 +27,159 @@ pub async fn get_authlib_injector(url: &str) -> Result<String, DownloadFileError
 
     Ok(format!("-javaagent:{}={url}", path.to_string_lossy()))
 }
+
+/// Header through which a server (eg: `https://example.com`)
+/// points to its actual authlib-injector API root
+/// (eg: `https://example.com/api/yggdrasil`).
+///
+/// See [API Location Indication](https://github.com/yushijinhun/authlib-injector/wiki/%E5%90%AF%E5%8A%A8%E5%99%A8%E6%8A%80%E6%9C%AF%E8%A7%84%E8%8C%83#api-%E5%9C%B0%E5%9D%80%E6%8C%87%E7%A4%BA-ali)
+const ALI_HEADER: &str = "X-Authlib-Injector-API-Location";
+
+/// An authlib-injector compatible authentication
+/// server (Drasl, Blessing Skin, etc.)
+#[derive(Debug, Clone)]
+pub struct AuthServer {
+    /// The API root, with no trailing slash.
+    /// Endpoints like `/authserver/authenticate`
+    /// are relative to this.
+    pub api_root: String,
+    pub metadata: AuthServerMetadata,
+    /// The metadata exactly as sent by the server,
+    /// for passing on to authlib-injector.
+    raw_metadata: String,
+}
+
+/// Returned by the server at its API root.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthServerMetadata {
+    #[serde(default)]
+    pub meta: AuthServerMeta,
+    /// Domains that skins can be loaded from
+    #[serde(default)]
+    pub skin_domains: Vec<String>,
+    /// PEM public key used by the game to
+    /// verify the signatures of skins/capes
+    pub signature_publickey: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthServerMeta {
+    pub server_name: Option<String>,
+    pub implementation_name: Option<String>,
+    pub implementation_version: Option<String>,
+}
+
+impl AuthServer {
+    /// Finds the authlib-injector API at a user-supplied URL,
+    /// following the [`ALI_HEADER`] if the server sends it.
+    ///
+    /// The URL may leave out `https://`.
+    ///
+    /// # Errors
+    /// - The URL is invalid
+    /// - The server couldn't be reached
+    /// - The server isn't an authlib-injector server
+    ///   (its metadata couldn't be parsed)
+    /// - The server's signature key is malformed
+    pub async fn discover(url: &str) -> Result<Self, Error> {
+        let url = url.trim();
+        let url = if url.contains("://") {
+            url.to_owned()
+        } else {
+            format!("https://{url}")
+        };
+        let url = reqwest::Url::parse(&url).map_err(|n| Error::InvalidServerUrl(n.to_string()))?;
+
+        info!("Finding authentication server at {url}");
+        let mut response = CLIENT.get(url.clone()).send().await?;
+
+        let location = response
+            .headers()
+            .get(ALI_HEADER)
+            .and_then(|n| n.to_str().ok())
+            .and_then(|n| response.url().join(n).ok());
+        if let Some(location) = location.filter(|n| n != response.url()) {
+            pt!("API is at {location}");
+            response = CLIENT.get(location).send().await?;
+        }
+        file_utils::check_for_success(&response)?;
+
+        let api_root = response.url().as_str().trim_end_matches('/').to_owned();
+        let raw_metadata = response.text().await?;
+        let metadata: AuthServerMetadata = serde_json::from_str(&raw_metadata).map_err(|_| {
+            Error::InvalidServerUrl(format!("{url} isn't an authlib-injector server"))
+        })?;
+
+        match &metadata.signature_publickey {
+            Some(key) => check_public_key(key)?,
+            None => err!("{api_root} has no signature key, skins may not load in-game"),
+        }
+
+        Ok(Self {
+            api_root,
+            metadata,
+            raw_metadata,
+        })
+    }
+
+    /// The server's name, or its URL if it doesn't have one.
+    #[must_use]
+    pub fn get_name(&self) -> &str {
+        self.metadata
+            .meta
+            .server_name
+            .as_deref()
+            .unwrap_or(&self.api_root)
+    }
+
+    /// The metadata exactly as sent by the server.
+    ///
+    /// Cached on the account (see [`super::AccountData::authlib_metadata`])
+    /// and passed to [`get_prefetched_arg`] when launching.
+    #[must_use]
+    pub fn get_raw_metadata(&self) -> &str {
+        &self.raw_metadata
+    }
+}
+
+/// Gets the java argument that passes the already fetched
+/// metadata (including the signature key) to authlib-injector,
+/// so the game doesn't have to fetch it again on startup.
+#[must_use]
+pub fn get_prefetched_arg(raw_metadata: &str) -> String {
+    format!(
+        "-Dauthlibinjector.yggdrasil.prefetched={}",
+        STANDARD.encode(raw_metadata)
+    )
+}
+
+/// Fetches the server metadata of an account when logging in,
+/// to be cached in [`super::AccountData::authlib_metadata`].
+///
+/// Failing isn't fatal: the game can still fetch it itself.
+pub(crate) async fn fetch_metadata(account_type: &AccountType) -> Option<String> {
+    let url = account_type.get_authlib_url()?;
+    match AuthServer::discover(url).await {
+        Ok(server) => Some(server.raw_metadata),
+        Err(error) => {
+            err!("Couldn't fetch authentication server info: {error}");
+            None
+        }
+    }
+}
+
+fn check_public_key(key: &str) -> Result<(), Error> {
+    let body: String = key
+        .trim()
+        .strip_prefix("-----BEGIN PUBLIC KEY-----")
+        .and_then(|n| n.strip_suffix("-----END PUBLIC KEY-----"))
+        .ok_or(Error::InvalidSignatureKey)?
+        .split_whitespace()
+        .collect();
+    match STANDARD.decode(body) {
+        Ok(der) if !der.is_empty() => Ok(()),
+        _ => Err(Error::InvalidSignatureKey),
+    }
+}
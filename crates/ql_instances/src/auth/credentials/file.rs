@@ -0,0 +1,471 @@
+//! Encrypted file storage for account tokens, for systems
+//! without an OS keyring (headless machines, containers,
+//! minimal window managers without a Secret Service).
+//!
+//! Tokens are stored in `QuantumLauncher/credentials.json`,
+//! encrypted with AES-256-GCM. The key is derived (PBKDF2) from either:
+//! - A **machine key** (eg: `/etc/machine-id`), so the file
+//!   is useless if copied to another computer. Note that this
+//!   doesn't protect against other programs running as you.
+//! - A **passphrase**, entered once per session in Settings,
+//!   or through the [`PASSPHRASE_ENV_VAR`] environment variable.
+
+use std::{collections::HashMap, io::Write, num::NonZeroU32, path::PathBuf, sync::Mutex};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ql_core::{info, IntoIoError, IntoJsonError, IoError, JsonError, LAUNCHER_DIR};
+use ring::{
+    aead::{self, Aad, LessSafeKey, Nonce, UnboundKey, NONCE_LEN},
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+
+/// Unlocks the credential file without asking,
+/// for headless setups and the command line.
+pub const PASSPHRASE_ENV_VAR: &str = "QL_CREDENTIALS_PASSPHRASE";
+
+const FILE_NAME: &str = "credentials.json";
+const PBKDF2_ITERATIONS: u32 = 100_000;
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+#[derive(Serialize, Deserialize)]
+struct CredentialFile {
+    unlock: UnlockMethod,
+    salt: String,
+    nonce: String,
+    /// Encrypted JSON map of `entry name: token`
+    data: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum UnlockMethod {
+    MachineKey,
+    Passphrase,
+}
+
+/// The key of the currently unlocked file,
+/// so the passphrase is only needed once per session.
+struct UnlockedKey {
+    key: [u8; KEY_LEN],
+    salt: Vec<u8>,
+    method: UnlockMethod,
+}
+
+static UNLOCKED: Mutex<Option<UnlockedKey>> = Mutex::new(None);
+
+const CREDENTIAL_ERR_PREFIX: &str = "while accessing encrypted credential file:\n";
+
+#[derive(Debug, thiserror::Error)]
+pub enum CredentialFileError {
+    #[error("{CREDENTIAL_ERR_PREFIX}{0}")]
+    Io(#[from] IoError),
+    #[error("{CREDENTIAL_ERR_PREFIX}{0}")]
+    Json(#[from] JsonError),
+    #[error("the encrypted credential file is locked\nEnter your passphrase in Settings -> Accounts\n(or set the {PASSPHRASE_ENV_VAR} environment variable)")]
+    Locked,
+    #[error("wrong passphrase for the encrypted credential file")]
+    WrongPassphrase,
+    #[error("{CREDENTIAL_ERR_PREFIX}couldn't decrypt it\nThe file is damaged, or was copied from another computer")]
+    Decrypt,
+    #[error("{CREDENTIAL_ERR_PREFIX}no machine key was found on this system\nSet a passphrase in Settings -> Accounts instead")]
+    NoMachineKey,
+    #[error("{CREDENTIAL_ERR_PREFIX}no saved login for {0}")]
+    NoEntry(String),
+    #[error("{CREDENTIAL_ERR_PREFIX}file is corrupted: {0}")]
+    Corrupted(String),
+    #[error("{CREDENTIAL_ERR_PREFIX}encryption failed")]
+    Crypto,
+}
+
+/// State of the encrypted credential file, see [`get_status`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialFileStatus {
+    /// Nothing has been stored in it yet
+    Missing,
+    /// Protected by a passphrase that hasn't been entered
+    Locked,
+    Unlocked {
+        passphrase: bool,
+    },
+}
+
+#[must_use]
+pub fn get_status() -> CredentialFileStatus {
+    let Ok(Some(file)) = read_file() else {
+        return CredentialFileStatus::Missing;
+    };
+    match get_key(&file) {
+        Ok(key) => {
+            *lock_unlocked() = Some(key);
+            CredentialFileStatus::Unlocked {
+                passphrase: file.unlock == UnlockMethod::Passphrase,
+            }
+        }
+        Err(_) => CredentialFileStatus::Locked,
+    }
+}
+
+/// Unlocks a passphrase-protected credential file
+/// for the rest of this session.
+///
+/// # Errors
+/// - [`CredentialFileError::WrongPassphrase`]
+/// - The file couldn't be read
+pub fn unlock(passphrase: &str) -> Result<(), CredentialFileError> {
+    let Some(file) = read_file()? else {
+        return Ok(());
+    };
+    let salt = decode(&file.salt)?;
+    let key = derive_key(passphrase, &salt);
+    decrypt(&file, &key).map_err(|_| CredentialFileError::WrongPassphrase)?;
+
+    *lock_unlocked() = Some(UnlockedKey {
+        key,
+        salt,
+        method: file.unlock,
+    });
+    info!("Unlocked encrypted credential file");
+    Ok(())
+}
+
+/// Protects the credential file with a passphrase,
+/// or switches back to the machine key (if `None`).
+///
+/// The file must be unlocked first.
+///
+/// # Errors
+/// - The file is locked
+/// - [`CredentialFileError::NoMachineKey`] when
+///   removing the passphrase on an unsupported system
+/// - The file couldn't be read or written
+pub fn set_passphrase(passphrase: Option<&str>) -> Result<(), CredentialFileError> {
+    let entries = read_entries()?.unwrap_or_default();
+
+    let salt = random_bytes::<SALT_LEN>()?.to_vec();
+    let (key, method) = if let Some(passphrase) = passphrase {
+        (derive_key(passphrase, &salt), UnlockMethod::Passphrase)
+    } else {
+        let machine_key = get_machine_key().ok_or(CredentialFileError::NoMachineKey)?;
+        (derive_key(&machine_key, &salt), UnlockMethod::MachineKey)
+    };
+    let unlocked = UnlockedKey { key, salt, method };
+    write_entries(&entries, &unlocked)?;
+    *lock_unlocked() = Some(unlocked);
+    Ok(())
+}
+
+pub(crate) fn get(name: &str) -> Result<String, CredentialFileError> {
+    read_entries()?
+        .and_then(|mut n| n.remove(name))
+        .ok_or_else(|| CredentialFileError::NoEntry(name.to_owned()))
+}
+
+pub(crate) fn set(name: &str, token: &str) -> Result<(), CredentialFileError> {
+    let (mut entries, unlocked) = match read_entries()? {
+        Some(entries) => (
+            entries,
+            lock_unlocked().take().ok_or(CredentialFileError::Locked)?,
+        ),
+        None => (HashMap::new(), new_key()?),
+    };
+    entries.insert(name.to_owned(), token.to_owned());
+    let result = write_entries(&entries, &unlocked);
+    *lock_unlocked() = Some(unlocked);
+    result
+}
+
+pub(crate) fn delete(name: &str) -> Result<(), CredentialFileError> {
+    let Some(mut entries) = read_entries()? else {
+        return Ok(());
+    };
+    if entries.remove(name).is_none() {
+        return Ok(());
+    }
+    let unlocked = lock_unlocked().take().ok_or(CredentialFileError::Locked)?;
+    let result = write_entries(&entries, &unlocked);
+    *lock_unlocked() = Some(unlocked);
+    result
+}
+
+fn get_path() -> PathBuf {
+    LAUNCHER_DIR.join(FILE_NAME)
+}
+
+fn lock_unlocked() -> std::sync::MutexGuard<'static, Option<UnlockedKey>> {
+    UNLOCKED
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+fn read_file() -> Result<Option<CredentialFile>, CredentialFileError> {
+    let path = get_path();
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let text = std::fs::read_to_string(&path).path(&path)?;
+    Ok(Some(serde_json::from_str(&text).json(text)?))
+}
+
+/// Decrypts the stored entries.
+/// Returns `None` if the file doesn't exist yet.
+fn read_entries() -> Result<Option<HashMap<String, String>>, CredentialFileError> {
+    let Some(file) = read_file()? else {
+        return Ok(None);
+    };
+    let key = get_key(&file)?;
+    let plaintext = decrypt(&file, &key.key)?;
+    let entries = serde_json::from_slice(&plaintext)
+        // Not using `.json()` to avoid printing tokens in the error
+        .map_err(|err| CredentialFileError::Corrupted(err.to_string()))?;
+    *lock_unlocked() = Some(key);
+    Ok(Some(entries))
+}
+
+fn write_entries(
+    entries: &HashMap<String, String>,
+    key: &UnlockedKey,
+) -> Result<(), CredentialFileError> {
+    let file = encrypt(entries, key)?;
+    let text = serde_json::to_string(&file).json_to()?;
+
+    // Write to a temporary file first, so a crash
+    // doesn't leave a half-written (unreadable) file
+    let path = get_path();
+    let temp_path = path.with_extension("json.tmp");
+    // Left over from a crash, may have other permissions
+    if temp_path.exists() {
+        std::fs::remove_file(&temp_path).path(&temp_path)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    // Only readable by the user, from the start
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut temp_file = options.open(&temp_path).path(&temp_path)?;
+    temp_file.write_all(text.as_bytes()).path(&temp_path)?;
+    temp_file.sync_all().path(&temp_path)?;
+    drop(temp_file);
+    std::fs::rename(&temp_path, &path).path(&path)?;
+    Ok(())
+}
+
+/// Gets the key for an existing file, from the
+/// unlocked session, the machine key or [`PASSPHRASE_ENV_VAR`].
+fn get_key(file: &CredentialFile) -> Result<UnlockedKey, CredentialFileError> {
+    let salt = decode(&file.salt)?;
+    if let Some(unlocked) = lock_unlocked().take() {
+        if unlocked.salt == salt && unlocked.method == file.unlock {
+            return Ok(unlocked);
+        }
+    }
+
+    let secret = match file.unlock {
+        UnlockMethod::MachineKey => get_machine_key().ok_or(CredentialFileError::NoMachineKey)?,
+        UnlockMethod::Passphrase => std::env::var(PASSPHRASE_ENV_VAR)
+            .ok()
+            .filter(|n| !n.is_empty())
+            .ok_or(CredentialFileError::Locked)?,
+    };
+    let key = derive_key(&secret, &salt);
+    decrypt(file, &key).map_err(|err| match file.unlock {
+        UnlockMethod::MachineKey => err,
+        UnlockMethod::Passphrase => CredentialFileError::WrongPassphrase,
+    })?;
+    Ok(UnlockedKey {
+        key,
+        salt,
+        method: file.unlock,
+    })
+}
+
+/// Key for a new file: the passphrase from [`PASSPHRASE_ENV_VAR`]
+/// if set, otherwise the machine key.
+fn new_key() -> Result<UnlockedKey, CredentialFileError> {
+    let salt = random_bytes::<SALT_LEN>()?.to_vec();
+    let (secret, method) = if let Some(passphrase) = std::env::var(PASSPHRASE_ENV_VAR)
+        .ok()
+        .filter(|n| !n.is_empty())
+    {
+        (passphrase, UnlockMethod::Passphrase)
+    } else {
+        (
+            get_machine_key().ok_or(CredentialFileError::NoMachineKey)?,
+            UnlockMethod::MachineKey,
+        )
+    };
+    info!("Creating encrypted credential file");
+    Ok(UnlockedKey {
+        key: derive_key(&secret, &salt),
+        salt,
+        method,
+    })
+}
+
+fn encrypt(
+    entries: &HashMap<String, String>,
+    key: &UnlockedKey,
+) -> Result<CredentialFile, CredentialFileError> {
+    let plaintext = serde_json::to_vec(entries).json_to()?;
+    let nonce = random_bytes::<NONCE_LEN>()?;
+
+    let mut data = plaintext;
+    get_aead_key(&key.key)?
+        .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+        .map_err(|_| CredentialFileError::Crypto)?;
+
+    Ok(CredentialFile {
+        unlock: key.method,
+        salt: STANDARD.encode(&key.salt),
+        nonce: STANDARD.encode(nonce),
+        data: STANDARD.encode(data),
+    })
+}
+
+fn decrypt(file: &CredentialFile, key: &[u8; KEY_LEN]) -> Result<Vec<u8>, CredentialFileError> {
+    let nonce: [u8; NONCE_LEN] = decode(&file.nonce)?
+        .try_into()
+        .map_err(|_| CredentialFileError::Corrupted("invalid nonce".to_owned()))?;
+    let mut data = decode(&file.data)?;
+    let plaintext = get_aead_key(key)?
+        .open_in_place(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut data)
+        .map_err(|_| CredentialFileError::Decrypt)?;
+    Ok(plaintext.to_vec())
+}
+
+fn get_aead_key(key: &[u8; KEY_LEN]) -> Result<LessSafeKey, CredentialFileError> {
+    let key = UnboundKey::new(&aead::AES_256_GCM, key).map_err(|_| CredentialFileError::Crypto)?;
+    Ok(LessSafeKey::new(key))
+}
+
+fn derive_key(secret: &str, salt: &[u8]) -> [u8; KEY_LEN] {
+    let mut key = [0; KEY_LEN];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(PBKDF2_ITERATIONS).unwrap_or(NonZeroU32::MIN),
+        salt,
+        secret.as_bytes(),
+        &mut key,
+    );
+    key
+}
+
+fn random_bytes<const N: usize>() -> Result<[u8; N], CredentialFileError> {
+    let mut bytes = [0; N];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| CredentialFileError::Crypto)?;
+    Ok(bytes)
+}
+
+fn decode(data: &str) -> Result<Vec<u8>, CredentialFileError> {
+    STANDARD
+        .decode(data)
+        .map_err(|err| CredentialFileError::Corrupted(err.to_string()))
+}
+
+/// A stable, per-installation ID of this computer.
+fn get_machine_key() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    let key = ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .find_map(|n| std::fs::read_to_string(n).ok());
+    #[cfg(target_os = "freebsd")]
+    let key = std::fs::read_to_string("/etc/hostid").ok();
+    #[cfg(target_os = "windows")]
+    let key = run_command(
+        "reg",
+        &[
+            "query",
+            r"HKLM\SOFTWARE\Microsoft\Cryptography",
+            "/v",
+            "MachineGuid",
+        ],
+    )
+    .and_then(|n| {
+        n.lines()
+            .find(|l| l.contains("MachineGuid"))
+            .and_then(|l| l.split_whitespace().last())
+            .map(str::to_owned)
+    });
+    #[cfg(target_os = "macos")]
+    let key = run_command("ioreg", &["-rd1", "-c", "IOPlatformExpertDevice"]).and_then(|n| {
+        n.lines()
+            .find(|l| l.contains("IOPlatformUUID"))
+            .and_then(|l| l.split('"').nth(3))
+            .map(str::to_owned)
+    });
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "freebsd",
+        target_os = "windows",
+        target_os = "macos"
+    )))]
+    let key: Option<String> = None;
+
+    key.map(|n| n.trim().to_owned()).filter(|n| !n.is_empty())
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+fn run_command(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = std::process::Command::new(program);
+    ql_core::no_window!(command);
+    let output = command.args(args).output().ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(secret: &str, salt: &[u8]) -> UnlockedKey {
+        UnlockedKey {
+            key: derive_key(secret, salt),
+            salt: salt.to_vec(),
+            method: UnlockMethod::Passphrase,
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let salt = random_bytes::<SALT_LEN>().unwrap();
+        let key = key("hunter2", &salt);
+        let entries = HashMap::from([
+            ("Steve".to_owned(), "token1".to_owned()),
+            ("Alex#elyby".to_owned(), "token2".to_owned()),
+        ]);
+
+        let file = encrypt(&entries, &key).unwrap();
+        assert!(!file.data.contains("token1"));
+        // Survives being saved
+        let file: CredentialFile =
+            serde_json::from_str(&serde_json::to_string(&file).unwrap()).unwrap();
+        assert_eq!(decode(&file.salt).unwrap(), salt);
+
+        let plaintext = decrypt(&file, &derive_key("hunter2", &salt)).unwrap();
+        let decrypted: HashMap<String, String> = serde_json::from_slice(&plaintext).unwrap();
+        assert_eq!(decrypted, entries);
+
+        assert!(matches!(
+            decrypt(&file, &derive_key("hunter3", &salt)),
+            Err(CredentialFileError::Decrypt)
+        ));
+    }
+
+    #[test]
+    fn fresh_nonce() {
+        let key = key("hunter2", &[0; SALT_LEN]);
+        let entries = HashMap::from([("Steve".to_owned(), "token".to_owned())]);
+        let (a, b) = (
+            encrypt(&entries, &key).unwrap(),
+            encrypt(&entries, &key).unwrap(),
+        );
+        assert_ne!(a.nonce, b.nonce);
+        assert_ne!(a.data, b.data);
+    }
+}
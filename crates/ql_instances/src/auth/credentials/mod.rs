@@ -0,0 +1,221 @@
+//! Where account tokens (Microsoft refresh tokens,
+//! Yggdrasil access tokens) are stored.
+//!
+//! See [`CredentialBackend`] for the options.
+
+use std::{fmt::Display, sync::RwLock};
+
+use ql_core::{err, info};
+use serde::{Deserialize, Serialize};
+
+use super::{AccountType, KeyringError};
+
+pub mod file;
+
+const KEYRING_SERVICE: &str = "QuantumLauncher";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CredentialBackend {
+    /// The OS keyring, falling back to the encrypted file
+    /// if the keyring isn't available (eg: no Secret Service on Linux)
+    #[serde(rename = "auto")]
+    #[default]
+    Auto,
+    /// Windows Credential Manager, macOS Keychain,
+    /// or Secret Service (GNOME Keyring, KWallet) on Linux
+    #[serde(rename = "keyring")]
+    Keyring,
+    /// `QuantumLauncher/credentials.json`, see [`file`]
+    #[serde(rename = "encrypted_file")]
+    EncryptedFile,
+}
+
+impl CredentialBackend {
+    pub const ALL: &'static [Self] = &[Self::Auto, Self::Keyring, Self::EncryptedFile];
+
+    #[must_use]
+    pub const fn get_description(self) -> &'static str {
+        match self {
+            CredentialBackend::Auto => "System keyring if available, otherwise encrypted file",
+            CredentialBackend::Keyring => "Credential Manager / Keychain / Secret Service",
+            CredentialBackend::EncryptedFile => {
+                "Stored in the launcher folder, for systems without a keyring"
+            }
+        }
+    }
+
+    const fn uses_keyring(self) -> bool {
+        matches!(self, Self::Auto | Self::Keyring)
+    }
+
+    const fn uses_file(self) -> bool {
+        matches!(self, Self::Auto | Self::EncryptedFile)
+    }
+}
+
+impl Display for CredentialBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CredentialBackend::Auto => write!(f, "Automatic"),
+            CredentialBackend::Keyring => write!(f, "System keyring"),
+            CredentialBackend::EncryptedFile => write!(f, "Encrypted file"),
+        }
+    }
+}
+
+static BACKEND: RwLock<CredentialBackend> = RwLock::new(CredentialBackend::Auto);
+
+/// Sets where account tokens are stored (from the launcher config).
+///
+/// This doesn't move already stored tokens,
+/// use [`migrate_credentials`] for that.
+pub fn set_credential_backend(backend: CredentialBackend) {
+    *BACKEND
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = backend;
+}
+
+#[must_use]
+pub fn get_credential_backend() -> CredentialBackend {
+    *BACKEND
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Moves the stored tokens of the given accounts
+/// (`(username, type)`) to another backend, and switches to it.
+///
+/// Accounts without a stored token are skipped.
+/// Tokens are only removed from the old backend
+/// once all of them have been copied.
+///
+/// # Errors
+/// [`KeyringError::Migrate`] if some tokens couldn't be read
+/// from the old backend or saved to the new one. The old
+/// backend is then kept, with all its tokens.
+pub fn migrate_credentials(
+    accounts: &[(String, AccountType)],
+    to: CredentialBackend,
+) -> Result<(), KeyringError> {
+    let from = get_credential_backend();
+    if from == to {
+        return Ok(());
+    }
+    info!("Moving account logins: {from} -> {to}");
+
+    let mut moved = Vec::new();
+    let mut failed = Vec::new();
+    for (username, account_type) in accounts {
+        let name = account_type.get_credential_name(username);
+        let token = match CredentialEntry::with_backend(name.clone(), from).get_password() {
+            Ok(token) => token,
+            Err(err) if err.is_missing() => {
+                err!("No saved login for {username}, skipping it");
+                continue;
+            }
+            Err(err) => {
+                failed.push((username.clone(), err.to_string()));
+                continue;
+            }
+        };
+        match CredentialEntry::with_backend(name.clone(), to).set_password(&token) {
+            Ok(()) => moved.push(name),
+            Err(err) => failed.push((username.clone(), err.to_string())),
+        }
+    }
+    if !failed.is_empty() {
+        return Err(KeyringError::Migrate(failed));
+    }
+
+    for name in moved {
+        let entry = CredentialEntry::with_backend(name, from);
+        entry.delete_from(!to.uses_keyring(), !to.uses_file());
+    }
+    set_credential_backend(to);
+    Ok(())
+}
+
+/// A stored token for one account, in the
+/// current [`CredentialBackend`].
+pub(crate) struct CredentialEntry {
+    name: String,
+    backend: CredentialBackend,
+}
+
+impl CredentialEntry {
+    pub(crate) fn new(name: String) -> Self {
+        Self::with_backend(name, get_credential_backend())
+    }
+
+    fn with_backend(name: String, backend: CredentialBackend) -> Self {
+        Self { name, backend }
+    }
+
+    fn keyring_entry(&self) -> Result<keyring::Entry, keyring::Error> {
+        keyring::Entry::new(KEYRING_SERVICE, &self.name)
+    }
+
+    pub(crate) fn get_password(&self) -> Result<String, KeyringError> {
+        match self.backend {
+            CredentialBackend::Keyring => Ok(self.keyring_entry()?.get_password()?),
+            CredentialBackend::EncryptedFile => Ok(file::get(&self.name)?),
+            CredentialBackend::Auto => match self.keyring_entry().and_then(|n| n.get_password()) {
+                Ok(token) => Ok(token),
+                // Saved in the file when the keyring wasn't available
+                Err(err) => match file::get(&self.name) {
+                    Ok(token) => Ok(token),
+                    Err(file::CredentialFileError::NoEntry(_)) => Err(err.into()),
+                    Err(file_err) => Err(file_err.into()),
+                },
+            },
+        }
+    }
+
+    pub(crate) fn set_password(&self, token: &str) -> Result<(), KeyringError> {
+        match self.backend {
+            CredentialBackend::Keyring => Ok(self.keyring_entry()?.set_password(token)?),
+            CredentialBackend::EncryptedFile => Ok(file::set(&self.name, token)?),
+            CredentialBackend::Auto => {
+                match self.keyring_entry().and_then(|n| n.set_password(token)) {
+                    Ok(()) => Ok(()),
+                    Err(err) if is_keyring_unavailable(&err) => {
+                        info!("System keyring isn't available ({err}), saving login to encrypted file");
+                        Ok(file::set(&self.name, token)?)
+                    }
+                    Err(err) => Err(err.into()),
+                }
+            }
+        }
+    }
+
+    pub(crate) fn delete_credential(&self) {
+        self.delete_from(self.backend.uses_keyring(), self.backend.uses_file());
+    }
+
+    fn delete_from(&self, keyring: bool, file: bool) {
+        if keyring {
+            match self.keyring_entry().and_then(|n| n.delete_credential()) {
+                Ok(()) | Err(keyring::Error::NoEntry) => {}
+                // Nothing to delete if there's no keyring
+                Err(err)
+                    if self.backend == CredentialBackend::Auto && is_keyring_unavailable(&err) => {}
+                Err(err) => err!("Couldn't remove login from keyring ({}): {err}", self.name),
+            }
+        }
+        if file {
+            if let Err(err) = file::delete(&self.name) {
+                err!(
+                    "Couldn't remove login from encrypted file ({}): {err}",
+                    self.name
+                );
+            }
+        }
+    }
+}
+
+fn is_keyring_unavailable(err: &keyring::Error) -> bool {
+    matches!(
+        err,
+        keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_)
+    )
+}
@@ -1,11 +1,18 @@
-use ql_core::{err, IntoStringError};
 use std::fmt::Display;
 
 mod alt;
 pub mod authlib;
+pub mod credentials;
 pub mod ms;
+pub mod offline;
+pub mod skin;
 pub mod yggdrasil;
-pub use authlib::get_authlib_injector;
+pub use authlib::{get_authlib_injector, AuthServer};
+use credentials::CredentialEntry;
+pub use credentials::{
+    file::{CredentialFileError, CredentialFileStatus},
+    get_credential_backend, migrate_credentials, set_credential_backend, CredentialBackend,
+};
 
 #[derive(Debug, Clone)]
 pub struct AccountData {
@@ -18,37 +25,37 @@ pub struct AccountData {
     pub nice_username: String,
 
     pub account_type: AccountType,
+    /// For authlib-injector accounts, the server's metadata
+    /// as fetched at login. Passed to the game so it doesn't
+    /// have to be fetched on every launch
+    /// (see [`authlib::get_prefetched_arg`]).
+    pub authlib_metadata: Option<String>,
 }
 
 impl AccountData {
     #[must_use]
     pub fn get_username_modified(&self) -> String {
-        let suffix = match self.account_type {
-            AccountType::Microsoft => "",
-            AccountType::ElyBy => " (elyby)",
-            AccountType::LittleSkin => " (littleskin)",
-            AccountType::Custom => " (custom)",
-        };
-        format!("{}{suffix}", self.nice_username)
+        format!("{}{}", self.nice_username, self.account_type.get_suffix())
     }
 
     #[must_use]
-    pub fn get_authlib_url(&self) -> Option<&'static str> {
-        match self.account_type {
-            AccountType::Microsoft => None,
-            AccountType::ElyBy => Some("ely.by"),
-            AccountType::LittleSkin => Some("https://littleskin.cn/api/yggdrasil"),
-            AccountType::Custom => Some("https://drasl.unmojang.org/authlib-injector"),
-        }
+    pub fn get_authlib_url(&self) -> Option<&str> {
+        self.account_type.get_authlib_url()
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// The server that older versions of the launcher
+/// used for all `Custom` accounts.
+pub const DEFAULT_CUSTOM_SERVER: &str = "https://drasl.unmojang.org/authlib-injector";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AccountType {
     Microsoft,
     ElyBy,
     LittleSkin,
-    Custom,
+    /// Any authlib-injector compatible server,
+    /// with its API root (see [`AuthServer::discover`]).
+    Custom(String),
 }
 
 impl Display for AccountType {
@@ -60,77 +67,115 @@ impl Display for AccountType {
                 AccountType::Microsoft => "Microsoft",
                 AccountType::ElyBy => "ElyBy",
                 AccountType::LittleSkin => "LittleSkin",
-                AccountType::Custom => "Custom",
+                AccountType::Custom(_) => "Custom",
             }
         )
     }
 }
 
 impl AccountType {
+    /// The authlib-injector server of the account,
+    /// or `None` for Microsoft accounts.
+    #[must_use]
+    pub fn get_authlib_url(&self) -> Option<&str> {
+        match self {
+            AccountType::Microsoft => None,
+            AccountType::ElyBy => Some("ely.by"),
+            AccountType::LittleSkin => Some("https://littleskin.cn/api/yggdrasil"),
+            AccountType::Custom(api_root) => Some(api_root),
+        }
+    }
+
     #[must_use]
-    pub fn yggdrasil_authenticate(self) -> &'static str {
+    pub fn yggdrasil_authenticate(&self) -> String {
         match self {
             AccountType::Microsoft => unreachable!(),
-            AccountType::ElyBy => "https://authserver.ely.by/auth/authenticate",
+            AccountType::ElyBy => "https://authserver.ely.by/auth/authenticate".to_owned(),
             AccountType::LittleSkin => {
-                "https://littleskin.cn/api/yggdrasil/authserver/authenticate"
+                "https://littleskin.cn/api/yggdrasil/authserver/authenticate".to_owned()
             }
-            AccountType::Custom => "https://drasl.unmojang.org/auth/authenticate",
+            AccountType::Custom(api_root) => format!("{api_root}/authserver/authenticate"),
         }
     }
 
     #[must_use]
-    pub fn yggdrasil_refresh(self) -> &'static str {
+    pub fn yggdrasil_refresh(&self) -> String {
         match self {
             AccountType::Microsoft => unreachable!(),
-            AccountType::ElyBy => "https://authserver.ely.by/auth/refresh",
-            AccountType::LittleSkin => "https://littleskin.cn/api/yggdrasil/authserver/refresh",
-            AccountType::Custom => "https://drasl.unmojang.org/auth/refresh",
+            AccountType::ElyBy => "https://authserver.ely.by/auth/refresh".to_owned(),
+            AccountType::LittleSkin => {
+                "https://littleskin.cn/api/yggdrasil/authserver/refresh".to_owned()
+            }
+            AccountType::Custom(api_root) => format!("{api_root}/authserver/refresh"),
         }
     }
 
     #[must_use]
-    pub fn yggdrasil_needs_agent_field(self) -> bool {
+    pub fn yggdrasil_needs_agent_field(&self) -> bool {
         match self {
             AccountType::Microsoft | AccountType::ElyBy => false,
-            AccountType::LittleSkin | AccountType::Custom => true,
+            AccountType::LittleSkin | AccountType::Custom(_) => true,
         }
     }
 
-    fn get_keyring_entry(self, username: &str) -> Result<keyring::Entry, KeyringError> {
-        Ok(keyring::Entry::new(
-            "QuantumLauncher",
-            &format!(
-                "{username}{}",
-                match self {
-                    AccountType::Microsoft => "",
-                    AccountType::ElyBy => "#elyby",
-                    AccountType::LittleSkin => "#littleskin",
-                    AccountType::Custom => "#custom"
-                }
-            ),
-        )?)
+    fn get_keyring_entry(&self, username: &str) -> CredentialEntry {
+        CredentialEntry::new(self.get_credential_name(username))
+    }
+
+    fn get_credential_name(&self, username: &str) -> String {
+        match self {
+            AccountType::Microsoft => username.to_owned(),
+            AccountType::ElyBy => format!("{username}#elyby"),
+            AccountType::LittleSkin => format!("{username}#littleskin"),
+            AccountType::Custom(api_root) if api_root == DEFAULT_CUSTOM_SERVER => {
+                format!("{username}#custom")
+            }
+            AccountType::Custom(api_root) => {
+                let server = api_root
+                    .split_once("://")
+                    .map_or(api_root.as_str(), |n| n.1);
+                format!("{username}#custom@{server}")
+            }
+        }
     }
 
     #[must_use]
-    pub(crate) fn get_client_id(self) -> &'static str {
+    pub(crate) fn get_client_id(&self) -> &'static str {
         match self {
             AccountType::Microsoft => ms::CLIENT_ID,
             AccountType::ElyBy => "quantumlauncher1",
             AccountType::LittleSkin => "1160",
-            AccountType::Custom => "quantumlauncher2",
+            AccountType::Custom(_) => "quantumlauncher2",
         }
     }
 
+    /// The suffix added to usernames in the account list,
+    /// eg: `" (elyby)"`. For custom servers this is
+    /// the server's domain, so that the same username
+    /// can be logged in on multiple servers.
     #[must_use]
-    pub fn strip_name(self, name: &str) -> &str {
+    pub fn get_suffix(&self) -> String {
         match self {
-            AccountType::Microsoft => name,
-            AccountType::ElyBy => name.strip_suffix(" (elyby)").unwrap_or(name),
-            AccountType::LittleSkin => name.strip_suffix(" (littleskin)").unwrap_or(name),
-            AccountType::Custom => name.strip_suffix(" (custom)").unwrap_or(name),
+            AccountType::Microsoft => String::new(),
+            AccountType::ElyBy => " (elyby)".to_owned(),
+            AccountType::LittleSkin => " (littleskin)".to_owned(),
+            AccountType::Custom(api_root) if api_root == DEFAULT_CUSTOM_SERVER => {
+                " (custom)".to_owned()
+            }
+            AccountType::Custom(api_root) => {
+                let server = api_root
+                    .split_once("://")
+                    .map_or(api_root.as_str(), |n| n.1);
+                let domain = server.split('/').next().unwrap_or(server);
+                format!(" ({domain})")
+            }
         }
     }
+
+    #[must_use]
+    pub fn strip_name<'a>(&self, name: &'a str) -> &'a str {
+        name.strip_suffix(&self.get_suffix()).unwrap_or(name)
+    }
 }
 
 impl AccountData {
@@ -148,22 +193,65 @@ impl AccountData {
     }
     #[must_use]
     pub fn is_custom(&self) -> bool {
-        matches!(self.account_type, AccountType::Custom)
+        matches!(self.account_type, AccountType::Custom(_))
     }
 }
 
 #[derive(Debug, thiserror::Error)]
-pub struct KeyringError(#[from] pub keyring::Error);
+pub enum KeyringError {
+    Keyring(#[from] keyring::Error),
+    File(#[from] CredentialFileError),
+    /// Accounts (and why) whose logins
+    /// couldn't be moved to another backend
+    Migrate(Vec<(String, String)>),
+}
+
+impl KeyringError {
+    /// Whether the encrypted credential file needs
+    /// a passphrase (see [`credentials::file::unlock`]).
+    ///
+    /// Accounts that fail with this shouldn't be
+    /// treated as logged out.
+    #[must_use]
+    pub fn is_locked(&self) -> bool {
+        matches!(self, KeyringError::File(CredentialFileError::Locked))
+    }
+
+    /// Whether there's no stored token
+    /// for the account (eg: logged out)
+    #[must_use]
+    pub fn is_missing(&self) -> bool {
+        matches!(
+            self,
+            KeyringError::Keyring(keyring::Error::NoEntry)
+                | KeyringError::File(CredentialFileError::NoEntry(_))
+        )
+    }
+}
 
 impl Display for KeyringError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let err = match self {
+            KeyringError::Keyring(err) => err,
+            KeyringError::File(err) => return write!(f, "Account credential error:\n{err}"),
+            KeyringError::Migrate(failed) => {
+                write!(
+                    f,
+                    "Couldn't move the logins of some accounts (nothing was changed):"
+                )?;
+                for (username, err) in failed {
+                    write!(f, "\n\n{username}: {err}")?;
+                }
+                return Ok(());
+            }
+        };
         writeln!(f, "Account keyring error:")?;
-        match &self.0 {
+        match err {
             #[cfg(target_os = "linux")]
             keyring::Error::PlatformFailure(error)
                 if error.to_string().contains("The name is not activatable") =>
             {
-                write!(f, "{error}\n\nTry installing gnome-keyring and libsecret packages\n(may be called differently depending on your distro)")
+                write!(f, "{error}\n\nTry installing gnome-keyring and libsecret packages\n(may be called differently depending on your distro)\n\nOr store logins in an encrypted file instead:\nSettings -> Accounts -> Encrypted file")
             }
             #[cfg(target_os = "linux")]
             keyring::Error::NoStorageAccess(error)
@@ -180,28 +268,29 @@ If it's there, make sure it's unlocked (right-click -> Unlock)
 If it's not there, click on + then "Password Keyring",
 and name it "Login" and put your preferred password
 
-Now after this, in the sidebar, right click it and click "Set as Default""#
+Now after this, in the sidebar, right click it and click "Set as Default"
+
+Or store logins in an encrypted file instead:
+Settings -> Accounts -> Encrypted file"#
                 )
             }
 
-            _ => write!(f, "{}", self.0),
+            _ => write!(f, "{err}"),
         }
     }
 }
 
 pub fn read_refresh_token(
     username: &str,
-    account_type: AccountType,
+    account_type: &AccountType,
 ) -> Result<String, KeyringError> {
-    let entry = account_type.get_keyring_entry(username)?;
+    let entry = account_type.get_keyring_entry(username);
     let refresh_token = entry.get_password()?;
     Ok(refresh_token)
 }
 
-pub fn logout(username: &str, account_type: AccountType) -> Result<(), String> {
-    let entry = account_type.get_keyring_entry(username).strerr()?;
-    if let Err(err) = entry.delete_credential() {
-        err!("Couldn't remove {account_type} account credential (Username: {username}):\n{err}");
-    }
+pub fn logout(username: &str, account_type: &AccountType) -> Result<(), String> {
+    let entry = account_type.get_keyring_entry(username);
+    entry.delete_credential();
     Ok(())
 }
@@ -195,7 +195,7 @@ impl From<reqwest::Error> for Error {
 
 impl From<keyring::Error> for Error {
     fn from(err: keyring::Error) -> Self {
-        Self::KeyringError(KeyringError(err))
+        Self::KeyringError(KeyringError::Keyring(err))
     }
 }
 
@@ -235,7 +235,7 @@ pub async fn login_refresh(
 
     let data: RefreshResponse = serde_json::from_str(&response).json(response)?;
 
-    let entry = keyring::Entry::new("QuantumLauncher", &username)?;
+    let entry = AccountType::Microsoft.get_keyring_entry(&username);
     entry.set_password(&data.refresh_token)?;
 
     let data = login_3_xbox(
@@ -300,7 +300,7 @@ pub async fn login_3_xbox(
         }
     }
 
-    let entry = keyring::Entry::new("QuantumLauncher", &final_details.name)?;
+    let entry = AccountType::Microsoft.get_keyring_entry(&final_details.name);
     entry.set_password(&data.refresh_token)?;
 
     let data = AccountData {
@@ -309,6 +309,7 @@ pub async fn login_3_xbox(
         refresh_token: data.refresh_token,
         needs_refresh: false,
         account_type: AccountType::Microsoft,
+        authlib_metadata: None,
 
         username: final_details.name.clone(),
         nice_username: final_details.name,
@@ -0,0 +1,103 @@
+//! # Offline profiles
+//!
+//! Named offline accounts with a stable UUID
+//! and an optional skin from a local PNG.
+//!
+//! By default the UUID is the one vanilla uses for
+//! offline players (see [`get_offline_uuid`]), so the
+//! same name gets the same identity on every computer
+//! (eg: in LAN worlds with friends).
+//!
+//! Skins are served to the game by a tiny
+//! authlib-injector compatible server running
+//! inside the launcher, see [`serve_skin`].
+
+use std::path::PathBuf;
+
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+
+use super::skin::SkinVariant;
+
+mod server;
+pub use server::serve_skin;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OfflineProfile {
+    /// The in-game username
+    pub name: String,
+    /// Without dashes, like other account UUIDs
+    pub uuid: String,
+    /// A skin PNG on this computer
+    #[serde(default)]
+    pub skin: Option<PathBuf>,
+    #[serde(default)]
+    pub skin_variant: SkinVariant,
+}
+
+impl OfflineProfile {
+    /// Creates a profile with the vanilla offline UUID
+    /// and no skin.
+    #[must_use]
+    pub fn new(name: String) -> Self {
+        Self {
+            uuid: get_offline_uuid(&name),
+            name,
+            skin: None,
+            skin_variant: SkinVariant::Classic,
+        }
+    }
+}
+
+/// Gets the UUID that vanilla Minecraft gives to an
+/// offline player: a version 3 (name-based) UUID of
+/// `OfflinePlayer:NAME`, without dashes.
+#[must_use]
+pub fn get_offline_uuid(name: &str) -> String {
+    // Java's `UUID.nameUUIDFromBytes`
+    let mut hash: [u8; 16] = Md5::digest(format!("OfflinePlayer:{name}")).into();
+    hash[6] = (hash[6] & 0x0f) | 0x30;
+    hash[8] = (hash[8] & 0x3f) | 0x80;
+    hash.iter().map(|n| format!("{n:02x}")).collect()
+}
+
+/// Checks if a user-entered UUID is valid,
+/// returning it without dashes (and lowercase).
+#[must_use]
+pub fn parse_uuid(uuid: &str) -> Option<String> {
+    let uuid: String = uuid.trim().chars().filter(|c| *c != '-').collect();
+    (uuid.len() == 32 && uuid.chars().all(|c| c.is_ascii_hexdigit())).then(|| uuid.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offline_uuid() {
+        assert_eq!(
+            get_offline_uuid("Notch"),
+            "b50ad385829d3141a2167e7d7539ba7f"
+        );
+        assert_eq!(
+            OfflineProfile::new("Notch".to_owned()).uuid,
+            "b50ad385829d3141a2167e7d7539ba7f"
+        );
+        assert_ne!(get_offline_uuid("notch"), get_offline_uuid("Notch"));
+    }
+
+    #[test]
+    fn uuid() {
+        assert_eq!(
+            parse_uuid(" B50AD385-829D-3141-A216-7E7D7539BA7F ").as_deref(),
+            Some("b50ad385829d3141a2167e7d7539ba7f")
+        );
+        assert_eq!(
+            parse_uuid("b50ad385829d3141a2167e7d7539ba7f").as_deref(),
+            Some("b50ad385829d3141a2167e7d7539ba7f")
+        );
+        assert_eq!(parse_uuid("b50ad385829d3141a2167e7d7539ba7"), None);
+        assert_eq!(parse_uuid("g50ad385829d3141a2167e7d7539ba7f"), None);
+        assert_eq!(parse_uuid(""), None);
+    }
+}
@@ -0,0 +1,348 @@
+//! A tiny authlib-injector compatible server,
+//! running on `127.0.0.1`, that serves the skins
+//! of offline profiles to the game.
+//!
+//! It only implements what the game needs to look up
+//! players and their textures. Textures aren't signed,
+//! so there's no `signaturePublickey`.
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{LazyLock, Mutex, PoisonError},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use base64::Engine;
+use ql_core::{err, info, IntoIoError};
+use serde_json::json;
+
+use super::{get_offline_uuid, OfflineProfile};
+use crate::auth::skin::{check_skin, SkinError, SkinVariant};
+
+struct ServedProfile {
+    name: String,
+    variant: SkinVariant,
+    image: Vec<u8>,
+    /// Hash of the image, used in the texture URL
+    hash: String,
+}
+
+#[derive(Default)]
+struct LocalServer {
+    /// `None` if not started yet
+    port: Option<u16>,
+    /// By UUID (lowercase, no dashes)
+    profiles: HashMap<String, ServedProfile>,
+}
+
+static SERVER: LazyLock<Mutex<LocalServer>> = LazyLock::new(Mutex::default);
+
+/// Request bodies are tiny (a list of names),
+/// anything bigger isn't from the game
+const MAX_BODY_LEN: usize = 64 * 1024;
+
+/// Serves the skin of an offline profile, starting
+/// the local server if it isn't already running.
+///
+/// Returns the API URL to pass to authlib-injector.
+///
+/// # Errors
+/// - The profile's skin couldn't be read, or isn't a valid skin
+/// - The server couldn't be started
+pub async fn serve_skin(profile: &OfflineProfile) -> Result<String, SkinError> {
+    let image = match &profile.skin {
+        Some(path) => {
+            let image = tokio::fs::read(path).await.path(path)?;
+            check_skin(&image)?;
+            image
+        }
+        None => Vec::new(),
+    };
+    let hash = ring::digest::digest(&ring::digest::SHA256, &image)
+        .as_ref()
+        .iter()
+        .map(|n| format!("{n:02x}"))
+        .collect();
+
+    let mut server = SERVER.lock().unwrap_or_else(PoisonError::into_inner);
+    let port = if let Some(port) = server.port {
+        port
+    } else {
+        let listener = TcpListener::bind("127.0.0.1:0").map_err(SkinError::LocalServer)?;
+        let port = listener
+            .local_addr()
+            .map_err(SkinError::LocalServer)?
+            .port();
+        std::thread::spawn(move || run(&listener, port));
+        info!("Started local skin server on port {port}");
+        server.port = Some(port);
+        port
+    };
+    server.profiles.insert(
+        profile.uuid.to_lowercase(),
+        ServedProfile {
+            name: profile.name.clone(),
+            variant: profile.skin_variant,
+            image,
+            hash,
+        },
+    );
+    Ok(format!("http://127.0.0.1:{port}"))
+}
+
+fn run(listener: &TcpListener, port: u16) {
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        // So one slow client can't hold up the others
+        std::thread::spawn(move || {
+            if let Err(error) = handle(stream, port) {
+                err!("Local skin server: {error}");
+            }
+        });
+    }
+}
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn json(value: &serde_json::Value) -> Self {
+        Self {
+            status: "200 OK",
+            content_type: "application/json; charset=utf-8",
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    fn empty(status: &'static str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain",
+            body: Vec::new(),
+        }
+    }
+}
+
+fn handle(stream: TcpStream, port: u16) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Ok(());
+    };
+    let (method, target) = (method.to_owned(), target.to_owned());
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            if key.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let response = if content_length > MAX_BODY_LEN {
+        Response::empty("413 Payload Too Large")
+    } else {
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+        let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+        route(&method, path, query, &body, port)
+    };
+
+    let mut stream = reader.into_inner();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len()
+    )?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+fn route(method: &str, path: &str, query: &str, body: &[u8], port: u16) -> Response {
+    let server = SERVER.lock().unwrap_or_else(PoisonError::into_inner);
+    let profiles = &server.profiles;
+    let segments: Vec<&str> = path.split('/').filter(|n| !n.is_empty()).collect();
+
+    match (method, segments.as_slice()) {
+        ("GET", []) => Response::json(&json!({
+            "meta": {
+                "serverName": "QuantumLauncher (offline)",
+                "implementationName": "QuantumLauncher",
+                "implementationVersion": env!("CARGO_PKG_VERSION"),
+            },
+            "skinDomains": ["127.0.0.1"],
+        })),
+        ("GET", ["sessionserver", "session", "minecraft", "profile", uuid]) => {
+            let uuid = uuid.replace('-', "").to_lowercase();
+            match profiles.get(&uuid) {
+                Some(profile) => Response::json(&get_profile_json(&uuid, profile, port)),
+                None => Response::empty("204 No Content"),
+            }
+        }
+        ("GET", ["sessionserver", "session", "minecraft", "hasJoined"]) => {
+            // Used by LAN worlds (the integrated server authenticates
+            // in online mode) to verify players joining them.
+            // Anyone is let in, other players get their offline UUID
+            let Some(name) = get_query_param(query, "username") else {
+                return Response::empty("204 No Content");
+            };
+            match profiles.iter().find(|(_, profile)| profile.name == name) {
+                Some((uuid, profile)) => Response::json(&get_profile_json(uuid, profile, port)),
+                None => Response::json(&json!({
+                    "id": get_offline_uuid(&name),
+                    "name": name,
+                    "properties": [],
+                })),
+            }
+        }
+        ("POST", ["sessionserver", "session", "minecraft", "join"]) => {
+            Response::empty("204 No Content")
+        }
+        ("GET", ["textures", hash]) => {
+            match profiles
+                .values()
+                .find(|n| n.hash == *hash && !n.image.is_empty())
+            {
+                Some(profile) => Response {
+                    status: "200 OK",
+                    content_type: "image/png",
+                    body: profile.image.clone(),
+                },
+                None => Response::empty("404 Not Found"),
+            }
+        }
+        ("POST", ["api", "profiles", "minecraft"]) => {
+            let names: Vec<String> = serde_json::from_slice(body).unwrap_or_default();
+            let found: Vec<_> = profiles
+                .iter()
+                .filter(|(_, profile)| names.iter().any(|n| n.eq_ignore_ascii_case(&profile.name)))
+                .map(|(uuid, profile)| json!({ "id": uuid, "name": profile.name }))
+                .collect();
+            Response::json(&serde_json::Value::Array(found))
+        }
+        ("GET", ["api", "users", "profiles", "minecraft", name]) => {
+            match profiles
+                .iter()
+                .find(|(_, profile)| profile.name.eq_ignore_ascii_case(name))
+            {
+                Some((uuid, profile)) => {
+                    Response::json(&json!({ "id": uuid, "name": profile.name }))
+                }
+                None => Response::empty("204 No Content"),
+            }
+        }
+        _ => Response::empty("404 Not Found"),
+    }
+}
+
+fn get_profile_json(uuid: &str, profile: &ServedProfile, port: u16) -> serde_json::Value {
+    let mut textures = serde_json::Map::new();
+    if !profile.image.is_empty() {
+        let mut skin =
+            json!({ "url": format!("http://127.0.0.1:{port}/textures/{}", profile.hash) });
+        if let SkinVariant::Slim = profile.variant {
+            skin["metadata"] = json!({ "model": "slim" });
+        }
+        textures.insert("SKIN".to_owned(), skin);
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |n| n.as_millis());
+    let value = json!({
+        "timestamp": timestamp,
+        "profileId": uuid,
+        "profileName": profile.name,
+        "textures": textures,
+    });
+    let value = base64::engine::general_purpose::STANDARD.encode(value.to_string());
+
+    json!({
+        "id": uuid,
+        "name": profile.name,
+        "properties": [{ "name": "textures", "value": value }],
+    })
+}
+
+/// Gets a (percent-decoded) parameter from a URL query string.
+fn get_query_param(query: &str, key: &str) -> Option<String> {
+    let value = query
+        .split('&')
+        .filter_map(|n| n.split_once('='))
+        .find(|(k, _)| *k == key)?
+        .1;
+
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut iter = value.bytes();
+    while let Some(b) = iter.next() {
+        match b {
+            b'%' => {
+                let hex = [iter.next()?, iter.next()?];
+                let hex = std::str::from_utf8(&hex).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            b'+' => bytes.push(b' '),
+            _ => bytes.push(b),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_param() {
+        let query = "username=Steve&serverId=abc";
+        assert_eq!(get_query_param(query, "username").as_deref(), Some("Steve"));
+        assert_eq!(get_query_param(query, "serverId").as_deref(), Some("abc"));
+        assert_eq!(get_query_param(query, "ip"), None);
+        assert_eq!(
+            get_query_param("username=a%20b+c", "username").as_deref(),
+            Some("a b c")
+        );
+        assert_eq!(get_query_param("username=%2", "username"), None);
+    }
+
+    #[test]
+    fn has_joined_unknown_player() {
+        let response = route(
+            "GET",
+            "/sessionserver/session/minecraft/hasJoined",
+            "username=LanFriend&serverId=123",
+            &[],
+            0,
+        );
+        assert_eq!(response.status, "200 OK");
+        let json: serde_json::Value = serde_json::from_slice(&response.body).unwrap();
+        assert_eq!(json["name"], "LanFriend");
+        assert_eq!(json["id"], get_offline_uuid("LanFriend"));
+
+        let response = route(
+            "GET",
+            "/sessionserver/session/minecraft/hasJoined",
+            "",
+            &[],
+            0,
+        );
+        assert_eq!(response.status, "204 No Content");
+    }
+}
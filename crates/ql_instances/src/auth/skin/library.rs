@@ -0,0 +1,122 @@
+//! Skins saved in `QuantumLauncher/skins/`,
+//! to quickly switch between them.
+//!
+//! Each skin is stored as `NAME.png`, with its
+//! [`SkinVariant`] in `skins/index.json`.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use ql_core::{IntoIoError, IntoJsonError, LAUNCHER_DIR};
+
+use super::{check_skin, SkinError, SkinVariant};
+
+#[derive(Debug, Clone)]
+pub struct LibrarySkin {
+    pub name: String,
+    pub variant: SkinVariant,
+    /// The skin PNG
+    pub image: Vec<u8>,
+}
+
+fn get_dir() -> PathBuf {
+    LAUNCHER_DIR.join("skins")
+}
+
+async fn read_index() -> Result<HashMap<String, SkinVariant>, SkinError> {
+    let path = get_dir().join("index.json");
+    if !path.is_file() {
+        return Ok(HashMap::new());
+    }
+    let text = tokio::fs::read_to_string(&path).await.path(&path)?;
+    Ok(serde_json::from_str(&text).json(text)?)
+}
+
+async fn write_index(index: &HashMap<String, SkinVariant>) -> Result<(), SkinError> {
+    let path = get_dir().join("index.json");
+    let text = serde_json::to_string_pretty(index).json_to()?;
+    tokio::fs::write(&path, text).await.path(&path)?;
+    Ok(())
+}
+
+/// Characters that can't be in a file name on some platform
+fn sanitize_name(name: &str) -> String {
+    name.trim()
+        .chars()
+        .map(|c| {
+            if c.is_control() || "/\\:*?\"<>|".contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Lists all saved skins, sorted by name.
+///
+/// # Errors
+/// If the skins folder or index couldn't be read.
+pub async fn list() -> Result<Vec<LibrarySkin>, SkinError> {
+    let index = read_index().await?;
+    let mut skins = Vec::new();
+    for (name, variant) in index {
+        let path = get_dir().join(format!("{name}.png"));
+        // Deleted manually
+        if !path.is_file() {
+            continue;
+        }
+        let image = tokio::fs::read(&path).await.path(&path)?;
+        skins.push(LibrarySkin {
+            name,
+            variant,
+            image,
+        });
+    }
+    skins.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(skins)
+}
+
+/// Saves a skin to the library, replacing any skin
+/// with the same name. Returns the name it was saved as
+/// (with characters invalid in file names replaced).
+///
+/// # Errors
+/// - The image isn't a valid skin
+/// - The skin couldn't be saved
+pub async fn add(name: &str, image: &[u8], variant: SkinVariant) -> Result<String, SkinError> {
+    check_skin(image)?;
+    let name = sanitize_name(name);
+    let name = if name.is_empty() {
+        "Skin".to_owned()
+    } else {
+        name
+    };
+
+    let dir = get_dir();
+    tokio::fs::create_dir_all(&dir).await.path(&dir)?;
+    let path = dir.join(format!("{name}.png"));
+    tokio::fs::write(&path, image).await.path(&path)?;
+
+    let mut index = read_index().await?;
+    index.insert(name.clone(), variant);
+    write_index(&index).await?;
+    Ok(name)
+}
+
+/// Removes a skin from the library.
+///
+/// # Errors
+/// - There's no skin with that name
+/// - The skin couldn't be deleted
+pub async fn remove(name: &str) -> Result<(), SkinError> {
+    let mut index = read_index().await?;
+    if index.remove(name).is_none() {
+        return Err(SkinError::NotInLibrary(name.to_owned()));
+    }
+    let path = get_dir().join(format!("{name}.png"));
+    if path.is_file() {
+        tokio::fs::remove_file(&path).await.path(&path)?;
+    }
+    write_index(&index).await?;
+    Ok(())
+}
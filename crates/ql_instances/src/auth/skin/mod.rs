@@ -0,0 +1,420 @@
+//! # Skins and capes of logged-in accounts
+//!
+//! - [`get_profile`]: The account's current skin and capes
+//! - [`upload_skin`]: Changes the skin (from a PNG)
+//! - [`set_cape`]: Switches (or hides) the active cape
+//! - [`library`]: Skins saved locally, to switch between
+//! - [`render_preview`]: A 2D front view of a skin
+//!
+//! Microsoft accounts use the `api.minecraftservices.com`
+//! profile API. Ely.by, LittleSkin and custom accounts use the
+//! [authlib-injector](https://github.com/yushijinhun/authlib-injector/wiki/Yggdrasil-%E6%9C%8D%E5%8A%A1%E7%AB%AF%E6%8A%80%E6%9C%AF%E8%A7%84%E8%8C%83)
+//! session server and texture upload API, which
+//! only has one cape (chosen on the server's website).
+
+use std::fmt::Display;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ql_core::{
+    file_utils, impl_3_errs_jri, info, IntoJsonError, IoError, JsonError, RequestError, CLIENT,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{AccountData, AccountType};
+
+pub mod library;
+mod preview;
+
+pub use preview::{render_preview, SkinPreview};
+
+const MS_PROFILE_URL: &str = "https://api.minecraftservices.com/minecraft/profile";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SkinVariant {
+    /// Steve, 4 pixel wide arms
+    #[default]
+    #[serde(rename = "classic")]
+    Classic,
+    /// Alex, 3 pixel wide arms
+    #[serde(rename = "slim")]
+    Slim,
+}
+
+impl SkinVariant {
+    pub const ALL: &'static [Self] = &[Self::Classic, Self::Slim];
+}
+
+impl Display for SkinVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkinVariant::Classic => write!(f, "Classic (wide arms)"),
+            SkinVariant::Slim => write!(f, "Slim (thin arms)"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Skin {
+    pub url: String,
+    pub variant: SkinVariant,
+    /// The skin PNG
+    pub image: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cape {
+    /// Used to switch to this cape
+    /// (only for Microsoft accounts)
+    pub id: Option<String>,
+    pub name: String,
+    pub url: String,
+    pub is_active: bool,
+}
+
+/// The skin and capes an account currently has.
+#[derive(Debug, Clone)]
+pub struct SkinProfile {
+    /// `None` if the account uses the default skin
+    pub skin: Option<Skin>,
+    pub capes: Vec<Cape>,
+}
+
+impl SkinProfile {
+    #[must_use]
+    pub fn get_active_cape(&self) -> Option<&Cape> {
+        self.capes.iter().find(|n| n.is_active)
+    }
+}
+
+const SKIN_ERR_PREFIX: &str = "while managing skin/cape:\n";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SkinError {
+    #[error("{SKIN_ERR_PREFIX}{0}")]
+    Request(#[from] RequestError),
+    #[error("{SKIN_ERR_PREFIX}{0}")]
+    Json(#[from] JsonError),
+    #[error("{SKIN_ERR_PREFIX}{0}")]
+    Io(#[from] IoError),
+    #[error("{SKIN_ERR_PREFIX}invalid skin image: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("{SKIN_ERR_PREFIX}skin must be 64x64 or 64x32 pixels (found {0}x{1})")]
+    InvalidSize(u32, u32),
+    #[error("{SKIN_ERR_PREFIX}account isn't logged in (try refreshing it)")]
+    NotLoggedIn,
+    #[error("{SKIN_ERR_PREFIX}server didn't return any skin info")]
+    NoTextures,
+    #[error("{SKIN_ERR_PREFIX}{0}")]
+    Unsupported(&'static str),
+    #[error("{SKIN_ERR_PREFIX}skin {0:?} not found in library")]
+    NotInLibrary(String),
+    #[error("{SKIN_ERR_PREFIX}couldn't start local skin server: {0}")]
+    LocalServer(std::io::Error),
+}
+
+impl_3_errs_jri!(SkinError, Json, Request, Io);
+
+impl From<reqwest::Error> for SkinError {
+    fn from(value: reqwest::Error) -> Self {
+        Self::Request(RequestError::ReqwestError(value))
+    }
+}
+
+fn get_access_token(account: &AccountData) -> Result<&str, SkinError> {
+    match &account.access_token {
+        Some(token) if !account.needs_refresh => Ok(token),
+        _ => Err(SkinError::NotLoggedIn),
+    }
+}
+
+/// Gets the skin and capes the account currently has,
+/// including the skin image.
+///
+/// Microsoft accounts must be refreshed (have an access token).
+///
+/// # Errors
+/// - The Microsoft account isn't refreshed
+/// - The server couldn't be reached,
+///   or returned something unexpected
+pub async fn get_profile(account: &AccountData) -> Result<SkinProfile, SkinError> {
+    let (skin, capes) = match &account.account_type {
+        AccountType::Microsoft => ms_get_profile(get_access_token(account)?).await?,
+        account_type => yggdrasil_get_profile(account_type, &account.uuid).await?,
+    };
+
+    let skin = match skin {
+        Some((url, variant)) => Some(Skin {
+            image: file_utils::download_file_to_bytes(&url, false).await?,
+            url,
+            variant,
+        }),
+        None => None,
+    };
+    Ok(SkinProfile { skin, capes })
+}
+
+/// Changes the account's skin to the given PNG.
+///
+/// # Errors
+/// - The image isn't a valid skin (see [`check_skin`])
+/// - The account isn't refreshed
+/// - The server rejected the skin, or
+///   doesn't support uploading (ely.by)
+pub async fn upload_skin(
+    account: &AccountData,
+    image: Vec<u8>,
+    variant: SkinVariant,
+) -> Result<(), SkinError> {
+    check_skin(&image)?;
+    let token = get_access_token(account)?;
+    info!("Uploading skin for {}", account.nice_username);
+
+    let request = match &account.account_type {
+        AccountType::Microsoft => {
+            let variant = match variant {
+                SkinVariant::Classic => "classic",
+                SkinVariant::Slim => "slim",
+            };
+            let (content_type, body) = multipart_body(&[("variant", variant)], &image);
+            CLIENT
+                .post(format!("{MS_PROFILE_URL}/skins"))
+                .header("Content-Type", content_type)
+                .body(body)
+        }
+        AccountType::ElyBy => {
+            return Err(SkinError::Unsupported(
+                "ely.by skins can only be changed on https://ely.by",
+            ))
+        }
+        account_type => {
+            let model = match variant {
+                SkinVariant::Classic => "",
+                SkinVariant::Slim => "slim",
+            };
+            let (content_type, body) = multipart_body(&[("model", model)], &image);
+            CLIENT
+                .put(format!(
+                    "{}/api/user/profile/{}/skin",
+                    get_yggdrasil_root(account_type),
+                    account.uuid
+                ))
+                .header("Content-Type", content_type)
+                .body(body)
+        }
+    };
+
+    let response = request.bearer_auth(token).send().await?;
+    file_utils::check_for_success(&response)?;
+    Ok(())
+}
+
+/// Switches the account's active cape
+/// (by [`Cape::id`]), or hides it if `None`.
+///
+/// Only supported for Microsoft accounts.
+///
+/// # Errors
+/// - The account isn't a Microsoft account,
+///   or isn't refreshed
+/// - The server couldn't be reached
+pub async fn set_cape(account: &AccountData, cape_id: Option<&str>) -> Result<(), SkinError> {
+    if !account.is_microsoft() {
+        return Err(SkinError::Unsupported(
+            "capes for this account can only be changed on its website",
+        ));
+    }
+    let token = get_access_token(account)?;
+    let url = format!("{MS_PROFILE_URL}/capes/active");
+
+    let request = if let Some(cape_id) = cape_id {
+        CLIENT
+            .put(url)
+            .json(&serde_json::json!({ "capeId": cape_id }))
+    } else {
+        CLIENT.delete(url)
+    };
+    let response = request.bearer_auth(token).send().await?;
+    file_utils::check_for_success(&response)?;
+    Ok(())
+}
+
+/// Checks if the PNG can be used as a skin
+/// (64x64, or 64x32 for old skins).
+///
+/// # Errors
+/// If it isn't a PNG, or is the wrong size.
+pub fn check_skin(image: &[u8]) -> Result<(), SkinError> {
+    let image = image::load_from_memory_with_format(image, image::ImageFormat::Png)?;
+    match (image.width(), image.height()) {
+        (64, 64 | 32) => Ok(()),
+        (w, h) => Err(SkinError::InvalidSize(w, h)),
+    }
+}
+
+#[derive(Deserialize)]
+struct MsProfile {
+    #[serde(default)]
+    skins: Vec<MsTexture>,
+    #[serde(default)]
+    capes: Vec<MsTexture>,
+}
+
+#[derive(Deserialize)]
+struct MsTexture {
+    id: String,
+    state: String,
+    url: String,
+    variant: Option<String>,
+    alias: Option<String>,
+}
+
+async fn ms_get_profile(
+    token: &str,
+) -> Result<(Option<(String, SkinVariant)>, Vec<Cape>), SkinError> {
+    let response = CLIENT.get(MS_PROFILE_URL).bearer_auth(token).send().await?;
+    file_utils::check_for_success(&response)?;
+    let text = response.text().await?;
+    let profile: MsProfile = serde_json::from_str(&text).json(text)?;
+
+    let skin = profile
+        .skins
+        .into_iter()
+        .find(|n| n.state == "ACTIVE")
+        .map(|n| {
+            let variant = if n.variant.as_deref() == Some("SLIM") {
+                SkinVariant::Slim
+            } else {
+                SkinVariant::Classic
+            };
+            (n.url, variant)
+        });
+    let capes = profile
+        .capes
+        .into_iter()
+        .map(|n| Cape {
+            is_active: n.state == "ACTIVE",
+            name: n.alias.unwrap_or_else(|| n.id.clone()),
+            id: Some(n.id),
+            url: n.url,
+        })
+        .collect();
+    Ok((skin, capes))
+}
+
+#[derive(Deserialize)]
+struct YggdrasilProfile {
+    #[serde(default)]
+    properties: Vec<YggdrasilProperty>,
+}
+
+#[derive(Deserialize)]
+struct YggdrasilProperty {
+    name: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct YggdrasilTextures {
+    #[serde(default)]
+    textures: YggdrasilTextureMap,
+}
+
+#[derive(Deserialize, Default)]
+struct YggdrasilTextureMap {
+    #[serde(rename = "SKIN")]
+    skin: Option<YggdrasilTexture>,
+    #[serde(rename = "CAPE")]
+    cape: Option<YggdrasilTexture>,
+}
+
+#[derive(Deserialize)]
+struct YggdrasilTexture {
+    url: String,
+    metadata: Option<YggdrasilTextureMetadata>,
+}
+
+#[derive(Deserialize)]
+struct YggdrasilTextureMetadata {
+    model: Option<String>,
+}
+
+fn get_yggdrasil_root(account_type: &AccountType) -> &str {
+    match account_type {
+        AccountType::Microsoft => unreachable!(),
+        AccountType::ElyBy => "https://authserver.ely.by/api/authlib-injector",
+        AccountType::LittleSkin => "https://littleskin.cn/api/yggdrasil",
+        AccountType::Custom(api_root) => api_root,
+    }
+}
+
+async fn yggdrasil_get_profile(
+    account_type: &AccountType,
+    uuid: &str,
+) -> Result<(Option<(String, SkinVariant)>, Vec<Cape>), SkinError> {
+    let url = format!(
+        "{}/sessionserver/session/minecraft/profile/{uuid}?unsigned=true",
+        get_yggdrasil_root(account_type)
+    );
+    let profile: YggdrasilProfile = file_utils::download_file_to_json(&url, false).await?;
+
+    let textures = profile
+        .properties
+        .into_iter()
+        .find(|n| n.name == "textures")
+        .ok_or(SkinError::NoTextures)?;
+    let textures = STANDARD
+        .decode(&textures.value)
+        .map_err(|_| SkinError::NoTextures)?;
+    let textures = String::from_utf8_lossy(&textures);
+    let textures: YggdrasilTextures = serde_json::from_str(&textures).json(textures.to_string())?;
+
+    let skin = textures.textures.skin.map(|n| {
+        let is_slim = n
+            .metadata
+            .and_then(|n| n.model)
+            .is_some_and(|n| n == "slim");
+        let variant = if is_slim {
+            SkinVariant::Slim
+        } else {
+            SkinVariant::Classic
+        };
+        (n.url, variant)
+    });
+    let capes = textures
+        .textures
+        .cape
+        .map(|n| Cape {
+            id: None,
+            name: "Cape".to_owned(),
+            url: n.url,
+            is_active: true,
+        })
+        .into_iter()
+        .collect();
+    Ok((skin, capes))
+}
+
+/// Builds a `multipart/form-data` body with some text
+/// fields and a PNG `file`, returning `(content_type, body)`.
+fn multipart_body(fields: &[(&str, &str)], png: &[u8]) -> (String, Vec<u8>) {
+    // Can't appear in the file itself
+    let boundary = format!("QuantumLauncher{}", file_utils::get_sha1(png));
+
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!("--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"skin.png\"\r\nContent-Type: image/png\r\n\r\n")
+            .as_bytes(),
+    );
+    body.extend_from_slice(png);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+    (format!("multipart/form-data; boundary={boundary}"), body)
+}
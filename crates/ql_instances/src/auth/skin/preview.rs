@@ -0,0 +1,171 @@
+use image::{imageops, RgbaImage};
+
+use super::{SkinError, SkinVariant};
+
+/// A front view of a skin, as raw RGBA pixels.
+///
+/// It's tiny (16x32), so scale it up with
+/// nearest-neighbour filtering when displaying.
+#[derive(Clone)]
+pub struct SkinPreview {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl std::fmt::Debug for SkinPreview {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SkinPreview")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A part of the skin texture: `(x, y, width, height)`
+type Area = (u32, u32, u32, u32);
+
+/// Renders the front of the head, body, arms and legs
+/// (with their outer layers) from a skin PNG.
+///
+/// # Errors
+/// If the image isn't a valid skin.
+pub fn render_preview(png: &[u8], variant: SkinVariant) -> Result<SkinPreview, SkinError> {
+    super::check_skin(png)?;
+    let skin = image::load_from_memory_with_format(png, image::ImageFormat::Png)?.to_rgba8();
+    // Skins from before 1.8 have no separate left arm/leg
+    // (they mirror the right ones) and no outer layers
+    // other than the hat
+    let is_legacy = skin.height() == 32;
+    let arm = if variant == SkinVariant::Slim { 3 } else { 4 };
+
+    let mut out = RgbaImage::new(16, 32);
+    let mut draw = |area: Area, x: u32, y: u32, flip: bool| {
+        let (ax, ay, w, h) = area;
+        let part = imageops::crop_imm(&skin, ax, ay, w, h).to_image();
+        let part = if flip {
+            imageops::flip_horizontal(&part)
+        } else {
+            part
+        };
+        imageops::overlay(&mut out, &part, i64::from(x), i64::from(y));
+    };
+
+    // Head and hat
+    draw((8, 8, 8, 8), 4, 0, false);
+    draw((40, 8, 8, 8), 4, 0, false);
+    // Body
+    draw((20, 20, 8, 12), 4, 8, false);
+    // Right arm and leg (on the left side of the image)
+    draw((44, 20, arm, 12), 4 - arm, 8, false);
+    draw((4, 20, 4, 12), 4, 20, false);
+
+    if is_legacy {
+        draw((44, 20, arm, 12), 12, 8, true);
+        draw((4, 20, 4, 12), 8, 20, true);
+    } else {
+        draw((36, 52, arm, 12), 12, 8, false);
+        draw((20, 52, 4, 12), 8, 20, false);
+
+        // Jacket, sleeves and pants
+        draw((20, 36, 8, 12), 4, 8, false);
+        draw((44, 36, arm, 12), 4 - arm, 8, false);
+        draw((52, 52, arm, 12), 12, 8, false);
+        draw((4, 36, 4, 12), 4, 20, false);
+        draw((4, 52, 4, 12), 8, 20, false);
+    }
+
+    Ok(SkinPreview {
+        width: out.width(),
+        height: out.height(),
+        rgba: out.into_raw(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const GREEN: Rgba<u8> = Rgba([0, 255, 0, 255]);
+    const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+    const YELLOW: Rgba<u8> = Rgba([255, 255, 0, 255]);
+
+    fn fill(img: &mut RgbaImage, (x, y, w, h): Area, color: Rgba<u8>) {
+        for py in y..y + h {
+            for px in x..x + w {
+                img.put_pixel(px, py, color);
+            }
+        }
+    }
+
+    /// A skin with the head, body, right arm
+    /// and left arm (if not legacy) filled in.
+    fn make_skin(height: u32) -> Vec<u8> {
+        let mut img = RgbaImage::new(64, height);
+        fill(&mut img, (8, 8, 8, 8), RED);
+        fill(&mut img, (20, 20, 8, 12), GREEN);
+        fill(&mut img, (44, 20, 4, 12), BLUE);
+        if height == 64 {
+            fill(&mut img, (36, 52, 4, 12), YELLOW);
+        }
+        let mut png = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        png
+    }
+
+    fn pixel(preview: &SkinPreview, x: u32, y: u32) -> Rgba<u8> {
+        let i = ((y * preview.width + x) * 4) as usize;
+        Rgba(preview.rgba[i..i + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn classic_skin() {
+        let preview = render_preview(&make_skin(64), SkinVariant::Classic).unwrap();
+        assert_eq!((preview.width, preview.height), (16, 32));
+        assert_eq!(preview.rgba.len(), 16 * 32 * 4);
+
+        assert_eq!(pixel(&preview, 4, 0), RED);
+        assert_eq!(pixel(&preview, 11, 7), RED);
+        assert_eq!(pixel(&preview, 4, 8), GREEN);
+        assert_eq!(pixel(&preview, 0, 8), BLUE);
+        assert_eq!(pixel(&preview, 12, 8), YELLOW);
+        assert_eq!(pixel(&preview, 15, 19), YELLOW);
+        // Empty outer layers don't cover anything
+        assert_eq!(pixel(&preview, 4, 19), GREEN);
+    }
+
+    #[test]
+    fn slim_skin() {
+        let preview = render_preview(&make_skin(64), SkinVariant::Slim).unwrap();
+        // 3 pixel wide arms
+        assert_eq!(pixel(&preview, 0, 8)[3], 0);
+        assert_eq!(pixel(&preview, 1, 8), BLUE);
+        assert_eq!(pixel(&preview, 14, 8), YELLOW);
+        assert_eq!(pixel(&preview, 15, 8)[3], 0);
+    }
+
+    #[test]
+    fn legacy_skin() {
+        let preview = render_preview(&make_skin(32), SkinVariant::Classic).unwrap();
+        assert_eq!(pixel(&preview, 4, 0), RED);
+        // The left arm mirrors the right one
+        assert_eq!(pixel(&preview, 0, 8), BLUE);
+        assert_eq!(pixel(&preview, 12, 8), BLUE);
+    }
+
+    #[test]
+    fn invalid_skin() {
+        let mut png = Vec::new();
+        RgbaImage::new(32, 32)
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        assert!(matches!(
+            render_preview(&png, SkinVariant::Classic),
+            Err(SkinError::InvalidSize(32, 32))
+        ));
+        assert!(render_preview(b"not a png", SkinVariant::Classic).is_err());
+    }
+}
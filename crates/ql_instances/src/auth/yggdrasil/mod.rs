@@ -32,7 +32,7 @@ pub async fn login_new(
         "password": &password,
         "clientToken": account_type.get_client_id()
     });
-    insert_agent_field(account_type, &mut value);
+    insert_agent_field(&account_type, &mut value);
 
     let response = CLIENT
         .post(account_type.yggdrasil_authenticate())
@@ -59,8 +59,9 @@ pub async fn login_new(
         }
     };
 
-    let entry = account_type.get_keyring_entry(&email_or_username)?;
+    let entry = account_type.get_keyring_entry(&email_or_username);
     entry.set_password(&account_response.accessToken)?;
+    let authlib_metadata = super::authlib::fetch_metadata(&account_type).await;
 
     Ok(Account::Account(AccountData {
         access_token: Some(account_response.accessToken.clone()),
@@ -72,10 +73,11 @@ pub async fn login_new(
         refresh_token: account_response.accessToken,
         needs_refresh: false,
         account_type,
+        authlib_metadata,
     }))
 }
 
-fn insert_agent_field(account_type: AccountType, value: &mut serde_json::Value) {
+fn insert_agent_field(account_type: &AccountType, value: &mut serde_json::Value) {
     if account_type.yggdrasil_needs_agent_field() {
         if let (Some(value), Ok(insert)) = (value.as_object_mut(), serde_json::to_value(AGENT)) {
             value.insert("agent".to_owned(), insert);
@@ -89,13 +91,13 @@ pub async fn login_refresh(
     account_type: AccountType,
 ) -> Result<AccountData, Error> {
     pt!("Refreshing {account_type} account...");
-    let entry = account_type.get_keyring_entry(&email_or_username)?;
+    let entry = account_type.get_keyring_entry(&email_or_username);
 
     let mut value = serde_json::json!({
         "accessToken": refresh_token,
         "clientToken": account_type.get_client_id()
     });
-    insert_agent_field(account_type, &mut value);
+    insert_agent_field(&account_type, &mut value);
     let response = CLIENT
         .post(account_type.yggdrasil_refresh())
         .json(&value)
@@ -117,5 +119,7 @@ pub async fn login_refresh(
         refresh_token: account_response.accessToken,
         needs_refresh: false,
         account_type,
+        // Kept from login by the caller
+        authlib_metadata: None,
     })
 }
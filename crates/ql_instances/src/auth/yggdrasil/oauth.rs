@@ -1,5 +1,5 @@
 use crate::auth::alt::OauthError;
-use keyring;
+use crate::auth::AccountType;
 use ql_core::file_utils::check_for_success;
 use ql_core::{IntoJsonError, CLIENT};
 use serde::{Deserialize, Serialize};
@@ -97,11 +97,11 @@ pub async fn poll_device_token(
     }
 
     // Store Minecraft token in keyring (same convention as password flow)
-    keyring::Entry::new(
-        "QuantumLauncher",
-        &format!("{}#littleskin", user_info.username),
-    )
-    .and_then(|e| e.set_password(&mc_token_resp.access_token))?;
+    AccountType::LittleSkin
+        .get_keyring_entry(&user_info.username)
+        .set_password(&mc_token_resp.access_token)?;
+
+    let authlib_metadata = crate::auth::authlib::fetch_metadata(&AccountType::LittleSkin).await;
 
     // Build account data compatible with existing flows
     Ok(super::Account::Account(super::AccountData {
@@ -119,6 +119,7 @@ pub async fn poll_device_token(
         refresh_token: mc_token_resp.access_token,
         needs_refresh: false,
         account_type: crate::auth::AccountType::LittleSkin,
+        authlib_metadata,
     }))
 }
 
@@ -332,6 +332,11 @@ impl GameDownloader {
                 is_special_lwjgl3: self.version_json.id.ends_with("-lwjgl3"),
             }),
             main_class_override: None,
+            modpack: None,
+            jvm_preset: None,
+            auto_ram: None,
+            account: None,
+            quick_play: None,
         };
         let config_json = serde_json::to_string(&config_json).json_to()?;
 
@@ -1,5 +1,10 @@
 use crate::{
-    auth::{ms::CLIENT_ID, AccountData, AccountType},
+    auth::{
+        authlib,
+        ms::CLIENT_ID,
+        offline::{self, OfflineProfile},
+        AccountData, AccountType,
+    },
     download::GameDownloader,
     jarmod,
 };
@@ -7,12 +12,14 @@ use ql_core::{
     err, file_utils, info,
     json::{
         forge, version::Library, FabricJSON, GlobalSettings, InstanceConfigJson, JsonOptifine,
-        VersionDetails, V_1_12_2, V_1_5_2, V_PAULSCODE_LAST, V_PRECLASSIC_LAST,
+        VersionDetails, V_1_12_2, V_1_5_2, V_PAULSCODE_LAST, V_PRECLASSIC_LAST, V_QUICK_PLAY_FIRST,
     },
     pt, GenericProgress, InstanceSelection, IntoIoError, IntoJsonError, IoError, JsonFileError,
     Loader, CLASSPATH_SEPARATOR, LAUNCHER_DIR,
 };
-use ql_java_handler::{get_java_binary, JavaVersion};
+use ql_java_handler::{
+    get_java_binary, get_jvm_args, JavaInfo, JavaMismatch, JavaRequirement, JavaVersion,
+};
 use std::{
     collections::HashSet,
     path::{Path, PathBuf},
@@ -21,7 +28,7 @@ use std::{
 };
 use tokio::process::Command;
 
-use super::{error::GameLaunchError, replace_var};
+use super::{error::GameLaunchError, replace_var, LaunchOverrides, QuickPlay};
 
 pub struct GameLauncher {
     username: String,
@@ -45,6 +52,14 @@ pub struct GameLauncher {
     /// can be overridden by `config_json.global_settings`.
     global_settings: Option<GlobalSettings>,
     extra_java_args: Vec<String>,
+    extra_game_args: Vec<String>,
+    quick_play: Option<QuickPlay>,
+    detached: bool,
+    /// The Java picked by [`Self::get_java_path`],
+    /// and its info (`None` if it couldn't be checked)
+    java: Option<(PathBuf, Option<JavaInfo>)>,
+    /// Used when playing offline (without an account)
+    offline_profile: Option<OfflineProfile>,
 }
 
 impl GameLauncher {
@@ -53,7 +68,8 @@ impl GameLauncher {
         username: String,
         java_install_progress_sender: Option<Sender<GenericProgress>>,
         global_settings: Option<GlobalSettings>,
-        extra_java_args: Vec<String>,
+        overrides: LaunchOverrides,
+        offline_profile: Option<OfflineProfile>,
     ) -> Result<Self, GameLaunchError> {
         let instance_dir = get_instance_dir(&instance_name).await?;
 
@@ -62,7 +78,21 @@ impl GameLauncher {
             .await
             .path(&minecraft_dir)?;
 
-        let config = InstanceConfigJson::read_from_dir(&instance_dir).await?;
+        let mut config = InstanceConfigJson::read_from_dir(&instance_dir).await?;
+        if let Some(java) = overrides.java {
+            config.java_override = Some(java.to_string_lossy().to_string());
+            config.java_override_version = None;
+        }
+        if let Some(ram) = overrides.ram_in_mb {
+            config.ram_in_mb = ram;
+            config.auto_ram = Some(false);
+        }
+
+        // One-off choice, or the instance's setting
+        let quick_play = overrides
+            .quick_play
+            .or_else(|| config.quick_play.clone())
+            .filter(|n| !n.target().trim().is_empty());
 
         let instance = InstanceSelection::Instance(instance_name.clone());
         let mut version_json = VersionDetails::load(&instance).await?;
@@ -77,7 +107,12 @@ impl GameLauncher {
             config,
             version_json,
             global_settings,
-            extra_java_args,
+            extra_java_args: overrides.java_args,
+            extra_game_args: overrides.game_args,
+            quick_play,
+            detached: overrides.detached,
+            java: None,
+            offline_profile,
         })
     }
 
@@ -101,7 +136,7 @@ impl GameLauncher {
                 )));
             };
 
-        if let Some(account_type) = account_details.map(|n| n.account_type) {
+        if let Some(account_type) = account_details.map(|n| &n.account_type) {
             if matches!(account_type, AccountType::ElyBy | AccountType::LittleSkin)
                 && !self.version_json.is_legacy_version()
                 && !game_arguments.iter().any(|n| n.contains("uuid"))
@@ -126,10 +161,46 @@ impl GameLauncher {
         }
 
         game_arguments.extend(self.config.game_args.clone().unwrap_or_default());
+        game_arguments.extend(self.extra_game_args.iter().cloned());
+        self.add_quick_play_args(&mut game_arguments);
 
         Ok(game_arguments)
     }
 
+    fn add_quick_play_args(&self, game_arguments: &mut Vec<String>) {
+        let Some(quick_play) = &self.quick_play else {
+            return;
+        };
+        let is_new = self.version_json.is_after_or_eq(V_QUICK_PLAY_FIRST);
+
+        match quick_play {
+            QuickPlay::Server(address) if is_new => {
+                game_arguments.push("--quickPlayMultiplayer".to_owned());
+                // The game expects IPv6 addresses in brackets
+                if address.matches(':').count() > 1 && !address.starts_with('[') {
+                    game_arguments.push(format!("[{address}]"));
+                } else {
+                    game_arguments.push(address.clone());
+                }
+            }
+            QuickPlay::Server(address) => {
+                // Older versions take the host and port separately
+                let (host, port) = split_server_address(address);
+                game_arguments.push("--server".to_owned());
+                game_arguments.push(host.to_owned());
+                game_arguments.push("--port".to_owned());
+                game_arguments.push(port.to_owned());
+            }
+            QuickPlay::World(world) if is_new => {
+                game_arguments.push("--quickPlaySingleplayer".to_owned());
+                game_arguments.push(world.clone());
+            }
+            QuickPlay::World(world) => {
+                err!("Can't join world {world:?} directly: only supported in 1.20 and above");
+            }
+        }
+    }
+
     pub async fn fill_game_arguments(
         &self,
         game_arguments: &mut [String],
@@ -147,10 +218,13 @@ impl GameLauncher {
             replace_var(arg, "auth_xuid", "0");
 
             let uuid = if let Some(account_details) = account_details {
-                &account_details.uuid
+                account_details.uuid.clone()
+            } else if let Some(profile) = &self.offline_profile {
+                profile.uuid.clone()
             } else {
-                "00000000-0000-0000-0000-000000000000"
+                offline::get_offline_uuid(&self.username)
             };
+            let uuid = uuid.as_str();
             replace_var(arg, "auth_uuid", uuid);
             replace_var(arg, "uuid", uuid);
 
@@ -233,11 +307,20 @@ impl GameLauncher {
             .to_str()
             .ok_or(GameLaunchError::PathBufToString(natives_path.clone()))?;
 
+        self.get_java_path().await?;
+        let java_info = self.java.as_ref().and_then(|(_, info)| info.as_ref());
+        let jvm_args = get_jvm_args(
+            &self.config,
+            &self.extra_java_args,
+            java_info,
+            &InstanceSelection::new(&self.instance_name, false),
+        )
+        .await;
+
         // TODO: deal with self.version_json.arguments.jvm (currently ignored)
-        let mut args: Vec<String> = self
-            .config
-            .get_java_args(&self.extra_java_args)
+        let mut args: Vec<String> = jvm_args
             .into_iter()
+            .chain(self.version_json.q_patch_java_args.iter().cloned())
             .filter(|arg| !arg.trim().is_empty())
             .chain([
                 "-Dminecraft.launcher.brand=minecraft-launcher".to_owned(),
@@ -246,11 +329,26 @@ impl GameLauncher {
                 format!("-Djna.tmpdir={natives_path}"),
                 format!("-Dorg.lwjgl.system.SharedLibraryExtractPath={natives_path}"),
                 format!("-Dio.netty.native.workdir={natives_path}"),
-                self.config.get_ram_argument(),
             ])
             .collect();
 
-        if auth.is_none_or(|n| !n.is_microsoft()) && self.version_json.id.starts_with("1.16") {
+        let offline_server = match (auth, &self.offline_profile) {
+            (None, Some(profile)) if profile.skin.is_some() => {
+                match offline::serve_skin(profile).await {
+                    Ok(url) => Some(url),
+                    Err(error) => {
+                        err!("Couldn't load skin of offline profile: {error}");
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        if let Some(server) = &offline_server {
+            args.push(crate::auth::get_authlib_injector(server).await?);
+        } else if auth.is_none_or(|n| !n.is_microsoft()) && self.version_json.id.starts_with("1.16")
+        {
             // Fixes "Multiplayer is disabled" issue on 1.16.x
             args.push("-Dminecraft.api.auth.host=https://nope.invalid".to_owned());
             args.push("-Dminecraft.api.account.host=https://nope.invalid".to_owned());
@@ -258,6 +356,11 @@ impl GameLauncher {
             args.push("-Dminecraft.api.services.host=https://nope.invalid".to_owned());
         } else if let Some(authlib) = auth.and_then(AccountData::get_authlib_url) {
             args.push(crate::auth::get_authlib_injector(authlib).await?);
+            // Otherwise authlib-injector fetches the server info
+            // (and skin signature key) itself on every startup
+            if let Some(metadata) = auth.and_then(|n| n.authlib_metadata.as_deref()) {
+                args.push(authlib::get_prefetched_arg(metadata));
+            }
         }
 
         if cfg!(target_pointer_width = "32") {
@@ -741,13 +844,50 @@ impl GameLauncher {
         Ok(())
     }
 
-    pub async fn get_java_command(&mut self) -> Result<(Command, PathBuf), GameLaunchError> {
+    /// Picks the Java to run the game with.
+    ///
+    /// If the user-provided Java (override) can't run this version
+    /// (too old, too new for LWJGL 2, or wrong architecture),
+    /// the launcher-provided Java is used instead.
+    /// If it couldn't be checked at all, it's used anyway.
+    ///
+    /// The choice is remembered, so this can be called
+    /// multiple times without installing or probing Java again.
+    pub async fn get_java_path(&mut self) -> Result<PathBuf, GameLaunchError> {
+        if let Some((path, _)) = &self.java {
+            return Ok(path.clone());
+        }
+        let (path, info) = self.pick_java().await?;
+        self.java = Some((path.clone(), info));
+        Ok(path)
+    }
+
+    async fn pick_java(&mut self) -> Result<(PathBuf, Option<JavaInfo>), GameLaunchError> {
+        let requirement = JavaRequirement::for_client(&self.version_json);
+
         if let Some(java_override) = self.config.get_java_override() {
-            info!("Java (override): {java_override:?}\n");
-            return Ok((Command::new(&java_override), java_override));
+            match requirement.check_binary(&java_override).await {
+                Ok(java) => {
+                    info!("Java (override): {java} {java_override:?}\n");
+                    return Ok((java_override, Some(java)));
+                }
+                // Not being able to check (wrapper scripts, unusual
+                // output) doesn't mean it can't run the game
+                Err(JavaMismatch::NotJava) => {
+                    err!("Couldn't check Java override {java_override:?}, using it anyway");
+                    return Ok((java_override, None));
+                }
+                Err(mismatch) => err!(
+                    "Can't use Java override {java_override:?}: {mismatch}\nUsing launcher-provided Java instead"
+                ),
+            }
         }
         let version = if let Some(version) = self.config.java_override_version {
-            version.into()
+            let clamped = requirement.clamp(version);
+            if clamped != version {
+                err!("Java {version} (override) can't run this version, using Java {clamped} instead");
+            }
+            clamped.into()
         } else if let Some(version) = self.version_json.javaVersion.clone() {
             version.into()
         } else {
@@ -765,7 +905,8 @@ impl GameLauncher {
         )
         .await?;
         info!("Java: {program:?}\n");
-        Ok((Command::new(&program), program))
+        let info = JavaInfo::for_managed(program.clone(), version);
+        Ok((program, Some(info)))
     }
 
     pub async fn cleanup_junk_files(&self) -> Result<(), GameLaunchError> {
@@ -792,7 +933,8 @@ impl GameLauncher {
         game_arguments: Vec<String>,
         java_arguments: Vec<String>,
     ) -> Result<(Command, PathBuf), GameLaunchError> {
-        let (mut command, mut path) = self.get_java_command().await?;
+        let mut path = self.get_java_path().await?;
+        let mut command = Command::new(&path);
 
         let prefix_commands = self.config.build_launch_prefix(
             self.global_settings
@@ -830,7 +972,15 @@ impl GameLauncher {
         }
 
         command.current_dir(&self.minecraft_dir);
-        if self.config.enable_logger.unwrap_or(true) {
+        if self.detached {
+            command
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .stdin(Stdio::null());
+            // Don't get killed along with the terminal (eg: Ctrl+C)
+            #[cfg(target_family = "unix")]
+            command.process_group(0);
+        } else if self.config.enable_logger.unwrap_or(true) {
             command.stdout(Stdio::piped()).stderr(Stdio::piped());
         }
 
@@ -958,6 +1108,32 @@ async fn migrate_to_new_assets_path(
     Ok(())
 }
 
+/// Splits a server address into host and port
+/// (25565 if not given), handling IPv6:
+///
+/// `"mc.example.com:25566"`, `"[::1]:25566"` or `"::1"`
+fn split_server_address(address: &str) -> (&str, &str) {
+    const DEFAULT_PORT: &str = "25565";
+    if let Some(rest) = address.strip_prefix('[') {
+        if let Some((host, rest)) = rest.split_once(']') {
+            let port = rest
+                .strip_prefix(':')
+                .filter(|n| n.parse::<u16>().is_ok())
+                .unwrap_or(DEFAULT_PORT);
+            return (host, port);
+        }
+    }
+    // More than one `:` means a bare IPv6 address, without a port
+    if address.matches(':').count() == 1 {
+        if let Some((host, port)) = address.split_once(':') {
+            if port.parse::<u16>().is_ok() {
+                return (host, port);
+            }
+        }
+    }
+    (address, DEFAULT_PORT)
+}
+
 fn get_after_p(args: &[String]) -> Option<String> {
     args.iter()
         .position(|arg| arg == "-p")
@@ -1015,3 +1191,28 @@ fn deduplicate_game_args(arr1: &[String], arr2: &[String]) -> Vec<String> {
     // HashMap -> Vec<String> (key, value, key, value, ...)
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::split_server_address;
+
+    #[test]
+    fn server_address() {
+        assert_eq!(
+            split_server_address("example.com"),
+            ("example.com", "25565")
+        );
+        assert_eq!(
+            split_server_address("example.com:25566"),
+            ("example.com", "25566")
+        );
+        assert_eq!(split_server_address("127.0.0.1:1"), ("127.0.0.1", "1"));
+        assert_eq!(split_server_address("[::1]:25566"), ("::1", "25566"));
+        assert_eq!(split_server_address("[::1]"), ("::1", "25565"));
+        assert_eq!(split_server_address("::1"), ("::1", "25565"));
+        assert_eq!(
+            split_server_address("2001:db8::1"),
+            ("2001:db8::1", "25565")
+        );
+    }
+}
@@ -1,15 +1,43 @@
-use crate::auth::AccountData;
+use crate::auth::{offline::OfflineProfile, AccountData};
 use error::GameLaunchError;
 use ql_core::{
     err, info, GenericProgress, InstanceSelection, LaunchedProcess, REDACT_SENSITIVE_INFO,
 };
-use std::sync::{mpsc::Sender, Arc};
+use std::{
+    path::PathBuf,
+    sync::{mpsc::Sender, Arc},
+};
 use tokio::sync::Mutex;
 
 pub(super) mod error;
 mod launcher;
 pub use launcher::GameLauncher;
 use ql_core::json::GlobalSettings;
+pub use ql_core::json::QuickPlay;
+
+/// One-off changes to how an instance is launched,
+/// on top of its saved config. Nothing here is saved.
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOverrides {
+    /// Use this `java` binary instead of the configured one.
+    pub java: Option<PathBuf>,
+    /// Allocate this much RAM (in MB) instead of the configured amount.
+    pub ram_in_mb: Option<usize>,
+    /// Extra arguments for Java, added after the configured ones.
+    pub java_args: Vec<String>,
+    /// Extra arguments for the game, added after the configured ones.
+    pub game_args: Vec<String>,
+    /// Join a server or world right after the game starts,
+    /// instead of the instance's `quick_play` setting.
+    pub quick_play: Option<QuickPlay>,
+    /// Don't capture the game's output, and (on Unix) start it
+    /// in its own process group so it keeps running after
+    /// the launcher (or terminal) closes.
+    pub detached: bool,
+    /// Ignore the instance's "close launcher on start" setting,
+    /// for launchers that must keep running (eg: a daemon).
+    pub keep_launcher_open: bool,
+}
 
 /// Launches a Minecraft instance.
 ///
@@ -23,14 +51,19 @@ use ql_core::json::GlobalSettings;
 /// - `auth`: Account authentication data. Pass `None` for offline play.
 /// - `global_settings`: Global launcher-level settings that apply to instance
 ///   like window width/height, etc.
-/// - `extra_java_args`
+/// - `overrides`: One-off changes on top of the instance config
+///   (Java, RAM, extra arguments, quick play). See [`LaunchOverrides`].
+/// - `offline_profile`: The offline profile to play as, if `auth` is `None`.
+///   Gives a stable UUID and (optionally) a skin. Without it,
+///   the UUID is derived from `username`.
 pub async fn launch(
     instance_name: String,
     username: String,
     java_install_progress_sender: Option<Sender<GenericProgress>>,
     auth: Option<AccountData>,
     global_settings: Option<GlobalSettings>,
-    extra_java_args: Vec<String>,
+    overrides: LaunchOverrides,
+    offline_profile: Option<OfflineProfile>,
 ) -> Result<LaunchedProcess, GameLaunchError> {
     if username.is_empty() {
         return Err(GameLaunchError::UsernameIsEmpty);
@@ -39,12 +72,14 @@ pub async fn launch(
         return Err(GameLaunchError::UsernameHasSpaces);
     }
 
+    let keep_launcher_open = overrides.keep_launcher_open;
     let mut game_launcher = GameLauncher::new(
         instance_name.clone(),
         username,
         java_install_progress_sender,
         global_settings,
-        extra_java_args,
+        overrides,
+        offline_profile,
     )
     .await?;
 
@@ -104,7 +139,7 @@ pub async fn launch(
         err!("No ID found!");
     }
 
-    if game_launcher.config.close_on_start.unwrap_or(false) {
+    if game_launcher.config.close_on_start.unwrap_or(false) && !keep_launcher_open {
         ql_core::logger_finish();
         std::process::exit(0);
     }
@@ -53,16 +53,23 @@
 pub mod auth;
 mod download;
 mod instance;
-mod json_profiles;
+pub mod json_profiles;
 mod launcher_update_detector;
 
 pub use download::{create_instance, repeat_stage, DownloadError};
-pub use instance::{launch::launch, list_versions::list_versions, notes};
+pub use instance::{
+    launch::{launch, LaunchOverrides, QuickPlay},
+    list_versions::list_versions,
+    notes,
+};
 pub use launcher_update_detector::{
     check_for_launcher_updates, install_launcher_update, UpdateCheckInfo, UpdateError,
 };
 pub use ql_core::jarmod;
-pub use ql_java_handler::delete_java_installs;
+pub use ql_java_handler::{
+    check_java_update, delete_java_install, delete_java_installs, delete_unused_java_installs,
+    find_system_java, list_java_installs, reinstall_java, repair_java, JavaInfo, JavaInstall,
+};
 
 use semver::{BuildMetadata, Prerelease};
 
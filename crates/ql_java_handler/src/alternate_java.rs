@@ -10,23 +10,38 @@ use std::{
 
 use cfg_if::cfg_if;
 use owo_colors::OwoColorize;
-use ql_core::{file_utils, pt, GenericProgress, JavaVersion};
+use ql_core::{err, file_utils, pt, GenericProgress, JavaVersion};
 use serde::Deserialize;
 
-use crate::{extract_tar_gz, send_progress, JavaInstallError};
+use crate::{extract_tar_gz, send_progress, verify::Checksum, JavaInstallError};
 
+/// Installs Java from a third party source,
+/// returning the URL it was downloaded from.
 pub(crate) async fn install(
     version: JavaVersion,
     sender: Option<&Sender<GenericProgress>>,
     install_dir: &Path,
-) -> Result<(), JavaInstallError> {
-    let Some(url) = get_url(version).await? else {
+) -> Result<String, JavaInstallError> {
+    let Some(download) = get_download(version).await? else {
         return Err(JavaInstallError::UnsupportedPlatform);
     };
+    let checksum = match download.get_checksum().await {
+        Ok(n) => n,
+        Err(err) => {
+            err!("Couldn't get checksum of Java archive: {err}");
+            None
+        }
+    };
+    let url = download.url;
 
     progress(sender, "Getting compressed archive", 0);
     pt!("URL: {}", url.bright_black());
     let file_bytes = file_utils::download_file_to_bytes(&url, false).await?;
+    if let Some(checksum) = checksum {
+        checksum.verify(&file_bytes, &url)?;
+    } else {
+        pt!("No checksum available, skipping verification");
+    }
 
     progress(sender, "Extracting archive", 1);
     if url.ends_with("tar.gz") {
@@ -36,7 +51,7 @@ pub(crate) async fn install(
     } else {
         return Err(JavaInstallError::UnknownExtension(url.to_owned()));
     }
-    Ok(())
+    Ok(url)
 }
 
 fn progress(sender: Option<&Sender<GenericProgress>>, msg: &str, done: usize) {
@@ -52,22 +67,97 @@ fn progress(sender: Option<&Sender<GenericProgress>>, msg: &str, done: usize) {
     );
 }
 
-async fn get_url(mut version: JavaVersion) -> Result<Option<String>, JavaInstallError> {
+/// A Java archive from a third party source
+pub(crate) struct AltDownload {
+    pub url: String,
+    source: AltSource,
+}
+
+enum AltSource {
+    Zulu {
+        package_uuid: String,
+    },
+    Bellsoft,
+    /// No checksums published
+    #[cfg_attr(
+        not(all(target_os = "freebsd", target_arch = "x86_64")),
+        allow(dead_code)
+    )]
+    GetJdk,
+}
+
+impl AltDownload {
+    fn bellsoft(url: &str) -> Self {
+        Self {
+            url: url.to_owned(),
+            source: AltSource::Bellsoft,
+        }
+    }
+
+    /// Looks up the hash of the archive
+    /// from the source's API (if it provides one).
+    async fn get_checksum(&self) -> Result<Option<Checksum>, JavaInstallError> {
+        Ok(match &self.source {
+            AltSource::Zulu { package_uuid } => {
+                let url = format!("https://api.azul.com/metadata/v1/zulu/packages/{package_uuid}");
+                let json: ZuluDetails = file_utils::download_file_to_json(&url, true).await?;
+                Some(Checksum::Sha256(json.sha256_hash))
+            }
+            AltSource::Bellsoft => {
+                // eg: `https://download.bell-sw.com/java/21.0.10+10/...`
+                let feature = self
+                    .url
+                    .split('/')
+                    .nth(4)
+                    .and_then(|n| n.split('.').next())
+                    .unwrap_or_default();
+                let url = format!(
+                    "https://api.bell-sw.com/v1/liberica/releases?version-feature={feature}&bundle-type=jdk"
+                );
+                let json: Vec<BellsoftRelease> =
+                    file_utils::download_file_to_json(&url, true).await?;
+                json.into_iter()
+                    .find(|n| n.downloadUrl == self.url)
+                    .map(|n| Checksum::Sha1(n.sha1))
+            }
+            AltSource::GetJdk => None,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct ZuluDetails {
+    sha256_hash: String,
+}
+
+#[derive(Deserialize)]
+#[allow(non_snake_case)]
+struct BellsoftRelease {
+    downloadUrl: String,
+    sha1: String,
+}
+
+pub(crate) async fn get_download(
+    mut version: JavaVersion,
+) -> Result<Option<AltDownload>, JavaInstallError> {
     #[cfg(all(target_os = "freebsd", target_arch = "x86_64"))]
     if let JavaVersion::Java8 = version {
-        return Ok(Some("https://github.com/Mrmayman/get-jdk/releases/download/java8-1/jdk-8u452-freebsd-x64.tar.gz".to_owned()));
+        return Ok(Some(AltDownload {
+            url: "https://github.com/Mrmayman/get-jdk/releases/download/java8-1/jdk-8u452-freebsd-x64.tar.gz".to_owned(),
+            source: AltSource::GetJdk,
+        }));
     }
     if let JavaVersion::Java21 = version {
         if cfg!(any(
             feature = "simulate_linux_arm32",
             all(target_os = "linux", target_arch = "arm")
         )) {
-            return Ok(Some("https://download.bell-sw.com/java/21.0.10+10/bellsoft-jdk21.0.10+10-linux-arm32-vfp-hflt.tar.gz".to_owned()));
+            return Ok(Some(AltDownload::bellsoft("https://download.bell-sw.com/java/21.0.10+10/bellsoft-jdk21.0.10+10-linux-arm32-vfp-hflt.tar.gz")));
         } else if cfg!(target_arch = "x86") {
             if cfg!(target_os = "windows") {
-                return Ok(Some("https://download.bell-sw.com/java/21.0.10+10/bellsoft-jdk21.0.10+10-windows-i586.zip".to_owned()));
+                return Ok(Some(AltDownload::bellsoft("https://download.bell-sw.com/java/21.0.10+10/bellsoft-jdk21.0.10+10-windows-i586.zip")));
             } else if cfg!(target_os = "linux") {
-                return Ok(Some("https://download.bell-sw.com/java/21.0.10+10/bellsoft-jdk21.0.10+10-linux-i586.tar.gz".to_owned()));
+                return Ok(Some(AltDownload::bellsoft("https://download.bell-sw.com/java/21.0.10+10/bellsoft-jdk21.0.10+10-linux-i586.tar.gz")));
             }
         }
     }
@@ -83,11 +173,12 @@ async fn get_url(mut version: JavaVersion) -> Result<Option<String>, JavaInstall
 
 #[derive(Deserialize)]
 struct ZuluOut {
+    package_uuid: String,
     latest: bool,
     download_url: String,
 }
 
-async fn get_inner(version: JavaVersion) -> Result<Option<String>, JavaInstallError> {
+async fn get_inner(version: JavaVersion) -> Result<Option<AltDownload>, JavaInstallError> {
     let os = get_os();
     let arch = get_arch();
 
@@ -102,7 +193,12 @@ async fn get_inner(version: JavaVersion) -> Result<Option<String>, JavaInstallEr
     pt!("Fetching URL: {}", url.bright_black());
     let json: Vec<ZuluOut> = file_utils::download_file_to_json(&url, true).await?;
     let java = find_with_extension(&json, ".zip").or_else(|| find_with_extension(&json, ".tar.gz"));
-    Ok(java.map(|n| n.download_url.clone()))
+    Ok(java.map(|n| AltDownload {
+        url: n.download_url.clone(),
+        source: AltSource::Zulu {
+            package_uuid: n.package_uuid.clone(),
+        },
+    }))
 }
 
 fn find_with_extension<'a>(json: &'a [ZuluOut], ext: &str) -> Option<&'a ZuluOut> {
@@ -0,0 +1,292 @@
+//! Finding Java installations on the user's system
+//! (ones not installed by the launcher).
+
+use std::{
+    collections::HashSet,
+    env::consts::ARCH,
+    fmt::Display,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use ql_core::{
+    do_jobs, file_utils::canonicalize_a, info, no_window, pt, JavaVersion, LAUNCHER_DIR,
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(target_os = "windows")]
+const JAVA_EXE: &str = "java.exe";
+#[cfg(not(target_os = "windows"))]
+const JAVA_EXE: &str = "java";
+
+/// How long [`JavaInfo::probe`] waits for `java` to respond.
+///
+/// A broken binary (or a wrapper script waiting for input)
+/// would otherwise hang discovery and every launch.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Info about a Java installation,
+/// read from the `java` binary itself (see [`JavaInfo::probe`]).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JavaInfo {
+    /// Path to the `java` binary
+    pub path: PathBuf,
+    /// eg: `Eclipse Adoptium`, `Oracle Corporation`
+    pub vendor: String,
+    /// Full version (eg: `17.0.9`, `1.8.0_392`)
+    pub version: String,
+    /// Major version (eg: `17`, `8`)
+    pub major: usize,
+    /// Architecture of the JVM (`os.arch`, eg: `amd64`, `aarch64`)
+    pub arch: String,
+}
+
+impl JavaInfo {
+    /// Runs `java -XshowSettings:properties -version`
+    /// to find out the version, vendor and architecture.
+    ///
+    /// Returns `None` if the binary couldn't be run,
+    /// doesn't look like Java or took too long
+    /// (over 5 seconds).
+    pub async fn probe(path: &Path) -> Option<Self> {
+        let mut command = tokio::process::Command::new(path);
+        no_window!(command);
+        command
+            .args(["-XshowSettings:properties", "-version"])
+            .kill_on_drop(true);
+        let Ok(output) = tokio::time::timeout(PROBE_TIMEOUT, command.output()).await else {
+            pt!("Timed out checking Java at {path:?}");
+            return None;
+        };
+        let output = output.ok()?;
+        // The settings are printed to stderr
+        let output = String::from_utf8_lossy(&output.stderr);
+
+        let mut vendor = None;
+        let mut version = None;
+        let mut arch = None;
+        for line in output.lines() {
+            let Some((key, value)) = line.trim().split_once(" = ") else {
+                continue;
+            };
+            match key {
+                "java.vendor" => vendor = Some(value.trim().to_owned()),
+                "java.version" => version = Some(value.trim().to_owned()),
+                "os.arch" => arch = Some(value.trim().to_owned()),
+                _ => {}
+            }
+        }
+
+        let version = version?;
+        Some(Self {
+            path: path.to_owned(),
+            vendor: vendor.unwrap_or_default(),
+            major: parse_major_version(&version)?,
+            version,
+            arch: arch.unwrap_or_default(),
+        })
+    }
+
+    /// Info about a Java runtime installed by the launcher,
+    /// without running it (its version is already known).
+    #[must_use]
+    pub fn for_managed(path: PathBuf, version: JavaVersion) -> Self {
+        let major = version as usize;
+        Self {
+            path,
+            vendor: String::new(),
+            version: major.to_string(),
+            major,
+            arch: ARCH.to_owned(),
+        }
+    }
+
+    /// Whether this Java runs natively on the
+    /// current machine's architecture.
+    #[must_use]
+    pub fn is_native_arch(&self) -> bool {
+        normalize_arch(&self.arch) == normalize_arch(ARCH)
+    }
+}
+
+impl Display for JavaInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Java {} ({} {}", self.major, self.vendor, self.version)?;
+        if !self.is_native_arch() {
+            write!(f, ", {}", self.arch)?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// `1.8.0_392` -> `8`, `17.0.9` -> `17`, `21` -> `21`,
+/// `22-ea` -> `22`
+fn parse_major_version(version: &str) -> Option<usize> {
+    let version = version.strip_prefix("1.").unwrap_or(version);
+    let major: String = version.chars().take_while(char::is_ascii_digit).collect();
+    major.parse().ok()
+}
+
+fn normalize_arch(arch: &str) -> &str {
+    match arch {
+        "amd64" | "x86_64" | "x64" => "x86_64",
+        "x86" | "i386" | "i486" | "i586" | "i686" => "x86",
+        "aarch64" | "arm64" => "aarch64",
+        "arm" | "aarch32" => "arm",
+        _ => arch,
+    }
+}
+
+/// Finds Java installations on the system, from:
+/// - `JAVA_HOME`
+/// - `PATH`
+/// - Common install locations (`/usr/lib/jvm`, `C:\Program Files\Java`, ...)
+/// - Version managers (SDKMAN, asdf)
+///
+/// Java installed by the launcher itself
+/// (`QuantumLauncher/java_installs`) is skipped.
+///
+/// Sorted by major version (newest first).
+pub async fn find_system_java() -> Vec<JavaInfo> {
+    info!("Looking for Java installations");
+
+    let mut binaries = Vec::new();
+    if let Some(java_home) = std::env::var_os("JAVA_HOME") {
+        binaries.extend(find_java_in_home(Path::new(&java_home)));
+    }
+    if let Some(path) = std::env::var_os("PATH") {
+        binaries.extend(
+            std::env::split_paths(&path)
+                .map(|n| n.join(JAVA_EXE))
+                .filter(|n| n.is_file()),
+        );
+    }
+    for root in get_install_roots() {
+        let Ok(entries) = std::fs::read_dir(&root) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            binaries.extend(find_java_in_home(&entry.path()));
+        }
+    }
+
+    // Symlinks like `/usr/bin/java` point into the
+    // other locations, so deduplicate real paths
+    let managed_dir = canonicalize_a(LAUNCHER_DIR.join("java_installs")).await;
+    let mut seen = HashSet::new();
+    let mut unique = Vec::new();
+    for binary in binaries {
+        let binary = canonicalize_a(&binary).await;
+        if !binary.starts_with(&managed_dir) && seen.insert(binary.clone()) {
+            unique.push(binary);
+        }
+    }
+
+    let found: Result<Vec<Option<JavaInfo>>, std::convert::Infallible> = do_jobs(
+        unique
+            .iter()
+            .map(|n| async { Ok(JavaInfo::probe(n).await) }),
+    )
+    .await;
+    let mut found: Vec<JavaInfo> = found.unwrap_or_default().into_iter().flatten().collect();
+    found.sort_by(|a, b| b.major.cmp(&a.major).then_with(|| a.path.cmp(&b.path)));
+
+    for java in &found {
+        pt!("{java}: {:?}", java.path);
+    }
+    found
+}
+
+/// Returns the `java` binary inside a Java home folder.
+fn find_java_in_home(home: &Path) -> Option<PathBuf> {
+    [
+        home.join("bin").join(JAVA_EXE),
+        // macOS bundles
+        home.join("Contents/Home/bin").join(JAVA_EXE),
+        home.join("jre/bin").join(JAVA_EXE),
+    ]
+    .into_iter()
+    .find(|n| n.is_file())
+}
+
+/// Folders containing multiple Java homes.
+fn get_install_roots() -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = Vec::new();
+
+    if let Some(home) = dirs::home_dir() {
+        // SDKMAN
+        let sdkman =
+            std::env::var_os("SDKMAN_DIR").map_or_else(|| home.join(".sdkman"), PathBuf::from);
+        roots.push(sdkman.join("candidates/java"));
+        // asdf
+        let asdf =
+            std::env::var_os("ASDF_DATA_DIR").map_or_else(|| home.join(".asdf"), PathBuf::from);
+        roots.push(asdf.join("installs/java"));
+        // IntelliJ IDEA downloads
+        roots.push(home.join(".jdks"));
+        #[cfg(target_os = "macos")]
+        roots.push(home.join("Library/Java/JavaVirtualMachines"));
+    }
+
+    #[cfg(target_os = "windows")]
+    for program_files in ["ProgramFiles", "ProgramFiles(x86)"]
+        .into_iter()
+        .filter_map(std::env::var_os)
+        .map(PathBuf::from)
+    {
+        for vendor in [
+            "Java",
+            "Eclipse Adoptium",
+            "Eclipse Foundation",
+            "AdoptOpenJDK",
+            "Zulu",
+            "Microsoft",
+            "BellSoft",
+            "Amazon Corretto",
+            "Semeru",
+        ] {
+            roots.push(program_files.join(vendor));
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    roots.push(PathBuf::from("/Library/Java/JavaVirtualMachines"));
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    roots.extend(
+        [
+            "/usr/lib/jvm",
+            "/usr/lib64/jvm",
+            "/usr/java",
+            "/usr/local/lib/jvm",
+            "/opt",
+            "/opt/java",
+            // FreeBSD (eg: `/usr/local/openjdk17`)
+            "/usr/local",
+        ]
+        .map(PathBuf::from),
+    );
+
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn major_version() {
+        assert_eq!(parse_major_version("1.8.0_392"), Some(8));
+        assert_eq!(parse_major_version("17.0.9"), Some(17));
+        assert_eq!(parse_major_version("21"), Some(21));
+        assert_eq!(parse_major_version("22-ea"), Some(22));
+        assert_eq!(parse_major_version("ea"), None);
+    }
+
+    #[test]
+    fn arch() {
+        assert_eq!(normalize_arch("amd64"), normalize_arch("x86_64"));
+        assert_eq!(normalize_arch("arm64"), "aarch64");
+        assert_eq!(normalize_arch("i686"), "x86");
+    }
+}
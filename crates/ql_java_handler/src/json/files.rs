@@ -39,7 +39,7 @@ pub struct JavaFileDownload {
 
 #[derive(Deserialize)]
 pub struct JavaFileDownloadDetails {
-    // sha1: String,
-    // size: usize,
+    pub sha1: String,
+    pub size: u64,
     pub url: String,
 }
@@ -49,14 +49,16 @@ impl JavaListJson {
         return None;
     }
 
-    pub fn get_url(&self, mut version: JavaVersion) -> Option<String> {
+    /// Returns the manifest listing the files of a Java version
+    /// (or a newer one, if not available for this platform).
+    pub fn get_manifest(&self, mut version: JavaVersion) -> Option<&JavaInstallListingManifest> {
         let java_list = self.get_platform()?;
         let mut fetched = read_ver_from_list(version, java_list);
         while fetched.is_none() {
             version = version.next()?;
             fetched = read_ver_from_list(version, java_list);
         }
-        Some(fetched?.manifest.url.clone())
+        Some(&fetched?.manifest)
     }
 }
 
@@ -116,9 +118,9 @@ pub struct JavaInstallListing {
 
 #[derive(Deserialize, Debug)]
 pub struct JavaInstallListingManifest {
-    // sha1: String,
+    pub sha1: String,
     // size: usize,
-    url: String,
+    pub url: String,
 }
 
 // #[derive(Deserialize, Debug)]
@@ -0,0 +1,372 @@
+//! Building the JVM arguments (memory, garbage collector
+//! tuning and custom flags) for launching an instance.
+
+use ql_core::{
+    err, file_utils,
+    json::{instance_config::JvmPreset, InstanceConfigJson},
+    pt, InstanceSelection,
+};
+use sysinfo::{MemoryRefreshKind, RefreshKind, System};
+
+use crate::JavaInfo;
+
+/// Flags that only work on some Java versions,
+/// as `(name, min, max)` (both inclusive).
+///
+/// Passing a removed or not-yet-added flag makes
+/// Java refuse to start, so these are left out
+/// when the version doesn't match.
+const VERSIONED_FLAGS: &[(&str, usize, usize)] = &[
+    ("UseZGC", 15, usize::MAX),
+    // Became the default in 23, removed in 24
+    ("ZGenerational", 21, 23),
+    ("UseShenandoahGC", 12, usize::MAX),
+    ("UseConcMarkSweepGC", 0, 13),
+    ("CMSIncrementalMode", 0, 8),
+    ("CMSParallelRemarkEnabled", 0, 13),
+    ("CMSClassUnloadingEnabled", 0, 13),
+    ("CMSInitiatingOccupancyFraction", 0, 13),
+    ("UseCMSInitiatingOccupancyOnly", 0, 13),
+    ("CMSScavengeBeforeRemark", 0, 13),
+    ("UseParNewGC", 0, 9),
+    ("AggressiveOpts", 0, 11),
+    ("PermSize", 0, 7),
+    ("MaxPermSize", 0, 7),
+    ("G1RSetUpdatingPauseTimePercent", 0, 19),
+    ("UseCompactObjectHeaders", 24, usize::MAX),
+    ("EnableDynamicAgentLoading", 21, usize::MAX),
+];
+
+/// Module system flags, added in Java 9.
+const MODULE_FLAGS: &[&str] = &[
+    "--add-opens",
+    "--add-exports",
+    "--add-modules",
+    "--add-reads",
+];
+
+/// Builds the JVM arguments chosen by the user for an instance:
+/// 1. The [`JvmPreset`] flags
+/// 2. Custom (instance and global) Java arguments
+/// 3. Memory (`-Xmx`, and `-Xms` for some presets)
+///
+/// Flags that `java` doesn't support
+/// (eg: CMS flags on Java 21, ZGC on Java 8) are left out,
+/// as they would stop the game from starting.
+/// `java` is `None` if it couldn't be checked.
+pub async fn get_jvm_args(
+    config: &InstanceConfigJson,
+    global_args: &[String],
+    java: Option<&JavaInfo>,
+    instance: &InstanceSelection,
+) -> Vec<String> {
+    let preset = config.jvm_preset.unwrap_or_default();
+    let custom_args = config.get_java_args(global_args);
+
+    if java.is_none() && (preset != JvmPreset::None || !custom_args.is_empty()) {
+        err!("Couldn't check Java version, Java arguments won't be checked");
+    }
+
+    let (xmx, xms) = get_memory(config, preset, instance).await;
+
+    let mut args = get_preset_args(preset, java, xmx);
+    args.extend(custom_args);
+    if let Some(java) = java {
+        args = filter_supported_args(args, java.major);
+    }
+
+    args.push(format!("-Xmx{xmx}M"));
+    if let Some(xms) = xms {
+        args.push(format!("-Xms{xms}M"));
+    }
+    args
+}
+
+fn get_preset_args(preset: JvmPreset, java: Option<&JavaInfo>, xmx: usize) -> Vec<String> {
+    let major = java.map(|n| n.major);
+
+    let flags: Vec<&str> = match preset {
+        JvmPreset::None => Vec::new(),
+        JvmPreset::Aikar => get_aikar_flags(xmx),
+        JvmPreset::G1 => get_g1_flags(),
+        JvmPreset::Zgc => {
+            if major.is_some_and(|n| n >= 15) {
+                // `ZGenerational` is filtered out on versions without it
+                vec!["-XX:+UseZGC", "-XX:+ZGenerational"]
+            } else {
+                pt!("ZGC needs Java 15+, using G1 instead");
+                get_g1_flags()
+            }
+        }
+        JvmPreset::Shenandoah => {
+            // Oracle's builds (including the OpenJDK ones
+            // from jdk.java.net) don't include Shenandoah
+            if java.is_some_and(|n| n.major >= 12 && !n.vendor.contains("Oracle")) {
+                vec!["-XX:+UseShenandoahGC"]
+            } else {
+                pt!("This Java doesn't support Shenandoah, using G1 instead");
+                get_g1_flags()
+            }
+        }
+        JvmPreset::LowMemory => vec![
+            "-XX:+UseSerialGC",
+            "-XX:MinHeapFreeRatio=10",
+            "-XX:MaxHeapFreeRatio=20",
+        ],
+    };
+    flags.into_iter().map(ToOwned::to_owned).collect()
+}
+
+/// Similar to the flags used by the official launcher
+fn get_g1_flags() -> Vec<&'static str> {
+    vec![
+        "-XX:+UnlockExperimentalVMOptions",
+        "-XX:+UseG1GC",
+        "-XX:G1NewSizePercent=20",
+        "-XX:G1ReservePercent=20",
+        "-XX:MaxGCPauseMillis=50",
+        "-XX:G1HeapRegionSize=32M",
+    ]
+}
+
+/// <https://docs.papermc.io/paper/aikars-flags>
+fn get_aikar_flags(xmx: usize) -> Vec<&'static str> {
+    let mut flags = vec![
+        "-XX:+UseG1GC",
+        "-XX:+ParallelRefProcEnabled",
+        "-XX:MaxGCPauseMillis=200",
+        "-XX:+UnlockExperimentalVMOptions",
+        "-XX:+DisableExplicitGC",
+        "-XX:+AlwaysPreTouch",
+        "-XX:G1HeapWastePercent=5",
+        "-XX:G1MixedGCCountTarget=4",
+        "-XX:G1MixedGCLiveThresholdPercent=90",
+        "-XX:G1RSetUpdatingPauseTimePercent=5",
+        "-XX:SurvivorRatio=32",
+        "-XX:+PerfDisableSharedMem",
+        "-XX:MaxTenuringThreshold=1",
+        "-Dusing.aikars.flags=https://mcflags.emc.gs",
+        "-Daikars.new.flags=true",
+    ];
+    // Larger heaps get a bigger young generation
+    if xmx > 12 * 1024 {
+        flags.extend([
+            "-XX:G1NewSizePercent=40",
+            "-XX:G1MaxNewSizePercent=50",
+            "-XX:G1HeapRegionSize=16M",
+            "-XX:G1ReservePercent=15",
+            "-XX:InitiatingHeapOccupancyPercent=20",
+        ]);
+    } else {
+        flags.extend([
+            "-XX:G1NewSizePercent=30",
+            "-XX:G1MaxNewSizePercent=40",
+            "-XX:G1HeapRegionSize=8M",
+            "-XX:G1ReservePercent=20",
+            "-XX:InitiatingHeapOccupancyPercent=15",
+        ]);
+    }
+    flags
+}
+
+/// Removes flags that Java `major` doesn't support.
+/// See [`VERSIONED_FLAGS`] and [`MODULE_FLAGS`].
+fn filter_supported_args(args: Vec<String>, major: usize) -> Vec<String> {
+    let mut filtered = Vec::with_capacity(args.len());
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if major < 9 {
+            if let Some(flag) = MODULE_FLAGS
+                .iter()
+                .find(|n| arg == **n || arg.starts_with(&format!("{n}=")))
+            {
+                // `--add-opens value` takes up two arguments
+                let value = if arg == *flag { args.next() } else { None };
+                let value = value.map(|n| format!(" {n}")).unwrap_or_default();
+                pt!("Skipping Java argument {arg}{value} (needs Java 9+)");
+                continue;
+            }
+        }
+
+        if let Some((name, min, max)) = get_xx_flag_name(&arg).and_then(|name| {
+            VERSIONED_FLAGS
+                .iter()
+                .find(|(flag, _, _)| *flag == name)
+                .copied()
+        }) {
+            if major < min || major > max {
+                pt!("Skipping Java argument {arg} ({name} isn't supported by Java {major})");
+                continue;
+            }
+        }
+        filtered.push(arg);
+    }
+    filtered
+}
+
+/// `-XX:+UseZGC` -> `UseZGC`, `-XX:MaxPermSize=256M` -> `MaxPermSize`
+fn get_xx_flag_name(arg: &str) -> Option<&str> {
+    let flag = arg.strip_prefix("-XX:")?;
+    let flag = flag
+        .strip_prefix('+')
+        .or_else(|| flag.strip_prefix('-'))
+        .unwrap_or(flag);
+    Some(flag.split_once('=').map_or(flag, |(name, _)| name))
+}
+
+/// Returns the heap size (`-Xmx`, `-Xms`) in MB.
+///
+/// With `auto_ram` enabled, the size is picked from the
+/// system's RAM and the number of installed mods.
+/// Otherwise `ram_in_mb` from the config is used.
+async fn get_memory(
+    config: &InstanceConfigJson,
+    preset: JvmPreset,
+    instance: &InstanceSelection,
+) -> (usize, Option<usize>) {
+    if !config.auto_ram.unwrap_or(false) {
+        // Aikar's flags expect the heap to be fully allocated upfront
+        let xms = (preset == JvmPreset::Aikar).then_some(config.ram_in_mb);
+        return (config.ram_in_mb, xms);
+    }
+
+    let system_ram = get_system_ram_mb();
+    let mods = count_mods(instance).await;
+    let xmx = get_auto_memory(system_ram, mods, preset, instance.is_server());
+    pt!(
+        "Auto memory: {xmx} MB ({mods} mods, {} system RAM)",
+        system_ram.map_or_else(|| "unknown".to_owned(), |n| format!("{n} MB"))
+    );
+
+    // Servers run for long and benefit from a fixed heap,
+    // clients start with half to keep startup light
+    let xms = if instance.is_server() || preset == JvmPreset::Aikar {
+        xmx
+    } else {
+        xmx / 2
+    };
+    (xmx, Some(xms))
+}
+
+fn get_auto_memory(
+    system_ram: Option<usize>,
+    mods: usize,
+    preset: JvmPreset,
+    is_server: bool,
+) -> usize {
+    const STEP: usize = 256;
+
+    let (base, per_mod, share) = match (preset, is_server) {
+        (JvmPreset::LowMemory, _) => (1024, 24, 4),
+        (_, true) => (3072, 48, 2),
+        (_, false) => (2048, 64, 2),
+    };
+    let wanted = base + mods * per_mod;
+    // Leave enough for the OS (and the client, if hosting locally)
+    let cap = system_ram
+        .map_or(8192, |n| (n / share).max(1024))
+        .min(16384);
+    let xmx = wanted.min(cap).max(1024);
+    xmx / STEP * STEP
+}
+
+/// Counts the `.jar` files in the `mods` (and for servers, `plugins`) folders.
+async fn count_mods(instance: &InstanceSelection) -> usize {
+    let dot_minecraft = instance.get_dot_minecraft_path();
+    let mut dirs = vec![dot_minecraft.join("mods")];
+    if instance.is_server() {
+        dirs.push(dot_minecraft.join("plugins"));
+    }
+
+    let mut count = 0;
+    for dir in dirs {
+        if !dir.is_dir() {
+            continue;
+        }
+        match file_utils::read_filenames_from_dir(&dir).await {
+            Ok(entries) => {
+                count += entries
+                    .iter()
+                    .filter(|n| n.is_file && n.name.ends_with(".jar"))
+                    .count();
+            }
+            Err(err) => err!("Couldn't count mods in {dir:?}: {err}"),
+        }
+    }
+    count
+}
+
+/// Total physical memory (in MB), if it could be found.
+fn get_system_ram_mb() -> Option<usize> {
+    let system = System::new_with_specifics(
+        RefreshKind::nothing().with_memory(MemoryRefreshKind::nothing().with_ram()),
+    );
+    let bytes = system.total_memory();
+    (bytes > 0).then(|| usize::try_from(bytes / (1024 * 1024)).unwrap_or(usize::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|n| (*n).to_owned()).collect()
+    }
+
+    #[test]
+    fn filter_versioned_flags() {
+        let input = args(&[
+            "-XX:+UseZGC",
+            "-XX:+ZGenerational",
+            "-XX:MaxPermSize=256M",
+            "-Xmx2G",
+        ]);
+        assert_eq!(
+            filter_supported_args(input.clone(), 7),
+            args(&["-XX:MaxPermSize=256M", "-Xmx2G"])
+        );
+        assert_eq!(
+            filter_supported_args(input.clone(), 21),
+            args(&["-XX:+UseZGC", "-XX:+ZGenerational", "-Xmx2G"])
+        );
+        assert_eq!(
+            filter_supported_args(input, 24),
+            args(&["-XX:+UseZGC", "-Xmx2G"])
+        );
+    }
+
+    #[test]
+    fn filter_module_flags() {
+        let input = args(&[
+            "--add-opens",
+            "java.base/java.lang=ALL-UNNAMED",
+            "--add-exports=java.base/sun.nio.ch=ALL-UNNAMED",
+            "-Xmx2G",
+        ]);
+        assert_eq!(filter_supported_args(input.clone(), 8), args(&["-Xmx2G"]));
+        assert_eq!(filter_supported_args(input.clone(), 17), input);
+    }
+
+    #[test]
+    fn xx_flag_name() {
+        assert_eq!(get_xx_flag_name("-XX:+UseZGC"), Some("UseZGC"));
+        assert_eq!(get_xx_flag_name("-XX:-UseZGC"), Some("UseZGC"));
+        assert_eq!(
+            get_xx_flag_name("-XX:MaxPermSize=256M"),
+            Some("MaxPermSize")
+        );
+        assert_eq!(get_xx_flag_name("-Xmx2G"), None);
+    }
+
+    #[test]
+    fn auto_memory() {
+        // No mods, plenty of RAM
+        assert_eq!(get_auto_memory(Some(16384), 0, JvmPreset::G1, false), 2048);
+        // Capped to half the RAM
+        assert_eq!(get_auto_memory(Some(4096), 200, JvmPreset::G1, false), 2048);
+        // Never below 1 GB, and rounded to 256 MB
+        assert_eq!(get_auto_memory(Some(1024), 10, JvmPreset::G1, true), 1024);
+        assert_eq!(get_auto_memory(Some(65536), 5, JvmPreset::G1, false), 2304);
+    }
+}
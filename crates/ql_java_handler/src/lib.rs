@@ -67,16 +67,30 @@ use ql_core::{
     constants::OS_NAME,
     do_jobs_with_limit, err,
     file_utils::{self, canonicalize_a, exists, DirItem},
-    info, pt, GenericProgress, IntoIoError, IoError, JsonDownloadError, JsonError, RequestError,
-    LAUNCHER_DIR,
+    info, pt, GenericProgress, IntoIoError, IntoJsonError, IoError, JsonDownloadError, JsonError,
+    RequestError, LAUNCHER_DIR,
 };
+use verify::Checksum;
 
 mod compression;
 pub use compression::extract_tar_gz;
 pub use ql_core::JavaVersion;
 
 mod alternate_java;
+mod discovery;
 mod json;
+mod jvm_args;
+mod manage;
+mod requirement;
+mod verify;
+
+pub use discovery::{find_system_java, JavaInfo};
+pub use jvm_args::get_jvm_args;
+pub use manage::{
+    check_java_update, delete_java_install, delete_unused_java_installs, list_java_installs,
+    reinstall_java, repair_java, JavaInstall,
+};
+pub use requirement::{JavaMismatch, JavaRequirement};
 
 #[allow(dead_code)]
 const fn which_java() -> &'static str {
@@ -151,10 +165,23 @@ pub async fn get_java_binary(
 
     if !java_dir.exists() || is_incomplete_install {
         info!("Installing Java: {version}");
-        install_java(version, java_install_progress_sender).await?;
+        let install_dir = get_install_dir(version).await?;
+        install_java(version, java_install_progress_sender, &install_dir).await?;
+    } else if !verify::is_intact(&java_dir).await {
+        info!("Repairing Java: {version}");
+        install_java(version, java_install_progress_sender, &java_dir).await?;
     }
 
-    let bin_path = find_java_bin(name, &java_dir).await?;
+    let bin_path = match find_java_bin(name, &java_dir).await {
+        Ok(n) => n,
+        Err(err) if name == "java" || name == "javaw" => {
+            // The runtime itself is broken
+            err!("{err}\nRepairing Java: {version}");
+            install_java(version, java_install_progress_sender, &java_dir).await?;
+            find_java_bin(name, &java_dir).await?
+        }
+        Err(err) => return Err(err),
+    };
     Ok(canonicalize_a(&bin_path).await)
 }
 
@@ -197,22 +224,27 @@ const CONCURRENCY_LIMIT: usize = 64;
 async fn install_java(
     version: JavaVersion,
     java_install_progress_sender: Option<&Sender<GenericProgress>>,
+    install_dir: &Path,
 ) -> Result<(), JavaInstallError> {
-    let install_dir = get_install_dir(version).await?;
-    let lock_file = lock_init(&install_dir).await?;
+    let lock_file = lock_init(install_dir).await?;
 
     send_progress(java_install_progress_sender, GenericProgress::default());
 
     let java_list_json = JavaListJson::download().await?;
-    let Some(java_files_url) = java_list_json.get_url(version) else {
+    let Some(manifest) = java_list_json.get_manifest(version) else {
         // Mojang doesn't officially provide java for som platforms.
         // In that case, fetch from alternate sources.
-        alternate_java::install(version, java_install_progress_sender, &install_dir).await?;
+        let url =
+            alternate_java::install(version, java_install_progress_sender, install_dir).await?;
+        manage::write_source(install_dir, &url).await?;
         lock_finish(&lock_file).await?;
         return Ok(());
     };
 
-    let json: JavaFilesJson = file_utils::download_file_to_json(&java_files_url, false).await?;
+    let manifest_bytes = file_utils::download_file_to_bytes(&manifest.url, false).await?;
+    Checksum::Sha1(manifest.sha1.clone()).verify(&manifest_bytes, &manifest.url)?;
+    let json: JavaFilesJson = serde_json::from_slice(&manifest_bytes)
+        .json(String::from_utf8_lossy(&manifest_bytes).to_string())?;
 
     let num_files = json.files.len();
     let file_num = Mutex::new(0);
@@ -224,7 +256,7 @@ async fn install_java(
                 &file_num,
                 num_files,
                 file_name,
-                &install_dir,
+                install_dir,
                 file,
             )
         }),
@@ -232,6 +264,11 @@ async fn install_java(
     )
     .await?;
 
+    let manifest_path = install_dir.join(verify::MANIFEST_FILE);
+    tokio::fs::write(&manifest_path, &manifest_bytes)
+        .await
+        .path(&manifest_path)?;
+    manage::write_source(install_dir, &manifest.url).await?;
     lock_finish(&lock_file).await?;
     send_progress(java_install_progress_sender, GenericProgress::finished());
     info!("Finished installing {}", version.to_string());
@@ -290,10 +327,19 @@ async fn java_install_fn(
             if let Some(parent) = file_path.parent() {
                 tokio::fs::create_dir_all(parent).await.path(parent)?;
             }
-            let file_bytes = download_file(downloads).await?;
-            tokio::fs::write(&file_path, &file_bytes)
-                .await
-                .path(file_path.clone())?;
+            // Files left by an earlier (interrupted or damaged)
+            // install are kept if they're intact
+            let is_intact = tokio::fs::read(&file_path).await.is_ok_and(|n| {
+                Checksum::Sha1(downloads.raw.sha1.clone())
+                    .verify(&n, file_name)
+                    .is_ok()
+            });
+            if !is_intact {
+                let file_bytes = download_file(downloads, file_name).await?;
+                tokio::fs::write(&file_path, &file_bytes)
+                    .await
+                    .path(file_path.clone())?;
+            }
             if *executable {
                 #[cfg(target_family = "unix")]
                 file_utils::set_executable(&file_path).await?;
@@ -333,13 +379,23 @@ async fn java_install_fn(
     Ok(())
 }
 
-async fn download_file(downloads: &JavaFileDownload) -> Result<Vec<u8>, JavaInstallError> {
-    async fn normal_download(downloads: &JavaFileDownload) -> Result<Vec<u8>, JavaInstallError> {
-        Ok(file_utils::download_file_to_bytes(&downloads.raw.url, false).await?)
+/// Downloads a Java file, checking it against
+/// the SHA-1 hash from the manifest.
+async fn download_file(
+    downloads: &JavaFileDownload,
+    name: &str,
+) -> Result<Vec<u8>, JavaInstallError> {
+    async fn normal_download(
+        downloads: &JavaFileDownload,
+        name: &str,
+    ) -> Result<Vec<u8>, JavaInstallError> {
+        let bytes = file_utils::download_file_to_bytes(&downloads.raw.url, false).await?;
+        Checksum::Sha1(downloads.raw.sha1.clone()).verify(&bytes, name)?;
+        Ok(bytes)
     }
 
     let Some(lzma) = &downloads.lzma else {
-        return normal_download(downloads).await;
+        return normal_download(downloads, name).await;
     };
     let mut lzma = std::io::BufReader::new(std::io::Cursor::new(
         file_utils::download_file_to_bytes(&lzma.url, false).await?,
@@ -347,13 +403,19 @@ async fn download_file(downloads: &JavaFileDownload) -> Result<Vec<u8>, JavaInst
 
     let mut out = Vec::new();
     match lzma_rs::lzma_decompress(&mut lzma, &mut out) {
-        Ok(()) => Ok(out),
+        Ok(()) => match Checksum::Sha1(downloads.raw.sha1.clone()).verify(&out, name) {
+            Ok(()) => Ok(out),
+            Err(err) => {
+                err!("{err}\n  Retrying without compression");
+                normal_download(downloads, name).await
+            }
+        },
         Err(err) => {
             err!(
                 "Could not decompress lzma file: {err}\n  ({})",
                 downloads.raw.url.bright_black()
             );
-            Ok(normal_download(downloads).await?)
+            normal_download(downloads, name).await
         }
     }
 }
@@ -389,6 +451,12 @@ pub enum JavaInstallError {
     TarGzExtract(std::io::Error),
     #[error("{ERR_PREF1}{OS_NAME} {ARCH}):\nunknown extension for java: {0}\n\nThis is a bug, please report on discord!")]
     UnknownExtension(String),
+    #[error("{ERR_PREF1}{OS_NAME} {ARCH}):\ndownloaded file is corrupted: {file}\n(expected hash {expected}, got {got})\n\nCheck your internet connection and try again")]
+    ChecksumMismatch {
+        file: String,
+        expected: String,
+        got: String,
+    },
 }
 
 /// Deletes all the auto-installed Java installations.
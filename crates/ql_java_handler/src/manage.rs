@@ -0,0 +1,308 @@
+//! Listing, updating and cleaning up the
+//! Java runtimes installed by the launcher
+//! (in `QuantumLauncher/java_installs/`).
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+};
+
+use ql_core::{
+    err, file_utils, info,
+    json::{InstanceConfigJson, VersionDetails},
+    pt, GenericProgress, InstanceSelection, IntoIoError, IoError, JavaVersion, JsonFileError,
+    LAUNCHER_DIR,
+};
+
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+use crate::{
+    alternate_java, find_java_bin, install_java, json::list::JavaListJson, JavaInfo,
+    JavaInstallError, JavaRequirement,
+};
+
+/// Stores the URL the runtime was downloaded from,
+/// to check for updates later.
+const SOURCE_FILE: &str = "install_source.txt";
+
+/// A Java runtime installed by the launcher.
+#[derive(Debug, Clone)]
+pub struct JavaInstall {
+    pub version: JavaVersion,
+    /// `QuantumLauncher/java_installs/<VERSION>/`
+    pub dir: PathBuf,
+    /// Total size on disk (in bytes)
+    pub size: u64,
+    /// Read from the `java` binary.
+    /// `None` if the install is broken or incomplete.
+    pub info: Option<JavaInfo>,
+    /// The installation was interrupted,
+    /// so it will be redone when next needed.
+    pub is_incomplete: bool,
+    /// Instances and servers that launch with this runtime.
+    pub used_by: Vec<InstanceSelection>,
+}
+
+impl JavaInstall {
+    #[must_use]
+    pub fn size_mb(&self) -> f64 {
+        self.size as f64 / (1024.0 * 1024.0)
+    }
+}
+
+/// Lists the Java runtimes installed by the launcher,
+/// along with their size, version and
+/// which instances use them.
+///
+/// # Errors
+/// If the `java_installs`, `instances` or `servers` dir
+/// couldn't be read.
+pub async fn list_java_installs() -> Result<Vec<JavaInstall>, JavaInstallError> {
+    let java_installs = LAUNCHER_DIR.join("java_installs");
+    if !java_installs.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let usage = get_java_usage().await?;
+    let mut installs = Vec::new();
+    for version in JavaVersion::ALL.iter().copied() {
+        let dir = java_installs.join(version.to_string());
+        if !dir.is_dir() {
+            continue;
+        }
+        let info = match find_java_bin("java", &dir).await {
+            Ok(bin) => JavaInfo::probe(&bin).await,
+            Err(_) => None,
+        };
+        installs.push(JavaInstall {
+            version,
+            size: get_dir_size(&dir).await?,
+            info,
+            is_incomplete: dir.join("install.lock").exists(),
+            used_by: usage.get(&version).cloned().unwrap_or_default(),
+            dir,
+        });
+    }
+    Ok(installs)
+}
+
+/// Checks if a newer build of an installed Java runtime
+/// is available (from Mojang, or Azul Zulu/BellSoft on
+/// platforms Mojang doesn't support).
+///
+/// Returns `None` if it's unknown where the runtime came from
+/// (installed by an older launcher version). Reinstalling it
+/// with [`reinstall_java`] fixes this.
+///
+/// # Errors
+/// If the list of Java builds couldn't be downloaded.
+pub async fn check_java_update(version: JavaVersion) -> Result<Option<bool>, JavaInstallError> {
+    let dir = LAUNCHER_DIR.join("java_installs").join(version.to_string());
+    let source_path = dir.join(SOURCE_FILE);
+    if !source_path.is_file() {
+        return Ok(None);
+    }
+    let installed = tokio::fs::read_to_string(&source_path)
+        .await
+        .path(&source_path)?;
+
+    let latest = get_source_url(version).await?;
+    Ok(Some(latest.is_some_and(|n| n != installed.trim())))
+}
+
+async fn get_source_url(version: JavaVersion) -> Result<Option<String>, JavaInstallError> {
+    let java_list_json = JavaListJson::download().await?;
+    if let Some(manifest) = java_list_json.get_manifest(version) {
+        return Ok(Some(manifest.url.clone()));
+    }
+    Ok(alternate_java::get_download(version).await?.map(|n| n.url))
+}
+
+pub(crate) async fn write_source(install_dir: &Path, url: &str) -> Result<(), IoError> {
+    let path = install_dir.join(SOURCE_FILE);
+    tokio::fs::write(&path, url).await.path(path)
+}
+
+/// Downloads the latest build of a Java runtime,
+/// replacing the installed one.
+///
+/// The new build is installed next to the old one first,
+/// so if anything goes wrong, the old one is kept.
+///
+/// # Errors
+/// See [`crate::get_java_binary`]
+pub async fn reinstall_java(
+    version: JavaVersion,
+    sender: Option<&Sender<GenericProgress>>,
+) -> Result<(), JavaInstallError> {
+    info!("Reinstalling Java: {version}");
+    let java_installs = LAUNCHER_DIR.join("java_installs");
+    let dir = java_installs.join(version.to_string());
+    let new_dir = java_installs.join(format!("{version}.new"));
+
+    if new_dir.exists() {
+        tokio::fs::remove_dir_all(&new_dir).await.path(&new_dir)?;
+    }
+    tokio::fs::create_dir_all(&new_dir).await.path(&new_dir)?;
+    if let Err(err) = install_java(version, sender, &new_dir).await {
+        _ = tokio::fs::remove_dir_all(&new_dir).await;
+        return Err(err);
+    }
+
+    if dir.exists() {
+        tokio::fs::remove_dir_all(&dir).await.path(&dir)?;
+    }
+    tokio::fs::rename(&new_dir, &dir).await.path(&dir)?;
+    Ok(())
+}
+
+/// Checks every file of an installed Java runtime against
+/// the hashes from its source, redownloading damaged or missing ones.
+///
+/// This happens automatically when launching if the runtime
+/// looks damaged, but can be forced with this.
+///
+/// # Errors
+/// See [`crate::get_java_binary`]
+pub async fn repair_java(
+    version: JavaVersion,
+    sender: Option<&Sender<GenericProgress>>,
+) -> Result<(), JavaInstallError> {
+    info!("Repairing Java: {version}");
+    let dir = LAUNCHER_DIR.join("java_installs").join(version.to_string());
+    tokio::fs::create_dir_all(&dir).await.path(&dir)?;
+    install_java(version, sender, &dir).await
+}
+
+/// Deletes a single Java runtime installed by the launcher.
+/// It will be reinstalled automatically if needed.
+///
+/// # Errors
+/// If the runtime couldn't be deleted.
+pub async fn delete_java_install(version: JavaVersion) -> Result<(), JavaInstallError> {
+    let dir = LAUNCHER_DIR.join("java_installs").join(version.to_string());
+    if dir.exists() {
+        info!("Deleting Java: {version}");
+        tokio::fs::remove_dir_all(&dir).await.path(&dir)?;
+    }
+    Ok(())
+}
+
+/// Deletes the Java runtimes that no instance or server uses,
+/// as well as incomplete installs.
+///
+/// Runtimes used temporarily (eg: by mod loader installers)
+/// count as unused, and will be reinstalled when needed.
+///
+/// Runtimes used by the `running` instances/servers,
+/// or by any other running Java process, are kept.
+///
+/// Returns the deleted versions.
+///
+/// # Errors
+/// If a runtime couldn't be listed or deleted.
+pub async fn delete_unused_java_installs(
+    running: &[InstanceSelection],
+) -> Result<Vec<JavaVersion>, JavaInstallError> {
+    info!("Deleting unused Java installs");
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_exe(UpdateKind::Always),
+    );
+
+    let mut deleted = Vec::new();
+    for install in list_java_installs().await? {
+        if install.used_by.iter().any(|n| running.contains(n)) || is_running(&system, &install.dir)
+        {
+            pt!("Skipping {} (in use)", install.version);
+            continue;
+        }
+        if install.used_by.is_empty() || install.is_incomplete {
+            pt!("Deleting {} ({:.1} MB)", install.version, install.size_mb());
+            tokio::fs::remove_dir_all(&install.dir)
+                .await
+                .path(&install.dir)?;
+            deleted.push(install.version);
+        }
+    }
+    Ok(deleted)
+}
+
+/// Whether any running process is a `java` binary from `dir`.
+fn is_running(system: &System, dir: &Path) -> bool {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_owned());
+    system
+        .processes()
+        .values()
+        .any(|n| n.exe().is_some_and(|exe| exe.starts_with(&dir)))
+}
+
+/// Works out which launcher-provided Java runtime
+/// each instance and server launches with.
+///
+/// Instances with a custom Java path still count, as the
+/// launcher falls back to its own Java if the custom one
+/// can't run the game (see [`JavaRequirement`]).
+///
+/// Instances that couldn't be read count as
+/// using every runtime, so none get deleted.
+async fn get_java_usage() -> Result<HashMap<JavaVersion, Vec<InstanceSelection>>, IoError> {
+    let mut usage: HashMap<JavaVersion, Vec<InstanceSelection>> = HashMap::new();
+
+    for is_server in [false, true] {
+        let dir = LAUNCHER_DIR.join(if is_server { "servers" } else { "instances" });
+        let entries = file_utils::read_filenames_from_dir(&dir).await?;
+        for entry in entries.into_iter().filter(|n| !n.is_file) {
+            let instance = InstanceSelection::new(&entry.name, is_server);
+            match get_java_version(&instance).await {
+                Ok(version) => usage.entry(version).or_default().push(instance),
+                Err(err) => {
+                    err!("While checking Java used by {}: {err}", entry.name);
+                    for version in JavaVersion::ALL {
+                        usage.entry(*version).or_default().push(instance.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(usage)
+}
+
+async fn get_java_version(instance: &InstanceSelection) -> Result<JavaVersion, JsonFileError> {
+    let instance_dir = instance.get_instance_path();
+    let config = InstanceConfigJson::read_from_dir(&instance_dir).await?;
+    let version_json = VersionDetails::load_from_path(&instance_dir).await?;
+
+    let requirement = if instance.is_server() {
+        JavaRequirement::for_server(&version_json)
+    } else {
+        JavaRequirement::for_client(&version_json)
+    };
+    Ok(match config.java_override_version {
+        Some(version) => requirement.clamp(version).into(),
+        None => version_json
+            .javaVersion
+            .map_or(JavaVersion::Java8, JavaVersion::from),
+    })
+}
+
+async fn get_dir_size(dir: &Path) -> Result<u64, IoError> {
+    let mut size = 0;
+    let mut stack = vec![dir.to_owned()];
+    while let Some(dir) = stack.pop() {
+        let mut read_dir = tokio::fs::read_dir(&dir).await.dir(&dir)?;
+        while let Some(entry) = read_dir.next_entry().await.dir(&dir)? {
+            let metadata = entry.metadata().await.path(entry.path())?;
+            if metadata.is_dir() {
+                stack.push(entry.path());
+            } else {
+                size += metadata.len();
+            }
+        }
+    }
+    Ok(size)
+}
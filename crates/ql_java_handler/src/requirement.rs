@@ -0,0 +1,139 @@
+//! Checking if a Java installation can
+//! run a specific Minecraft version.
+
+use std::{fmt::Display, path::Path};
+
+use ql_core::json::{VersionDetails, V_1_12_2};
+
+use crate::JavaInfo;
+
+/// The range of Java versions a Minecraft version can run on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JavaRequirement {
+    /// Minimum major version (from `javaVersion` in the version JSON,
+    /// or `8` if not specified)
+    pub min: usize,
+    /// Maximum major version, if any.
+    ///
+    /// Legacy versions using LWJGL 2 (1.12.2 and below)
+    /// only run reliably on Java 8.
+    pub max: Option<usize>,
+    /// Whether the JVM must match the architecture of
+    /// this machine (needed for loading native libraries)
+    pub native_arch: bool,
+}
+
+impl JavaRequirement {
+    /// The requirement for launching the game (client).
+    #[must_use]
+    pub fn for_client(version_json: &VersionDetails) -> Self {
+        let uses_lwjgl2 =
+            !version_json.id.ends_with("-lwjgl3") && version_json.is_before_or_eq(V_1_12_2);
+        let min = get_min_version(version_json);
+        Self {
+            min,
+            max: (uses_lwjgl2 && min <= 8).then_some(8),
+            native_arch: true,
+        }
+    }
+
+    /// The requirement for running a server.
+    ///
+    /// Servers don't use LWJGL or any native libraries,
+    /// so only the minimum version matters.
+    #[must_use]
+    pub fn for_server(version_json: &VersionDetails) -> Self {
+        Self {
+            min: get_min_version(version_json),
+            max: None,
+            native_arch: false,
+        }
+    }
+
+    /// Brings a Java version (eg: from `java_override_version`)
+    /// into the supported range.
+    #[must_use]
+    pub fn clamp(&self, version: usize) -> usize {
+        let version = version.max(self.min);
+        self.max.map_or(version, |max| version.min(max))
+    }
+
+    /// Checks if a Java installation can run this version.
+    ///
+    /// # Errors
+    /// If the Java version is out of range, or
+    /// the JVM is built for a different architecture.
+    pub fn check(&self, java: &JavaInfo) -> Result<(), JavaMismatch> {
+        if java.major < self.min {
+            return Err(JavaMismatch::TooOld {
+                found: java.major,
+                required: self.min,
+            });
+        }
+        if let Some(max) = self.max {
+            if java.major > max {
+                return Err(JavaMismatch::TooNew {
+                    found: java.major,
+                    max,
+                });
+            }
+        }
+        if self.native_arch && !java.arch.is_empty() && !java.is_native_arch() {
+            return Err(JavaMismatch::WrongArch(java.arch.clone()));
+        }
+        Ok(())
+    }
+
+    /// Probes the `java` binary at `path` and checks
+    /// if it can run this version (see [`Self::check`]).
+    ///
+    /// # Errors
+    /// - See [`Self::check`]
+    /// - The binary couldn't be run, or isn't Java
+    pub async fn check_binary(&self, path: &Path) -> Result<JavaInfo, JavaMismatch> {
+        let java = JavaInfo::probe(path).await.ok_or(JavaMismatch::NotJava)?;
+        self.check(&java)?;
+        Ok(java)
+    }
+}
+
+fn get_min_version(version_json: &VersionDetails) -> usize {
+    version_json
+        .javaVersion
+        .as_ref()
+        .map_or(8, |n| n.majorVersion)
+}
+
+/// Why a Java installation can't run a Minecraft version.
+///
+/// See [`JavaRequirement::check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JavaMismatch {
+    TooOld { found: usize, required: usize },
+    TooNew { found: usize, max: usize },
+    WrongArch(String),
+    NotJava,
+}
+
+impl Display for JavaMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JavaMismatch::TooOld { found, required } => {
+                write!(
+                    f,
+                    "Java {found} is too old (needs Java {required} or newer)"
+                )
+            }
+            JavaMismatch::TooNew { found, max } => write!(
+                f,
+                "Java {found} is too new for this version (needs Java {max} or older)"
+            ),
+            JavaMismatch::WrongArch(arch) => write!(
+                f,
+                "Java is built for {arch}, but this computer is {}",
+                std::env::consts::ARCH
+            ),
+            JavaMismatch::NotJava => write!(f, "couldn't run Java to check its version"),
+        }
+    }
+}
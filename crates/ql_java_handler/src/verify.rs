@@ -0,0 +1,79 @@
+//! Checking downloaded Java files against
+//! the hashes provided by their source.
+
+use std::path::Path;
+
+use ql_core::{err, file_utils};
+
+use crate::{
+    json::files::{JavaFile, JavaFilesJson},
+    JavaInstallError,
+};
+
+/// Copy of the Mojang files manifest, kept in the install dir
+/// to detect damaged files without redownloading it.
+pub(crate) const MANIFEST_FILE: &str = "manifest.json";
+
+/// An expected hash of a downloaded file
+#[derive(Debug, Clone)]
+pub(crate) enum Checksum {
+    Sha1(String),
+    Sha256(String),
+}
+
+impl Checksum {
+    /// # Errors
+    /// [`JavaInstallError::ChecksumMismatch`] if
+    /// `bytes` don't match the expected hash.
+    pub fn verify(&self, bytes: &[u8], name: &str) -> Result<(), JavaInstallError> {
+        let (expected, got) = match self {
+            Checksum::Sha1(expected) => (expected, file_utils::get_sha1(bytes)),
+            Checksum::Sha256(expected) => (expected, file_utils::get_sha256(bytes)),
+        };
+        if got.eq_ignore_ascii_case(expected) {
+            Ok(())
+        } else {
+            Err(JavaInstallError::ChecksumMismatch {
+                file: name.to_owned(),
+                expected: expected.clone(),
+                got,
+            })
+        }
+    }
+}
+
+/// Quickly checks if an installed runtime looks intact,
+/// by comparing file sizes against the saved manifest
+/// (see [`MANIFEST_FILE`]).
+///
+/// Files aren't hashed (that would slow down every launch),
+/// so this catches missing or truncated files, the usual result
+/// of an interrupted install. Runtimes without a saved manifest
+/// (from alternate sources, or older launcher versions)
+/// are assumed intact.
+pub(crate) async fn is_intact(java_dir: &Path) -> bool {
+    let manifest_path = java_dir.join(MANIFEST_FILE);
+    let Ok(manifest) = tokio::fs::read(&manifest_path).await else {
+        return true;
+    };
+    let manifest: JavaFilesJson = match serde_json::from_slice(&manifest) {
+        Ok(n) => n,
+        Err(err) => {
+            err!("Couldn't read Java manifest {manifest_path:?}: {err}");
+            return true;
+        }
+    };
+
+    for (name, file) in &manifest.files {
+        let JavaFile::file { downloads, .. } = file else {
+            continue;
+        };
+        let path = java_dir.join(name);
+        let size = tokio::fs::metadata(&path).await.map(|n| n.len());
+        if size.ok() != Some(downloads.raw.size) {
+            err!("Java file is missing or damaged: {path:?}");
+            return false;
+        }
+    }
+    true
+}
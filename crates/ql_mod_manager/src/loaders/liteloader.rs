@@ -0,0 +1,148 @@
+//! LiteLoader installer.
+//!
+//! LiteLoader doesn't have an installer we can run headlessly,
+//! so instead we fetch its component JSON from PrismLauncher's
+//! metadata server and save it as an instance patch
+//! (`patches/com.mumfrey.liteloader.json`). The patch is then
+//! applied at launch time by
+//! [`VersionDetails::apply_tweaks`](ql_core::json::VersionDetails::apply_tweaks).
+
+use ql_core::{
+    file_utils, impl_3_errs_jri, info,
+    json::{instance_config::ModTypeInfo, VersionDetails},
+    pt, InstanceSelection, IntoIoError, IoError, JsonError, Loader, RequestError,
+};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::loaders::change_instance_type;
+
+const META_URL: &str = "https://meta.prismlauncher.org/v1/com.mumfrey.liteloader";
+pub const PATCH_UID: &str = "com.mumfrey.liteloader";
+
+#[derive(Deserialize)]
+struct MetaIndex {
+    versions: Vec<MetaVersion>,
+}
+
+#[derive(Deserialize)]
+struct MetaVersion {
+    version: String,
+    #[serde(default)]
+    requires: Vec<MetaRequirement>,
+}
+
+#[derive(Deserialize)]
+struct MetaRequirement {
+    uid: String,
+    equals: Option<String>,
+}
+
+impl MetaVersion {
+    fn supports(&self, mc_version: &str) -> bool {
+        self.requires
+            .iter()
+            .any(|n| n.uid == "net.minecraft" && n.equals.as_deref() == Some(mc_version))
+    }
+}
+
+/// Gets the list of LiteLoader versions available for
+/// the given Minecraft version.
+pub async fn get_list_of_versions(mc_version: &str) -> Result<Vec<String>, LiteLoaderError> {
+    let index: MetaIndex =
+        file_utils::download_file_to_json(&format!("{META_URL}/index.json"), false).await?;
+    Ok(index
+        .versions
+        .into_iter()
+        .filter(|n| n.supports(mc_version))
+        .map(|n| n.version)
+        .collect())
+}
+
+/// Installs LiteLoader to a client instance.
+///
+/// If `version` is `None`, the first LiteLoader
+/// version listed for the instance's Minecraft version is picked.
+///
+/// # Errors
+/// - No LiteLoader version exists for this Minecraft version
+/// - Instance is a server (LiteLoader is client-only)
+/// - Metadata couldn't be downloaded
+/// - Patch couldn't be saved
+pub async fn install(
+    version: Option<String>,
+    instance: InstanceSelection,
+) -> Result<(), LiteLoaderError> {
+    if instance.is_server() {
+        return Err(LiteLoaderError::ServerUnsupported);
+    }
+    info!("Installing LiteLoader");
+    let json = VersionDetails::load(&instance).await?;
+
+    let version = if let Some(version) = version {
+        version
+    } else {
+        get_list_of_versions(json.get_id())
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| LiteLoaderError::NoVersionFound(json.get_id().to_owned()))?
+    };
+    pt!("Version: {version}");
+
+    let patch =
+        file_utils::download_file_to_string(&format!("{META_URL}/{version}.json"), false).await?;
+
+    let patches_dir = instance.get_instance_path().join("patches");
+    tokio::fs::create_dir_all(&patches_dir)
+        .await
+        .path(&patches_dir)?;
+    let patch_path = patches_dir.join(format!("{PATCH_UID}.json"));
+    tokio::fs::write(&patch_path, patch)
+        .await
+        .path(&patch_path)?;
+
+    change_instance_type(
+        &instance.get_instance_path(),
+        Loader::Liteloader,
+        Some(ModTypeInfo {
+            version: Some(version),
+            backend_implementation: None,
+            optifine_jar: None,
+        }),
+    )
+    .await?;
+
+    pt!("Done");
+    Ok(())
+}
+
+pub async fn uninstall(instance: InstanceSelection) -> Result<(), LiteLoaderError> {
+    let instance_dir = instance.get_instance_path();
+    let patch_path = instance_dir
+        .join("patches")
+        .join(format!("{PATCH_UID}.json"));
+    if patch_path.is_file() {
+        tokio::fs::remove_file(&patch_path).await.path(patch_path)?;
+    }
+    change_instance_type(&instance_dir, Loader::Vanilla, None).await?;
+    Ok(())
+}
+
+const LITELOADER_ERR_PREFIX: &str = "while managing LiteLoader:\n";
+
+#[derive(Debug, Error)]
+pub enum LiteLoaderError {
+    #[error("{LITELOADER_ERR_PREFIX}{0}")]
+    Request(#[from] RequestError),
+    #[error("{LITELOADER_ERR_PREFIX}{0}")]
+    Io(#[from] IoError),
+    #[error("{LITELOADER_ERR_PREFIX}json error: {0}")]
+    Json(#[from] JsonError),
+    #[error("{LITELOADER_ERR_PREFIX}no LiteLoader version found for Minecraft {0}")]
+    NoVersionFound(String),
+    #[error("{LITELOADER_ERR_PREFIX}LiteLoader is only supported on clients, not servers")]
+    ServerUnsupported,
+}
+
+impl_3_errs_jri!(LiteLoaderError, Json, Request, Io);
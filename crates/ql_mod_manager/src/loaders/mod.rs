@@ -15,6 +15,7 @@ use ql_core::{
 
 pub mod fabric;
 pub mod forge;
+pub mod liteloader;
 pub mod neoforge;
 pub mod optifine;
 pub mod paper;
@@ -123,9 +124,16 @@ pub async fn install_specified_loader(
             })
         }
 
-        Loader::Liteloader | Loader::Modloader | Loader::Rift => {
-            return Ok(LoaderInstallResult::Unsupported)
+        Loader::Liteloader => {
+            if instance.is_server() {
+                return Ok(LoaderInstallResult::Unsupported);
+            }
+            liteloader::install(specified_version, instance)
+                .await
+                .strerr()?;
         }
+
+        Loader::Modloader | Loader::Rift => return Ok(LoaderInstallResult::Unsupported),
     }
     Ok(LoaderInstallResult::Ok)
 }
@@ -148,7 +156,8 @@ pub async fn uninstall_loader(instance: InstanceSelection) -> Result<(), String>
         Loader::Paper => paper::uninstall(instance.get_name().to_owned())
             .await
             .strerr(),
+        Loader::Liteloader => liteloader::uninstall(instance).await.strerr(),
         // Not yet supported
-        Loader::Liteloader | Loader::Modloader | Loader::Rift | Loader::Vanilla => Ok(()),
+        Loader::Modloader | Loader::Rift | Loader::Vanilla => Ok(()),
     }
 }
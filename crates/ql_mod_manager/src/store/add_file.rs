@@ -9,9 +9,15 @@ use super::{
     CurseforgeNotAllowed,
 };
 
+/// Adds mod (`.jar`), modpack (`.zip`/`.mrpack`)
+/// and preset (`.qmp`) files to an instance.
+///
+/// `optional`: Optional files of Modrinth modpacks
+/// to install, see [`modpack::get_modpack_plan`].
 pub async fn add_files(
     instance: InstanceSelection,
     paths: Vec<PathBuf>,
+    optional: HashSet<String>,
     progress: Option<Sender<GenericProgress>>,
 ) -> Result<HashSet<CurseforgeNotAllowed>, PackError> {
     let mods_dir = instance.get_dot_minecraft_path().join("mods");
@@ -55,8 +61,13 @@ pub async fn add_files(
             }
             "zip" | "mrpack" => {
                 let file = tokio::fs::read(&path).await.path(&path)?;
-                if let Some(not_allowed_new) =
-                    modpack::install_modpack(file, instance.clone(), progress.as_ref()).await?
+                if let Some(not_allowed_new) = modpack::install_modpack_with_optional(
+                    file,
+                    instance.clone(),
+                    &optional,
+                    progress.as_ref(),
+                )
+                .await?
                 {
                     not_allowed.extend(not_allowed_new);
                 }
@@ -4,15 +4,17 @@ use std::{
 };
 
 use ql_core::{
-    err, file_utils, info, json::VersionDetails, pt, GenericProgress, InstanceSelection, ModId,
+    err, file_utils, info,
+    json::{ModpackInfo, VersionDetails},
+    pt, GenericProgress, InstanceSelection, ModId,
 };
 
 use crate::{
     rate_limiter::lock,
     store::{
-        curseforge::{get_query_type, ModQuery},
-        install_modpack, CurseforgeNotAllowed, DirStructure, ModConfig, ModError, ModFile,
-        ModIndex, QueryType, SOURCE_ID_CURSEFORGE,
+        curseforge::{get_query_type, CurseforgeFileQuery, ModQuery},
+        install_modpack, set_modpack_info, CurseforgeNotAllowed, DirStructure, ModConfig, ModError,
+        ModFile, ModIndex, QueryType, StoreModpack, SOURCE_ID_CURSEFORGE,
     },
 };
 
@@ -29,6 +31,9 @@ pub struct ModDownloader<'a> {
     pub query_cache: HashMap<String, Mod>,
     pub not_allowed: HashSet<CurseforgeNotAllowed>,
     pub already_installed: HashSet<String>,
+    /// Specific file IDs to install (by mod ID),
+    /// instead of the latest compatible file
+    pub pinned_versions: HashMap<String, String>,
     pub sender: Option<&'a Sender<GenericProgress>>,
 
     _guard: tokio::sync::MutexGuard<'a, ()>,
@@ -52,6 +57,7 @@ impl<'a> ModDownloader<'a> {
             index: ModIndex::load(&instance).await?,
             dirs: DirStructure::new(&instance, &version_json).await?,
             already_installed: HashSet::new(),
+            pinned_versions: HashMap::new(),
             query_cache: HashMap::new(),
             instance,
             sender,
@@ -100,15 +106,20 @@ impl<'a> ModDownloader<'a> {
 
         let query_type = get_query_type(response.classId).await?;
 
-        let (file_query, file_id) = response
-            .get_file(
-                response.name.clone(),
-                id,
-                self.version.clone(),
-                self.loader,
-                query_type,
-            )
-            .await?;
+        let (file_query, file_id) = if let Some(pinned) = self.pinned_versions.get(id) {
+            let file_id = pinned.parse()?;
+            (CurseforgeFileQuery::load(id, file_id).await?, file_id)
+        } else {
+            response
+                .get_file(
+                    response.name.clone(),
+                    id,
+                    self.version.clone(),
+                    self.loader,
+                    query_type,
+                )
+                .await?
+        };
         let Some(url) = file_query.data.downloadUrl.clone() else {
             self.not_allowed.insert(CurseforgeNotAllowed {
                 name: response.name.clone(),
@@ -134,6 +145,17 @@ impl<'a> ModDownloader<'a> {
                         .map_err(Box::new)?
                 {
                     self.not_allowed.extend(not_allowed_new);
+                    set_modpack_info(
+                        &self.instance,
+                        ModpackInfo {
+                            project_id: ModId::Curseforge(response.id.to_string()).get_index_str(),
+                            name: response.name.clone(),
+                            installed_version: file_query.data.displayName.clone(),
+                            version_release_time: file_query.data.fileDate.clone(),
+                        },
+                    )
+                    .await
+                    .map_err(Box::new)?;
                 } else {
                     err!("Invalid modpack downloaded from curseforge! Corrupted?");
                 }
@@ -160,6 +182,41 @@ impl<'a> ModDownloader<'a> {
         Ok(())
     }
 
+    /// Downloads the latest compatible version of a modpack
+    /// without installing it, see [`StoreModpack`].
+    pub async fn get_modpack(&mut self, id: &str) -> Result<StoreModpack, ModError> {
+        info!("Getting modpack info (id: {id})");
+        let response = self.get_query(id).await?;
+        let (file_query, _) = response
+            .get_file(
+                response.name.clone(),
+                id,
+                self.version.clone(),
+                self.loader,
+                QueryType::ModPacks,
+            )
+            .await?;
+        let url = file_query.data.downloadUrl.clone().ok_or_else(|| {
+            ModError::CurseforgeModNotAllowedForDownload(
+                response.name.clone(),
+                response.slug.clone(),
+            )
+        })?;
+
+        pt!("Downloading {}", file_query.data.fileName);
+        let bytes = file_utils::download_file_to_bytes(&url, true).await?;
+        StoreModpack::new(
+            bytes,
+            ModpackInfo {
+                project_id: ModId::Curseforge(response.id.to_string()).get_index_str(),
+                name: response.name,
+                installed_version: file_query.data.displayName,
+                version_release_time: file_query.data.fileDate,
+            },
+            &self.instance,
+        )
+    }
+
     pub async fn ensure_essential_mods(&mut self) -> Result<(), ModError> {
         const FABRIC: &str = "4";
 
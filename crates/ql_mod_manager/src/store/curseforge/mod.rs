@@ -14,7 +14,7 @@ use serde::Deserialize;
 
 use crate::{rate_limiter::RATE_LIMITER, store::SearchMod};
 
-use super::{Backend, CurseforgeNotAllowed, ModError, QueryType, SearchResult};
+use super::{Backend, CurseforgeNotAllowed, ModError, QueryType, SearchResult, StoreModpack};
 use categories::get_categories;
 use ql_core::file_utils::check_for_success;
 
@@ -195,6 +195,28 @@ impl CFSearchResult {
 
 pub struct CurseforgeBackend;
 
+impl CurseforgeBackend {
+    pub(super) async fn get_version_name(id: &str, version: &str) -> Result<String, ModError> {
+        let file_query = CurseforgeFileQuery::load(id, version.parse()?).await?;
+        Ok(file_query.data.displayName)
+    }
+
+    pub(super) async fn get_project_type(id: &str) -> Result<QueryType, ModError> {
+        let response = ModQuery::load(id).await?;
+        get_query_type(response.data.classId).await
+    }
+
+    pub(super) async fn download_modpack(
+        id: &str,
+        instance: &ql_core::InstanceSelection,
+    ) -> Result<StoreModpack, ModError> {
+        ModDownloader::new(instance.clone(), None)
+            .await?
+            .get_modpack(id)
+            .await
+    }
+}
+
 impl Backend for CurseforgeBackend {
     async fn search(
         query: super::Query,
@@ -319,9 +341,11 @@ impl Backend for CurseforgeBackend {
         instance: &ql_core::InstanceSelection,
         ignore_incompatible: bool,
         set_manually_installed: bool,
+        pinned_versions: &HashMap<String, String>,
         sender: Option<&Sender<GenericProgress>>,
     ) -> Result<HashSet<CurseforgeNotAllowed>, ModError> {
         let mut downloader = ModDownloader::new(instance.clone(), sender).await?;
+        downloader.pinned_versions.clone_from(pinned_versions);
         downloader.ensure_essential_mods().await?;
         downloader.query_cache.extend(
             CFSearchResult::get_from_ids(ids)
@@ -17,6 +17,8 @@ pub enum ModError {
     Io(#[from] IoError),
     #[error("{MOD_ERR_PREFIX}no compatible version found for mod: {0}")]
     NoCompatibleVersionFound(String),
+    #[error("{MOD_ERR_PREFIX}version {1} of mod {0} not found")]
+    VersionNotFound(String, String),
     #[error("{MOD_ERR_PREFIX}no valid files found for mod")]
     NoFilesFound,
     #[error("{MOD_ERR_PREFIX}couldn't add entry {1} to zip: {0}")]
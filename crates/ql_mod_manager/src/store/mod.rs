@@ -1,4 +1,10 @@
-use std::{collections::HashSet, fmt::Display, path::PathBuf, sync::mpsc::Sender, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    path::PathBuf,
+    sync::mpsc::Sender,
+    time::Instant,
+};
 
 use chrono::DateTime;
 use ql_core::{
@@ -24,7 +30,11 @@ pub use delete::delete_mods;
 pub use error::{GameExpectation, ModError};
 pub use image::{download_image, ImageResult};
 pub use local_json::{ModConfig, ModFile, ModIndex};
-pub use modpack::{install_modpack, PackError};
+pub use modpack::{
+    check_for_modpack_update, get_modpack_optional, get_modpack_plan, install_modpack,
+    install_modpack_with_optional, set_modpack_info, update_modpack, PackError, PackPlan,
+    PackUpdateReport, StoreModpack,
+};
 pub use modrinth::ModrinthBackend;
 pub use recommended::{RecommendedMod, RECOMMENDED_MODS};
 pub use toggle::{flip_filename, toggle_mods, toggle_mods_local};
@@ -68,6 +78,7 @@ pub trait Backend {
         instance: &InstanceSelection,
         ignore_incompatible: bool,
         set_manually_installed: bool,
+        pinned_versions: &HashMap<String, String>,
         sender: Option<&Sender<GenericProgress>>,
     ) -> Result<HashSet<CurseforgeNotAllowed>, ModError>;
 }
@@ -91,6 +102,28 @@ pub async fn search(
     }
 }
 
+/// Turns a user-entered project (ID or slug) into
+/// the [`ModId`] used by the mod index.
+///
+/// Only Modrinth supports slugs,
+/// CurseForge projects must be entered by ID.
+pub async fn resolve_mod_id(project: &str, backend: StoreBackendType) -> Result<ModId, ModError> {
+    Ok(match backend {
+        StoreBackendType::Modrinth => {
+            ModId::Modrinth(ModrinthBackend::get_project_id(project).await?)
+        }
+        StoreBackendType::Curseforge => ModId::Curseforge(project.to_owned()),
+    })
+}
+
+/// Gets what kind of project `id` is (mod, modpack, shader, ...)
+pub async fn get_project_type(id: &ModId) -> Result<QueryType, ModError> {
+    match id {
+        ModId::Modrinth(n) => ModrinthBackend::get_project_type(n).await,
+        ModId::Curseforge(n) => CurseforgeBackend::get_project_type(n).await,
+    }
+}
+
 pub async fn download_mod(
     id: &ModId,
     instance: &InstanceSelection,
@@ -102,15 +135,53 @@ pub async fn download_mod(
     }
 }
 
+/// Downloads the latest compatible version of a store
+/// modpack without installing it, so the user can pick
+/// its optional files first (see [`StoreModpack::install`]).
+pub async fn download_modpack(
+    id: &ModId,
+    instance: &InstanceSelection,
+) -> Result<StoreModpack, ModError> {
+    match id {
+        ModId::Modrinth(n) => ModrinthBackend::download_modpack(n, instance).await,
+        ModId::Curseforge(n) => CurseforgeBackend::download_modpack(n, instance).await,
+    }
+}
+
 pub async fn download_mods_bulk(
     ids: Vec<ModId>,
     instance: InstanceSelection,
     sender: Option<Sender<GenericProgress>>,
 ) -> Result<HashSet<CurseforgeNotAllowed>, ModError> {
-    let (modrinth, other): (Vec<ModId>, Vec<ModId>) = ids.into_iter().partition(|n| match n {
-        ModId::Modrinth(_) => true,
-        ModId::Curseforge(_) => false,
-    });
+    download_mods_pinned(
+        ids.into_iter().map(|n| (n, None)).collect(),
+        instance,
+        sender,
+    )
+    .await
+}
+
+/// Like [`download_mods_bulk`], but some mods can be given
+/// a specific version to install instead of the latest compatible one.
+///
+/// Versions are Modrinth version numbers (or version IDs)
+/// and CurseForge file IDs. Already installed mods are left
+/// as is, even if another version is installed.
+pub async fn download_mods_pinned(
+    mods: Vec<(ModId, Option<String>)>,
+    instance: InstanceSelection,
+    sender: Option<Sender<GenericProgress>>,
+) -> Result<HashSet<CurseforgeNotAllowed>, ModError> {
+    let pinned_versions: HashMap<String, String> = mods
+        .iter()
+        .filter_map(|(id, version)| Some((id.get_internal_id().to_owned(), version.clone()?)))
+        .collect();
+
+    let (modrinth, other): (Vec<ModId>, Vec<ModId>) =
+        mods.into_iter().map(|(n, _)| n).partition(|n| match n {
+            ModId::Modrinth(_) => true,
+            ModId::Curseforge(_) => false,
+        });
 
     let modrinth: Vec<String> = modrinth
         .into_iter()
@@ -126,17 +197,41 @@ pub async fn download_mods_bulk(
     //     err!("Unimplemented downloading for mods: {other:#?}");
     // }
 
-    let not_allowed =
-        ModrinthBackend::download_bulk(&modrinth, &instance, true, true, sender.as_ref()).await?;
+    let not_allowed = ModrinthBackend::download_bulk(
+        &modrinth,
+        &instance,
+        true,
+        true,
+        &pinned_versions,
+        sender.as_ref(),
+    )
+    .await?;
     debug_assert!(not_allowed.is_empty());
 
-    let not_allowed =
-        CurseforgeBackend::download_bulk(&curseforge, &instance, true, true, sender.as_ref())
-            .await?;
+    let not_allowed = CurseforgeBackend::download_bulk(
+        &curseforge,
+        &instance,
+        true,
+        true,
+        &pinned_versions,
+        sender.as_ref(),
+    )
+    .await?;
 
     Ok(not_allowed)
 }
 
+/// Gets the name that a specific version of a mod is saved as
+/// in the mod index ([`ModConfig::installed_version`]).
+///
+/// See [`download_mods_pinned`] for the version format.
+pub async fn get_version_name(id: &ModId, version: &str) -> Result<String, ModError> {
+    match id {
+        ModId::Modrinth(n) => ModrinthBackend::get_version_name(n, version).await,
+        ModId::Curseforge(n) => CurseforgeBackend::get_version_name(n, version).await,
+    }
+}
+
 pub async fn get_latest_version_date(
     loader: Loader,
     mod_id: &ModId,
@@ -1,5 +1,6 @@
 use std::{
     collections::{HashMap, HashSet},
+    path::PathBuf,
     sync::mpsc::Sender,
 };
 
@@ -59,9 +60,9 @@ impl PackFile {
         (i, len): (&Mutex<usize>, usize),
         cache: &HashMap<i32, curseforge::Mod>,
         index: &Mutex<ModIndex>,
-    ) -> Result<(), PackError> {
+    ) -> Result<Option<PathBuf>, PackError> {
         if !self.required {
-            return Ok(());
+            return Ok(None);
         }
 
         let mod_info = if let Some(n) = cache.get(&self.projectID) {
@@ -75,7 +76,7 @@ impl PackFile {
         let Some(url) = query.data.downloadUrl.clone() else {
             self.add_to_not_allowed(not_allowed, mod_info, query, query_type)
                 .await;
-            return Ok(());
+            return Ok(None);
         };
 
         let path = dirs.get(query_type)?.join(&query.data.fileName);
@@ -84,7 +85,7 @@ impl PackFile {
             let got_len = metadata.len();
             if query.data.fileLength == got_len {
                 pt!("Already installed {}, skipping", mod_info.name);
-                return Ok(());
+                return Ok(Some(path));
             }
         }
 
@@ -92,7 +93,7 @@ impl PackFile {
         add_to_index(index, self.projectID.to_string(), &mod_info, query, url).await;
 
         send_progress(sender, i, len, &mod_info).await;
-        Ok(())
+        Ok(Some(path))
     }
 
     async fn add_to_not_allowed(
@@ -185,7 +186,7 @@ pub async fn install(
     json: &VersionDetails,
     index: &PackIndex,
     sender: Option<&Sender<GenericProgress>>,
-) -> Result<HashSet<CurseforgeNotAllowed>, PackError> {
+) -> Result<(HashSet<CurseforgeNotAllowed>, Vec<String>), PackError> {
     if json.get_id() != index.minecraft.version {
         return Err(PackError::GameVersion {
             expect: index.minecraft.version.clone(),
@@ -235,7 +236,7 @@ pub async fn install(
             .collect()
     };
 
-    do_jobs::<(), PackError>(
+    let paths = do_jobs::<Option<PathBuf>, PackError>(
         index
             .files
             .iter()
@@ -245,8 +246,19 @@ pub async fn install(
 
     mod_index.lock().await.save(instance).await?;
 
+    let mc_dir = instance.get_dot_minecraft_path();
+    let paths = paths
+        .into_iter()
+        .flatten()
+        .filter_map(|n| {
+            n.strip_prefix(&mc_dir)
+                .ok()
+                .map(|n| n.to_string_lossy().replace('\\', "/"))
+        })
+        .collect();
+
     let not_allowed = not_allowed.lock().await;
-    Ok(not_allowed.clone())
+    Ok((not_allowed.clone(), paths))
 }
 
 fn expect_got_curseforge(index: &PackIndex, config: &InstanceConfigJson) -> PackError {
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use ql_core::{impl_3_errs_jri, IoError, JsonError, Loader, RequestError};
 use thiserror::Error;
 
@@ -27,6 +29,14 @@ pub enum PackError {
     Mod(#[from] ModError),
     #[error("{PACK_ERR_PREFIX}found modpack inside modpack!")]
     ModpackInModpack,
+    #[error(
+        "{PACK_ERR_PREFIX}couldn't download {0}:\nthe file didn't match its hash in the modpack"
+    )]
+    HashMismatch(String),
+    #[error("This instance wasn't installed from a Modrinth/CurseForge modpack,\nso it can't be updated")]
+    NotFromStore,
+    #[error("A previous modpack update was interrupted,\nand the old version's files are still in:\n{0:?}\n\nMove them back into the instance's .minecraft folder\n(or delete that folder if you don't need them) to update again")]
+    UpdateBackupExists(PathBuf),
 }
 
 impl_3_errs_jri!(PackError, Json, Request, Io);
@@ -0,0 +1,76 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use ql_core::{file_utils, InstanceSelection, IntoIoError, IntoJsonError};
+use serde::{Deserialize, Serialize};
+
+use super::PackError;
+
+const FILE_NAME: &str = "modpack_files.json";
+
+/// The files installed by a modpack, used to
+/// work out what changed when updating the pack.
+///
+/// Stored in:
+/// - Client: `QuantumLauncher/instances/<NAME>/modpack_files.json`
+/// - Server: `QuantumLauncher/servers/<NAME>/modpack_files.json`
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct PackFiles {
+    /// Path relative to `.minecraft` (with `/` separators)
+    /// mapped to the SHA-1 hash of the file, as installed by the pack.
+    ///
+    /// If the hash of the file on disk doesn't match,
+    /// the user has changed it.
+    pub files: BTreeMap<String, String>,
+    /// The optional files (see [`super::PackPlan::optional`])
+    /// the user chose, picked again when updating the pack.
+    #[serde(default)]
+    pub optional: BTreeSet<String>,
+}
+
+impl PackFiles {
+    /// Loads the list of pack files of an instance.
+    /// Returns an empty list if no modpack was installed.
+    pub async fn load(instance: &InstanceSelection) -> Result<Self, PackError> {
+        let path = instance.get_instance_path().join(FILE_NAME);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let file = tokio::fs::read_to_string(&path).await.path(&path)?;
+        Ok(serde_json::from_str(&file).json(file)?)
+    }
+
+    pub async fn save(&self, instance: &InstanceSelection) -> Result<(), PackError> {
+        let path = instance.get_instance_path().join(FILE_NAME);
+        let file = serde_json::to_string_pretty(self).json_to()?;
+        tokio::fs::write(&path, file).await.path(&path)?;
+        Ok(())
+    }
+
+    /// Hashes the freshly installed `paths` (relative to `mc_dir`).
+    /// Missing files are skipped.
+    pub async fn hash(mc_dir: &Path, paths: Vec<String>) -> Result<Self, PackError> {
+        let mut files = BTreeMap::new();
+        for path in paths {
+            if let Some(hash) = hash_file(&mc_dir.join(&path)).await? {
+                files.insert(path, hash);
+            }
+        }
+        Ok(Self {
+            files,
+            optional: BTreeSet::new(),
+        })
+    }
+}
+
+/// Returns the SHA-1 hash of a file,
+/// or `None` if it doesn't exist.
+pub async fn hash_file(path: &Path) -> Result<Option<String>, PackError> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let bytes = tokio::fs::read(path).await.path(path)?;
+    Ok(Some(file_utils::get_sha1(&bytes)))
+}
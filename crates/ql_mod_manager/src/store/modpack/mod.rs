@@ -1,22 +1,31 @@
 use std::{
     collections::HashSet,
-    io::{Cursor, Read},
+    io::{Cursor, Read, Seek},
+    path::Path,
     sync::mpsc::Sender,
 };
 
 use ql_core::{
-    err, info,
-    json::{InstanceConfigJson, VersionDetails},
+    err, file_utils, info,
+    json::{InstanceConfigJson, ModpackInfo, VersionDetails},
     pt, GenericProgress, InstanceSelection, IntoIoError, IntoJsonError,
 };
 
 mod curseforge;
 mod error;
+mod lock;
 mod modrinth;
+mod update;
 
 pub use error::PackError;
+pub use update::{
+    check_for_modpack_update, get_modpack_optional, set_modpack_info, update_modpack,
+    PackUpdateReport,
+};
+
+use lock::PackFiles;
 
-use super::CurseforgeNotAllowed;
+use super::{CurseforgeNotAllowed, ModError};
 
 /// Installs a modpack file.
 ///
@@ -33,6 +42,9 @@ use super::CurseforgeNotAllowed;
 /// - `sender: Option<&Sender<GenericProgress>>`: Supply a [`Sender`] if you want
 ///   to see the progress of installation. Leave `None` if otherwise.
 ///
+/// Optional files of Modrinth packs are skipped,
+/// see [`install_modpack_with_optional`] for picking them.
+///
 /// # Returns
 /// - `Ok(Some(HashSet<CurseforgeNotAllowed))` - The list of mods that
 ///   Curseforge blocked the launcher from automatically downloading. The user must
@@ -44,6 +56,106 @@ pub async fn install_modpack(
     file: Vec<u8>,
     instance: InstanceSelection,
     sender: Option<&Sender<GenericProgress>>,
+) -> Result<Option<HashSet<CurseforgeNotAllowed>>, PackError> {
+    install_modpack_with_optional(file, instance, &HashSet::new(), sender).await
+}
+
+/// A summary of a modpack, read before installing it.
+#[derive(Debug, Clone)]
+pub struct PackPlan {
+    pub name: String,
+    /// Paths of files the user can choose to install
+    /// (Modrinth packs only). Pass the chosen ones
+    /// to [`install_modpack_with_optional`].
+    pub optional: Vec<String>,
+}
+
+/// Reads a modpack file without installing anything,
+/// listing the optional files the user can pick from.
+///
+/// Returns `Ok(None)` if this isn't a modpack.
+pub fn get_modpack_plan(
+    file: &[u8],
+    instance: &InstanceSelection,
+) -> Result<Option<PackPlan>, PackError> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(file))?;
+
+    if let Some(index) =
+        read_json_from_zip::<_, modrinth::PackIndex>(&mut zip, "modrinth.index.json")?
+    {
+        let optional = index
+            .files
+            .iter()
+            .filter(|n| n.get_side(instance) == "optional")
+            .map(|n| n.path.clone())
+            .collect();
+        return Ok(Some(PackPlan {
+            name: index.name,
+            optional,
+        }));
+    }
+    Ok(
+        read_json_from_zip::<_, curseforge::PackIndex>(&mut zip, "manifest.json")?.map(|n| {
+            PackPlan {
+                name: n.name,
+                optional: Vec::new(),
+            }
+        }),
+    )
+}
+
+/// A modpack downloaded from the store, but not installed yet,
+/// so the user can pick its optional files first.
+///
+/// See [`crate::store::download_modpack`].
+#[derive(Debug, Clone)]
+pub struct StoreModpack {
+    pub plan: PackPlan,
+    pub info: ModpackInfo,
+    file: Vec<u8>,
+}
+
+impl StoreModpack {
+    pub(crate) fn new(
+        file: Vec<u8>,
+        info: ModpackInfo,
+        instance: &InstanceSelection,
+    ) -> Result<Self, ModError> {
+        let plan = get_modpack_plan(&file, instance)
+            .map_err(Box::new)?
+            .ok_or(ModError::NotValidPack)?;
+        Ok(Self { plan, info, file })
+    }
+
+    /// Installs the modpack along with the chosen
+    /// `optional` files, and records which store
+    /// modpack the instance is from (see [`update_modpack`]).
+    ///
+    /// Returns the mods Curseforge blocked,
+    /// see [`install_modpack`].
+    pub async fn install(
+        self,
+        instance: &InstanceSelection,
+        optional: &HashSet<String>,
+        sender: Option<&Sender<GenericProgress>>,
+    ) -> Result<HashSet<CurseforgeNotAllowed>, PackError> {
+        let not_allowed =
+            install_modpack_with_optional(self.file, instance.clone(), optional, sender)
+                .await?
+                .ok_or(ModError::NotValidPack)?;
+        set_modpack_info(instance, self.info).await?;
+        Ok(not_allowed)
+    }
+}
+
+/// Same as [`install_modpack`], but also installs
+/// the `optional` files (paths, see [`PackPlan::optional`])
+/// of a Modrinth pack.
+pub async fn install_modpack_with_optional(
+    file: Vec<u8>,
+    instance: InstanceSelection,
+    optional: &HashSet<String>,
+    sender: Option<&Sender<GenericProgress>>,
 ) -> Result<Option<HashSet<CurseforgeNotAllowed>>, PackError> {
     let mut zip = zip::ZipArchive::new(Cursor::new(file))?;
 
@@ -54,23 +166,40 @@ pub async fn install_modpack(
     let index_json_curseforge: Option<curseforge::PackIndex> =
         read_json_from_zip(&mut zip, "manifest.json")?;
 
-    let overrides = index_json_curseforge
-        .as_ref()
-        .map_or("overrides".to_owned(), |n| n.overrides.clone());
+    // Later folders take precedence over earlier ones
+    let override_dirs = if let Some(index) = &index_json_curseforge {
+        vec![index.overrides.clone()]
+    } else {
+        let side = if instance.is_server() {
+            "server-overrides"
+        } else {
+            "client-overrides"
+        };
+        vec!["overrides".to_owned(), side.to_owned()]
+    };
 
     let mc_dir = instance.get_dot_minecraft_path();
+    // Files of a previously installed pack that
+    // the user didn't change are fine to replace
+    let old_files = PackFiles::load(&instance).await?;
     let config = InstanceConfigJson::read(&instance).await?;
     let json = VersionDetails::load(&instance).await?;
 
     let mut is_valid = false;
+    // Files installed by the pack, relative to `.minecraft`
+    let mut installed = Vec::new();
 
     if let Some(index) = index_json_modrinth {
         is_valid = true;
-        modrinth::install(&instance, &mc_dir, &config, &json, &index, sender).await?;
+        installed =
+            modrinth::install(&instance, &mc_dir, &config, &json, &index, optional, sender).await?;
     }
     let not_allowed = if let Some(index) = index_json_curseforge {
         is_valid = true;
-        curseforge::install(&instance, &config, &json, &index, sender).await?
+        let (not_allowed, paths) =
+            curseforge::install(&instance, &config, &json, &index, sender).await?;
+        installed.extend(paths);
+        not_allowed
     } else {
         HashSet::new()
     };
@@ -80,30 +209,34 @@ pub async fn install_modpack(
     }
 
     let len = zip.len();
-    for i in 0..len {
-        let mut file = zip.by_index(i)?;
-        let name = file.name().to_owned();
+    for (dir_i, dir) in override_dirs.iter().enumerate() {
+        for i in 0..len {
+            let mut file = zip.by_index(i)?;
+            let name = file.name().to_owned();
 
-        if name == "modrinth.index.json" || name == "manifest.json" || name == "modlist.html" {
-            continue;
-        }
+            let Some(name) = strip_dir(&name, dir) else {
+                if dir_i == 0 && !is_known_file(&name) {
+                    err!("Unrecognised file: {name}");
+                }
+                continue;
+            };
+            if !modrinth::is_safe_path(name) {
+                err!("Skipping override {name} (invalid path)");
+                continue;
+            }
 
-        if let Some(sender) = sender {
-            _ = sender.send(GenericProgress {
-                done: i,
-                total: len,
-                message: Some(format!(
-                    "Modpack: Creating overrides: {name} ({i}/{len})",
-                    i = i + 1
-                )),
-                has_finished: false,
-            });
-        }
+            if let Some(sender) = sender {
+                _ = sender.send(GenericProgress {
+                    done: i,
+                    total: len,
+                    message: Some(format!(
+                        "Modpack: Creating overrides ({dir}): {name} ({i}/{len})",
+                        i = i + 1
+                    )),
+                    has_finished: false,
+                });
+            }
 
-        if let Some(name) = name
-            .strip_prefix(&format!("{overrides}/"))
-            .or(name.strip_prefix(&format!("{overrides}\\")))
-        {
             let path = mc_dir.join(name);
             let parent = if file.is_dir() {
                 &path
@@ -120,20 +253,66 @@ pub async fn install_modpack(
                 file.read_to_end(&mut buf)
                     .map_err(|n| PackError::ZipIoError(n, name.to_owned()))?;
 
+                let name = name.replace('\\', "/");
+                // Later override folders replace earlier ones
+                if !installed.contains(&name) {
+                    backup_user_file(&path, &buf, old_files.files.get(&name)).await?;
+                }
                 tokio::fs::write(&path, &buf).await.path(&path)?;
+                installed.push(name);
             }
-        } else {
-            err!("Unrecognised file: {name}");
         }
     }
 
+    let mut files = PackFiles::hash(&mc_dir, installed).await?;
+    files.optional = optional.iter().cloned().collect();
+    files.save(&instance).await?;
+
     pt!("Done!");
 
     Ok(Some(not_allowed))
 }
 
-fn read_json_from_zip<T: serde::de::DeserializeOwned>(
-    zip: &mut zip::ZipArchive<Cursor<Vec<u8>>>,
+/// Moves a file the user changed or added themselves to
+/// `<file>.bak`, before it gets replaced by a modpack override.
+///
+/// `tracked` is the hash of the file as installed
+/// by an earlier pack, if it came from one.
+async fn backup_user_file(
+    path: &Path,
+    new: &[u8],
+    tracked: Option<&String>,
+) -> Result<(), PackError> {
+    if !path.is_file() {
+        return Ok(());
+    }
+    let old = tokio::fs::read(path).await.path(path)?;
+    if old == new || tracked == Some(&file_utils::get_sha1(&old)) {
+        return Ok(());
+    }
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    tokio::fs::rename(path, &backup).await.path(&backup)?;
+    pt!("Kept your version of {path:?} as {backup:?}");
+    Ok(())
+}
+
+fn strip_dir<'a>(name: &'a str, dir: &str) -> Option<&'a str> {
+    name.strip_prefix(dir)
+        .and_then(|n| n.strip_prefix('/').or(n.strip_prefix('\\')))
+}
+
+/// Files in the root of the pack that aren't overrides
+fn is_known_file(name: &str) -> bool {
+    const OVERRIDE_DIRS: &[&str] = &["overrides", "client-overrides", "server-overrides"];
+    name == "modrinth.index.json"
+        || name == "manifest.json"
+        || name == "modlist.html"
+        || OVERRIDE_DIRS.iter().any(|n| strip_dir(name, n).is_some())
+}
+
+fn read_json_from_zip<R: Read + Seek, T: serde::de::DeserializeOwned>(
+    zip: &mut zip::ZipArchive<R>,
     name: &str,
 ) -> Result<Option<T>, PackError> {
     Ok(if let Ok(mut index_file) = zip.by_name(name) {
@@ -1,9 +1,13 @@
-use std::{collections::HashMap, path::Path, sync::mpsc::Sender};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::mpsc::Sender,
+};
 
 use ql_core::{
-    do_jobs, file_utils,
+    do_jobs, err, file_utils,
     json::{InstanceConfigJson, VersionDetails},
-    pt, GenericProgress, InstanceSelection, Loader,
+    pt, GenericProgress, InstanceSelection, IntoIoError, Loader,
 };
 use serde::Deserialize;
 use tokio::sync::Mutex;
@@ -29,24 +33,116 @@ pub struct PackIndex {
 #[derive(Deserialize)]
 pub struct PackFile {
     pub path: String,
-    pub env: PackEnv,
+    #[serde(default)]
+    pub hashes: PackHashes,
+    /// If missing, the file is required on both sides
+    pub env: Option<PackEnv>,
+    /// Download URLs, tried in order
     pub downloads: Vec<String>,
 }
 
+#[derive(Deserialize, Default)]
+pub struct PackHashes {
+    pub sha1: Option<String>,
+    pub sha512: Option<String>,
+}
+
 #[derive(Deserialize)]
 pub struct PackEnv {
     pub client: String,
     pub server: String,
 }
 
+impl PackFile {
+    /// Whether this file is `"required"`, `"optional"`
+    /// or `"unsupported"` on the side (client/server) of the instance.
+    pub fn get_side(&self, instance: &InstanceSelection) -> &str {
+        match (&self.env, instance) {
+            (None, _) => "required",
+            (Some(env), InstanceSelection::Instance(_)) => &env.client,
+            (Some(env), InstanceSelection::Server(_)) => &env.server,
+        }
+    }
+
+    fn should_install(&self, instance: &InstanceSelection, optional: &HashSet<String>) -> bool {
+        match self.get_side(instance) {
+            "required" => true,
+            "optional" => optional.contains(&self.path),
+            _ => false,
+        }
+    }
+
+    /// Checks the downloaded file against the hashes in the index.
+    fn verify(&self, bytes: &[u8]) -> bool {
+        self.hashes
+            .sha1
+            .as_ref()
+            .is_none_or(|n| n.eq_ignore_ascii_case(&file_utils::get_sha1(bytes)))
+            && self
+                .hashes
+                .sha512
+                .as_ref()
+                .is_none_or(|n| n.eq_ignore_ascii_case(&file_utils::get_sha512(bytes)))
+    }
+
+    /// Downloads the file, trying each URL in [`Self::downloads`]
+    /// until one succeeds and matches the hashes.
+    async fn download(&self) -> Result<Vec<u8>, PackError> {
+        let mut last_err = None;
+        for download in &self.downloads {
+            let (download, is_patched) = patch_broken_url(download);
+            match file_utils::download_file_to_bytes(&download, true).await {
+                // Patched files won't match the hashes in the index
+                Ok(bytes) if is_patched || self.verify(&bytes) => return Ok(bytes),
+                Ok(_) => {
+                    err!("Hash mismatch for {} (from {download})", self.path);
+                    last_err = Some(PackError::HashMismatch(self.path.clone()));
+                }
+                Err(error) => {
+                    err!("Couldn't download {} (from {download}): {error}", self.path);
+                    last_err = Some(error.into());
+                }
+            }
+        }
+        Err(last_err.unwrap_or_else(|| PackError::HashMismatch(self.path.clone())))
+    }
+}
+
+/// Swaps out known broken mods,
+/// included in Re-Console modpack
+/// <https://modrinth.com/modpack/legacy-minecraft>
+///
+/// These fix the crash, but I still get a black screen
+fn patch_broken_url(download: &str) -> (String, bool) {
+    let patched = match download {
+        "https://cdn.modrinth.com/data/u58R1TMW/versions/WFiIDhbD/connector-2.0.0-beta.2%2B1.21.1-full.jar" => {
+            "https://cdn.modrinth.com/data/u58R1TMW/versions/k3UrqfQk/connector-2.0.0-beta.6%2B1.21.1-full.jar"
+        }
+        "https://cdn.modrinth.com/data/gHvKJofA/versions/GvTZJhPo/Legacy4J-1.21-1.7.2-neoforge.jar"
+        | "https://cdn.modrinth.com/data/gHvKJofA/versions/fYlGcfZd/Legacy4J-1.21-1.7.3-neoforge.jar" => {
+            "https://cdn.modrinth.com/data/gHvKJofA/versions/RD8XgI0Y/Legacy4J-1.21-1.7.4-neoforge.jar"
+        }
+        _ => return (download.to_owned(), false),
+    };
+    (patched.to_owned(), true)
+}
+
+/// Rejects paths that would escape the `.minecraft` folder.
+pub fn is_safe_path(path: &str) -> bool {
+    let path = Path::new(path);
+    path.components()
+        .all(|n| matches!(n, std::path::Component::Normal(_)))
+}
+
 pub async fn install(
     instance: &InstanceSelection,
     mc_dir: &Path,
     config: &InstanceConfigJson,
     json: &VersionDetails,
     index: &PackIndex,
+    optional: &HashSet<String>,
     sender: Option<&Sender<GenericProgress>>,
-) -> Result<(), PackError> {
+) -> Result<Vec<String>, PackError> {
     if let Some(version) = index.dependencies.get("minecraft") {
         if json.get_id() != *version {
             return Err(PackError::GameVersion {
@@ -74,63 +170,51 @@ pub async fn install(
     let i = &i;
 
     let len = index.files.len();
-    let jobs: Result<Vec<()>, PackError> = do_jobs(
-        index
-            .files
-            .iter()
-            .filter_map(|file| file.downloads.first().map(|n| (file, n)))
-            .map(|(file, download)| async move {
-                let required_field = match instance {
-                    InstanceSelection::Instance(_) => &file.env.client,
-                    InstanceSelection::Server(_) => &file.env.server,
-                };
-                if required_field != "required" {
-                    pt!("Skipping {} (optional)", file.path);
-                    return Ok(());
-                }
-
-                let mut download = download.clone();
-
-                // Known broken mods, included in Re-Console modpack
-                // https://modrinth.com/modpack/legacy-minecraft
-                // These fix the crash, but I still get a black screen
-                if download == "https://cdn.modrinth.com/data/u58R1TMW/versions/WFiIDhbD/connector-2.0.0-beta.2%2B1.21.1-full.jar" {
-                    "https://cdn.modrinth.com/data/u58R1TMW/versions/k3UrqfQk/connector-2.0.0-beta.6%2B1.21.1-full.jar".clone_into(&mut download);
-                } else if download == "https://cdn.modrinth.com/data/gHvKJofA/versions/GvTZJhPo/Legacy4J-1.21-1.7.2-neoforge.jar"
-                    || download == "https://cdn.modrinth.com/data/gHvKJofA/versions/fYlGcfZd/Legacy4J-1.21-1.7.3-neoforge.jar" {
-                    "https://cdn.modrinth.com/data/gHvKJofA/versions/RD8XgI0Y/Legacy4J-1.21-1.7.4-neoforge.jar".clone_into(&mut download);
-                }
-
-                let bytes_path = mc_dir.join(&file.path);
-                file_utils::download_file_to_path(&download, true, &bytes_path).await?;
-
-                if let Some(sender) = sender {
-                    let mut i = i.lock().await;
-                    _ = sender.send(GenericProgress {
-                        done: *i,
-                        total: len,
-                        message: Some(format!(
-                            "Modpack: Installed mod (modrinth) ({i}/{len}):\n{}",
-                            file.path,
-                            i = *i + 1
-                        )),
-                        has_finished: false,
-                    });
-                    pt!(
-                        "Installed mod (modrinth) ({i}/{len}): {}",
+    let jobs: Result<Vec<Option<String>>, PackError> =
+        do_jobs(index.files.iter().map(|file| async move {
+            if !file.should_install(instance, optional) {
+                pt!("Skipping {} ({})", file.path, file.get_side(instance));
+                return Ok(None);
+            }
+            if !is_safe_path(&file.path) {
+                err!("Skipping {} (invalid path)", file.path);
+                return Ok(None);
+            }
+
+            let bytes = file.download().await?;
+            let bytes_path = mc_dir.join(&file.path);
+            if let Some(parent) = bytes_path.parent() {
+                tokio::fs::create_dir_all(parent).await.path(parent)?;
+            }
+            tokio::fs::write(&bytes_path, &bytes)
+                .await
+                .path(&bytes_path)?;
+
+            if let Some(sender) = sender {
+                let mut i = i.lock().await;
+                _ = sender.send(GenericProgress {
+                    done: *i,
+                    total: len,
+                    message: Some(format!(
+                        "Modpack: Installed mod (modrinth) ({i}/{len}):\n{}",
                         file.path,
-                        i = *i + 1,
-                    );
-                    *i += 1;
-                }
-
-                Ok(())
-            }),
-    )
-    .await;
-    jobs?;
-
-    Ok(())
+                        i = *i + 1
+                    )),
+                    has_finished: false,
+                });
+                pt!(
+                    "Installed mod (modrinth) ({i}/{len}): {}",
+                    file.path,
+                    i = *i + 1,
+                );
+                *i += 1;
+            }
+
+            Ok(Some(file.path.clone()))
+        }))
+        .await;
+
+    Ok(jobs?.into_iter().flatten().collect())
 }
 
 fn expect_got_modrinth(index_json: &PackIndex, config: &InstanceConfigJson) -> PackError {
@@ -0,0 +1,309 @@
+use std::{collections::HashSet, path::Path, sync::mpsc::Sender};
+
+use chrono::DateTime;
+use ql_core::{
+    err, info,
+    json::{InstanceConfigJson, ModpackInfo, VersionDetails},
+    pt, GenericProgress, InstanceSelection, IntoIoError, ModId,
+};
+
+use crate::store::{get_latest_version_date, CurseforgeNotAllowed, ModError, ModIndex};
+
+use super::{
+    lock::{hash_file, PackFiles},
+    PackError, StoreModpack,
+};
+
+/// Where the old version's files are kept during an update,
+/// in the instance folder (next to `.minecraft`)
+const BACKUP_DIR: &str = "modpack_update_backup";
+
+/// What changed when updating a modpack.
+///
+/// All paths are relative to `.minecraft`.
+#[derive(Debug, Clone, Default)]
+pub struct PackUpdateReport {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub updated: Vec<String>,
+    /// Files changed by both the user and the update.
+    ///
+    /// The user's version is kept, and the version
+    /// from the pack (if any) is saved next to it as `<file>.new`.
+    pub conflicts: Vec<String>,
+    /// See [`crate::store::install_modpack`]
+    pub not_allowed: HashSet<CurseforgeNotAllowed>,
+}
+
+/// Records which store modpack an instance was installed from,
+/// so it can be updated later with [`update_modpack`].
+pub async fn set_modpack_info(
+    instance: &InstanceSelection,
+    info: ModpackInfo,
+) -> Result<(), PackError> {
+    let mut config = InstanceConfigJson::read(instance).await?;
+    config.modpack = Some(info);
+    config.save(instance).await?;
+    Ok(())
+}
+
+/// Checks if a newer version of the modpack
+/// the instance was installed from is available.
+///
+/// Returns the name of the new version, or `None`
+/// if up-to-date (or not installed from a modpack).
+pub async fn check_for_modpack_update(
+    instance: InstanceSelection,
+) -> Result<Option<String>, ModError> {
+    let config = InstanceConfigJson::read(&instance).await?;
+    let Some(pack) = config.modpack else {
+        return Ok(None);
+    };
+    let version_json = VersionDetails::load(&instance).await?;
+    info!(no_log, "Checking for modpack update ({})", pack.name);
+
+    let (latest_time, latest_name) = get_latest_version_date(
+        config.mod_type,
+        &ModId::from_index_str(&pack.project_id),
+        version_json.get_id(),
+    )
+    .await?;
+    let installed_time = DateTime::parse_from_rfc3339(&pack.version_release_time)?;
+
+    Ok((latest_time > installed_time).then_some(latest_name))
+}
+
+/// Updates the modpack an instance was installed from,
+/// to `pack` (its latest compatible version, downloaded with
+/// [`crate::store::download_modpack`]).
+///
+/// Files that came from the old version of the pack
+/// are removed, replaced or kept based on the new version.
+/// Files added by the user (eg: extra mods) are left alone,
+/// and pack files modified by the user (eg: configs) are kept,
+/// see [`PackUpdateReport::conflicts`].
+///
+/// `optional` are the optional files to install, usually the
+/// ones picked last time (see [`get_modpack_optional`]).
+///
+/// If the update fails, the old version is put back.
+pub async fn update_modpack(
+    instance: InstanceSelection,
+    pack: StoreModpack,
+    optional: HashSet<String>,
+    sender: Option<Sender<GenericProgress>>,
+) -> Result<PackUpdateReport, PackError> {
+    let config = InstanceConfigJson::read(&instance).await?;
+    let old_info = config.modpack.ok_or(PackError::NotFromStore)?;
+    info!("Updating modpack: {}", old_info.name);
+
+    let backup_dir = instance.get_instance_path().join(BACKUP_DIR);
+    if backup_dir.exists() {
+        // Left over from an interrupted update. It may be
+        // the only copy of the old files, so leave it to the user
+        return Err(PackError::UpdateBackupExists(backup_dir));
+    }
+    let old = PackFiles::load(&instance).await?;
+    let old_index = ModIndex::load(&instance).await?;
+
+    let mut moved = Vec::new();
+    let report = match apply_update(
+        &instance,
+        pack,
+        &optional,
+        sender.as_ref(),
+        &old,
+        &backup_dir,
+        &mut moved,
+    )
+    .await
+    {
+        Ok(n) => n,
+        Err(err) => {
+            if let Err(restore_err) =
+                restore(&instance, &backup_dir, &moved, &old, old_index, old_info).await
+            {
+                err!("Couldn't restore the old version of the modpack (its files are in {backup_dir:?}): {restore_err}");
+            }
+            return Err(err);
+        }
+    };
+    tokio::fs::remove_dir_all(&backup_dir)
+        .await
+        .path(&backup_dir)?;
+
+    pt!(
+        "Modpack updated: {} added, {} removed, {} updated, {} conflicts",
+        report.added.len(),
+        report.removed.len(),
+        report.updated.len(),
+        report.conflicts.len()
+    );
+    Ok(report)
+}
+
+/// The part of [`update_modpack`] that can be undone with [`restore`].
+///
+/// `moved` gets every old file moved into `backup_dir`,
+/// as soon as it's moved.
+async fn apply_update(
+    instance: &InstanceSelection,
+    pack: StoreModpack,
+    optional: &HashSet<String>,
+    sender: Option<&Sender<GenericProgress>>,
+    old: &PackFiles,
+    backup_dir: &Path,
+    moved: &mut Vec<String>,
+) -> Result<PackUpdateReport, PackError> {
+    let mc_dir = instance.get_dot_minecraft_path();
+
+    // Move the old version's files aside (so they can be put back
+    // if the update fails), remembering the ones the user changed
+    let mut modified = HashSet::new();
+    for (path, hash) in &old.files {
+        let full_path = mc_dir.join(path);
+        let Some(current_hash) = hash_file(&full_path).await? else {
+            continue;
+        };
+        if current_hash != *hash {
+            modified.insert(path.clone());
+        }
+        let backup_path = backup_dir.join(path);
+        if let Some(parent) = backup_path.parent() {
+            tokio::fs::create_dir_all(parent).await.path(parent)?;
+        }
+        tokio::fs::rename(&full_path, &backup_path)
+            .await
+            .path(&backup_path)?;
+        moved.push(path.clone());
+    }
+    remove_from_index(instance, old).await?;
+    pt!(
+        "Removed {} old files ({} modified by user)",
+        moved.len(),
+        modified.len()
+    );
+
+    let not_allowed = pack.install(instance, optional, sender).await?;
+
+    let new = PackFiles::load(instance).await?;
+    let mut report = PackUpdateReport {
+        not_allowed,
+        ..Default::default()
+    };
+
+    for (path, new_hash) in &new.files {
+        match old.files.get(path) {
+            None => report.added.push(path.clone()),
+            Some(old_hash) if old_hash != new_hash => report.updated.push(path.clone()),
+            Some(_) => {}
+        }
+    }
+    report.removed = old
+        .files
+        .keys()
+        .filter(|n| !new.files.contains_key(*n))
+        .cloned()
+        .collect();
+
+    for path in modified {
+        let full_path = mc_dir.join(&path);
+        let unchanged_in_pack = old.files.get(&path) == new.files.get(&path);
+        if !unchanged_in_pack {
+            if full_path.is_file() {
+                let new_path = mc_dir.join(format!("{path}.new"));
+                tokio::fs::rename(&full_path, &new_path)
+                    .await
+                    .path(&new_path)?;
+            }
+            report.conflicts.push(path.clone());
+        }
+        if let Some(parent) = full_path.parent() {
+            tokio::fs::create_dir_all(parent).await.path(parent)?;
+        }
+        let backup_path = backup_dir.join(&path);
+        tokio::fs::rename(&backup_path, &full_path)
+            .await
+            .path(&full_path)?;
+    }
+    Ok(report)
+}
+
+/// The optional files picked when the
+/// instance's modpack was installed.
+pub async fn get_modpack_optional(
+    instance: &InstanceSelection,
+) -> Result<HashSet<String>, PackError> {
+    Ok(PackFiles::load(instance)
+        .await?
+        .optional
+        .into_iter()
+        .collect())
+}
+
+/// Removes mod index entries of the mods
+/// that were installed by the old version of the pack.
+async fn remove_from_index(instance: &InstanceSelection, old: &PackFiles) -> Result<(), PackError> {
+    let filenames: HashSet<&str> = old
+        .files
+        .keys()
+        .filter_map(|n| n.strip_prefix("mods/"))
+        .collect();
+
+    let mut index = ModIndex::load(instance).await?;
+    index.mods.retain(|_, config| {
+        !config
+            .files
+            .iter()
+            .any(|file| filenames.contains(file.filename.as_str()))
+    });
+    index.save(instance).await?;
+    Ok(())
+}
+
+/// Puts the old version of the pack back if the update failed.
+///
+/// The backup folder is only deleted if
+/// every file in it could be moved back.
+async fn restore(
+    instance: &InstanceSelection,
+    backup_dir: &Path,
+    moved: &[String],
+    old: &PackFiles,
+    mut old_index: ModIndex,
+    old_info: ModpackInfo,
+) -> Result<(), PackError> {
+    err!("Modpack update failed, restoring the old version");
+    let mc_dir = instance.get_dot_minecraft_path();
+
+    // Files added by the new version (if it got installed)
+    let new = PackFiles::load(instance).await?;
+    for path in new.files.keys().filter(|n| !old.files.contains_key(*n)) {
+        let full_path = mc_dir.join(path);
+        if full_path.is_file() {
+            tokio::fs::remove_file(&full_path).await.path(&full_path)?;
+        }
+    }
+
+    for path in moved {
+        let backup_path = backup_dir.join(path);
+        if !backup_path.exists() {
+            // Already moved back (user-modified file)
+            continue;
+        }
+        let full_path = mc_dir.join(path);
+        if let Some(parent) = full_path.parent() {
+            tokio::fs::create_dir_all(parent).await.path(parent)?;
+        }
+        tokio::fs::rename(&backup_path, &full_path)
+            .await
+            .path(&full_path)?;
+    }
+    old.save(instance).await?;
+    old_index.save(instance).await?;
+    set_modpack_info(instance, old_info).await?;
+    tokio::fs::remove_dir_all(backup_dir)
+        .await
+        .path(backup_dir)?;
+    Ok(())
+}
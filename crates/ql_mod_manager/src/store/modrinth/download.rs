@@ -6,14 +6,16 @@ use std::{
 
 use chrono::DateTime;
 use ql_core::{
-    err, file_utils, info, json::VersionDetails, pt, GenericProgress, InstanceSelection,
+    err, file_utils, info,
+    json::{ModpackInfo, VersionDetails},
+    pt, GenericProgress, InstanceSelection,
 };
 
 use crate::store::{
     install_modpack,
     local_json::{ModConfig, ModIndex},
     modrinth::versions::ModVersion,
-    DirStructure, ModError, QueryType, SOURCE_ID_MODRINTH,
+    set_modpack_info, DirStructure, ModError, QueryType, StoreModpack, SOURCE_ID_MODRINTH,
 };
 
 use super::info::ProjectInfo;
@@ -26,6 +28,9 @@ pub struct ModDownloader {
     pub index: ModIndex,
     currently_installing_mods: HashSet<String>,
     pub info: HashMap<String, ProjectInfo>,
+    /// Specific versions to install (by mod ID),
+    /// instead of the latest compatible one
+    pub pinned_versions: HashMap<String, String>,
     sender: Option<Sender<GenericProgress>>,
     dirs: DirStructure,
 }
@@ -50,6 +55,7 @@ impl ModDownloader {
             loader,
             currently_installing_mods,
             info: HashMap::new(),
+            pinned_versions: HashMap::new(),
             instance: instance.clone(),
             sender,
 
@@ -113,6 +119,19 @@ impl ModDownloader {
                     self.download_file(query_type, file).await?;
                 }
             }
+            if let QueryType::ModPacks = query_type {
+                set_modpack_info(
+                    &self.instance,
+                    ModpackInfo {
+                        project_id: project_info.id.clone(),
+                        name: project_info.title.clone(),
+                        installed_version: download_version.version_number.clone(),
+                        version_release_time: download_version.date_published.clone(),
+                    },
+                )
+                .await
+                .map_err(Box::new)?;
+            }
 
             self.add_mod_to_index(
                 &project_info,
@@ -127,6 +146,38 @@ impl ModDownloader {
         Ok(())
     }
 
+    /// Downloads the latest compatible version of a modpack
+    /// without installing it, see [`StoreModpack`].
+    pub async fn get_modpack(&self, id: &str) -> Result<StoreModpack, ModError> {
+        info!("Getting project info (id: {id})");
+        let project_info = ProjectInfo::download(id).await?;
+        if !self.has_compatible_loader(&project_info) {
+            return Err(ModError::NoCompatibleVersionFound(project_info.title));
+        }
+        let download_version = self
+            .get_download_version(id, project_info.title.clone(), QueryType::ModPacks)
+            .await?;
+        let file = download_version
+            .files
+            .iter()
+            .find(|file| file.primary)
+            .or(download_version.files.first())
+            .ok_or(ModError::NoFilesFound)?;
+
+        pt!("Downloading {}", file.filename);
+        let bytes = file_utils::download_file_to_bytes(&file.url, true).await?;
+        StoreModpack::new(
+            bytes,
+            ModpackInfo {
+                project_id: project_info.id,
+                name: project_info.title,
+                installed_version: download_version.version_number,
+                version_release_time: download_version.date_published,
+            },
+            &self.instance,
+        )
+    }
+
     async fn download_dependencies(
         &mut self,
         id: &str,
@@ -200,6 +251,13 @@ impl ModDownloader {
         pt!("Getting download info");
         let download_info = ModVersion::download(id).await?;
 
+        if let Some(pinned) = self.pinned_versions.get(id) {
+            return download_info
+                .into_iter()
+                .find(|v| v.version_number == *pinned || v.id == *pinned)
+                .ok_or_else(|| ModError::VersionNotFound(title, pinned.clone()));
+        }
+
         let mut download_versions: Vec<ModVersion> = download_info
             .iter()
             .filter(|v| v.game_versions.contains(&self.version))
@@ -1,4 +1,8 @@
-use std::{collections::HashSet, sync::mpsc::Sender, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::mpsc::Sender,
+    time::Instant,
+};
 
 use chrono::DateTime;
 use download::version_sort;
@@ -11,7 +15,9 @@ use crate::{
     store::{SearchMod, StoreBackendType},
 };
 
-use super::{Backend, CurseforgeNotAllowed, ModError, Query, QueryType, SearchResult};
+use super::{
+    Backend, CurseforgeNotAllowed, ModError, Query, QueryType, SearchResult, StoreModpack,
+};
 
 mod download;
 mod info;
@@ -20,6 +26,39 @@ mod versions;
 
 pub struct ModrinthBackend;
 
+impl ModrinthBackend {
+    /// Gets the ID of a project from its slug
+    /// (eg: `sodium` -> `AANobbMI`). IDs are also accepted.
+    pub async fn get_project_id(slug: &str) -> Result<String, ModError> {
+        Ok(ProjectInfo::download(slug).await?.id)
+    }
+
+    pub(super) async fn get_project_type(id: &str) -> Result<QueryType, ModError> {
+        let project_type = ProjectInfo::download(id).await?.project_type;
+        QueryType::from_modrinth_str(&project_type)
+            .ok_or(ModError::UnknownProjectType(project_type))
+    }
+
+    pub(super) async fn download_modpack(
+        id: &str,
+        instance: &InstanceSelection,
+    ) -> Result<StoreModpack, ModError> {
+        download::ModDownloader::new(instance, None)
+            .await?
+            .get_modpack(id)
+            .await
+    }
+
+    pub(super) async fn get_version_name(id: &str, version: &str) -> Result<String, ModError> {
+        ModVersion::download(id)
+            .await?
+            .into_iter()
+            .find(|n| n.version_number == version || n.id == version)
+            .map(|n| n.version_number)
+            .ok_or_else(|| ModError::VersionNotFound(id.to_owned(), version.to_owned()))
+    }
+}
+
 impl Backend for ModrinthBackend {
     async fn search(
         query: Query,
@@ -120,11 +159,13 @@ impl Backend for ModrinthBackend {
         instance: &InstanceSelection,
         ignore_incompatible: bool,
         set_manually_installed: bool,
+        pinned_versions: &HashMap<String, String>,
         sender: Option<&Sender<GenericProgress>>,
     ) -> Result<HashSet<CurseforgeNotAllowed>, ModError> {
         let _guard = lock().await;
 
         let mut downloader = download::ModDownloader::new(instance, None).await?;
+        downloader.pinned_versions.clone_from(pinned_versions);
         let bulk_info = ProjectInfo::download_bulk(ids).await?;
 
         downloader
@@ -9,7 +9,7 @@ use super::ModError;
 pub struct ModVersion {
     pub game_versions: Vec<String>,
     pub loaders: Vec<String>,
-    // pub id: String,
+    pub id: String,
     // pub project_id: String,
     // pub author_id: String,
     // pub featured: bool,
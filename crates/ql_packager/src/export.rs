@@ -1,11 +1,13 @@
+use ql_core::json::{InstanceConfigJson, VersionDetails};
 use ql_core::{file_utils, GenericProgress};
-use ql_core::{info, pt, InstanceSelection, IntoIoError, IntoJsonError};
+use ql_core::{info, pt, InstanceSelection, IntoIoError, IntoJsonError, Loader};
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::Sender;
 use tokio::fs;
 
-use crate::{InstanceInfo, InstancePackageError};
+use crate::{modpack, multimc, InstanceInfo, InstancePackageError};
 
 pub const EXCEPTIONS: &[&str] = &[
     ".minecraft/versions",
@@ -15,6 +17,55 @@ pub const EXCEPTIONS: &[&str] = &[
     "forge",
 ];
 
+/// The format to export an instance in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    /// QuantumLauncher's own format (zip with `quantum-config.json`)
+    #[default]
+    QuantumLauncher,
+    /// MultiMC/PrismLauncher instance (zip with `mmc-pack.json`)
+    MultiMC,
+    /// Modrinth modpack (`.mrpack`)
+    Modrinth,
+    /// CurseForge modpack (zip with `manifest.json`)
+    CurseForge,
+}
+
+impl ExportFormat {
+    pub const ALL: &[Self] = &[
+        Self::QuantumLauncher,
+        Self::MultiMC,
+        Self::Modrinth,
+        Self::CurseForge,
+    ];
+
+    /// File extension (without the leading `.`)
+    #[must_use]
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Modrinth => "mrpack",
+            ExportFormat::QuantumLauncher | ExportFormat::MultiMC | ExportFormat::CurseForge => {
+                "zip"
+            }
+        }
+    }
+}
+
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ExportFormat::QuantumLauncher => "QuantumLauncher",
+                ExportFormat::MultiMC => "MultiMC/PrismLauncher",
+                ExportFormat::Modrinth => "Modrinth (.mrpack)",
+                ExportFormat::CurseForge => "CurseForge",
+            }
+        )
+    }
+}
+
 fn create_instance_info(
     instance: &InstanceSelection,
     mut exceptions: HashSet<String>,
@@ -27,8 +78,8 @@ fn create_instance_info(
     }
 }
 
-/// Exports a Minecraft instance by copying its files to a temporary directory,
-/// removing specified exceptions, generating a metadata JSON, and zipping the result.
+/// Exports a Minecraft instance into a zip file
+/// of the given [`ExportFormat`].
 ///
 /// # Arguments
 ///
@@ -38,19 +89,27 @@ fn create_instance_info(
 ///   don't want any extra exceptions, just pass an empty `Vec`.
 ///   **Note: All exception paths are relative to instance dir
 ///   (parent dir of `.minecraft`)**
+/// - `format` - the format to export in. Only [`ExportFormat::QuantumLauncher`]
+///   supports servers.
 ///
 /// # Returns
 ///
 /// Returns `Ok(Vec<u8>)` (bytes of the packaged file)
 /// if the export succeeds, or an error if any step fails.
 ///
-/// # Process
+/// # Process (QuantumLauncher format)
+///
+/// 1. Constructs a new `InstanceInfo` with exceptions.
+/// 2. Copies the instance files into a temporary directory.
+/// 3. Writes the `InstanceInfo` to a `quantum-config.json` inside temp folder.
+/// 4. Deletes the excluded directories/files from the temp copy.
+/// 5. Compresses the temp folder into a `.zip` archive at the given destination.
 ///
-/// 2. Constructs a new `InstanceInfo` with exceptions.
-/// 3. Copies the instance files into a temporary directory.
-/// 4. Writes the `InstanceInfo` to a `quantum-config.json` inside temp folder.
-/// 5. Deletes the excluded directories/files from the temp copy.
-/// 6. Compresses the temp folder into a `.zip` archive at the given destination.
+/// Other formats only carry the `.minecraft` folder over,
+/// along with the format's own metadata (see [`ExportFormat`]).
+/// Mods installed from the store are referenced by URL
+/// (Modrinth) or ID (CurseForge) where possible,
+/// instead of being packaged.
 ///
 /// # Errors
 ///
@@ -58,12 +117,37 @@ fn create_instance_info(
 /// - The instance version can't be found.
 /// - The instance directory doesn't exist.
 /// - File I/O operations (copying, deleting, zipping) fail.
+/// - The instance is a server, or its loader isn't
+///   supported by the chosen format.
 pub async fn export_instance(
+    instance: InstanceSelection,
+    exceptions: HashSet<String>,
+    format: ExportFormat,
+    progress: Option<Sender<GenericProgress>>,
+) -> Result<Vec<u8>, InstancePackageError> {
+    info!("Exporting instance ({format})...");
+    if instance.is_server() && format != ExportFormat::QuantumLauncher {
+        return Err(InstancePackageError::ExportServer(format));
+    }
+    match format {
+        ExportFormat::QuantumLauncher => {
+            export_quantumlauncher(instance, exceptions, progress).await
+        }
+        ExportFormat::MultiMC => multimc::export(&instance, &exceptions, progress.as_ref()).await,
+        ExportFormat::Modrinth => {
+            modpack::export_mrpack(&instance, &exceptions, progress.as_ref()).await
+        }
+        ExportFormat::CurseForge => {
+            modpack::export_curseforge(&instance, &exceptions, progress.as_ref()).await
+        }
+    }
+}
+
+async fn export_quantumlauncher(
     instance: InstanceSelection,
     exceptions: HashSet<String>,
     progress: Option<Sender<GenericProgress>>,
 ) -> Result<Vec<u8>, InstancePackageError> {
-    info!("Exporting instance...");
     let export_config = create_instance_info(&instance, exceptions);
     // println!("{:?}",export_config);
 
@@ -111,3 +195,71 @@ pub async fn export_instance(
 
     Ok(bytes)
 }
+
+/// Copies the `.minecraft` folder of an instance to `dest`,
+/// leaving out the `exceptions` (see [`export_instance`]),
+/// [`EXCEPTIONS`] and any QuantumLauncher-specific files.
+pub(crate) async fn copy_dot_minecraft(
+    instance: &InstanceSelection,
+    exceptions: &HashSet<String>,
+    dest: &Path,
+) -> Result<(), InstancePackageError> {
+    let instance_path = instance.get_instance_path();
+    let dot_minecraft = instance.get_dot_minecraft_path();
+    let exceptions: Vec<PathBuf> = exceptions
+        .iter()
+        .map(String::as_str)
+        .chain(EXCEPTIONS.iter().copied())
+        .map(|n| instance_path.join(n))
+        .chain(std::iter::once(dot_minecraft.join("mod_index.json")))
+        .collect();
+    file_utils::copy_dir_recursive_ext(&dot_minecraft, dest, &exceptions).await?;
+    Ok(())
+}
+
+pub(crate) fn send_progress(
+    progress: Option<&Sender<GenericProgress>>,
+    done: usize,
+    total: usize,
+    message: &str,
+) {
+    if let Some(prog) = progress {
+        _ = prog.send(GenericProgress {
+            done,
+            total,
+            message: Some(message.to_owned()),
+            has_finished: false,
+        });
+    }
+}
+
+/// Returns the installed loader and its version,
+/// or `None` for vanilla instances.
+///
+/// Errors if the loader isn't one of the `supported` ones.
+pub(crate) fn get_loader_version(
+    config: &InstanceConfigJson,
+    format: ExportFormat,
+    supported: &[Loader],
+) -> Result<Option<(Loader, String)>, InstancePackageError> {
+    let loader = config.mod_type;
+    if loader.is_vanilla() {
+        return Ok(None);
+    }
+    if !supported.contains(&loader) {
+        return Err(InstancePackageError::ExportLoader(loader, format));
+    }
+    let version = config
+        .mod_type_info
+        .as_ref()
+        .and_then(|n| n.version.clone())
+        .ok_or(InstancePackageError::ExportLoaderVersion(loader))?;
+    Ok(Some((loader, version)))
+}
+
+/// Gets the Minecraft version of an instance,
+/// without any launcher-specific suffixes.
+pub(crate) fn get_minecraft_version(json: &VersionDetails) -> &str {
+    let id = json.get_id();
+    id.strip_suffix("-lwjgl3").unwrap_or(id)
+}
@@ -1,7 +1,10 @@
 use std::{collections::HashSet, path::PathBuf};
 
-use ql_core::{impl_3_errs_jri, IoError, JsonError, RequestError};
-use ql_mod_manager::loaders::{fabric::FabricInstallError, forge::ForgeInstallError};
+use ql_core::{impl_3_errs_jri, IoError, JsonError, Loader, RequestError};
+use ql_mod_manager::{
+    loaders::{fabric::FabricInstallError, forge::ForgeInstallError, liteloader::LiteLoaderError},
+    store::ModError,
+};
 use ql_servers::ServerError;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -10,9 +13,11 @@ use ql_instances::DownloadError;
 
 mod export;
 mod import;
+pub mod migrate;
+mod modpack;
 mod multimc;
 
-pub use export::{export_instance, EXCEPTIONS};
+pub use export::{export_instance, ExportFormat, EXCEPTIONS};
 pub use import::import_instance;
 
 const PKG_ERR_PREFIX: &str = "while importing/exporting instance:\n";
@@ -43,6 +48,10 @@ pub enum InstancePackageError {
     Forge(#[from] ForgeInstallError),
     #[error("{PKG_ERR_PREFIX}{0}")]
     Fabric(#[from] FabricInstallError),
+    #[error("{PKG_ERR_PREFIX}{0}")]
+    LiteLoader(#[from] LiteLoaderError),
+    #[error("{PKG_ERR_PREFIX}while importing mod metadata:\n{0}")]
+    Mod(#[from] ModError),
 
     #[error("{PKG_ERR_PREFIX}while dealing with zip:\n{0}")]
     Zip(#[from] zip::result::ZipError),
@@ -55,6 +64,13 @@ pub enum InstancePackageError {
     Ini(#[from] ini::ParseError),
     #[error("{PKG_ERR_PREFIX}in ini file:\nentry {1:?} of section {0:?} is missing!")]
     IniFieldMissing(String, String),
+
+    #[error("{PKG_ERR_PREFIX}servers can't be exported in the {0} format")]
+    ExportServer(ExportFormat),
+    #[error("{PKG_ERR_PREFIX}{0} isn't supported by the {1} format")]
+    ExportLoader(Loader, ExportFormat),
+    #[error("{PKG_ERR_PREFIX}couldn't find the installed {0} version.\nTry reinstalling {0}")]
+    ExportLoaderVersion(Loader),
 }
 
 impl_3_errs_jri!(InstancePackageError, Json, Request, Io);
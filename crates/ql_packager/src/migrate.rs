@@ -0,0 +1,756 @@
+//! Migrating instances from other launchers
+//! installed on this computer.
+//!
+//! Supported launchers:
+//! - Official Minecraft Launcher (`launcher_profiles.json`)
+//! - MultiMC / PrismLauncher
+//! - ATLauncher
+//! - GDLauncher (legacy and Carbon)
+//! - CurseForge app
+//!
+//! Use [`find_other_instances`] to list the instances,
+//! then [`migrate_instance`] to import the chosen ones.
+//!
+//! MultiMC/PrismLauncher instances go through the regular
+//! MultiMC importer. For everything else, a fresh instance is
+//! created with the same version and loader, then the game
+//! directory (saves, mods, configs, etc.) is copied over.
+
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    sync::{mpsc::Sender, Arc},
+};
+
+use ini::Ini;
+use ql_core::{
+    err, file_utils, info, json::Manifest, pt, GenericProgress, InstanceSelection, IntoIoError,
+    IntoJsonError, Loader,
+};
+use ql_instances::json_profiles::ProfileJson;
+use serde::{de::DeserializeOwned, Deserialize};
+use tokio::fs;
+
+use crate::{
+    import::OUT_OF,
+    multimc::{
+        self, create_minecraft_instance, filter_bytearray, get_unique_instance, install_loader,
+        InstanceRecipe, MmcPack,
+    },
+    InstancePackageError,
+};
+
+/// Files and folders in the game directory that belong
+/// to the other launcher (or are re-downloaded anyway),
+/// so they aren't copied over.
+const GAME_DIR_EXCEPTIONS: &[&str] = &[
+    "versions",
+    "libraries",
+    "assets",
+    "logs",
+    "natives",
+    "bin",
+    "runtime",
+    "webcache2",
+    "launcher_profiles.json",
+    "launcher_accounts.json",
+    "launcher_accounts_microsoft_store.json",
+    "launcher_log.txt",
+    "launcher_settings.json",
+    "launcher_ui_state.json",
+    "launcher_cef_log.txt",
+    "treatment_tags.json",
+    // ATLauncher, GDLauncher and CurseForge metadata
+    "instance.json",
+    "minecraftinstance.json",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OtherLauncher {
+    Official,
+    MultiMC,
+    PrismLauncher,
+    ATLauncher,
+    GDLauncher,
+    CurseForge,
+}
+
+impl Display for OtherLauncher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                OtherLauncher::Official => "Minecraft Launcher",
+                OtherLauncher::MultiMC => "MultiMC",
+                OtherLauncher::PrismLauncher => "PrismLauncher",
+                OtherLauncher::ATLauncher => "ATLauncher",
+                OtherLauncher::GDLauncher => "GDLauncher",
+                OtherLauncher::CurseForge => "CurseForge",
+            }
+        )
+    }
+}
+
+/// An instance found in another launcher.
+#[derive(Debug, Clone)]
+pub struct MigrationEntry {
+    pub launcher: OtherLauncher,
+    pub name: String,
+    /// May be `latest-release` or `latest-snapshot`
+    /// for the official launcher.
+    pub mc_version: String,
+    pub loader: Option<Loader>,
+    pub loader_version: Option<String>,
+    /// - MultiMC/PrismLauncher: instance folder
+    ///   (containing `instance.cfg`)
+    /// - Others: game directory (like `.minecraft`)
+    pub path: PathBuf,
+}
+
+/// Looks for instances of other launchers on this computer.
+///
+/// Launchers that aren't installed (or whose files
+/// can't be read) are skipped, errors are only logged.
+pub async fn find_other_instances() -> Vec<MigrationEntry> {
+    info!("Looking for instances of other launchers...");
+    let mut entries = Vec::new();
+
+    for dir in launcher_dirs(OtherLauncher::Official) {
+        entries.extend(log_err(find_official(&dir).await));
+    }
+    for launcher in [OtherLauncher::PrismLauncher, OtherLauncher::MultiMC] {
+        for dir in launcher_dirs(launcher) {
+            entries.extend(log_err(find_multimc(&dir, launcher).await));
+        }
+    }
+    for dir in launcher_dirs(OtherLauncher::ATLauncher) {
+        entries.extend(log_err(find_atlauncher(&dir).await));
+    }
+    for dir in launcher_dirs(OtherLauncher::GDLauncher) {
+        entries.extend(log_err(find_gdlauncher(&dir).await));
+    }
+    for dir in launcher_dirs(OtherLauncher::CurseForge) {
+        entries.extend(log_err(find_curseforge(&dir).await));
+    }
+
+    pt!("Found {} instances", entries.len());
+    entries
+}
+
+/// Imports an instance found by [`find_other_instances`]
+/// as a new QuantumLauncher instance.
+///
+/// The original instance is left untouched.
+///
+/// # Errors
+/// - Instance files couldn't be read
+/// - Instance creation (downloading) failed
+/// - Loader installation failed
+pub async fn migrate_instance(
+    entry: MigrationEntry,
+    download_assets: bool,
+    sender: Option<Sender<GenericProgress>>,
+) -> Result<InstanceSelection, InstancePackageError> {
+    info!("Migrating {} instance: {}", entry.launcher, entry.name);
+    let sender = sender.map(Arc::new);
+
+    if let OtherLauncher::MultiMC | OtherLauncher::PrismLauncher = entry.launcher {
+        let mmc_pack_path = entry.path.join("mmc-pack.json");
+        let mmc_pack = fs::read_to_string(&mmc_pack_path)
+            .await
+            .path(&mmc_pack_path)?;
+        return multimc::import(download_assets, &entry.path, &mmc_pack, sender).await;
+    }
+
+    let mc_version = match entry.mc_version.as_str() {
+        n @ ("latest-release" | "latest-snapshot") => {
+            let manifest = Manifest::download().await?;
+            let latest = if n == "latest-release" {
+                manifest.get_latest_release()
+            } else {
+                manifest.get_latest_snapshot()
+            };
+            latest.map_or_else(|| entry.mc_version.clone(), |n| n.id.clone())
+        }
+        n => n.to_owned(),
+    };
+
+    let instance = get_unique_instance(entry.name.clone()).await?;
+    let recipe = InstanceRecipe::new(mc_version.clone(), entry.loader, entry.loader_version);
+
+    create_minecraft_instance(
+        download_assets,
+        sender.clone(),
+        instance.get_name(),
+        mc_version,
+    )
+    .await?;
+    install_loader(&sender, &instance, &recipe).await?;
+
+    if let Some(sender) = sender.as_deref() {
+        _ = sender.send(GenericProgress {
+            done: 2,
+            total: OUT_OF,
+            message: Some("Copying files...".to_owned()),
+            has_finished: false,
+        });
+    }
+    let exceptions: Vec<PathBuf> = GAME_DIR_EXCEPTIONS
+        .iter()
+        .map(|n| entry.path.join(n))
+        .collect();
+    file_utils::copy_dir_recursive_ext(
+        &entry.path,
+        &instance.get_dot_minecraft_path(),
+        &exceptions,
+    )
+    .await?;
+
+    info!("Finished migrating instance");
+    Ok(instance)
+}
+
+fn log_err(res: Result<Vec<MigrationEntry>, InstancePackageError>) -> Vec<MigrationEntry> {
+    res.unwrap_or_else(|error| {
+        err!("{error}");
+        Vec::new()
+    })
+}
+
+/// Default data folders of each launcher.
+fn launcher_dirs(launcher: OtherLauncher) -> Vec<PathBuf> {
+    let home = dirs::home_dir();
+    let data = dirs::data_dir();
+    let config = dirs::config_dir();
+
+    let dirs = match launcher {
+        OtherLauncher::Official => vec![if cfg!(target_os = "windows") {
+            data.map(|n| n.join(".minecraft"))
+        } else if cfg!(target_os = "macos") {
+            data.map(|n| n.join("minecraft"))
+        } else {
+            home.map(|n| n.join(".minecraft"))
+        }],
+        OtherLauncher::PrismLauncher => vec![
+            data.map(|n| n.join("PrismLauncher")),
+            // Flatpak
+            home.map(|n| n.join(".var/app/org.prismlauncher.PrismLauncher/data/PrismLauncher")),
+        ],
+        // MultiMC is usually portable on Windows,
+        // so there's nothing to find there
+        OtherLauncher::MultiMC => vec![
+            data.as_ref().map(|n| n.join("multimc")),
+            data.map(|n| n.join("MultiMC")),
+        ],
+        OtherLauncher::ATLauncher => vec![
+            data.as_ref().map(|n| n.join("ATLauncher")),
+            data.map(|n| n.join("atlauncher")),
+        ],
+        OtherLauncher::GDLauncher => vec![
+            config.map(|n| n.join("gdlauncher_next")),
+            data.map(|n| n.join("gdlauncher_carbon/data")),
+        ],
+        OtherLauncher::CurseForge => vec![
+            home.map(|n| n.join("curseforge/minecraft")),
+            dirs::document_dir().map(|n| n.join("curseforge/minecraft")),
+        ],
+    };
+
+    let mut dirs: Vec<PathBuf> = dirs.into_iter().flatten().filter(|n| n.is_dir()).collect();
+    // Case-insensitive file systems
+    dirs.dedup_by(|a, b| file_utils::canonicalize_s(a) == file_utils::canonicalize_s(b));
+    dirs
+}
+
+/// Lists the subfolders of `dir` (if it exists).
+async fn read_subdirs(dir: &Path) -> Result<Vec<PathBuf>, InstancePackageError> {
+    let mut dirs = Vec::new();
+    if !dir.is_dir() {
+        return Ok(dirs);
+    }
+    let mut entries = fs::read_dir(dir).await.dir(dir)?;
+    while let Some(entry) = entries.next_entry().await.dir(dir)? {
+        let path = entry.path();
+        if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+    Ok(dirs)
+}
+
+/// Parses an instance's metadata, skipping
+/// (and logging) instances that can't be read.
+fn parse_json<T: DeserializeOwned>(instance_dir: &Path, json: &str) -> Option<T> {
+    match serde_json::from_str(json) {
+        Ok(n) => Some(n),
+        Err(error) => {
+            err!("Couldn't read instance {instance_dir:?}, skipping...\n{error}");
+            None
+        }
+    }
+}
+
+fn get_dir_name(dir: &Path) -> String {
+    dir.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+async fn find_official(dir: &Path) -> Result<Vec<MigrationEntry>, InstancePackageError> {
+    let profiles_path = dir.join("launcher_profiles.json");
+    if !profiles_path.is_file() {
+        return Ok(Vec::new());
+    }
+    let profiles = fs::read_to_string(&profiles_path)
+        .await
+        .path(&profiles_path)?;
+    let profiles: ProfileJson = serde_json::from_str(&profiles).json(profiles)?;
+
+    let mut entries = Vec::new();
+    for profile in profiles.profiles.into_values() {
+        let name = if profile.name.is_empty() {
+            match profile.r#type.as_deref() {
+                Some("latest-release") => "Latest Release".to_owned(),
+                Some("latest-snapshot") => "Latest Snapshot".to_owned(),
+                _ => profile.lastVersionId.clone(),
+            }
+        } else {
+            profile.name.clone()
+        };
+        if profile.lastVersionId.is_empty() {
+            continue;
+        }
+
+        let (mc_version, loader, loader_version) =
+            parse_official_version(dir, &profile.lastVersionId).await;
+        entries.push(MigrationEntry {
+            launcher: OtherLauncher::Official,
+            name,
+            mc_version,
+            loader,
+            loader_version,
+            path: profile
+                .gameDir
+                .map_or_else(|| dir.to_owned(), PathBuf::from),
+        });
+    }
+    Ok(entries)
+}
+
+/// Figures out the Minecraft version and loader
+/// from an official launcher version ID.
+async fn parse_official_version(dir: &Path, id: &str) -> (String, Option<Loader>, Option<String>) {
+    #[derive(Deserialize)]
+    #[allow(non_snake_case)]
+    struct InheritingJson {
+        inheritsFrom: Option<String>,
+    }
+
+    let json_path = dir.join("versions").join(id).join(format!("{id}.json"));
+    let inherits_from = fs::read_to_string(&json_path)
+        .await
+        .ok()
+        .and_then(|n| serde_json::from_str::<InheritingJson>(&n).ok())
+        .and_then(|n| n.inheritsFrom);
+
+    parse_version_id(id, inherits_from)
+}
+
+/// Splits an official launcher version ID, like:
+/// - `1.20.1`
+/// - `fabric-loader-0.15.0-1.20.1`
+/// - `quilt-loader-0.23.0-1.20.1`
+/// - `1.20.1-forge-47.2.0`
+/// - `neoforge-21.1.1`
+///
+/// `inherits_from` is the vanilla version the
+/// version JSON is based on (if any).
+fn parse_version_id(
+    id: &str,
+    inherits_from: Option<String>,
+) -> (String, Option<Loader>, Option<String>) {
+    for (prefix, loader) in [
+        ("fabric-loader-", Loader::Fabric),
+        ("quilt-loader-", Loader::Quilt),
+    ] {
+        if let Some((loader_version, mc_version)) =
+            id.strip_prefix(prefix).and_then(|n| n.split_once('-'))
+        {
+            return (
+                mc_version.to_owned(),
+                Some(loader),
+                Some(loader_version.to_owned()),
+            );
+        }
+    }
+    if let Some(version) = id.strip_prefix("neoforge-") {
+        let mc_version = inherits_from.unwrap_or_else(|| {
+            // `21.1.1` -> `1.21.1`, `20.4.237` -> `1.20.4`
+            let mut parts = version.split('.');
+            match (parts.next(), parts.next()) {
+                (Some(major), Some("0")) => format!("1.{major}"),
+                (Some(major), Some(minor)) => format!("1.{major}.{minor}"),
+                _ => version.to_owned(),
+            }
+        });
+        return (mc_version, Some(Loader::Neoforge), Some(version.to_owned()));
+    }
+    if let Some((mc_version, forge_version)) = id.split_once("-forge") {
+        // Older versions look like `1.12.2-forge1.12.2-14.23.5.2860`
+        let forge_version = forge_version.trim_start_matches('-');
+        let forge_version = forge_version
+            .strip_prefix(mc_version)
+            .map_or(forge_version, |n| n.trim_start_matches('-'));
+        return (
+            mc_version.to_owned(),
+            Some(Loader::Forge),
+            Some(forge_version.to_owned()),
+        );
+    }
+
+    if let Some(inherits_from) = inherits_from {
+        pt!("Unknown modded version {id}, importing as {inherits_from}");
+        return (inherits_from, None, None);
+    }
+    (id.to_owned(), None, None)
+}
+
+async fn find_multimc(
+    dir: &Path,
+    launcher: OtherLauncher,
+) -> Result<Vec<MigrationEntry>, InstancePackageError> {
+    // The instances folder can be changed in the launcher settings
+    let mut instances_dir = dir.join("instances");
+    for cfg in ["prismlauncher.cfg", "multimc.cfg"] {
+        if let Ok(cfg) = fs::read_to_string(dir.join(cfg)).await {
+            if let Some(instance_dir) = Ini::load_from_str(&filter_bytearray(&cfg))
+                .ok()
+                .and_then(|n| n.get_from(None::<String>, "InstanceDir").map(PathBuf::from))
+            {
+                instances_dir = dir.join(instance_dir);
+            }
+        }
+    }
+
+    let mut entries = Vec::new();
+    for instance_dir in read_subdirs(&instances_dir).await? {
+        let (Ok(cfg), Ok(mmc_pack)) = (
+            fs::read_to_string(instance_dir.join("instance.cfg")).await,
+            fs::read_to_string(instance_dir.join("mmc-pack.json")).await,
+        ) else {
+            continue;
+        };
+        let name = Ini::load_from_str(&filter_bytearray(&cfg))
+            .ok()
+            .and_then(|n| {
+                n.get_from(Some("General"), "name")
+                    .or(n.get_from(None::<String>, "name"))
+                    .map(str::to_owned)
+            })
+            .unwrap_or_else(|| get_dir_name(&instance_dir));
+        let Some(mmc_pack) = parse_json::<MmcPack>(&instance_dir, &mmc_pack) else {
+            continue;
+        };
+
+        let mut entry = MigrationEntry {
+            launcher,
+            name,
+            mc_version: String::new(),
+            loader: None,
+            loader_version: None,
+            path: instance_dir,
+        };
+        for component in mmc_pack.components {
+            let version = component.get_version();
+            let loader = match component.uid.as_str() {
+                "net.minecraft" => {
+                    entry.mc_version = version;
+                    continue;
+                }
+                "net.minecraftforge" => Loader::Forge,
+                "net.neoforged" => Loader::Neoforge,
+                "net.fabricmc.fabric-loader" => Loader::Fabric,
+                "org.quiltmc.quilt-loader" => Loader::Quilt,
+                "com.mumfrey.liteloader" => Loader::Liteloader,
+                _ => continue,
+            };
+            entry.loader = Some(loader);
+            entry.loader_version = Some(version);
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+async fn find_atlauncher(dir: &Path) -> Result<Vec<MigrationEntry>, InstancePackageError> {
+    #[derive(Deserialize)]
+    struct AtInstance {
+        /// Minecraft version
+        id: String,
+        launcher: AtLauncherInfo,
+    }
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct AtLauncherInfo {
+        name: String,
+        loader_version: Option<AtLoaderVersion>,
+    }
+    #[derive(Deserialize)]
+    struct AtLoaderVersion {
+        version: String,
+        r#type: String,
+    }
+
+    let mut entries = Vec::new();
+    for instance_dir in read_subdirs(&dir.join("instances")).await? {
+        let Ok(json) = fs::read_to_string(instance_dir.join("instance.json")).await else {
+            continue;
+        };
+        let Some(instance) = parse_json::<AtInstance>(&instance_dir, &json) else {
+            continue;
+        };
+        let (loader, loader_version) = instance
+            .launcher
+            .loader_version
+            .and_then(|n| Some((parse_loader_name(&n.r#type)?, n.version)))
+            .unzip();
+
+        entries.push(MigrationEntry {
+            launcher: OtherLauncher::ATLauncher,
+            name: instance.launcher.name,
+            mc_version: instance.id,
+            loader,
+            loader_version,
+            path: instance_dir,
+        });
+    }
+    Ok(entries)
+}
+
+async fn find_gdlauncher(dir: &Path) -> Result<Vec<MigrationEntry>, InstancePackageError> {
+    // Legacy GDLauncher (`config.json`)
+    #[derive(Deserialize)]
+    struct GdlConfig {
+        loader: GdlLoader,
+    }
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct GdlLoader {
+        loader_type: String,
+        mc_version: String,
+        loader_version: Option<String>,
+    }
+
+    // GDLauncher Carbon (`instance.json`)
+    #[derive(Deserialize)]
+    struct CarbonInstance {
+        name: String,
+        game_configuration: CarbonGameConfig,
+    }
+    #[derive(Deserialize)]
+    struct CarbonGameConfig {
+        version: CarbonVersion,
+    }
+    #[derive(Deserialize)]
+    struct CarbonVersion {
+        #[serde(rename = "Standard")]
+        standard: CarbonStandardVersion,
+    }
+    #[derive(Deserialize)]
+    struct CarbonStandardVersion {
+        release: String,
+        #[serde(default)]
+        modloaders: Vec<CarbonModloader>,
+    }
+    #[derive(Deserialize)]
+    struct CarbonModloader {
+        type_: String,
+        version: String,
+    }
+
+    let mut entries = Vec::new();
+    for instance_dir in read_subdirs(&dir.join("instances")).await? {
+        if let Ok(json) = fs::read_to_string(instance_dir.join("config.json")).await {
+            let Some(config) = parse_json::<GdlConfig>(&instance_dir, &json) else {
+                continue;
+            };
+            let loader = parse_loader_name(&config.loader.loader_type);
+            entries.push(MigrationEntry {
+                launcher: OtherLauncher::GDLauncher,
+                name: get_dir_name(&instance_dir),
+                mc_version: config.loader.mc_version,
+                loader,
+                loader_version: loader.and(config.loader.loader_version),
+                path: instance_dir,
+            });
+        } else if let Ok(json) = fs::read_to_string(instance_dir.join("instance.json")).await {
+            let Some(instance) = parse_json::<CarbonInstance>(&instance_dir, &json) else {
+                continue;
+            };
+            let version = instance.game_configuration.version.standard;
+            let (loader, loader_version) = version
+                .modloaders
+                .into_iter()
+                .find_map(|n| Some((parse_loader_name(&n.type_)?, n.version)))
+                .unzip();
+            entries.push(MigrationEntry {
+                launcher: OtherLauncher::GDLauncher,
+                name: instance.name,
+                mc_version: version.release,
+                loader,
+                loader_version,
+                path: instance_dir.join("instance"),
+            });
+        }
+    }
+    Ok(entries)
+}
+
+async fn find_curseforge(dir: &Path) -> Result<Vec<MigrationEntry>, InstancePackageError> {
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct CfInstance {
+        name: String,
+        game_version: String,
+        base_mod_loader: Option<CfModLoader>,
+    }
+    #[derive(Deserialize)]
+    struct CfModLoader {
+        /// `forge-47.2.0`, `fabric-0.15.0-1.20.1`, etc.
+        name: String,
+    }
+
+    let mut entries = Vec::new();
+    for instance_dir in read_subdirs(&dir.join("Instances")).await? {
+        let Ok(json) = fs::read_to_string(instance_dir.join("minecraftinstance.json")).await else {
+            continue;
+        };
+        let Some(instance) = parse_json::<CfInstance>(&instance_dir, &json) else {
+            continue;
+        };
+        let (loader, loader_version) = instance
+            .base_mod_loader
+            .and_then(|n| {
+                let (loader, version) = n.name.split_once('-')?;
+                let version = version
+                    .strip_suffix(&format!("-{}", instance.game_version))
+                    .unwrap_or(version);
+                Some((parse_loader_name(loader)?, version.to_owned()))
+            })
+            .unzip();
+
+        entries.push(MigrationEntry {
+            launcher: OtherLauncher::CurseForge,
+            name: instance.name,
+            mc_version: instance.game_version,
+            loader,
+            loader_version,
+            path: instance_dir,
+        });
+    }
+    Ok(entries)
+}
+
+fn parse_loader_name(name: &str) -> Option<Loader> {
+    match name.to_lowercase().as_str() {
+        "fabric" => Some(Loader::Fabric),
+        "quilt" => Some(Loader::Quilt),
+        "forge" => Some(Loader::Forge),
+        "neoforge" => Some(Loader::Neoforge),
+        "vanilla" | "" => None,
+        _ => {
+            err!("Unsupported loader for migration: {name}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(id: &str) -> (String, Option<Loader>, Option<String>) {
+        parse_version_id(id, None)
+    }
+
+    #[test]
+    fn vanilla_version() {
+        assert_eq!(parse("1.20.1"), ("1.20.1".to_owned(), None, None));
+        assert_eq!(parse("b1.7.3"), ("b1.7.3".to_owned(), None, None));
+    }
+
+    #[test]
+    fn fabric_quilt_version() {
+        assert_eq!(
+            parse("fabric-loader-0.15.0-1.20.1"),
+            (
+                "1.20.1".to_owned(),
+                Some(Loader::Fabric),
+                Some("0.15.0".to_owned())
+            )
+        );
+        assert_eq!(
+            parse("quilt-loader-0.23.0-1.20.1"),
+            (
+                "1.20.1".to_owned(),
+                Some(Loader::Quilt),
+                Some("0.23.0".to_owned())
+            )
+        );
+    }
+
+    #[test]
+    fn forge_version() {
+        assert_eq!(
+            parse("1.20.1-forge-47.2.0"),
+            (
+                "1.20.1".to_owned(),
+                Some(Loader::Forge),
+                Some("47.2.0".to_owned())
+            )
+        );
+        assert_eq!(
+            parse("1.12.2-forge1.12.2-14.23.5.2860"),
+            (
+                "1.12.2".to_owned(),
+                Some(Loader::Forge),
+                Some("14.23.5.2860".to_owned())
+            )
+        );
+    }
+
+    #[test]
+    fn neoforge_version() {
+        assert_eq!(
+            parse("neoforge-21.1.1"),
+            (
+                "1.21.1".to_owned(),
+                Some(Loader::Neoforge),
+                Some("21.1.1".to_owned())
+            )
+        );
+        assert_eq!(
+            parse("neoforge-21.0.167"),
+            (
+                "1.21".to_owned(),
+                Some(Loader::Neoforge),
+                Some("21.0.167".to_owned())
+            )
+        );
+        // The version JSON knows better
+        assert_eq!(
+            parse_version_id("neoforge-20.2.86", Some("1.20.2".to_owned())).0,
+            "1.20.2"
+        );
+    }
+
+    #[test]
+    fn unknown_modded_version() {
+        assert_eq!(
+            parse_version_id("OptiFine_1.20.1_HD_U_I6", Some("1.20.1".to_owned())),
+            ("1.20.1".to_owned(), None, None)
+        );
+    }
+}
@@ -0,0 +1,375 @@
+//! Exporting instances as Modrinth (`.mrpack`)
+//! and CurseForge modpacks.
+//!
+//! Both formats consist of a metadata file listing
+//! the mods to download, and an `overrides/` folder
+//! with everything else from `.minecraft`.
+//!
+//! Only mods installed from the matching store
+//! (see [`ModIndex`]) are referenced, the rest
+//! (manually added or from the other store) are
+//! packaged in `overrides/mods/`.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+};
+
+use ql_core::{
+    file_utils,
+    json::{InstanceConfigJson, VersionDetails},
+    pt, GenericProgress, InstanceSelection, IntoIoError, IntoJsonError, Loader, ModId,
+};
+use ql_mod_manager::store::{ModIndex, SOURCE_ID_CURSEFORGE, SOURCE_ID_MODRINTH};
+use serde::Serialize;
+use tokio::fs;
+
+use crate::{
+    export::{copy_dot_minecraft, get_loader_version, get_minecraft_version, send_progress},
+    ExportFormat, InstancePackageError,
+};
+
+const SUPPORTED_LOADERS: &[Loader] = &[
+    Loader::Fabric,
+    Loader::Quilt,
+    Loader::Forge,
+    Loader::Neoforge,
+];
+
+/// Domains that Modrinth allows in `.mrpack` downloads.
+/// Mods hosted elsewhere must go in the overrides.
+const MRPACK_ALLOWED_DOMAINS: &[&str] = &[
+    "https://cdn.modrinth.com/",
+    "https://github.com/",
+    "https://raw.githubusercontent.com/",
+    "https://gitlab.com/",
+];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MrpackIndex {
+    format_version: usize,
+    game: &'static str,
+    version_id: String,
+    name: String,
+    files: Vec<MrpackFile>,
+    dependencies: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MrpackFile {
+    path: String,
+    hashes: MrpackHashes,
+    env: MrpackEnv,
+    downloads: Vec<String>,
+    file_size: usize,
+}
+
+#[derive(Serialize)]
+struct MrpackHashes {
+    sha1: String,
+    sha512: String,
+}
+
+#[derive(Serialize)]
+struct MrpackEnv {
+    client: &'static str,
+    server: &'static str,
+}
+
+#[derive(Serialize)]
+#[allow(non_snake_case)]
+struct CfManifest {
+    minecraft: CfMinecraft,
+    manifestType: &'static str,
+    manifestVersion: usize,
+    name: String,
+    version: String,
+    author: String,
+    files: Vec<CfFile>,
+    overrides: &'static str,
+}
+
+#[derive(Serialize)]
+#[allow(non_snake_case)]
+struct CfMinecraft {
+    version: String,
+    modLoaders: Vec<CfLoader>,
+}
+
+#[derive(Serialize)]
+struct CfLoader {
+    id: String,
+    primary: bool,
+}
+
+#[derive(Serialize)]
+#[allow(non_snake_case)]
+struct CfFile {
+    projectID: usize,
+    fileID: usize,
+    required: bool,
+}
+
+pub async fn export_mrpack(
+    instance: &InstanceSelection,
+    exceptions: &HashSet<String>,
+    progress: Option<&Sender<GenericProgress>>,
+) -> Result<Vec<u8>, InstancePackageError> {
+    let (config, json) = tokio::try_join!(
+        InstanceConfigJson::read(instance),
+        VersionDetails::load(instance)
+    )?;
+
+    let mut dependencies = serde_json::Map::new();
+    dependencies.insert("minecraft".to_owned(), get_minecraft_version(&json).into());
+    if let Some((loader, version)) =
+        get_loader_version(&config, ExportFormat::Modrinth, SUPPORTED_LOADERS)?
+    {
+        let loader = match loader {
+            Loader::Fabric => "fabric-loader",
+            Loader::Quilt => "quilt-loader",
+            Loader::Neoforge => "neoforge",
+            _ => "forge",
+        };
+        dependencies.insert(loader.to_owned(), version.into());
+    }
+
+    send_progress(progress, 0, 3, "Copying data...");
+    let dir = tempfile::TempDir::new().map_err(InstancePackageError::TempDir)?;
+    let overrides = dir.path().join("overrides");
+    copy_dot_minecraft(instance, exceptions, &overrides).await?;
+
+    send_progress(progress, 1, 3, "Hashing mods...");
+    let mut files = Vec::new();
+    for store_mod in get_store_mods(instance, &overrides, SOURCE_ID_MODRINTH).await? {
+        if !MRPACK_ALLOWED_DOMAINS
+            .iter()
+            .any(|n| store_mod.url.starts_with(n))
+        {
+            continue;
+        }
+        let file = store_mod.path;
+        let Some(filename) = file.file_name() else {
+            continue;
+        };
+        let bytes = fs::read(&file).await.path(&file)?;
+        files.push(MrpackFile {
+            path: format!("mods/{}", filename.to_string_lossy()),
+            hashes: MrpackHashes {
+                sha1: file_utils::get_sha1(&bytes),
+                sha512: file_utils::get_sha512(&bytes),
+            },
+            // We don't know which side the mod is for,
+            // so assume both.
+            env: MrpackEnv {
+                client: "required",
+                server: "required",
+            },
+            downloads: vec![store_mod.url],
+            file_size: bytes.len(),
+        });
+        fs::remove_file(&file).await.path(&file)?;
+    }
+    pt!("Referenced {} mods from Modrinth", files.len());
+
+    let index = MrpackIndex {
+        format_version: 1,
+        game: "minecraft",
+        version_id: "1.0.0".to_owned(),
+        name: instance.get_name().to_owned(),
+        files,
+        dependencies,
+    };
+    let index = serde_json::to_string_pretty(&index).json_to()?;
+    let index_path = dir.path().join("modrinth.index.json");
+    fs::write(&index_path, index).await.path(&index_path)?;
+
+    zip(dir.path(), progress).await
+}
+
+pub async fn export_curseforge(
+    instance: &InstanceSelection,
+    exceptions: &HashSet<String>,
+    progress: Option<&Sender<GenericProgress>>,
+) -> Result<Vec<u8>, InstancePackageError> {
+    let (config, json) = tokio::try_join!(
+        InstanceConfigJson::read(instance),
+        VersionDetails::load(instance)
+    )?;
+
+    let mod_loaders = get_loader_version(&config, ExportFormat::CurseForge, SUPPORTED_LOADERS)?
+        .map(|(loader, version)| CfLoader {
+            id: format!("{}-{version}", loader.to_modrinth_str()),
+            primary: true,
+        })
+        .into_iter()
+        .collect();
+
+    send_progress(progress, 0, 3, "Copying data...");
+    let dir = tempfile::TempDir::new().map_err(InstancePackageError::TempDir)?;
+    let overrides = dir.path().join("overrides");
+    copy_dot_minecraft(instance, exceptions, &overrides).await?;
+
+    send_progress(progress, 1, 3, "Collecting mods...");
+    let mut files = Vec::new();
+    for store_mod in get_store_mods(instance, &overrides, SOURCE_ID_CURSEFORGE).await? {
+        let Some((project_id, file_id)) = get_curseforge_ids(&store_mod) else {
+            continue;
+        };
+        files.push(CfFile {
+            projectID: project_id,
+            fileID: file_id,
+            required: true,
+        });
+        let file = store_mod.path;
+        fs::remove_file(&file).await.path(&file)?;
+    }
+    pt!("Referenced {} mods from CurseForge", files.len());
+
+    let manifest = CfManifest {
+        minecraft: CfMinecraft {
+            version: get_minecraft_version(&json).to_owned(),
+            modLoaders: mod_loaders,
+        },
+        manifestType: "minecraftModpack",
+        manifestVersion: 1,
+        name: instance.get_name().to_owned(),
+        version: "1.0.0".to_owned(),
+        author: String::new(),
+        files,
+        overrides: "overrides",
+    };
+    let manifest = serde_json::to_string_pretty(&manifest).json_to()?;
+    let manifest_path = dir.path().join("manifest.json");
+    fs::write(&manifest_path, manifest)
+        .await
+        .path(&manifest_path)?;
+
+    zip(dir.path(), progress).await
+}
+
+/// A mod installed from the store,
+/// that can be referenced instead of packaged.
+struct StoreMod {
+    /// Path of the mod file (inside `overrides/`)
+    path: PathBuf,
+    url: String,
+    id: ModId,
+}
+
+/// Returns every enabled mod installed from the given store.
+///
+/// Mods that were left out of the export are skipped.
+async fn get_store_mods(
+    instance: &InstanceSelection,
+    overrides: &Path,
+    source: &str,
+) -> Result<Vec<StoreMod>, InstancePackageError> {
+    let index = ModIndex::load(instance).await?;
+    let mods_dir = overrides.join("mods");
+
+    let mut mods = Vec::new();
+    for (id, config) in index.mods {
+        if !config.enabled || config.project_source != source {
+            continue;
+        }
+        let id = ModId::from_index_str(&id);
+        for file in config.files {
+            let path = mods_dir.join(&file.filename);
+            if path.is_file() {
+                mods.push(StoreMod {
+                    path,
+                    url: file.url,
+                    id: id.clone(),
+                });
+            }
+        }
+    }
+    Ok(mods)
+}
+
+/// Gets the CurseForge project and file ID of a mod.
+///
+/// The file ID isn't stored in the mod index,
+/// but is a part of the download URL:
+/// `https://edge.forgecdn.net/files/4567/890/name.jar` -> `4567890`
+fn get_curseforge_ids(store_mod: &StoreMod) -> Option<(usize, usize)> {
+    let ModId::Curseforge(project_id) = &store_mod.id else {
+        return None;
+    };
+    let project_id = project_id.parse().ok()?;
+
+    let mut parts = store_mod
+        .url
+        .split('/')
+        .skip_while(|n| *n != "files")
+        .skip(1);
+    let first: usize = parts.next()?.parse().ok()?;
+    let second: usize = parts.next()?.parse().ok()?;
+    Some((project_id, first * 1000 + second))
+}
+
+async fn zip(
+    dir: &Path,
+    progress: Option<&Sender<GenericProgress>>,
+) -> Result<Vec<u8>, InstancePackageError> {
+    send_progress(progress, 2, 3, "Zipping files...");
+    let bytes = file_utils::zip_directory_to_bytes(dir)
+        .await
+        .map_err(InstancePackageError::ZipIo)?;
+    pt!("Done!");
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store_mod(id: ModId, url: &str) -> StoreMod {
+        StoreMod {
+            path: PathBuf::from("mods/name.jar"),
+            url: url.to_owned(),
+            id,
+        }
+    }
+
+    #[test]
+    fn curseforge_ids() {
+        let m = store_mod(
+            ModId::Curseforge("238222".to_owned()),
+            "https://edge.forgecdn.net/files/4567/890/name.jar",
+        );
+        assert_eq!(get_curseforge_ids(&m), Some((238_222, 4_567_890)));
+
+        // Leading zeroes in the second part
+        let m = store_mod(
+            ModId::Curseforge("238222".to_owned()),
+            "https://mediafilez.forgecdn.net/files/5000/7/name.jar",
+        );
+        assert_eq!(get_curseforge_ids(&m), Some((238_222, 5_000_007)));
+    }
+
+    #[test]
+    fn curseforge_ids_invalid() {
+        let m = store_mod(
+            ModId::Modrinth("AANobbMI".to_owned()),
+            "https://edge.forgecdn.net/files/4567/890/name.jar",
+        );
+        assert_eq!(get_curseforge_ids(&m), None);
+
+        let m = store_mod(
+            ModId::Curseforge("238222".to_owned()),
+            "https://example.com/name.jar",
+        );
+        assert_eq!(get_curseforge_ids(&m), None);
+
+        let m = store_mod(
+            ModId::Curseforge("not-a-number".to_owned()),
+            "https://edge.forgecdn.net/files/4567/890/name.jar",
+        );
+        assert_eq!(get_curseforge_ids(&m), None);
+    }
+}
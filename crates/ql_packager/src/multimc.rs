@@ -1,11 +1,17 @@
 use chrono::DateTime;
 use ini::Ini;
 use std::{
+    collections::HashSet,
     path::Path,
     sync::{mpsc::Sender, Arc, Mutex},
 };
 
-use crate::{import::pipe_progress, import::OUT_OF, InstancePackageError};
+use crate::{
+    export::{copy_dot_minecraft, get_loader_version, get_minecraft_version, send_progress},
+    import::pipe_progress,
+    import::OUT_OF,
+    ExportFormat, InstancePackageError,
+};
 use ql_core::{
     do_jobs, err, file_utils, info,
     jarmod::{JarMod, JarMods},
@@ -13,26 +19,60 @@ use ql_core::{
         FabricJSON, InstanceConfigJson, Manifest, VersionDetails, V_1_12_2,
         V_OFFICIAL_FABRIC_SUPPORT,
     },
-    pt, GenericProgress, InstanceSelection, IntoIoError, IntoJsonError, ListEntry, Loader,
+    pt, GenericProgress, InstanceSelection, IntoIoError, IntoJsonError, ListEntry, Loader, ModId,
     LAUNCHER_DIR,
 };
-use ql_mod_manager::loaders::fabric::{self, get_list_of_versions_from_backend};
+use ql_mod_manager::{
+    loaders::fabric::{self, get_list_of_versions_from_backend},
+    store::{ModConfig, ModFile, ModIndex, SOURCE_ID_CURSEFORGE, SOURCE_ID_MODRINTH},
+};
 use serde::{Deserialize, Serialize};
 use tokio::fs;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
 pub struct MmcPack {
     pub components: Vec<MmcPackComponent>,
+    #[serde(default)]
+    pub formatVersion: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(non_snake_case)]
 pub struct MmcPackComponent {
-    pub cachedName: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cachedName: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cachedVersion: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
     pub uid: String,
 }
 
+impl MmcPackComponent {
+    pub(crate) fn get_version(&self) -> String {
+        self.version
+            .clone()
+            .or_else(|| self.cachedVersion.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// Components that are set up natively by the launcher
+/// (through the regular instance creation and loader installers).
+///
+/// Their `patches/<uid>.json` files (if any) are **not** copied over
+/// as they would conflict with our own installation.
+const NATIVE_COMPONENTS: &[&str] = &[
+    "net.minecraft",
+    "net.minecraftforge",
+    "net.neoforged",
+    "net.fabricmc.fabric-loader",
+    "net.fabricmc.intermediary",
+    "org.quiltmc.quilt-loader",
+    "org.quiltmc.hashed",
+];
+
 #[derive(Debug, Clone)]
 pub struct InstanceRecipe {
     is_lwjgl3: bool,
@@ -44,6 +84,21 @@ pub struct InstanceRecipe {
 }
 
 impl InstanceRecipe {
+    pub(crate) fn new(
+        mc_version: String,
+        loader: Option<Loader>,
+        loader_version: Option<String>,
+    ) -> Self {
+        Self {
+            is_lwjgl3: false,
+            mc_version,
+            loader,
+            loader_version,
+            force_vanilla_launch: false,
+            jarmods: Vec::new(),
+        }
+    }
+
     async fn setup_lwjgl3(&mut self) -> Result<(), InstancePackageError> {
         async fn adjust_for_lwjgl3(mc_version: &str) -> Result<bool, InstancePackageError> {
             let manifest = Manifest::download().await?;
@@ -78,7 +133,7 @@ pub async fn import(
 
     let ini = read_config_ini(temp_dir).await?;
     let (instance, instance_recipe) =
-        tokio::try_join!(get_instance(&ini), get_instance_recipe(&mmc_pack))?;
+        tokio::try_join!(get_instance(&ini), get_instance_recipe(&mmc_pack, temp_dir))?;
 
     create_minecraft_instance(
         download_assets,
@@ -90,10 +145,11 @@ pub async fn import(
 
     install_loader(&sender, &instance, &instance_recipe).await?;
 
-    copy_files(temp_dir, sender, &instance).await?;
+    copy_files(temp_dir, sender, &instance, &instance_recipe).await?;
 
     tokio::try_join!(
         setup_details(&instance),
+        import_mod_index(&instance, &instance_recipe.mc_version),
         async {
             let mut config = InstanceConfigJson::read(&instance).await?;
             setup_config(&ini, &instance_recipe, &mut config);
@@ -166,11 +222,46 @@ fn setup_config(ini: &Ini, instance_recipe: &InstanceRecipe, config: &mut Instan
         }
     }
 
-    if let Ok(jvmargs) = general_get(ini, "JvmArgs") {
-        config
-            .java_args
-            .get_or_insert_with(Vec::new)
-            .extend(jvmargs.split_whitespace().map(str::to_owned));
+    // Older MultiMC versions don't have the `Override*` keys,
+    // so only skip the setting if explicitly turned off.
+    let is_overridden = |key: &str| !matches!(general_get(ini, key), Ok("false"));
+
+    if is_overridden("OverrideJavaArgs") {
+        if let Ok(jvmargs) = general_get(ini, "JvmArgs") {
+            config
+                .java_args
+                .get_or_insert_with(Vec::new)
+                .extend(jvmargs.split_whitespace().map(str::to_owned));
+        }
+    }
+
+    if is_overridden("OverrideMemory") {
+        if let Some(max) = general_get(ini, "MaxMemAlloc")
+            .ok()
+            .and_then(|n| n.parse::<usize>().ok())
+        {
+            config.ram_in_mb = max;
+        }
+        if let Some(min) = general_get(ini, "MinMemAlloc")
+            .ok()
+            .and_then(|n| n.parse::<usize>().ok())
+        {
+            config
+                .java_args
+                .get_or_insert_with(Vec::new)
+                .push(format!("-Xms{min}M"));
+        }
+    }
+
+    if is_overridden("OverrideJavaLocation") && is_overridden("OverrideJava") {
+        if let Ok(java_path) = general_get(ini, "JavaPath") {
+            // The instance may have been exported from another computer
+            if Path::new(java_path).is_file() {
+                config.java_override = Some(java_path.to_owned());
+            } else if !java_path.is_empty() {
+                pt!("Ignoring Java path (not found on this system): {java_path}");
+            }
+        }
     }
 
     if let Ok(prefix) = general_get(ini, "WrapperCommand") {
@@ -191,8 +282,14 @@ fn general_get<'a>(ini: &'a Ini, key: &str) -> Result<&'a str, InstancePackageEr
 }
 
 async fn get_instance(ini: &Ini) -> Result<InstanceSelection, InstancePackageError> {
-    let mut instance_name = general_get(ini, "name")?.to_owned();
+    get_unique_instance(general_get(ini, "name")?.to_owned()).await
+}
 
+/// Picks a name for an imported instance that doesn't
+/// clash with any existing one.
+pub(crate) async fn get_unique_instance(
+    mut instance_name: String,
+) -> Result<InstanceSelection, InstancePackageError> {
     // If `MyInstance` exists, try `MyInstance (1)`, `(2)`...
     let instance_dir = LAUNCHER_DIR.join("instances");
     let mut path = instance_dir.join(&instance_name);
@@ -217,51 +314,63 @@ async fn read_config_ini(temp_dir: &Path) -> Result<Ini, InstancePackageError> {
     Ok(Ini::load_from_str(&filter_bytearray(&ini))?)
 }
 
-async fn get_instance_recipe(mmc_pack: &MmcPack) -> Result<InstanceRecipe, InstancePackageError> {
-    let mut recipe = InstanceRecipe {
-        is_lwjgl3: false,
-        mc_version: "(MultiMC) Couldn't find minecraft version".to_owned(),
-        loader: None,
-        loader_version: None,
-        force_vanilla_launch: false,
-        jarmods: Vec::new(),
-    };
+async fn get_instance_recipe(
+    mmc_pack: &MmcPack,
+    temp_dir: &Path,
+) -> Result<InstanceRecipe, InstancePackageError> {
+    let mut recipe = InstanceRecipe::new(
+        "(MultiMC) Couldn't find minecraft version".to_owned(),
+        None,
+        None,
+    );
 
     for component in &mmc_pack.components {
-        if component.uid.starts_with("custom.jarmod.") {
-            recipe.force_vanilla_launch = true; // ?
-        }
-        let version = component.cachedVersion.clone().unwrap_or_default();
+        let version = component.get_version();
+        let name = component.cachedName.as_deref().unwrap_or_default();
 
-        match component.cachedName.as_str() {
-            "Minecraft" => recipe.mc_version.clone_from(&version),
-
-            "Forge" => {
-                recipe.loader = Some(Loader::Forge);
-                recipe.loader_version = Some(version);
-            }
-            "NeoForge" => {
-                recipe.loader = Some(Loader::Neoforge);
-                recipe.loader_version = Some(version);
-            }
-            "Fabric Loader" => {
-                recipe.loader = Some(Loader::Fabric);
-                recipe.loader_version = Some(version);
-            }
-            "Quilt Loader" => {
-                recipe.loader = Some(Loader::Quilt);
-                recipe.loader_version = Some(version);
+        let loader = match component.uid.as_str() {
+            "net.minecraft" => {
+                recipe.mc_version.clone_from(&version);
+                None
             }
 
-            "LWJGL 3" => recipe.is_lwjgl3 = true,
+            "net.minecraftforge" => Some(Loader::Forge),
+            "net.neoforged" => Some(Loader::Neoforge),
+            "net.fabricmc.fabric-loader" => Some(Loader::Fabric),
+            "org.quiltmc.quilt-loader" => Some(Loader::Quilt),
+            "com.mumfrey.liteloader" => Some(Loader::Liteloader),
 
-            "LWJGL 2" | "Intermediary Mappings" => {}
-            name if name.contains("(jar mod)") => {
-                if let Some(jarmod_filename) = component.uid.split('.').next_back() {
+            "org.lwjgl3" => {
+                recipe.is_lwjgl3 = true;
+                None
+            }
+            "org.lwjgl" | "net.fabricmc.intermediary" | "org.quiltmc.hashed" => None,
+
+            uid if uid.starts_with("custom.jarmod.") => {
+                recipe.force_vanilla_launch = true; // ?
+                if let Some(jarmod_filename) = uid.split('.').next_back() {
                     recipe.jarmods.push(format!("{jarmod_filename}.jar"));
                 }
+                None
+            }
+            // Custom components, carried over in `copy_patches()`
+            uid if temp_dir
+                .join("patches")
+                .join(format!("{uid}.json"))
+                .is_file() =>
+            {
+                pt!("Custom component: {name} ({uid})");
+                None
             }
-            name => err!("Unknown MultiMC Component: {name}"),
+            uid => {
+                err!("Unknown MultiMC Component: {name} ({uid})");
+                None
+            }
+        };
+
+        if let Some(loader) = loader {
+            recipe.loader = Some(loader);
+            recipe.loader_version = Some(version);
         }
     }
 
@@ -269,7 +378,7 @@ async fn get_instance_recipe(mmc_pack: &MmcPack) -> Result<InstanceRecipe, Insta
     Ok(recipe)
 }
 
-async fn install_loader(
+pub(crate) async fn install_loader(
     sender: &Option<Arc<Sender<GenericProgress>>>,
     instance: &InstanceSelection,
     instance_recipe: &InstanceRecipe,
@@ -294,8 +403,15 @@ async fn install_loader(
                 )
                 .await?;
             }
+            Loader::Liteloader => {
+                ql_mod_manager::loaders::liteloader::install(
+                    instance_recipe.loader_version.clone(),
+                    instance.clone(),
+                )
+                .await?;
+            }
             loader => {
-                err!("Unimplemented MultiMC Component: {loader:?}");
+                err!("Unimplemented loader: {loader:?}");
             }
         }
     }
@@ -410,8 +526,14 @@ async fn copy_files(
     temp_dir: &Path,
     sender: Option<Arc<Sender<GenericProgress>>>,
     instance_selection: &InstanceSelection,
+    instance_recipe: &InstanceRecipe,
 ) -> Result<(), InstancePackageError> {
-    let src = temp_dir.join("minecraft");
+    // Older instances use `.minecraft`
+    let src = if temp_dir.join("minecraft").is_dir() {
+        temp_dir.join("minecraft")
+    } else {
+        temp_dir.join(".minecraft")
+    };
     if src.is_dir() {
         let dst = instance_selection.get_dot_minecraft_path();
         if let Some(sender) = sender.as_deref() {
@@ -426,11 +548,233 @@ async fn copy_files(
     }
 
     copy_folder_over(temp_dir, instance_selection, "jarmods").await?;
-    copy_folder_over(temp_dir, instance_selection, "patches").await?;
+    copy_patches(temp_dir, instance_selection, instance_recipe).await?;
+
+    Ok(())
+}
+
+/// Copies over the `patches/*.json` of components
+/// that the launcher doesn't set up natively (see [`NATIVE_COMPONENTS`]).
+///
+/// Libraries marked as `"MMC-hint": "local"` are stored
+/// in the instance's `libraries/` folder, so they get
+/// copied and pointed to directly.
+async fn copy_patches(
+    temp_dir: &Path,
+    instance_selection: &InstanceSelection,
+    instance_recipe: &InstanceRecipe,
+) -> Result<(), InstancePackageError> {
+    let src = temp_dir.join("patches");
+    if !src.is_dir() {
+        return Ok(());
+    }
+    let instance_dir = instance_selection.get_instance_path();
+    let dst = instance_dir.join("patches");
+    fs::create_dir_all(&dst).await.path(&dst)?;
+
+    let mut entries = fs::read_dir(&src).await.dir(&src)?;
+    while let Some(entry) = entries.next_entry().await.dir(&src)? {
+        let path = entry.path();
+        let Some(uid) = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_suffix(".json"))
+        else {
+            continue;
+        };
+        if NATIVE_COMPONENTS.contains(&uid)
+            || (instance_recipe.loader == Some(Loader::Liteloader)
+                && uid == ql_mod_manager::loaders::liteloader::PATCH_UID)
+        {
+            continue;
+        }
+
+        let patch = fs::read_to_string(&path).await.path(&path)?;
+        let mut patch: serde_json::Value = serde_json::from_str(&patch).json(patch)?;
+        for filename in localize_libraries(&mut patch) {
+            let lib_src = temp_dir.join("libraries").join(&filename);
+            if lib_src.is_file() {
+                let lib_dir = instance_dir.join("libraries");
+                fs::create_dir_all(&lib_dir).await.path(&lib_dir)?;
+                fs::copy(&lib_src, lib_dir.join(&filename))
+                    .await
+                    .path(&lib_src)?;
+            }
+        }
+
+        let patch_path = dst.join(format!("{uid}.json"));
+        let patch = serde_json::to_string_pretty(&patch).json_to()?;
+        fs::write(&patch_path, patch).await.path(&patch_path)?;
+    }
+    Ok(())
+}
+
+/// Points `"MMC-hint": "local"` libraries of a patch
+/// to the instance's `libraries/` folder.
+///
+/// Returns the filenames of the libraries that need to be copied over.
+fn localize_libraries(patch: &mut serde_json::Value) -> Vec<String> {
+    let mut filenames = Vec::new();
+    let Some(patch) = patch.as_object_mut() else {
+        return filenames;
+    };
+    for (key, libraries) in patch.iter_mut() {
+        if key != "libraries" && key != "+libraries" {
+            continue;
+        }
+        let Some(libraries) = libraries.as_array_mut() else {
+            continue;
+        };
+        for library in libraries {
+            if library.get("MMC-hint").and_then(|n| n.as_str()) != Some("local") {
+                continue;
+            }
+            let Some(filename) = library
+                .get("name")
+                .and_then(|n| n.as_str())
+                .and_then(get_local_library_filename)
+            else {
+                continue;
+            };
+            library["downloads"] = serde_json::json!({
+                "artifact": { "path": filename, "sha1": "", "size": 0, "url": "" }
+            });
+            filenames.push(filename);
+        }
+    }
+    filenames
+}
+
+/// `group:artifact:version[:classifier]` -> `artifact-version[-classifier].jar`
+fn get_local_library_filename(name: &str) -> Option<String> {
+    let mut parts = name.split(':').skip(1);
+    let artifact = parts.next()?;
+    let version = parts.next()?;
+    Some(if let Some(classifier) = parts.next() {
+        format!("{artifact}-{version}-{classifier}.jar")
+    } else {
+        format!("{artifact}-{version}.jar")
+    })
+}
+
+/// Reads PrismLauncher's mod metadata (`mods/.index/*.pw.toml`,
+/// in the [packwiz](https://packwiz.infra.link/) format)
+/// and adds the corresponding store mods to the [`ModIndex`],
+/// so that they can be updated and managed from the launcher.
+async fn import_mod_index(
+    instance: &InstanceSelection,
+    mc_version: &str,
+) -> Result<(), InstancePackageError> {
+    let index_dir = instance.get_dot_minecraft_path().join("mods/.index");
+    if !index_dir.is_dir() {
+        return Ok(());
+    }
+    let mods_dir = instance.get_dot_minecraft_path().join("mods");
+
+    let mut mod_index = ModIndex::load(instance).await?;
+    let mut entries = fs::read_dir(&index_dir).await.dir(&index_dir)?;
+    while let Some(entry) = entries.next_entry().await.dir(&index_dir)? {
+        let path = entry.path();
+        if !path.to_string_lossy().ends_with(".pw.toml") {
+            continue;
+        }
+        let file = fs::read_to_string(&path).await.path(&path)?;
+        let metadata: PackwizMod = match toml::from_str(&file) {
+            Ok(n) => n,
+            Err(error) => {
+                err!("Couldn't parse mod metadata {path:?}, skipping...\n{error}");
+                continue;
+            }
+        };
+        let Some((id, installed_version)) = metadata.get_id() else {
+            continue;
+        };
 
+        pt!("Mod: {} ({})", metadata.name, id.get_index_str());
+        mod_index.mods.insert(
+            id.get_index_str(),
+            ModConfig {
+                name: metadata.name,
+                manually_installed: true,
+                installed_version,
+                version_release_time: String::new(),
+                enabled: mods_dir.join(&metadata.filename).is_file(),
+                description: String::new(),
+                icon_url: None,
+                project_source: match id {
+                    ModId::Modrinth(_) => SOURCE_ID_MODRINTH,
+                    ModId::Curseforge(_) => SOURCE_ID_CURSEFORGE,
+                }
+                .to_owned(),
+                project_id: id.get_index_str(),
+                files: vec![ModFile {
+                    url: metadata.download.url.unwrap_or_default(),
+                    filename: metadata.filename,
+                    primary: true,
+                }],
+                supported_versions: vec![mc_version.to_owned()],
+                dependencies: HashSet::new(),
+                dependents: HashSet::new(),
+            },
+        );
+    }
+    mod_index.save(instance).await?;
     Ok(())
 }
 
+#[derive(Deserialize)]
+struct PackwizMod {
+    name: String,
+    filename: String,
+    download: PackwizDownload,
+    update: Option<PackwizUpdate>,
+}
+
+#[derive(Deserialize)]
+struct PackwizDownload {
+    url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PackwizUpdate {
+    modrinth: Option<PackwizModrinth>,
+    curseforge: Option<PackwizCurseforge>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct PackwizModrinth {
+    mod_id: String,
+    version: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct PackwizCurseforge {
+    project_id: usize,
+    file_id: usize,
+}
+
+impl PackwizMod {
+    /// Returns the store ID and installed version
+    fn get_id(&self) -> Option<(ModId, String)> {
+        let update = self.update.as_ref()?;
+        if let Some(modrinth) = &update.modrinth {
+            Some((
+                ModId::Modrinth(modrinth.mod_id.clone()),
+                modrinth.version.clone(),
+            ))
+        } else {
+            update.curseforge.as_ref().map(|cf| {
+                (
+                    ModId::Curseforge(cf.project_id.to_string()),
+                    cf.file_id.to_string(),
+                )
+            })
+        }
+    }
+}
+
 async fn copy_folder_over(
     temp_dir: &Path,
     instance_selection: &InstanceSelection,
@@ -444,7 +788,7 @@ async fn copy_folder_over(
     Ok(())
 }
 
-async fn create_minecraft_instance(
+pub(crate) async fn create_minecraft_instance(
     download_assets: bool,
     sender: Option<Arc<Sender<GenericProgress>>>,
     instance_name: &str,
@@ -499,7 +843,7 @@ async fn mmc_forge(
     Ok(())
 }
 
-fn filter_bytearray(input: &str) -> String {
+pub(crate) fn filter_bytearray(input: &str) -> String {
     // PrismLauncher puts some weird ByteArray
     // field in the INI config file, that `ini`
     // doesn't understand. So we have to filter it out.
@@ -509,3 +853,213 @@ fn filter_bytearray(input: &str) -> String {
         .collect::<Vec<_>>()
         .join("\n")
 }
+
+/// Exports a client instance as a MultiMC/PrismLauncher instance
+/// (`instance.cfg`, `mmc-pack.json` and `minecraft/`).
+///
+/// Custom components (`patches/*.json`) are carried over,
+/// jar mods are not.
+pub async fn export(
+    instance: &InstanceSelection,
+    exceptions: &HashSet<String>,
+    progress: Option<&Sender<GenericProgress>>,
+) -> Result<Vec<u8>, InstancePackageError> {
+    let (config, json) = tokio::try_join!(
+        InstanceConfigJson::read(instance),
+        VersionDetails::load(instance)
+    )?;
+    let mc_version = get_minecraft_version(&json).to_owned();
+
+    let mut components = vec![MmcPackComponent {
+        cachedName: Some("Minecraft".to_owned()),
+        cachedVersion: None,
+        version: Some(mc_version.clone()),
+        uid: "net.minecraft".to_owned(),
+    }];
+    let mut add_component = |uid: &str, name: &str, version: String| {
+        components.push(MmcPackComponent {
+            cachedName: Some(name.to_owned()),
+            cachedVersion: None,
+            version: Some(version),
+            uid: uid.to_owned(),
+        });
+    };
+    match get_loader_version(
+        &config,
+        ExportFormat::MultiMC,
+        &[
+            Loader::Fabric,
+            Loader::Quilt,
+            Loader::Forge,
+            Loader::Neoforge,
+            Loader::Liteloader,
+        ],
+    )? {
+        Some((Loader::Fabric, version)) => {
+            add_component(
+                "net.fabricmc.intermediary",
+                "Intermediary Mappings",
+                mc_version,
+            );
+            add_component("net.fabricmc.fabric-loader", "Fabric Loader", version);
+        }
+        Some((Loader::Quilt, version)) => {
+            add_component(
+                "net.fabricmc.intermediary",
+                "Intermediary Mappings",
+                mc_version,
+            );
+            add_component("org.quiltmc.quilt-loader", "Quilt Loader", version);
+        }
+        Some((Loader::Forge, version)) => add_component("net.minecraftforge", "Forge", version),
+        Some((Loader::Neoforge, version)) => add_component("net.neoforged", "NeoForge", version),
+        Some((Loader::Liteloader, version)) => {
+            add_component(
+                ql_mod_manager::loaders::liteloader::PATCH_UID,
+                "LiteLoader",
+                version,
+            );
+        }
+        _ => {}
+    }
+    if let Some(backend) = config
+        .mod_type_info
+        .as_ref()
+        .and_then(|n| n.backend_implementation.as_deref())
+    {
+        err!("{backend} isn't natively supported by MultiMC/PrismLauncher, the exported instance may not work");
+    }
+
+    send_progress(progress, 0, 2, "Copying data...");
+    let dir = tempfile::TempDir::new().map_err(InstancePackageError::TempDir)?;
+    copy_dot_minecraft(instance, exceptions, &dir.path().join("minecraft")).await?;
+    components.extend(export_patches(instance, dir.path()).await?);
+
+    if instance
+        .get_instance_path()
+        .join("jarmods")
+        .read_dir()
+        .is_ok_and(|mut n| n.next().is_some())
+    {
+        pt!("Jar mods aren't exported, you'll have to add them manually");
+    }
+
+    let mmc_pack = MmcPack {
+        components,
+        formatVersion: 1,
+    };
+    let mmc_pack = serde_json::to_string_pretty(&mmc_pack).json_to()?;
+    let mmc_pack_path = dir.path().join("mmc-pack.json");
+    fs::write(&mmc_pack_path, mmc_pack)
+        .await
+        .path(&mmc_pack_path)?;
+
+    let mut ini_bytes = Vec::new();
+    export_config_ini(instance, &config)
+        .write_to(&mut ini_bytes)
+        .map_err(InstancePackageError::ZipIo)?;
+    let ini_path = dir.path().join("instance.cfg");
+    fs::write(&ini_path, ini_bytes).await.path(&ini_path)?;
+
+    send_progress(progress, 1, 2, "Zipping files...");
+    let bytes = file_utils::zip_directory_to_bytes(dir.path())
+        .await
+        .map_err(InstancePackageError::ZipIo)?;
+    pt!("Done!");
+    Ok(bytes)
+}
+
+fn export_config_ini(instance: &InstanceSelection, config: &InstanceConfigJson) -> Ini {
+    let mut ini = Ini::new();
+    let mut general = ini.with_section(Some("General"));
+    general
+        .set("InstanceType", "OneSix")
+        .set("name", instance.get_name())
+        .set("OverrideMemory", "true")
+        .set("MaxMemAlloc", config.ram_in_mb.to_string());
+
+    if let Some(java_args) = config.java_args.as_ref().filter(|n| !n.is_empty()) {
+        general
+            .set("OverrideJavaArgs", "true")
+            .set("JvmArgs", java_args.join(" "));
+    }
+    if let Some(java_path) = &config.java_override {
+        general
+            .set("OverrideJavaLocation", "true")
+            .set("JavaPath", java_path);
+    }
+    if config.close_on_start == Some(true) {
+        general.set("CloseAfterLaunch", "true");
+    }
+
+    if let Some(settings) = &config.global_settings {
+        if let (Some(width), Some(height)) = (settings.window_width, settings.window_height) {
+            general
+                .set("OverrideWindow", "true")
+                .set("MinecraftWinWidth", width.to_string())
+                .set("MinecraftWinHeight", height.to_string());
+        }
+        if let Some(prefix) = settings
+            .pre_launch_prefix
+            .as_ref()
+            .filter(|n| !n.is_empty())
+        {
+            general
+                .set("OverrideCommands", "true")
+                .set("WrapperCommand", prefix.join(" "));
+        }
+    }
+    ini
+}
+
+/// Copies the instance's custom components (`patches/*.json`)
+/// into the export, returning their `mmc-pack.json` entries.
+async fn export_patches(
+    instance: &InstanceSelection,
+    dest: &Path,
+) -> Result<Vec<MmcPackComponent>, InstancePackageError> {
+    let src = instance.get_instance_path().join("patches");
+    if !src.is_dir() {
+        return Ok(Vec::new());
+    }
+    let dst = dest.join("patches");
+    fs::create_dir_all(&dst).await.path(&dst)?;
+
+    let mut components = Vec::new();
+    let mut entries = fs::read_dir(&src).await.dir(&src)?;
+    while let Some(entry) = entries.next_entry().await.dir(&src)? {
+        let path = entry.path();
+        let Some(uid) = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_suffix(".json"))
+            .map(str::to_owned)
+        else {
+            continue;
+        };
+        // Set up by PrismLauncher itself
+        if uid == ql_mod_manager::loaders::liteloader::PATCH_UID {
+            continue;
+        }
+
+        let patch = fs::read_to_string(&path).await.path(&path)?;
+        let patch_json: serde_json::Value = serde_json::from_str(&patch).json(patch.clone())?;
+        let get = |key: &str| {
+            patch_json
+                .get(key)
+                .and_then(|n| n.as_str())
+                .map(str::to_owned)
+        };
+
+        pt!("Custom component: {uid}");
+        components.push(MmcPackComponent {
+            cachedName: get("name"),
+            cachedVersion: None,
+            version: get("version"),
+            uid: uid.clone(),
+        });
+        let patch_path = dst.join(format!("{uid}.json"));
+        fs::write(&patch_path, patch).await.path(&patch_path)?;
+    }
+    Ok(components)
+}
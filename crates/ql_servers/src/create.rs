@@ -122,6 +122,11 @@ async fn write_config(
             is_special_lwjgl3: version_json.id.ends_with("-lwjgl3"),
         }),
         main_class_override: None,
+        modpack: None,
+        jvm_preset: None,
+        auto_ram: None,
+        account: None,
+        quick_play: None,
     };
     let server_config_path = server_dir.join("config.json");
     tokio::fs::write(
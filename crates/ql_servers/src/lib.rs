@@ -12,11 +12,13 @@ use ql_core::{impl_3_errs_jri, IoError, JsonError, RequestError};
 use ql_java_handler::JavaInstallError;
 
 mod create;
+mod process;
 mod run;
 mod server_properties;
 // mod ssh;
 pub use create::{create_server, delete_server};
-pub use run::run;
+pub use process::{get_running_pid, get_running_servers, stop};
+pub use run::{run, run_detached};
 pub use server_properties::ServerProperties;
 // pub use ssh::run_tunnel;
 
@@ -48,6 +50,10 @@ pub enum ServerError {
     NoForgeShimFound,
     #[error("{SERVER_ERR_PREFIX}couldn't convert PathBuf to str: {0:?}")]
     PathBufToStr(PathBuf),
+    #[error("{SERVER_ERR_PREFIX}couldn't get the process ID of the started server")]
+    NoProcessId,
+    #[error("{SERVER_ERR_PREFIX}couldn't stop the server (process ID: {0})")]
+    Stop(u32),
 }
 
 impl_3_errs_jri!(ServerError, Json, Request, Io);
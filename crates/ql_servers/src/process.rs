@@ -0,0 +1,142 @@
+//! Keeping track of running servers through a
+//! `server.pid` file in the server folder, so they
+//! can be stopped from another launcher process
+//! (eg: the command line).
+//!
+//! Process IDs get reused once a program exits, so
+//! a process is only treated as the server if it's
+//! running in (or from) the server folder.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Weak,
+    time::Duration,
+};
+
+use ql_core::{file_utils, IntoIoError, LAUNCHER_DIR};
+use sysinfo::{Pid, Process, ProcessRefreshKind, ProcessesToUpdate, Signal, System, UpdateKind};
+use tokio::{process::Child, sync::Mutex};
+
+use crate::ServerError;
+
+const PID_FILE: &str = "server.pid";
+
+fn pid_path(name: &str) -> PathBuf {
+    LAUNCHER_DIR.join("servers").join(name).join(PID_FILE)
+}
+
+pub(crate) async fn save_pid(name: &str, pid: u32) -> Result<(), ServerError> {
+    let path = pid_path(name);
+    tokio::fs::write(&path, pid.to_string()).await.path(path)?;
+    Ok(())
+}
+
+async fn read_pid(name: &str) -> Option<u32> {
+    let pid = tokio::fs::read_to_string(pid_path(name)).await.ok()?;
+    pid.trim().parse().ok()
+}
+
+/// Removes `server.pid` (if it's still for `pid`)
+async fn remove_pid(name: &str, pid: u32) {
+    if read_pid(name).await == Some(pid) {
+        _ = tokio::fs::remove_file(pid_path(name)).await;
+    }
+}
+
+/// Removes `server.pid` once the server
+/// (started by [`crate::run`]) exits.
+pub(crate) fn remove_pid_on_exit(name: String, pid: u32, child: Weak<Mutex<Child>>) {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            // Dropped by the launcher, which kills it
+            let Some(child) = child.upgrade() else {
+                break;
+            };
+            if !matches!(child.lock().await.try_wait(), Ok(None)) {
+                break;
+            }
+        }
+        remove_pid(&name, pid).await;
+    });
+}
+
+/// Gets the process ID of a server, if it's running.
+///
+/// Only knows about servers started by the launcher.
+pub async fn get_running_pid(name: &str) -> Option<u32> {
+    let pid = read_pid(name).await?;
+    let mut system = System::new();
+    if find_server_process(&mut system, name, pid).is_some() {
+        Some(pid)
+    } else {
+        remove_pid(name, pid).await;
+        None
+    }
+}
+
+/// Gets the servers that are currently running
+/// (see [`get_running_pid`]).
+pub async fn get_running_servers() -> Vec<String> {
+    let Ok(entries) = file_utils::read_filenames_from_dir(LAUNCHER_DIR.join("servers")).await
+    else {
+        return Vec::new();
+    };
+    let mut running = Vec::new();
+    for entry in entries.into_iter().filter(|n| !n.is_file) {
+        if get_running_pid(&entry.name).await.is_some() {
+            running.push(entry.name);
+        }
+    }
+    running
+}
+
+/// Stops a running server.
+///
+/// On Unix the server gets a `SIGTERM`, so it saves the
+/// worlds and shuts down cleanly. On Windows it's killed.
+///
+/// Returns `false` if the server wasn't running.
+///
+/// # Errors
+/// If the server couldn't be stopped (eg: no permission).
+pub async fn stop(name: &str) -> Result<bool, ServerError> {
+    let Some(pid) = read_pid(name).await else {
+        return Ok(false);
+    };
+    let mut system = System::new();
+    let Some(process) = find_server_process(&mut system, name, pid) else {
+        remove_pid(name, pid).await;
+        return Ok(false);
+    };
+
+    // `None` if signals aren't supported (Windows)
+    let stopped = process
+        .kill_with(Signal::Term)
+        .unwrap_or_else(|| process.kill());
+    if stopped {
+        Ok(true)
+    } else {
+        Err(ServerError::Stop(pid))
+    }
+}
+
+/// Gets process `pid`, if it's the server `name`
+/// and not some other program that got its ID.
+fn find_server_process<'a>(system: &'a mut System, name: &str, pid: u32) -> Option<&'a Process> {
+    let pid = Pid::from_u32(pid);
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing()
+            .with_cwd(UpdateKind::Always)
+            .with_cmd(UpdateKind::Always),
+    );
+    let process = system.process(pid)?;
+
+    let dir = LAUNCHER_DIR.join("servers").join(name);
+    let dir = dir.canonicalize().unwrap_or(dir);
+    let is_server = process.cwd().is_some_and(|n| n == dir)
+        || process.cmd().iter().any(|n| Path::new(n).starts_with(&dir));
+    is_server.then_some(process)
+}
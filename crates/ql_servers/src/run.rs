@@ -5,15 +5,20 @@ use std::{
 };
 
 use ql_core::{
-    find_forge_shim_file, info,
+    err, find_forge_shim_file, info,
     json::{InstanceConfigJson, VersionDetails},
     no_window, pt, GenericProgress, InstanceSelection, IntoIoError, LaunchedProcess, Loader,
     LAUNCHER_DIR,
 };
-use ql_java_handler::{get_java_binary, JavaVersion};
+use ql_java_handler::{
+    get_java_binary, get_jvm_args, JavaInfo, JavaMismatch, JavaRequirement, JavaVersion,
+};
 use tokio::{process::Command, sync::Mutex};
 
-use crate::ServerError;
+use crate::{
+    process::{remove_pid_on_exit, save_pid},
+    ServerError,
+};
 
 /// Runs a server.
 ///
@@ -38,25 +43,8 @@ pub async fn run(
     name: String,
     java_install_progress: Option<Sender<GenericProgress>>,
 ) -> Result<LaunchedProcess, ServerError> {
-    let launcher = ServerLauncher::new(&name).await?;
-
-    let server_jar_path = launcher.get_server_jar().await?;
-
-    let java_path = launcher.get_java(java_install_progress.as_ref()).await?;
-
-    let java_args = launcher.get_java_args(&server_jar_path).await?;
-    let mut game_args = launcher.config.game_args.clone().unwrap_or_default();
-    game_args.push("nogui".to_owned());
-
-    info!("Java: {java_path:?}\n");
-    info!("Java args: {java_args:?}\n");
-    info!("Server args: {game_args:?}\n");
-
-    let mut command = Command::new(java_path);
-    command
-        .args(java_args.iter().chain(game_args.iter()))
-        .current_dir(&launcher.dir)
-        .kill_on_drop(true);
+    let (mut command, launcher) = get_command(&name, java_install_progress).await?;
+    command.kill_on_drop(true);
 
     if launcher.config.enable_logger.unwrap_or(true) {
         no_window!(command);
@@ -66,20 +54,84 @@ pub async fn run(
             .stdin(Stdio::piped());
     }
 
-    let child = command.spawn().path(server_jar_path)?;
-    if let Some(id) = child.id() {
+    let child = command.spawn().path(&launcher.dir)?;
+    let id = child.id();
+    let child = Arc::new(Mutex::new(child));
+    if let Some(id) = id {
         pt!("PID: {id}");
+        save_pid(&name, id).await?;
+        remove_pid_on_exit(name.clone(), id, Arc::downgrade(&child));
     } else {
         pt!("No ID found!");
     }
     Ok(LaunchedProcess {
-        child: Arc::new(Mutex::new(child)),
+        child,
         instance: InstanceSelection::Server(name),
         is_classic_server: launcher.is_classic_server(),
     })
 }
 
+/// Runs a server in the background, not tied to
+/// the launcher. It keeps running after the launcher
+/// closes, until stopped with [`crate::stop`].
+///
+/// There's no console, the output can be found
+/// in the server's `logs/` folder.
+///
+/// Returns the process ID of the server.
+///
+/// # Errors
+/// Same as [`run`]
+pub async fn run_detached(
+    name: String,
+    java_install_progress: Option<Sender<GenericProgress>>,
+) -> Result<u32, ServerError> {
+    let (mut command, launcher) = get_command(&name, java_install_progress).await?;
+    no_window!(command);
+    command
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .stdin(Stdio::null());
+    // Don't get killed along with the terminal (eg: Ctrl+C)
+    #[cfg(target_family = "unix")]
+    command.process_group(0);
+
+    let child = command.spawn().path(&launcher.dir)?;
+    let id = child.id().ok_or(ServerError::NoProcessId)?;
+    pt!("PID: {id}");
+    save_pid(&name, id).await?;
+    Ok(id)
+}
+
+async fn get_command(
+    name: &str,
+    java_install_progress: Option<Sender<GenericProgress>>,
+) -> Result<(Command, ServerLauncher), ServerError> {
+    let launcher = ServerLauncher::new(name).await?;
+
+    let server_jar_path = launcher.get_server_jar().await?;
+
+    let (java_path, java_info) = launcher.get_java(java_install_progress.as_ref()).await?;
+
+    let java_args = launcher
+        .get_java_args(&server_jar_path, java_info.as_ref())
+        .await?;
+    let mut game_args = launcher.config.game_args.clone().unwrap_or_default();
+    game_args.push("nogui".to_owned());
+
+    info!("Java: {java_path:?}\n");
+    info!("Java args: {java_args:?}\n");
+    info!("Server args: {game_args:?}\n");
+
+    let mut command = Command::new(java_path);
+    command
+        .args(java_args.iter().chain(game_args.iter()))
+        .current_dir(&launcher.dir);
+    Ok((command, launcher))
+}
+
 struct ServerLauncher {
+    name: String,
     dir: PathBuf,
     version_json: VersionDetails,
     config: InstanceConfigJson,
@@ -91,6 +143,7 @@ impl ServerLauncher {
         Ok(Self {
             version_json: VersionDetails::load_from_path(&dir).await?,
             config: InstanceConfigJson::read_from_dir(&dir).await?,
+            name: name.to_owned(),
             dir,
         })
     }
@@ -103,21 +156,49 @@ impl ServerLauncher {
         self.config.is_classic_server.unwrap_or_default()
     }
 
+    /// Picks the Java to run the server with.
+    ///
+    /// Falls back to the launcher-provided Java if the
+    /// user-provided Java (override) can't run the server
+    /// (too old, too new or wrong architecture). If it couldn't
+    /// be checked at all, it's used anyway.
+    ///
+    /// Returns the path and the info about it
+    /// (`None` if it couldn't be checked).
     pub async fn get_java(
         &self,
         java_install_progress: Option<&Sender<GenericProgress>>,
-    ) -> Result<PathBuf, ServerError> {
-        let version = if let Some(version) = self.version_json.javaVersion.clone() {
+    ) -> Result<(PathBuf, Option<JavaInfo>), ServerError> {
+        let requirement = JavaRequirement::for_server(&self.version_json);
+
+        if let Some(java_path) = self.config.get_java_override() {
+            match requirement.check_binary(&java_path).await {
+                Ok(java) => return Ok((java_path, Some(java))),
+                Err(JavaMismatch::NotJava) => {
+                    err!("Couldn't check Java override {java_path:?}, using it anyway");
+                    return Ok((java_path, None));
+                }
+                Err(mismatch) => err!(
+                    "Can't use Java override {java_path:?}: {mismatch}\nUsing launcher-provided Java instead"
+                ),
+            }
+        }
+        let version = if let Some(version) = self.config.java_override_version {
+            let clamped = requirement.clamp(version);
+            if clamped != version {
+                err!(
+                    "Java {version} (override) can't run this server, using Java {clamped} instead"
+                );
+            }
+            clamped.into()
+        } else if let Some(version) = self.version_json.javaVersion.clone() {
             version.into()
         } else {
             JavaVersion::Java8
         };
-
-        if let Some(java_path) = self.config.get_java_override() {
-            return Ok(java_path);
-        }
         let path = get_java_binary(version, "java", java_install_progress).await?;
-        Ok(path)
+        let info = JavaInfo::for_managed(path.clone(), version);
+        Ok((path, Some(info)))
     }
 
     pub async fn get_server_jar(&self) -> Result<PathBuf, ServerError> {
@@ -145,9 +226,18 @@ impl ServerLauncher {
         })
     }
 
-    pub async fn get_java_args(&self, jar: &Path) -> Result<Vec<String>, ServerError> {
-        let mut java_args: Vec<String> = self.config.get_java_args(&[]);
-        java_args.push(self.config.get_ram_argument());
+    pub async fn get_java_args(
+        &self,
+        jar: &Path,
+        java: Option<&JavaInfo>,
+    ) -> Result<Vec<String>, ServerError> {
+        let mut java_args: Vec<String> = get_jvm_args(
+            &self.config,
+            &[],
+            java,
+            &InstanceSelection::new(&self.name, true),
+        )
+        .await;
         if self.config.mod_type == Loader::Forge {
             java_args.push("-Djava.net.preferIPv6Addresses=system".to_owned());
         } else if self.config.mod_type == Loader::Fabric {
@@ -0,0 +1,758 @@
+//! Instance definitions: an instance described in a TOML
+//! or JSON file, so it can be shared and version controlled.
+//!
+//! `apply` creates the instance if it's missing, or changes
+//! an existing one to match the file. `diff` only shows
+//! what `apply` would change.
+//!
+//! Resource packs and shaders aren't in the mod index, so the
+//! files installed for them are recorded in `applied_packs.json`
+//! (in the instance folder).
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use owo_colors::OwoColorize;
+use ql_core::{
+    json::{GlobalSettings, InstanceConfigJson, VersionDetails},
+    InstanceSelection, ListEntry, Loader, ModId, StoreBackendType,
+};
+use ql_mod_manager::{
+    loaders::LoaderInstallResult,
+    store::{self, ModIndex},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::cli::{
+    command::{check_not_allowed, download_progress_printer, progress_printer},
+    helpers::{fail, is_json, print_result},
+};
+
+/// An instance, as described in a definition file.
+///
+/// ```toml
+/// name = "Team SMP"
+/// version = "1.21.1"
+/// memory = 4096
+/// java_args = ["-XX:+UseZGC"]
+///
+/// [loader]
+/// kind = "fabric"
+/// version = "0.16.5"
+///
+/// [[mods]]
+/// id = "sodium"
+/// version = "mc1.21.1-0.6.0-fabric"
+///
+/// [[resource_packs]]
+/// id = "CF:12345"
+///
+/// [settings]
+/// window_width = 1280
+/// ```
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct InstanceDefinition {
+    name: String,
+    /// Minecraft version
+    version: String,
+    /// Vanilla if not given
+    loader: Option<LoaderDefinition>,
+    #[serde(default)]
+    mods: Vec<ModDefinition>,
+    #[serde(default)]
+    resource_packs: Vec<ModDefinition>,
+    #[serde(default)]
+    shaders: Vec<ModDefinition>,
+    /// Memory to allocate (in MB)
+    memory: Option<usize>,
+    java_args: Option<Vec<String>>,
+    game_args: Option<Vec<String>>,
+    settings: Option<GlobalSettings>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LoaderDefinition {
+    /// Eg: `fabric`, `forge`, `neoforge`
+    kind: String,
+    /// The latest one if not given
+    version: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ModDefinition {
+    /// Modrinth project ID or slug,
+    /// or CurseForge project ID with a `CF:` prefix
+    id: String,
+    /// Modrinth version number, or CurseForge file ID.
+    /// The latest compatible one if not given
+    version: Option<String>,
+}
+
+/// Resource packs and shaders installed by `apply`,
+/// by [`ModId::get_index_str`]
+#[derive(Serialize, Deserialize, Default)]
+struct AppliedPacks {
+    packs: HashMap<String, AppliedPack>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AppliedPack {
+    name: String,
+    /// The pinned version, if any
+    version: Option<String>,
+    /// Relative to `.minecraft`
+    files: Vec<PathBuf>,
+}
+
+impl AppliedPacks {
+    const FILE: &str = "applied_packs.json";
+
+    async fn load(instance: &InstanceSelection) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = instance.get_instance_path().join(Self::FILE);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let text = tokio::fs::read_to_string(&path).await?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    async fn save(&self, instance: &InstanceSelection) -> Result<(), Box<dyn std::error::Error>> {
+        let path = instance.get_instance_path().join(Self::FILE);
+        tokio::fs::write(&path, serde_json::to_string_pretty(self)?).await?;
+        Ok(())
+    }
+}
+
+enum Change {
+    Create {
+        version: String,
+    },
+    /// Can't be applied, the instance has to be recreated
+    Version {
+        from: String,
+        to: String,
+    },
+    Loader {
+        from: Loader,
+        to: Loader,
+        version: Option<String>,
+    },
+    AddMod {
+        id: ModId,
+        label: String,
+        version: Option<String>,
+    },
+    RemoveMod {
+        id: ModId,
+        name: String,
+    },
+    /// A resource pack or shader
+    AddPack {
+        id: ModId,
+        label: String,
+        version: Option<String>,
+    },
+    RemovePack {
+        id: String,
+        name: String,
+        files: Vec<PathBuf>,
+    },
+    /// A pinned mod with a different version installed
+    ModVersion {
+        id: ModId,
+        name: String,
+        from: String,
+        to: String,
+        pinned: String,
+    },
+    Config {
+        field: &'static str,
+        from: String,
+        to: String,
+    },
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Create { version } => write!(f, "+ create instance ({version})"),
+            Change::Version { from, to } => write!(
+                f,
+                "! version: {from} -> {to} (can't be changed, delete the instance first)"
+            ),
+            Change::Loader { from, to, version } => {
+                write!(f, "~ loader: {from} -> {to}")?;
+                if let Some(version) = version {
+                    write!(f, " {version}")?;
+                }
+                Ok(())
+            }
+            Change::AddMod { label, version, .. } | Change::AddPack { label, version, .. } => {
+                write!(f, "+ {label}")?;
+                if let Some(version) = version {
+                    write!(f, " ({version})")?;
+                }
+                Ok(())
+            }
+            Change::RemoveMod { name, .. } | Change::RemovePack { name, .. } => {
+                write!(f, "- {name}")
+            }
+            Change::ModVersion { name, from, to, .. } => {
+                write!(f, "~ {name}: {from} -> {to}")
+            }
+            Change::Config { field, from, to } => write!(f, "~ {field}: {from} -> {to}"),
+        }
+    }
+}
+
+pub async fn apply(file: &Path, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let definition = load(file).await?;
+    let changes = plan(&definition).await?;
+
+    if is_json() {
+        if dry_run {
+            print_result(json!({
+                "name": definition.name,
+                "changes": changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            }));
+            return Ok(());
+        }
+    } else if changes.is_empty() {
+        println!("{} is up to date", definition.name.bold());
+    } else {
+        for change in &changes {
+            let line = change.to_string();
+            match change {
+                Change::Create { .. } | Change::AddMod { .. } | Change::AddPack { .. } => {
+                    println!("{}", line.green());
+                }
+                Change::RemoveMod { .. } | Change::RemovePack { .. } => println!("{}", line.red()),
+                Change::Version { .. } => println!("{}", line.red().bold()),
+                _ => println!("{}", line.yellow()),
+            }
+        }
+    }
+    if dry_run {
+        return Ok(());
+    }
+
+    execute(&definition, &changes).await?;
+    if is_json() {
+        print_result(json!({
+            "name": definition.name,
+            "changes": changes.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        }));
+    }
+    Ok(())
+}
+
+async fn load(file: &Path) -> Result<InstanceDefinition, Box<dyn std::error::Error>> {
+    let text = tokio::fs::read_to_string(file).await?;
+    let is_json = file
+        .extension()
+        .is_some_and(|n| n.eq_ignore_ascii_case("json"));
+    let definition: InstanceDefinition = if is_json {
+        serde_json::from_str(&text)?
+    } else {
+        toml::from_str(&text)?
+    };
+    Ok(definition)
+}
+
+/// Works out what needs to change to make
+/// the instance match the definition.
+async fn plan(definition: &InstanceDefinition) -> Result<Vec<Change>, Box<dyn std::error::Error>> {
+    let instance = InstanceSelection::new(&definition.name, false);
+    let mut changes = Vec::new();
+
+    let (loader, loader_version) = match &definition.loader {
+        Some(n) => (parse_loader(&n.kind), n.version.clone()),
+        None => (Loader::Vanilla, None),
+    };
+    let mods = resolve_mods(&definition.mods).await?;
+    let packs = resolve_mods(definition.resource_packs.iter().chain(&definition.shaders)).await?;
+
+    if !instance.get_instance_path().exists() {
+        changes.push(Change::Create {
+            version: definition.version.clone(),
+        });
+        if loader != Loader::Vanilla {
+            changes.push(Change::Loader {
+                from: Loader::Vanilla,
+                to: loader,
+                version: loader_version,
+            });
+        }
+        changes.extend(mods.into_iter().map(|(id, label, version)| Change::AddMod {
+            id,
+            label,
+            version,
+        }));
+        changes.extend(
+            packs
+                .into_iter()
+                .map(|(id, label, version)| Change::AddPack { id, label, version }),
+        );
+        plan_config(definition, None, &mut changes);
+        return Ok(changes);
+    }
+
+    let details = VersionDetails::load(&instance).await?;
+    if details.get_id() != definition.version {
+        changes.push(Change::Version {
+            from: details.get_id().to_owned(),
+            to: definition.version.clone(),
+        });
+    }
+
+    let config = InstanceConfigJson::read(&instance).await?;
+    let installed_version = config
+        .mod_type_info
+        .as_ref()
+        .and_then(|n| n.version.clone());
+    if config.mod_type != loader
+        || (loader_version.is_some() && loader_version != installed_version)
+    {
+        changes.push(Change::Loader {
+            from: config.mod_type,
+            to: loader,
+            version: loader_version,
+        });
+    }
+
+    let index = ModIndex::load(&instance).await?;
+    let wanted: HashSet<String> = mods.iter().map(|(id, ..)| id.get_index_str()).collect();
+    for (id, config) in &index.mods {
+        if config.manually_installed && !wanted.contains(id) {
+            changes.push(Change::RemoveMod {
+                id: ModId::from_index_str(id),
+                name: config.name.clone(),
+            });
+        }
+    }
+    for (id, label, version) in mods {
+        let Some(installed) = index.mods.get(&id.get_index_str()) else {
+            changes.push(Change::AddMod { id, label, version });
+            continue;
+        };
+        let Some(pinned) = version else {
+            continue;
+        };
+        let to = store::get_version_name(&id, &pinned).await?;
+        if installed.installed_version != to {
+            changes.push(Change::ModVersion {
+                id,
+                name: installed.name.clone(),
+                from: installed.installed_version.clone(),
+                to,
+                pinned,
+            });
+        }
+    }
+
+    let applied = AppliedPacks::load(&instance).await?;
+    plan_packs(
+        packs,
+        &applied,
+        &instance.get_dot_minecraft_path(),
+        &mut changes,
+    );
+
+    plan_config(definition, Some(&config), &mut changes);
+    Ok(changes)
+}
+
+/// Packs are reinstalled if their pinned version
+/// changed, or any of their files are gone
+fn plan_packs(
+    packs: Vec<(ModId, String, Option<String>)>,
+    applied: &AppliedPacks,
+    dot_minecraft: &Path,
+    changes: &mut Vec<Change>,
+) {
+    let wanted: HashSet<String> = packs.iter().map(|(id, ..)| id.get_index_str()).collect();
+    let mut ids: Vec<&String> = applied.packs.keys().collect();
+    ids.sort();
+    for id in ids {
+        let pack = &applied.packs[id];
+        if !wanted.contains(id) {
+            changes.push(Change::RemovePack {
+                id: id.clone(),
+                name: pack.name.clone(),
+                files: pack.files.clone(),
+            });
+        }
+    }
+    for (id, label, version) in packs {
+        if let Some(pack) = applied.packs.get(&id.get_index_str()) {
+            if pack.version == version && pack.files.iter().all(|n| dot_minecraft.join(n).exists())
+            {
+                continue;
+            }
+            changes.push(Change::RemovePack {
+                id: id.get_index_str(),
+                name: pack.name.clone(),
+                files: pack.files.clone(),
+            });
+        }
+        changes.push(Change::AddPack { id, label, version });
+    }
+}
+
+fn plan_config(
+    definition: &InstanceDefinition,
+    config: Option<&InstanceConfigJson>,
+    changes: &mut Vec<Change>,
+) {
+    let mut check = |field, from: Option<String>, to: Option<String>| {
+        if let Some(to) = to {
+            if from.as_ref() != Some(&to) {
+                changes.push(Change::Config {
+                    field,
+                    from: from.unwrap_or_else(|| "(default)".to_owned()),
+                    to,
+                });
+            }
+        }
+    };
+    check(
+        "memory",
+        config.map(|n| format!("{} MB", n.ram_in_mb)),
+        definition.memory.map(|n| format!("{n} MB")),
+    );
+    check(
+        "java_args",
+        config.and_then(|n| n.java_args.as_ref()).map(to_json),
+        definition.java_args.as_ref().map(to_json),
+    );
+    check(
+        "game_args",
+        config.and_then(|n| n.game_args.as_ref()).map(to_json),
+        definition.game_args.as_ref().map(to_json),
+    );
+    check(
+        "settings",
+        config.and_then(|n| n.global_settings.as_ref()).map(to_json),
+        definition.settings.as_ref().map(to_json),
+    );
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+async fn execute(
+    definition: &InstanceDefinition,
+    changes: &[Change],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let instance = InstanceSelection::new(&definition.name, false);
+
+    if let Some(change) = changes.iter().find(|n| matches!(n, Change::Version { .. })) {
+        fail(format!("Can't apply {}:\n    {change}", definition.name))
+    }
+
+    for change in changes {
+        match change {
+            Change::Create { version } => {
+                let (sender, printer) = download_progress_printer();
+                let result = ql_instances::create_instance(
+                    definition.name.clone(),
+                    ListEntry::new(version.clone()),
+                    Some(sender),
+                    true,
+                )
+                .await;
+                _ = printer.join();
+                result?;
+            }
+            Change::Loader { from, to, version } => {
+                if *from != Loader::Vanilla {
+                    ql_mod_manager::loaders::uninstall_loader(instance.clone()).await?;
+                }
+                if *to != Loader::Vanilla {
+                    match ql_mod_manager::loaders::install_specified_loader(
+                        instance.clone(),
+                        *to,
+                        None,
+                        version.clone(),
+                    )
+                    .await?
+                    {
+                        LoaderInstallResult::Ok => {}
+                        LoaderInstallResult::NeedsOptifine => fail(
+                            "OptiFine needs its installer, use `quantum_launcher loader install optifine`",
+                        ),
+                        LoaderInstallResult::Unsupported => {
+                            fail(format!("Installing {to} is unsupported!"))
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let remove: Vec<ModId> = changes
+        .iter()
+        .filter_map(|n| match n {
+            Change::RemoveMod { id, .. } | Change::ModVersion { id, .. } => Some(id.clone()),
+            _ => None,
+        })
+        .collect();
+    if !remove.is_empty() {
+        store::delete_mods(remove, instance.clone()).await?;
+    }
+
+    let add: Vec<(ModId, Option<String>)> = changes
+        .iter()
+        .filter_map(|n| match n {
+            Change::AddMod { id, version, .. } => Some((id.clone(), version.clone())),
+            Change::ModVersion { id, pinned, .. } => Some((id.clone(), Some(pinned.clone()))),
+            _ => None,
+        })
+        .collect();
+    if !add.is_empty() {
+        let (sender, printer) = progress_printer();
+        let not_allowed = store::download_mods_pinned(add, instance.clone(), Some(sender)).await;
+        _ = printer.join();
+        check_not_allowed(not_allowed?);
+    }
+
+    execute_packs(&instance, changes).await?;
+
+    if changes.iter().any(|n| matches!(n, Change::Config { .. })) {
+        let mut config = InstanceConfigJson::read(&instance).await?;
+        if let Some(memory) = definition.memory {
+            config.ram_in_mb = memory;
+            config.auto_ram = Some(false);
+        }
+        if let Some(args) = &definition.java_args {
+            config.java_args = Some(args.clone());
+        }
+        if let Some(args) = &definition.game_args {
+            config.game_args = Some(args.clone());
+        }
+        if let Some(settings) = &definition.settings {
+            config.global_settings = Some(settings.clone());
+        }
+        config.save(&instance).await?;
+    }
+
+    Ok(())
+}
+
+async fn execute_packs(
+    instance: &InstanceSelection,
+    changes: &[Change],
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !changes
+        .iter()
+        .any(|n| matches!(n, Change::AddPack { .. } | Change::RemovePack { .. }))
+    {
+        return Ok(());
+    }
+    let dot_minecraft = instance.get_dot_minecraft_path();
+    let mut applied = AppliedPacks::load(instance).await?;
+
+    for change in changes {
+        if let Change::RemovePack { id, files, .. } = change {
+            for file in files {
+                let path = dot_minecraft.join(file);
+                if path.is_file() {
+                    tokio::fs::remove_file(&path).await?;
+                }
+            }
+            applied.packs.remove(id);
+        }
+    }
+    applied.save(instance).await?;
+
+    for change in changes {
+        let Change::AddPack { id, label, version } = change else {
+            continue;
+        };
+        // One at a time, to know which files belong to which pack
+        let before = pack_files(&dot_minecraft).await?;
+        let (sender, printer) = progress_printer();
+        let not_allowed = store::download_mods_pinned(
+            vec![(id.clone(), version.clone())],
+            instance.clone(),
+            Some(sender),
+        )
+        .await;
+        _ = printer.join();
+        check_not_allowed(not_allowed?);
+
+        let files: Vec<PathBuf> = pack_files(&dot_minecraft)
+            .await?
+            .into_iter()
+            .filter(|(path, modified)| before.get(path) != Some(modified))
+            .map(|(path, _)| path)
+            .collect();
+        applied.packs.insert(
+            id.get_index_str(),
+            AppliedPack {
+                name: label.clone(),
+                version: version.clone(),
+                files,
+            },
+        );
+        applied.save(instance).await?;
+    }
+    Ok(())
+}
+
+/// Files in the resource pack and shader folders (relative
+/// to `.minecraft`), with when they were last modified
+async fn pack_files(
+    dot_minecraft: &Path,
+) -> Result<HashMap<PathBuf, Option<SystemTime>>, Box<dyn std::error::Error>> {
+    let mut files = HashMap::new();
+    for dir in ["resourcepacks", "texturepacks", "shaderpacks"] {
+        let Ok(mut entries) = tokio::fs::read_dir(dot_minecraft.join(dir)).await else {
+            continue;
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let modified = entry.metadata().await?.modified().ok();
+            files.insert(Path::new(dir).join(entry.file_name()), modified);
+        }
+    }
+    Ok(files)
+}
+
+fn parse_loader(kind: &str) -> Loader {
+    if kind.eq_ignore_ascii_case("vanilla") {
+        return Loader::Vanilla;
+    }
+    Loader::ALL
+        .iter()
+        .copied()
+        .find(|n| n.to_modrinth_str().eq_ignore_ascii_case(kind))
+        .unwrap_or_else(|| fail(format!("Invalid loader: {kind}")))
+}
+
+/// Turns mods, resource packs or shaders of the definition
+/// into `(id, label, pinned version)`, looking up Modrinth slugs
+async fn resolve_mods(
+    list: impl IntoIterator<Item = &ModDefinition>,
+) -> Result<Vec<(ModId, String, Option<String>)>, Box<dyn std::error::Error>> {
+    let mut mods = Vec::new();
+    for n in list {
+        let id = if n.id.starts_with("CF:") {
+            ModId::from_index_str(&n.id)
+        } else {
+            store::resolve_mod_id(&n.id, StoreBackendType::Modrinth).await?
+        };
+        mods.push((id, n.id.clone(), n.version.clone()));
+    }
+    Ok(mods)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn definition(text: &str) -> InstanceDefinition {
+        toml::from_str(text).unwrap()
+    }
+
+    fn pack(version: Option<&str>, files: &[&str]) -> AppliedPack {
+        AppliedPack {
+            name: "Pack".to_owned(),
+            version: version.map(str::to_owned),
+            files: files.iter().map(PathBuf::from).collect(),
+        }
+    }
+
+    fn lines(changes: &[Change]) -> Vec<String> {
+        changes.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn config() {
+        let definition = definition(
+            r#"
+            name = "Test"
+            version = "1.21.1"
+            memory = 4096
+            java_args = ["-XX:+UseZGC"]
+            "#,
+        );
+        let mut changes = Vec::new();
+        plan_config(&definition, None, &mut changes);
+        assert_eq!(
+            lines(&changes),
+            [
+                "~ memory: (default) -> 4096 MB",
+                r#"~ java_args: (default) -> ["-XX:+UseZGC"]"#
+            ]
+        );
+
+        let mut config: InstanceConfigJson =
+            serde_json::from_str(r#"{"ram_in_mb": 2048, "mod_type": "Vanilla"}"#).unwrap();
+        config.ram_in_mb = 4096;
+        config.java_args = Some(vec!["-XX:+UseZGC".to_owned()]);
+        let mut changes = Vec::new();
+        plan_config(&definition, Some(&config), &mut changes);
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn packs() {
+        let dir = std::env::temp_dir().join("ql_apply_test_packs");
+        std::fs::create_dir_all(dir.join("resourcepacks")).unwrap();
+        std::fs::write(dir.join("resourcepacks/a.zip"), "").unwrap();
+
+        let mut applied = AppliedPacks::default();
+        applied
+            .packs
+            .insert("CF:1".to_owned(), pack(None, &["resourcepacks/a.zip"]));
+        applied
+            .packs
+            .insert("CF:2".to_owned(), pack(Some("5"), &["resourcepacks/b.zip"]));
+        applied.packs.insert("CF:3".to_owned(), pack(None, &[]));
+
+        let wanted = vec![
+            // Installed, nothing to do
+            (ModId::Curseforge("1".to_owned()), "CF:1".to_owned(), None),
+            // File is gone, reinstall
+            (
+                ModId::Curseforge("2".to_owned()),
+                "CF:2".to_owned(),
+                Some("5".to_owned()),
+            ),
+            // New
+            (ModId::Curseforge("4".to_owned()), "CF:4".to_owned(), None),
+        ];
+        let mut changes = Vec::new();
+        plan_packs(wanted, &applied, &dir, &mut changes);
+        // CF:3 isn't wanted anymore
+        assert_eq!(
+            lines(&changes),
+            ["- Pack", "- Pack", "+ CF:2 (5)", "+ CF:4"]
+        );
+        assert!(matches!(&changes[0], Change::RemovePack { id, .. } if id == "CF:3"));
+        assert!(matches!(&changes[1], Change::RemovePack { id, .. } if id == "CF:2"));
+
+        // Pinned to another version
+        let wanted = vec![(
+            ModId::Curseforge("1".to_owned()),
+            "CF:1".to_owned(),
+            Some("7".to_owned()),
+        )];
+        applied.packs.retain(|id, _| id == "CF:1");
+        let mut changes = Vec::new();
+        plan_packs(wanted, &applied, &dir, &mut changes);
+        assert_eq!(lines(&changes), ["- Pack", "+ CF:1 (7)"]);
+
+        _ = std::fs::remove_dir_all(&dir);
+    }
+}
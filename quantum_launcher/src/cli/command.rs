@@ -2,16 +2,32 @@ use owo_colors::{OwoColorize, Style};
 use ql_core::{
     eeprintln, err, info,
     json::{InstanceConfigJson, VersionDetails},
-    InstanceSelection, IntoStringError, ListEntry, Loader, OptifineUniqueVersion, LAUNCHER_DIR,
+    read_log::LogLine,
+    DownloadProgress, GenericProgress, InstanceSelection, IntoIoError, IntoStringError,
+    JavaVersion, LaunchedProcess, ListEntry, Loader, ModId, OptifineUniqueVersion, Progress,
+    SelectedMod, StoreBackendType, LAUNCHER_DIR,
 };
-use ql_instances::auth::{self, AccountType};
-use ql_mod_manager::loaders::LoaderInstallResult;
-use std::{path::PathBuf, process::exit};
+use ql_instances::{
+    auth::{self, offline::OfflineProfile, AccountType},
+    LaunchOverrides, QuickPlay,
+};
+use ql_mod_manager::{
+    loaders::LoaderInstallResult,
+    store::{self, CurseforgeNotAllowed, ModConfig, ModIndex, Query, QueryType},
+};
+use ql_packager::ExportFormat;
+use serde_json::json;
+use std::{collections::HashSet, path::PathBuf, process::exit, sync::mpsc::Sender};
 
 use crate::{
-    cli::{helpers::render_row, QLoader},
+    cli::{
+        helpers::{fail, is_json, print_event, print_result, render_row},
+        CliBackend, CliExportFormat, CliQueryType, QInstance, QJava, QLaunch, QLoader, QModpack,
+        QMods, QPreset, QServer, QServerProperties,
+    },
     config::LauncherConfig,
-    state::get_entries,
+    shortcut,
+    state::{get_entries, OFFLINE_ACCOUNT_NAME},
 };
 
 use super::PrintCmd;
@@ -19,18 +35,34 @@ use super::PrintCmd;
 pub fn list_available_versions() {
     use std::io::Write;
 
-    eeprintln!("Listing downloadable versions...");
+    if !is_json() {
+        eeprintln!("Listing downloadable versions...");
+    }
     let (versions, _) = match tokio::runtime::Runtime::new()
         .unwrap()
         .block_on(ql_instances::list_versions())
         .strerr()
     {
         Ok(n) => n,
-        Err(err) => {
-            panic!("Could not list versions!\n{err}");
-        }
+        Err(err) => fail(format!("Could not list versions!\n{err}")),
     };
 
+    if is_json() {
+        print_result(
+            versions
+                .iter()
+                .map(|n| {
+                    json!({
+                        "name": n.name,
+                        "kind": n.kind,
+                        "supports_server": n.supports_server,
+                    })
+                })
+                .collect(),
+        );
+        return;
+    }
+
     let mut stdout = std::io::stdout().lock();
     for version in versions {
         writeln!(stdout, "{version}").unwrap();
@@ -62,6 +94,32 @@ pub fn list_instances(
     let dirname = if is_server { "servers" } else { "instances" };
     let (instances, _) = tokio::runtime::Runtime::new()?.block_on(get_entries(is_server))?;
 
+    if is_json() {
+        let list = instances
+            .iter()
+            .map(|instance| {
+                let instance_dir = LAUNCHER_DIR.join(dirname).join(instance);
+                let version = runtime
+                    .block_on(VersionDetails::load_from_path(&instance_dir))
+                    .ok()
+                    .map(|n| n.id);
+                let config = runtime
+                    .block_on(InstanceConfigJson::read_from_dir(&instance_dir))
+                    .ok();
+                json!({
+                    "name": instance,
+                    "version": version,
+                    "loader": config.as_ref().map(|n| n.mod_type.to_string()),
+                    "loader_version": config
+                        .and_then(|n| n.mod_type_info)
+                        .and_then(|n| n.version),
+                })
+            })
+            .collect();
+        print_result(list);
+        return Ok(());
+    }
+
     let mut cmds_name = String::new();
     let mut cmds_version = String::new();
     let mut cmds_loader = String::new();
@@ -139,10 +197,29 @@ pub async fn create_instance(
     servers: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let entry = ListEntry::new(version);
-    if servers {
-        ql_servers::create_server(instance_name, entry, None).await?;
+    // Only shown in JSON mode, there's already
+    // enough logging otherwise
+    let (sender, printer) = if is_json() {
+        let (sender, printer) = download_progress_printer();
+        (Some(sender), Some(printer))
     } else {
-        ql_instances::create_instance(instance_name, entry, None, !skip_assets).await?;
+        (None, None)
+    };
+    let name: Result<String, Box<dyn std::error::Error>> = if servers {
+        ql_servers::create_server(instance_name, entry, sender.as_ref())
+            .await
+            .map_err(Into::into)
+    } else {
+        ql_instances::create_instance(instance_name, entry, sender, !skip_assets)
+            .await
+            .map_err(Into::into)
+    };
+    if let Some(printer) = printer {
+        _ = printer.join();
+    }
+    let name = name?;
+    if is_json() {
+        print_result(json!({ "name": name }));
     }
 
     Ok(())
@@ -151,11 +228,16 @@ pub async fn create_instance(
 pub fn delete_instance(
     instance_name: String,
     force: bool,
+    servers: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let kind = if servers { "server" } else { "instance" };
+    if !force && is_json() {
+        fail("Can't ask for confirmation in JSON mode, pass --force");
+    }
     if !force {
         println!(
             "{} {instance_name}?",
-            "Are you SURE you want to delete the instance"
+            format!("Are you SURE you want to delete the {kind}")
                 .yellow()
                 .bold()
         );
@@ -166,10 +248,10 @@ pub fn delete_instance(
         }
     }
 
-    let selected_instance = InstanceSelection::Instance(instance_name.clone());
+    let selected_instance = InstanceSelection::new(&instance_name, servers);
     let deleted_instance_dir = selected_instance.get_instance_path();
     std::fs::remove_dir_all(&deleted_instance_dir)?;
-    info!("Deleted instance {instance_name}");
+    info!("Deleted {kind} {instance_name}");
 
     Ok(())
 }
@@ -198,40 +280,136 @@ fn confirm_action() -> bool {
 }
 
 pub async fn launch_instance(
-    instance_name: String,
-    username: String,
-    use_account: bool,
+    args: QLaunch,
     servers: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let account = refresh_account(&username, use_account).await?;
-
-    let child = if servers {
+    if servers {
         // TODO: stdin input
-        ql_servers::run(instance_name.clone(), None).await?
-    } else {
-        ql_instances::launch(
-            instance_name.clone(),
-            username,
-            None,
-            account.clone(),
-            None, // No global defaults in CLI mode
-            Vec::new(),
-        )
-        .await?
+        let child = ql_servers::run(args.instance_name, None).await?;
+        return wait_for_exit(&child, Vec::new()).await;
+    }
+
+    let detach = args.detach;
+    // Otherwise instances with "close launcher on start" make
+    // the launcher exit before it can show the log
+    let (child, censors) = start_game(args, !detach).await?;
+
+    if detach {
+        let Some(pid) = child.child.lock().await.id() else {
+            fail("Game exited right after launching")
+        };
+        if is_json() {
+            print_result(json!({ "pid": pid }));
+        } else {
+            info!("Game is running in the background (PID: {pid})");
+        }
+        return Ok(());
+    }
+
+    wait_for_exit(&child, censors).await
+}
+
+/// Launches an instance with the options in `args`,
+/// returning the game process and the text to censor
+/// from its log (access tokens).
+pub async fn start_game(
+    args: QLaunch,
+    keep_launcher_open: bool,
+) -> Result<(LaunchedProcess, Vec<String>), Box<dyn std::error::Error>> {
+    let (username, account, offline_profile) = get_launch_account(&args).await?;
+
+    let quick_play = match (args.server, args.world) {
+        (Some(server), _) => Some(QuickPlay::Server(server)),
+        (None, Some(world)) => Some(QuickPlay::World(world)),
+        (None, None) => None,
     };
+    let overrides = LaunchOverrides {
+        java: args.java,
+        ram_in_mb: args.memory,
+        java_args: args.jvm_args,
+        game_args: args.game_args,
+        quick_play,
+        detached: args.detach,
+        keep_launcher_open,
+    };
+
+    let child = ql_instances::launch(
+        args.instance_name,
+        username,
+        None,
+        account.clone(),
+        None, // No global defaults in CLI mode
+        overrides,
+        offline_profile,
+    )
+    .await?;
 
     let mut censors = Vec::new();
     if let Some(token) = account.as_ref().and_then(|n| n.access_token.as_ref()) {
         censors.push(token.clone());
     }
+    Ok((child, censors))
+}
 
-    match child.read_logs(censors, None).await {
+/// The exit code of a process, like a shell would report it:
+/// `128 + signal` if it was killed by a signal (Unix).
+fn get_exit_code(status: std::process::ExitStatus) -> i32 {
+    if let Some(code) = status.code() {
+        return code;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    1
+}
+
+/// Shows the output of a running game or server,
+/// exiting with its exit code once it closes
+async fn wait_for_exit(
+    process: &LaunchedProcess,
+    censors: Vec<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (sender, printer) = if is_json() {
+        let (sender, receiver) = std::sync::mpsc::channel::<LogLine>();
+        let printer = std::thread::spawn(move || {
+            for line in receiver {
+                print_event(&log_event(&line));
+            }
+        });
+        (Some(sender), Some(printer))
+    } else {
+        (None, None)
+    };
+
+    let result = process.read_logs(censors, sender).await;
+    if let Some(printer) = printer {
+        _ = printer.join();
+    }
+
+    match result {
         Some(Ok((s, _, diag))) => {
-            info!("Game exited with code {s}");
-            if let Some(diag) = diag {
-                err!("{diag}");
+            let code = get_exit_code(s);
+            if is_json() {
+                print_result(json!({
+                    "exit_code": code,
+                    "diagnostic": diag.map(|n| n.to_string()),
+                }));
+            } else {
+                let kind = if process.instance.is_server() {
+                    "Server"
+                } else {
+                    "Game"
+                };
+                info!("{kind} exited with code {s}");
+                if let Some(diag) = diag {
+                    err!("{diag}");
+                }
             }
-            exit(s.code().unwrap_or_default());
+            exit(code);
         }
         Some(Err(err)) => Err(err)?,
         None => {}
@@ -239,56 +417,118 @@ pub async fn launch_instance(
     Ok(())
 }
 
-async fn refresh_account(
-    username: &String,
-    use_account: bool,
-) -> Result<Option<auth::AccountData>, Box<dyn std::error::Error>> {
-    Ok(if use_account {
-        let config = LauncherConfig::load_s()?;
-        let Some((real_name, account)) = config.accounts.as_ref().and_then(|accounts| {
-            accounts.get_key_value(username).or_else(|| {
-                accounts
-                    .iter()
-                    .find(|n| n.1.username_nice.as_ref().is_some_and(|n| n == username))
-            })
-        }) else {
-            err!("No logged-in account called {username:?} was found!");
-            exit(1);
-        };
+/// The `--json` event for a line of game/server output
+pub fn log_event(line: &LogLine) -> serde_json::Value {
+    let level = match line {
+        LogLine::Info(event) => Some(event.level.clone()),
+        LogLine::Error(_) => Some("ERROR".to_owned()),
+        LogLine::Message(_) => None,
+    };
+    json!({
+        "type": "log",
+        "level": level,
+        "message": line.to_string(),
+    })
+}
 
-        match account.account_type.as_deref() {
-            // Hook: Account types
-            Some(kind @ ("ElyBy" | "LittleSkin")) => {
-                let account_type = if kind == "ElyBy" {
-                    AccountType::ElyBy
-                } else {
-                    AccountType::LittleSkin
-                };
-                let refresh_token = auth::read_refresh_token(real_name, account_type)?;
-                Some(
-                    auth::yggdrasil::login_refresh(
-                        real_name.to_owned(),
-                        refresh_token,
-                        account_type,
-                    )
-                    .await?,
-                )
-            }
-            _ => {
-                let refresh_token = auth::read_refresh_token(real_name, AccountType::Microsoft)?;
-                Some(auth::ms::login_refresh(real_name.clone(), refresh_token, None).await?)
+/// Picks the username, account and offline profile to launch with.
+///
+/// Without a username, the instance's default account
+/// (set in Edit Instance) is used.
+async fn get_launch_account(
+    args: &QLaunch,
+) -> Result<(String, Option<auth::AccountData>, Option<OfflineProfile>), Box<dyn std::error::Error>>
+{
+    let config = LauncherConfig::load_s()?;
+    let instance_name = &args.instance_name;
+
+    if let Some(account) = &args.account {
+        let account = refresh_account(&config, account).await?;
+        return Ok((account.nice_username.clone(), Some(account), None));
+    }
+
+    let Some(username) = args.username.clone() else {
+        let instance_config =
+            InstanceConfigJson::read(&InstanceSelection::new(instance_name, false)).await?;
+        let Some(account) = instance_config.account else {
+            if args.offline {
+                return Ok((config.username.clone(), None, None));
             }
-        }
+            return Err(format!("No username given, and {instance_name:?} has no default account!\n    Set one in Edit Instance, or pass a username").into());
+        };
+
+        return Ok(if account == OFFLINE_ACCOUNT_NAME {
+            (config.username.clone(), None, None)
+        } else if let Some(profile) = config.get_offline_profile(&account) {
+            (profile.name.clone(), None, Some(profile.clone()))
+        } else if args.offline {
+            (config.username.clone(), None, None)
+        } else {
+            let account = refresh_account(&config, &account).await?;
+            (account.nice_username.clone(), Some(account), None)
+        });
+    };
+
+    Ok(if args.use_account {
+        let account = refresh_account(&config, &username).await?;
+        (username, Some(account), None)
     } else {
-        None
+        let profile = config
+            .offline_profiles
+            .iter()
+            .flatten()
+            .find(|n| n.name == username)
+            .cloned();
+        (username, None, profile)
     })
 }
 
+async fn refresh_account(
+    config: &LauncherConfig,
+    username: &String,
+) -> Result<auth::AccountData, Box<dyn std::error::Error>> {
+    auth::set_credential_backend(config.credential_backend.unwrap_or_default());
+    let Some((real_name, account)) = config.accounts.as_ref().and_then(|accounts| {
+        accounts.get_key_value(username).or_else(|| {
+            accounts
+                .iter()
+                .find(|n| n.1.username_nice.as_ref().is_some_and(|n| n == username))
+        })
+    }) else {
+        return Err(format!("No logged-in account called {username:?} was found!").into());
+    };
+
+    // Hook: Account types
+    let mut data = match account.get_account_type(real_name) {
+        AccountType::Microsoft => {
+            let refresh_token = auth::read_refresh_token(real_name, &AccountType::Microsoft)?;
+            auth::ms::login_refresh(real_name.clone(), refresh_token, None).await?
+        }
+        account_type => {
+            let refresh_token = auth::read_refresh_token(real_name, &account_type)?;
+            auth::yggdrasil::login_refresh(real_name.to_owned(), refresh_token, account_type)
+                .await?
+        }
+    };
+    data.authlib_metadata.clone_from(&account.authlib_metadata);
+    Ok(data)
+}
+
 pub async fn loader(cmd: QLoader, servers: bool) -> Result<(), Box<dyn std::error::Error>> {
     match cmd {
         QLoader::Info { instance } => {
             let json =
                 InstanceConfigJson::read(&InstanceSelection::new(&instance, servers)).await?;
+            if is_json() {
+                let info = json.mod_type_info.unwrap_or_default();
+                print_result(json!({
+                    "kind": json.mod_type.to_string(),
+                    "version": info.version,
+                    "backend": info.backend_implementation,
+                    "optifine_jar": info.optifine_jar,
+                }));
+                return Ok(());
+            }
             println!("Kind: {}", json.mod_type);
             if let Some(info) = json.mod_type_info {
                 if let Some(version) = info.version {
@@ -309,16 +549,14 @@ pub async fn loader(cmd: QLoader, servers: bool) -> Result<(), Box<dyn std::erro
             version,
         } => {
             if loader.eq_ignore_ascii_case("vanilla") {
-                err!("Vanilla refers to the base game.\n    Maybe you meant `./quantum_launcher loader uninstall ...`");
-                exit(1);
+                fail("Vanilla refers to the base game.\n    Maybe you meant `./quantum_launcher loader uninstall ...`")
             }
             let Some(loader) = Loader::ALL
                 .iter()
                 .copied()
                 .find(|n| n.to_modrinth_str().eq_ignore_ascii_case(&loader))
             else {
-                err!("Invalid loader: {loader}");
-                exit(1)
+                fail(format!("Invalid loader: {loader}"))
             };
 
             let instance = InstanceSelection::new(&instance, servers);
@@ -332,11 +570,10 @@ pub async fn loader(cmd: QLoader, servers: bool) -> Result<(), Box<dyn std::erro
                 || (mt == Loader::Forge && loader == Loader::OptiFine)
                 || (mt == Loader::OptiFine && loader == Loader::Forge))
             {
-                err!(
+                fail(
                     r"You can't install a loader on top of another loader!
-    Did you mean to uninstall the other one first: `./quantum_launcher loader uninstall ...`"
-                );
-                exit(1);
+    Did you mean to uninstall the other one first: `./quantum_launcher loader uninstall ...`",
+                )
             }
 
             match ql_mod_manager::loaders::install_specified_loader(
@@ -351,10 +588,7 @@ pub async fn loader(cmd: QLoader, servers: bool) -> Result<(), Box<dyn std::erro
                 LoaderInstallResult::NeedsOptifine => {
                     install_optifine(more, instance).await?;
                 }
-                LoaderInstallResult::Unsupported => {
-                    err!("This loader is unsupported!");
-                    exit(1);
-                }
+                LoaderInstallResult::Unsupported => fail("This loader is unsupported!"),
             }
         }
         QLoader::Uninstall { instance } => {
@@ -380,11 +614,10 @@ async fn install_optifine(
     }
 
     let Some(more) = more else {
-        err!(
+        fail(
             r"Please download the OptiFine installer at: https://optifine.net/downloads
-    and pass the path via: `quantum_launcher loader install optifine path/to/installer.jar`"
-        );
-        exit(1);
+    and pass the path via: `quantum_launcher loader install optifine path/to/installer.jar`",
+        )
     };
 
     ql_mod_manager::loaders::optifine::install(
@@ -397,3 +630,794 @@ async fn install_optifine(
     .await?;
     Ok(())
 }
+
+pub async fn java(cmd: QJava) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        QJava::List => {
+            let installs = ql_instances::list_java_installs().await?;
+            if is_json() {
+                print_result(
+                    installs
+                        .iter()
+                        .map(|install| {
+                            json!({
+                                "version": install.version as usize,
+                                "size_mb": install.size_mb(),
+                                "is_incomplete": install.is_incomplete,
+                                "info": install.info.as_ref().map(ToString::to_string),
+                                "used_by": install
+                                    .used_by
+                                    .iter()
+                                    .map(InstanceSelection::get_name)
+                                    .collect::<Vec<_>>(),
+                            })
+                        })
+                        .collect(),
+                );
+                return Ok(());
+            }
+            if installs.is_empty() {
+                println!("No Java installed by the launcher");
+            }
+            for install in installs {
+                println!(
+                    "{} {}",
+                    format!("Java {}", install.version as usize).bold(),
+                    format!("({:.1} MB)", install.size_mb()).bright_black()
+                );
+                if install.is_incomplete {
+                    println!("  {}", "Incomplete install".yellow());
+                } else if let Some(info) = &install.info {
+                    println!("  {info}");
+                } else {
+                    println!("  {}", "Broken install".red());
+                }
+                if install.used_by.is_empty() {
+                    println!("  {}", "Not used by any instance".bright_black());
+                } else {
+                    let names: Vec<&str> = install
+                        .used_by
+                        .iter()
+                        .map(InstanceSelection::get_name)
+                        .collect();
+                    println!("  Used by: {}", names.join(", "));
+                }
+            }
+        }
+        QJava::CheckUpdates => {
+            let mut updates = Vec::new();
+            for install in ql_instances::list_java_installs().await? {
+                let version = install.version as usize;
+                let update = ql_instances::check_java_update(install.version).await?;
+                if is_json() {
+                    updates.push(json!({ "version": version, "update_available": update }));
+                    continue;
+                }
+                match update {
+                    Some(true) => println!("Java {version}: {}", "update available".yellow()),
+                    Some(false) => println!("Java {version}: {}", "up to date".green()),
+                    None => println!(
+                        "Java {version}: unknown build (reinstall to enable update checks)"
+                    ),
+                }
+            }
+            if is_json() {
+                print_result(updates.into());
+            }
+        }
+        QJava::Reinstall { version } => {
+            ql_instances::reinstall_java(parse_java_version(&version), None).await?;
+        }
+        QJava::Repair { version } => {
+            ql_instances::repair_java(parse_java_version(&version), None).await?;
+        }
+        QJava::Delete { version } => {
+            ql_instances::delete_java_install(parse_java_version(&version)).await?;
+        }
+        QJava::Clean => {
+            // Games launched from here aren't tracked, but
+            // their Java is still seen as running
+            let running: Vec<InstanceSelection> = ql_servers::get_running_servers()
+                .await
+                .iter()
+                .map(|n| InstanceSelection::new(n, true))
+                .collect();
+            let deleted = ql_instances::delete_unused_java_installs(&running).await?;
+            if is_json() {
+                print_result(deleted.iter().map(|n| json!(*n as usize)).collect());
+            } else if deleted.is_empty() {
+                println!("No unused Java found");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_java_version(version: &str) -> JavaVersion {
+    let number = version.strip_prefix("java_").unwrap_or(version);
+    let Some(version) = JavaVersion::ALL
+        .iter()
+        .copied()
+        .find(|n| (*n as usize).to_string() == number)
+    else {
+        fail(format!(
+            "Invalid Java version: {version}\n    Supported: {}",
+            JavaVersion::ALL
+                .iter()
+                .map(|n| (*n as usize).to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    };
+    version
+}
+
+impl From<CliBackend> for StoreBackendType {
+    fn from(value: CliBackend) -> Self {
+        match value {
+            CliBackend::Modrinth => StoreBackendType::Modrinth,
+            CliBackend::Curseforge => StoreBackendType::Curseforge,
+        }
+    }
+}
+
+impl From<CliExportFormat> for ExportFormat {
+    fn from(value: CliExportFormat) -> Self {
+        match value {
+            CliExportFormat::Quantumlauncher => ExportFormat::QuantumLauncher,
+            CliExportFormat::Multimc => ExportFormat::MultiMC,
+            CliExportFormat::Modrinth => ExportFormat::Modrinth,
+            CliExportFormat::Curseforge => ExportFormat::CurseForge,
+        }
+    }
+}
+
+impl From<CliQueryType> for QueryType {
+    fn from(value: CliQueryType) -> Self {
+        match value {
+            CliQueryType::Mod => QueryType::Mods,
+            CliQueryType::Resourcepack => QueryType::ResourcePacks,
+            CliQueryType::Shader => QueryType::Shaders,
+        }
+    }
+}
+
+/// Prints progress updates until the sender is dropped
+pub fn progress_printer() -> (Sender<GenericProgress>, std::thread::JoinHandle<()>) {
+    let (sender, receiver) = std::sync::mpsc::channel::<GenericProgress>();
+    let handle = std::thread::spawn(move || {
+        for progress in receiver {
+            print_progress(progress);
+        }
+    });
+    (sender, handle)
+}
+
+/// Same as [`progress_printer`], for game downloads
+pub fn download_progress_printer() -> (Sender<DownloadProgress>, std::thread::JoinHandle<()>) {
+    let (sender, receiver) = std::sync::mpsc::channel::<DownloadProgress>();
+    let handle = std::thread::spawn(move || {
+        for progress in receiver {
+            print_progress(progress.into_generic());
+        }
+    });
+    (sender, handle)
+}
+
+fn print_progress(progress: GenericProgress) {
+    if progress.has_finished {
+        return;
+    }
+    if is_json() {
+        print_event(&progress_event(&progress));
+    } else {
+        let message = progress.message.unwrap_or_default();
+        eeprintln!(
+            "{} {message}",
+            format!("[{}/{}]", progress.done, progress.total).bright_black()
+        );
+    }
+}
+
+/// The `--json` event for a progress update
+pub fn progress_event(progress: &GenericProgress) -> serde_json::Value {
+    json!({
+        "type": "progress",
+        "done": progress.done,
+        "total": progress.total,
+        "message": progress.message.as_deref().unwrap_or_default(),
+    })
+}
+
+pub async fn mods(
+    instance: String,
+    cmd: QMods,
+    servers: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let instance = InstanceSelection::new(&instance, servers);
+    match cmd {
+        QMods::Search {
+            query,
+            backend,
+            kind,
+            limit,
+        } => {
+            let config = InstanceConfigJson::read(&instance).await?;
+            let version_json = VersionDetails::load(&instance).await?;
+            let query = Query {
+                name: query.join(" "),
+                version: version_json.get_id().to_owned(),
+                loader: config.mod_type,
+                server_side: servers,
+            };
+            let result = store::search(query, 0, backend.into(), kind.into()).await?;
+            if is_json() {
+                print_result(
+                    result
+                        .mods
+                        .iter()
+                        .take(limit)
+                        .map(|n| {
+                            json!({
+                                "id": n.id,
+                                "slug": n.internal_name,
+                                "title": n.title,
+                                "description": n.description.trim(),
+                                "downloads": n.downloads,
+                            })
+                        })
+                        .collect(),
+                );
+                return Ok(());
+            }
+            if result.mods.is_empty() {
+                println!("No results found");
+            }
+            for n in result.mods.into_iter().take(limit) {
+                println!(
+                    "{} {}",
+                    n.title.bold(),
+                    format!("({}, id: {})", n.internal_name, n.id).bright_black()
+                );
+                println!("  {}", n.description.trim());
+                println!("  {} downloads", n.downloads);
+            }
+        }
+        QMods::Install { projects, backend } => {
+            let mut ids = Vec::new();
+            for project in &projects {
+                ids.push(store::resolve_mod_id(project, backend.into()).await?);
+            }
+            let (sender, printer) = progress_printer();
+            let not_allowed = store::download_mods_bulk(ids, instance, Some(sender)).await;
+            _ = printer.join();
+
+            check_not_allowed(not_allowed?);
+        }
+        QMods::Remove { mods } => {
+            let ids = find_installed_mods(&instance, &mods).await?;
+            store::delete_mods(ids, instance).await?;
+        }
+        QMods::Enable { mods } => toggle_installed_mods(&instance, &mods, true).await?,
+        QMods::Disable { mods } => toggle_installed_mods(&instance, &mods, false).await?,
+        QMods::List { kind } => match kind.map(QueryType::from) {
+            None | Some(QueryType::Mods) => {
+                let index = ModIndex::load(&instance).await?;
+                let mut mods: Vec<_> = index.mods.iter().collect();
+                mods.sort_by_key(|n| n.1.name.to_lowercase());
+                if is_json() {
+                    print_result(
+                        mods.iter()
+                            .map(|(id, config)| installed_mod_json(id, config))
+                            .collect(),
+                    );
+                    return Ok(());
+                }
+                if mods.is_empty() {
+                    println!("No mods installed from the store");
+                }
+                for (id, config) in mods {
+                    let name = if config.enabled {
+                        config.name.bold().to_string()
+                    } else {
+                        format!("{} (disabled)", config.name)
+                            .bright_black()
+                            .to_string()
+                    };
+                    println!(
+                        "{name} {} {}",
+                        config.installed_version,
+                        format!("({}, id: {id})", config.project_source).bright_black()
+                    );
+                    if !config.manually_installed {
+                        println!("  {}", "Installed as a dependency".bright_black());
+                    }
+                }
+            }
+            Some(kind) => {
+                let dir = instance.get_dot_minecraft_path().join(match kind {
+                    QueryType::Shaders => "shaderpacks",
+                    _ => "resourcepacks",
+                });
+                let mut files = Vec::new();
+                if dir.is_dir() {
+                    let mut entries = tokio::fs::read_dir(&dir).await.path(&dir)?;
+                    while let Some(entry) = entries.next_entry().await.path(&dir)? {
+                        files.push(entry.file_name().to_string_lossy().to_string());
+                    }
+                }
+                files.sort();
+                if is_json() {
+                    print_result(files.into());
+                    return Ok(());
+                }
+                if files.is_empty() {
+                    println!("No {} installed", kind.to_string().to_lowercase());
+                }
+                for file in files {
+                    println!("{file}");
+                }
+            }
+        },
+        QMods::Update { check } => {
+            let updates = store::check_for_updates(instance.clone()).await?;
+            let index = ModIndex::load(&instance).await?;
+            if is_json() {
+                print_result(
+                    updates
+                        .iter()
+                        .map(|(id, version)| {
+                            let name = index.mods.get(&id.get_index_str()).map(|n| &n.name);
+                            json!({ "id": id.get_index_str(), "name": name, "version": version })
+                        })
+                        .collect(),
+                );
+            } else if updates.is_empty() {
+                println!("All mods are up to date");
+            } else {
+                for (id, version) in &updates {
+                    let name = index
+                        .mods
+                        .get(&id.get_index_str())
+                        .map_or_else(|| id.get_index_str(), |n| n.name.clone());
+                    println!("{}: {}", name.bold(), version.yellow());
+                }
+            }
+            if !check && !updates.is_empty() {
+                let ids = updates.into_iter().map(|(id, _)| id).collect();
+                let (sender, printer) = progress_printer();
+                let result = store::apply_updates(instance, ids, Some(sender)).await;
+                _ = printer.join();
+                result?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn installed_mod_json(id: &str, config: &ModConfig) -> serde_json::Value {
+    json!({
+        "id": id,
+        "name": config.name,
+        "version": config.installed_version,
+        "source": config.project_source,
+        "enabled": config.enabled,
+        "manually_installed": config.manually_installed,
+        "files": config
+            .files
+            .iter()
+            .map(|n| n.filename.as_str())
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// Lists the CurseForge projects that couldn't be
+/// downloaded (if any) and exits
+pub fn check_not_allowed(not_allowed: HashSet<CurseforgeNotAllowed>) {
+    if not_allowed.is_empty() {
+        return;
+    }
+    const MESSAGE: &str = "Some CurseForge projects don't allow third-party downloads.\n    Download them manually and put them in the instance:";
+    if is_json() {
+        print_event(&json!({
+            "type": "error",
+            "message": MESSAGE,
+            "not_allowed": not_allowed
+                .iter()
+                .map(|n| json!({ "name": n.name, "url": not_allowed_url(n) }))
+                .collect::<Vec<_>>(),
+        }));
+    } else {
+        err!("{MESSAGE}");
+        for n in &not_allowed {
+            eeprintln!("    - {}: {}", n.name, not_allowed_url(n));
+        }
+    }
+    exit(1);
+}
+
+/// Where to manually download a project that
+/// doesn't allow third-party downloads
+pub fn not_allowed_url(n: &CurseforgeNotAllowed) -> String {
+    format!(
+        "https://www.curseforge.com/minecraft/{}/{}/download/{}",
+        n.project_type, n.slug, n.file_id
+    )
+}
+
+/// Finds installed mods by their ID or name (case-insensitive),
+/// failing if any aren't installed
+pub async fn find_installed_mods(
+    instance: &InstanceSelection,
+    mods: &[String],
+) -> Result<Vec<ModId>, Box<dyn std::error::Error>> {
+    let index = ModIndex::load(instance).await?;
+    let mut ids = Vec::new();
+    for name in mods {
+        let Some(id) = index.mods.iter().find_map(|(id, config)| {
+            (id == name || config.name.eq_ignore_ascii_case(name)).then_some(id)
+        }) else {
+            return Err(format!(
+                "Mod {name:?} isn't installed!\n    See `quantum_launcher mods INSTANCE list`"
+            )
+            .into());
+        };
+        ids.push(ModId::from_index_str(id));
+    }
+    Ok(ids)
+}
+
+async fn toggle_installed_mods(
+    instance: &InstanceSelection,
+    mods: &[String],
+    enable: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let index = ModIndex::load(instance).await?;
+    // `toggle_mods` flips the state, so skip ones
+    // that are already enabled/disabled
+    let ids: Vec<String> = find_installed_mods(instance, mods)
+        .await?
+        .into_iter()
+        .map(|n| n.get_index_str())
+        .filter(|id| index.mods.get(id).is_some_and(|n| n.enabled != enable))
+        .collect();
+    store::toggle_mods(ids, instance.clone()).await?;
+    Ok(())
+}
+
+pub async fn preset(cmd: QPreset, servers: bool) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        QPreset::Create {
+            instance,
+            output,
+            mods,
+            include_config,
+        } => {
+            let instance = InstanceSelection::new(&instance, servers);
+            let selected = get_preset_mods(&instance, &mods).await?;
+            if selected.is_empty() {
+                fail("No mods to put in the preset!")
+            }
+            let bytes =
+                ql_mod_manager::Preset::generate(instance, selected, include_config).await?;
+            tokio::fs::write(&output, bytes).await.path(&output)?;
+            info!("Saved preset to {output:?}");
+            if is_json() {
+                print_result(json!({ "path": output }));
+            }
+        }
+        QPreset::Apply { instance, file } => {
+            let instance = InstanceSelection::new(&instance, servers);
+            let bytes = tokio::fs::read(&file).await.path(&file)?;
+            let output = ql_mod_manager::Preset::load(instance.clone(), bytes, true).await?;
+
+            let (sender, printer) = progress_printer();
+            let not_allowed =
+                store::download_mods_bulk(output.to_install, instance, Some(sender)).await;
+            _ = printer.join();
+            check_not_allowed(not_allowed?);
+        }
+    }
+    Ok(())
+}
+
+/// Gets the mods of an instance to put in a preset:
+/// all of them if `filter` is empty, otherwise the
+/// ones matching an ID, name or file name in it.
+async fn get_preset_mods(
+    instance: &InstanceSelection,
+    filter: &[String],
+) -> Result<HashSet<SelectedMod>, Box<dyn std::error::Error>> {
+    let index = ModIndex::load(instance).await?;
+    let is_selected = |names: &[&str]| {
+        filter.is_empty()
+            || filter
+                .iter()
+                .any(|n| names.iter().any(|name| n.eq_ignore_ascii_case(name)))
+    };
+
+    let mut selected: HashSet<SelectedMod> = index
+        .mods
+        .iter()
+        .filter(|(id, config)| is_selected(&[id.as_str(), config.name.as_str()]))
+        .map(|(id, config)| SelectedMod::Downloaded {
+            name: config.name.clone(),
+            id: ModId::from_index_str(id),
+        })
+        .collect();
+
+    let mods_dir = instance.get_dot_minecraft_path().join("mods");
+    if mods_dir.is_dir() {
+        let mut entries = tokio::fs::read_dir(&mods_dir).await.path(&mods_dir)?;
+        while let Some(entry) = entries.next_entry().await.path(&mods_dir)? {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let is_jar = file_name.ends_with(".jar") || file_name.ends_with(".jar.disabled");
+            // Files of store mods are covered by their index entry
+            let is_store = index
+                .mods
+                .values()
+                .any(|n| n.files.iter().any(|f| f.filename == file_name));
+            if is_jar && !is_store && is_selected(&[file_name.as_str()]) {
+                selected.insert(SelectedMod::Local { file_name });
+            }
+        }
+    }
+    Ok(selected)
+}
+
+pub async fn modpack(cmd: QModpack, servers: bool) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        QModpack::Install {
+            instance,
+            pack,
+            backend,
+        } => {
+            let instance = InstanceSelection::new(&instance, servers);
+            let (sender, printer) = progress_printer();
+            let result = install_pack(&pack, instance, backend.into(), sender).await;
+            _ = printer.join();
+
+            let Some(not_allowed) = result? else {
+                fail(format!("{pack:?} isn't a Modrinth or CurseForge modpack!"))
+            };
+            check_not_allowed(not_allowed);
+        }
+    }
+    Ok(())
+}
+
+/// Installs a modpack from a file, or from the store if `pack`
+/// isn't a file. Returns `None` if the file isn't a modpack.
+async fn install_pack(
+    pack: &str,
+    instance: InstanceSelection,
+    backend: StoreBackendType,
+    sender: Sender<GenericProgress>,
+) -> Result<Option<HashSet<CurseforgeNotAllowed>>, Box<dyn std::error::Error>> {
+    let path = PathBuf::from(pack);
+    if path.is_file() {
+        let bytes = tokio::fs::read(&path).await.path(&path)?;
+        Ok(store::install_modpack(bytes, instance, Some(&sender)).await?)
+    } else {
+        let id = store::resolve_mod_id(pack, backend).await?;
+        let project_type = store::get_project_type(&id).await?;
+        if project_type != QueryType::ModPacks {
+            return Err(format!(
+                "{pack:?} isn't a modpack (it's a {})",
+                project_type.to_modrinth_str()
+            )
+            .into());
+        }
+        Ok(Some(
+            store::download_mod(&id, &instance, Some(sender)).await?,
+        ))
+    }
+}
+
+pub async fn instance(cmd: QInstance, servers: bool) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        QInstance::Export {
+            instance,
+            output,
+            format,
+            exclude,
+        } => {
+            let instance = InstanceSelection::new(&instance, servers);
+            let exceptions = exclude
+                .iter()
+                .map(|n| format!(".minecraft/{}", n.trim_matches('/')))
+                .collect();
+
+            let (sender, printer) = progress_printer();
+            let bytes =
+                ql_packager::export_instance(instance, exceptions, format.into(), Some(sender))
+                    .await;
+            _ = printer.join();
+
+            tokio::fs::write(&output, bytes?).await.path(&output)?;
+            info!("Exported instance to {output:?}");
+            if is_json() {
+                print_result(json!({ "path": output }));
+            }
+        }
+        QInstance::Import { file, skip_assets } => {
+            let (sender, printer) = progress_printer();
+            let instance =
+                ql_packager::import_instance(file.clone(), !skip_assets, Some(sender)).await;
+            _ = printer.join();
+
+            let Some(instance) = instance? else {
+                fail(format!("{file:?} isn't a QuantumLauncher, MultiMC or PrismLauncher instance!\n    For modpacks, create an instance and use `quantum_launcher modpack install`"))
+            };
+            info!("Imported {}", instance.get_name());
+            if is_json() {
+                print_result(json!({
+                    "name": instance.get_name(),
+                    "is_server": instance.is_server(),
+                }));
+            }
+        }
+        QInstance::Shortcut {
+            instance,
+            username,
+            account,
+        } => {
+            if servers {
+                fail("Shortcuts can only be made for instances, not servers");
+            }
+            if !InstanceSelection::new(&instance, false)
+                .get_instance_path()
+                .is_dir()
+            {
+                fail(format!("Instance {instance:?} doesn't exist"));
+            }
+            let mut args = vec!["launch".to_owned()];
+            if let Some(account) = account {
+                args.push(format!("--account={account}"));
+            }
+            args.push("--".to_owned());
+            args.push(instance.clone());
+            args.extend(username);
+
+            let paths = shortcut::create(&instance, &args).await?;
+            if is_json() {
+                print_result(json!({ "files": paths }));
+            } else {
+                for path in paths {
+                    println!("{}", path.to_string_lossy());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+pub async fn server(cmd: QServer) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        QServer::Create {
+            version,
+            server_name,
+        } => {
+            ql_servers::create_server(server_name, ListEntry::new(version), None).await?;
+        }
+        QServer::Delete { server_name, force } => {
+            if ql_servers::get_running_pid(&server_name).await.is_some() {
+                fail(format!("Server {server_name} is running! Stop it first"))
+            }
+            delete_instance(server_name, force, true)?;
+        }
+        QServer::Start {
+            server_name,
+            detach,
+        } => {
+            if let Some(pid) = ql_servers::get_running_pid(&server_name).await {
+                fail(format!(
+                    "Server {server_name} is already running (PID: {pid})"
+                ))
+            }
+            if detach {
+                let pid = ql_servers::run_detached(server_name.clone(), None).await?;
+                if is_json() {
+                    print_result(json!({ "pid": pid }));
+                } else {
+                    info!("Started {server_name} in the background (PID: {pid})");
+                    eeprintln!(
+                        "{}",
+                        format!("Stop it with: quantum_launcher server stop {server_name}")
+                            .bright_black()
+                    );
+                }
+            } else {
+                start_server_attached(server_name).await?;
+            }
+        }
+        QServer::Stop { server_name } => {
+            if !ql_servers::stop(&server_name).await? {
+                fail(format!("Server {server_name} isn't running"))
+            }
+            info!("Stopping {server_name}...");
+            // Saving the worlds can take a while
+            for _ in 0..120 {
+                if ql_servers::get_running_pid(&server_name).await.is_none() {
+                    info!("Stopped {server_name}");
+                    return Ok(());
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            }
+            fail(format!(
+                "Server {server_name} is taking long to stop, it may still be saving"
+            ))
+        }
+        // Not async, handled in `start_cli`
+        QServer::List { .. } => unreachable!(),
+        QServer::Properties(cmd) => server_properties(cmd).await?,
+        QServer::Loader(cmd) => loader(cmd, true).await?,
+    }
+    Ok(())
+}
+
+/// Runs a server in this terminal, sending
+/// typed lines to the server console.
+async fn start_server_attached(server_name: String) -> Result<(), Box<dyn std::error::Error>> {
+    use tokio::io::AsyncWriteExt;
+
+    let process = ql_servers::run(server_name, None).await?;
+
+    if let Some(mut stdin) = process.child.lock().await.stdin.take() {
+        let handle = tokio::runtime::Handle::current();
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if handle
+                    .block_on(stdin.write_all(format!("{line}\n").as_bytes()))
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+    }
+
+    wait_for_exit(&process, Vec::new()).await
+}
+
+async fn server_properties(cmd: QServerProperties) -> Result<(), Box<dyn std::error::Error>> {
+    let server_name = match &cmd {
+        QServerProperties::List { server_name }
+        | QServerProperties::Get { server_name, .. }
+        | QServerProperties::Set { server_name, .. } => server_name.clone(),
+    };
+    let Some(mut properties) = ql_servers::ServerProperties::load(&server_name).await else {
+        fail(format!("Couldn't read server.properties of {server_name}\n    (It gets created when the server is started for the first time)"))
+    };
+
+    match cmd {
+        QServerProperties::List { .. } if is_json() => {
+            print_result(json!(properties.entries));
+        }
+        QServerProperties::List { .. } => {
+            let mut entries: Vec<_> = properties.entries.iter().collect();
+            entries.sort();
+            for (key, value) in entries {
+                println!("{}={value}", key.bold());
+            }
+        }
+        QServerProperties::Get { key, .. } => {
+            let Some(value) = properties.entries.get(&key) else {
+                fail(format!("Property {key:?} not found"))
+            };
+            if is_json() {
+                print_result(json!(value));
+            } else {
+                println!("{value}");
+            }
+        }
+        QServerProperties::Set { key, value, .. } => {
+            properties.entries.insert(key, value);
+            properties.save(&server_name).await?;
+        }
+    }
+    Ok(())
+}
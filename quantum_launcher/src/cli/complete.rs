@@ -0,0 +1,112 @@
+//! Shell completions.
+//!
+//! The shell script (from `quantum_launcher completions SHELL`)
+//! calls back into the launcher with `COMPLETE=SHELL` set,
+//! so instance names, versions, etc. are always up to date.
+
+use std::ffi::OsString;
+
+use clap::CommandFactory;
+use clap_complete::{env::Shells, CompletionCandidate};
+use ql_core::{json::Manifest, Loader};
+
+use crate::{cli::Cli, state::get_entries};
+
+const ENV_VAR: &str = "COMPLETE";
+const BIN_NAME: &str = "quantum_launcher";
+
+/// Answers the shell if it's asking for completions
+/// (and exits), otherwise does nothing.
+///
+/// Must run before anything is printed.
+pub fn handle_env() {
+    clap_complete::CompleteEnv::with_factory(Cli::command)
+        .var(ENV_VAR)
+        .bin(BIN_NAME)
+        .complete();
+}
+
+/// Prints the script that sets up completions for `shell`.
+pub fn print_registration(shell: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let completer = std::env::current_exe()?;
+    let completer = completer.to_string_lossy();
+    let shells = Shells::builtins();
+    let Some(shell) = shells.completer(shell) else {
+        return Err(format!("Unsupported shell: {shell}").into());
+    };
+    shell.write_registration(
+        ENV_VAR,
+        BIN_NAME,
+        BIN_NAME,
+        &completer,
+        &mut std::io::stdout(),
+    )?;
+    Ok(())
+}
+
+/// Instance names, or server names when completing
+/// a `server` subcommand (or with `--server`).
+pub fn instances() -> Vec<CompletionCandidate> {
+    entries(completing_servers())
+}
+
+pub fn servers() -> Vec<CompletionCandidate> {
+    entries(true)
+}
+
+fn entries(is_server: bool) -> Vec<CompletionCandidate> {
+    let Ok(runtime) = tokio::runtime::Runtime::new() else {
+        return Vec::new();
+    };
+    runtime
+        .block_on(get_entries(is_server))
+        .map(|(entries, _)| entries.into_iter().map(CompletionCandidate::new).collect())
+        .unwrap_or_default()
+}
+
+/// Downloadable versions, from the last downloaded
+/// version list (no network access, to stay fast).
+pub fn versions() -> Vec<CompletionCandidate> {
+    let Ok(runtime) = tokio::runtime::Runtime::new() else {
+        return Vec::new();
+    };
+    let Some(manifest) = runtime.block_on(Manifest::load_cached()) else {
+        return Vec::new();
+    };
+    let servers = completing_servers();
+    manifest
+        .versions
+        .into_iter()
+        .filter(|n| !servers || n.supports_server())
+        .map(|n| CompletionCandidate::new(n.id))
+        .collect()
+}
+
+/// Loaders that can be installed
+pub fn loaders() -> Vec<CompletionCandidate> {
+    Loader::ALL
+        .iter()
+        .filter(|n| !matches!(n, Loader::Vanilla | Loader::Modloader | Loader::Rift))
+        .map(|n| CompletionCandidate::new(n.to_modrinth_str()))
+        .collect()
+}
+
+/// While completing, the shell passes the command line
+/// being typed after a `--`. This checks if it's about
+/// servers (`server ...`, or `--server`/`-s` before the subcommand).
+fn completing_servers() -> bool {
+    // Non-UTF-8 args (paths, usually) shouldn't crash completion
+    let args: Vec<OsString> = std::env::args_os().collect();
+    let Some(start) = args.iter().position(|n| n == "--") else {
+        return false;
+    };
+    // Skip the program name
+    for arg in args.iter().skip(start + 2) {
+        match arg.to_str() {
+            Some("server" | "--server" | "-s") => return true,
+            Some(n) if n.starts_with('-') => {}
+            _ => return false,
+        }
+    }
+    false
+}
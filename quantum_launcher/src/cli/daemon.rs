@@ -0,0 +1,454 @@
+//! Headless daemon mode (`quantum_launcher daemon`).
+//!
+//! Listens on `127.0.0.1` for line-delimited JSON requests,
+//! so other programs (scripts, bots, custom frontends) can
+//! control the launcher without the GUI:
+//!
+//! ```json
+//! {"id": 1, "token": "...", "method": "launch", "params": {"instance_name": "SMP"}}
+//! ```
+//!
+//! Every request needs the `token` from the `daemon_token` file
+//! in the launcher directory (readable only by the user), which
+//! is regenerated whenever the daemon starts. This keeps out
+//! websites, which can also reach `127.0.0.1`.
+//!
+//! Every reply is one line with the request's `id` and a `type`,
+//! the same events as `--json` mode: `progress`, `log`, `error`
+//! and `result` (with the output in `data`). `launch` replies
+//! with the PID, then streams `log` events and finishes with
+//! an `exit` event once the game closes.
+//!
+//! Methods:
+//! - `version`
+//! - `list_instances`: `server`
+//! - `list_versions`
+//! - `create_instance`: `name`, `version`, `server`, `skip_assets`
+//! - `delete_instance`: `name`, `server`
+//! - `launch`: same options as the `launch` command (`instance_name`,
+//!   `username`, `account`, `offline`, `memory`, `server`, ...),
+//!   except `java` and `jvm_args`, which could run any program
+//! - `running`: instances launched by the daemon that are still open
+//! - `kill`: `instance`
+//! - `mods_list`, `mods_install`, `mods_remove`: `instance`, `server`,
+//!   plus `projects`/`backend` (install) or `mods` (remove)
+
+use std::{cell::RefCell, collections::HashMap, net::Ipv4Addr, path::Path, rc::Rc, sync::Arc};
+
+use ql_core::{
+    info, read_log::LogLine, DownloadProgress, GenericProgress, InstanceSelection, IntoIoError,
+    ListEntry, Progress, LAUNCHER_DIR, LAUNCHER_VERSION_NAME,
+};
+use ql_mod_manager::store::{self, ModIndex};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    process::Child,
+    sync::{mpsc, Mutex},
+};
+
+use crate::{
+    cli::{
+        command::{
+            find_installed_mods, installed_mod_json, log_event, not_allowed_url, progress_event,
+            start_game,
+        },
+        CliBackend, QLaunch,
+    },
+    state::get_entries,
+};
+
+pub const DEFAULT_PORT: u16 = 47390;
+const TOKEN_FILE: &str = "daemon_token";
+
+type Res<T = ()> = Result<T, Box<dyn std::error::Error>>;
+
+/// Games launched by the daemon, by instance name
+type Running = Rc<RefCell<HashMap<String, Arc<Mutex<Child>>>>>;
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    #[serde(default)]
+    token: String,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Deserialize)]
+struct InstanceParams {
+    #[serde(alias = "name")]
+    instance: String,
+    #[serde(default)]
+    server: bool,
+}
+
+impl InstanceParams {
+    fn selection(&self) -> Res<InstanceSelection> {
+        check_name(&self.instance)?;
+        Ok(InstanceSelection::new(&self.instance, self.server))
+    }
+}
+
+#[derive(Deserialize)]
+struct CreateParams {
+    name: String,
+    version: String,
+    #[serde(default)]
+    server: bool,
+    #[serde(default)]
+    skip_assets: bool,
+}
+
+#[derive(Deserialize)]
+struct ModsInstallParams {
+    #[serde(flatten)]
+    target: InstanceParams,
+    projects: Vec<String>,
+    #[serde(default)]
+    backend: CliBackend,
+}
+
+#[derive(Deserialize)]
+struct ModsRemoveParams {
+    #[serde(flatten)]
+    target: InstanceParams,
+    mods: Vec<String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ListParams {
+    server: bool,
+}
+
+/// Sends the replies to one request
+#[derive(Clone)]
+struct Reply {
+    id: Value,
+    sender: mpsc::UnboundedSender<String>,
+}
+
+impl Reply {
+    fn send(&self, mut event: Value) {
+        event["id"] = self.id.clone();
+        // Client disconnected, nothing to do
+        _ = self.sender.send(event.to_string());
+    }
+
+    fn result(&self, data: Value) {
+        self.send(json!({ "type": "result", "data": data }));
+    }
+
+    /// A sender whose messages are forwarded
+    /// to the client as events (if `map` gives one)
+    fn forward<T: Send + 'static>(
+        &self,
+        map: impl Fn(T) -> Option<Value> + Send + 'static,
+    ) -> std::sync::mpsc::Sender<T> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let reply = self.clone();
+        std::thread::spawn(move || {
+            for message in receiver {
+                if let Some(event) = map(message) {
+                    reply.send(event);
+                }
+            }
+        });
+        sender
+    }
+
+    fn progress(&self) -> std::sync::mpsc::Sender<GenericProgress> {
+        self.forward(|n: GenericProgress| (!n.has_finished).then(|| progress_event(&n)))
+    }
+}
+
+/// Runs the daemon until it's killed. Must run inside a
+/// [`tokio::task::LocalSet`].
+pub async fn run(port: u16) -> Res {
+    let token = Rc::new(create_token().await?);
+
+    // Only local programs may connect, this API can
+    // launch games with the logged-in accounts
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port)).await?;
+    info!("Daemon listening on 127.0.0.1:{port}");
+    info!("Token for requests: {:?}", LAUNCHER_DIR.join(TOKEN_FILE));
+
+    let running = Running::default();
+    loop {
+        let (stream, addr) = listener.accept().await?;
+        info!("Daemon: client connected ({addr})");
+        tokio::task::spawn_local(handle_client(stream, running.clone(), token.clone()));
+    }
+}
+
+/// Makes a new random token, saved in a file
+/// only the current user can read
+async fn create_token() -> Res<String> {
+    let mut bytes = [0; 32];
+    getrandom::fill(&mut bytes).map_err(|err| format!("Couldn't generate token: {err}"))?;
+    let token: String = bytes.iter().map(|n| format!("{n:02x}")).collect();
+
+    let path = LAUNCHER_DIR.join(TOKEN_FILE);
+    // Remove the old one, so the permissions below apply
+    if path.exists() {
+        tokio::fs::remove_file(&path).await.path(&path)?;
+    }
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(&path).await.path(&path)?;
+    file.write_all(token.as_bytes()).await.path(&path)?;
+    Ok(token)
+}
+
+async fn handle_client(stream: TcpStream, running: Running, token: Rc<String>) {
+    let (reader, mut writer) = stream.into_split();
+    let (sender, mut receiver) = mpsc::unbounded_channel::<String>();
+    tokio::task::spawn_local(async move {
+        while let Some(mut line) = receiver.recv().await {
+            line.push('\n');
+            if writer.write_all(line.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        // Not JSON: likely an HTTP request (eg: from a website),
+        // don't read any further
+        if !line.trim_start().starts_with('{') {
+            info!("Daemon: dropped a client that didn't send JSON");
+            break;
+        }
+        let request = match serde_json::from_str::<Request>(&line) {
+            Ok(n) => n,
+            Err(err) => {
+                let reply = Reply {
+                    id: Value::Null,
+                    sender: sender.clone(),
+                };
+                reply
+                    .send(json!({ "type": "error", "message": format!("Invalid request: {err}") }));
+                continue;
+            }
+        };
+        let reply = Reply {
+            id: request.id,
+            sender: sender.clone(),
+        };
+        if !tokens_match(&request.token, &token) {
+            reply.send(json!({ "type": "error", "message": "Invalid token" }));
+            continue;
+        }
+        let running = running.clone();
+        // Requests run concurrently, so a long download
+        // doesn't block others (eg: `running`)
+        tokio::task::spawn_local(async move {
+            if let Err(err) = handle_request(&request.method, request.params, &reply, running).await
+            {
+                reply.send(json!({ "type": "error", "message": err.to_string() }));
+            }
+        });
+    }
+}
+
+async fn handle_request(method: &str, params: Value, reply: &Reply, running: Running) -> Res {
+    match method {
+        "version" => reply.result(json!({ "version": LAUNCHER_VERSION_NAME })),
+        "list_instances" => {
+            let params: ListParams = parse(params)?;
+            let (entries, _) = get_entries(params.server).await?;
+            reply.result(entries.into());
+        }
+        "list_versions" => {
+            let (versions, _) = ql_instances::list_versions().await?;
+            reply.result(
+                versions
+                    .iter()
+                    .map(|n| {
+                        json!({
+                            "name": n.name,
+                            "kind": n.kind,
+                            "supports_server": n.supports_server,
+                        })
+                    })
+                    .collect(),
+            );
+        }
+        "create_instance" => {
+            let params: CreateParams = parse(params)?;
+            check_name(&params.name)?;
+            let entry = ListEntry::new(params.version);
+            let sender = reply.forward(|n: DownloadProgress| {
+                let n = n.into_generic();
+                (!n.has_finished).then(|| progress_event(&n))
+            });
+            let name = if params.server {
+                ql_servers::create_server(params.name, entry, Some(&sender)).await?
+            } else {
+                ql_instances::create_instance(params.name, entry, Some(sender), !params.skip_assets)
+                    .await?
+            };
+            reply.result(json!({ "name": name }));
+        }
+        "delete_instance" => {
+            let params: InstanceParams = parse(params)?;
+            let instance = params.selection()?;
+            let is_running = if params.server {
+                ql_servers::get_running_pid(&params.instance)
+                    .await
+                    .is_some()
+            } else {
+                running.borrow().contains_key(&params.instance)
+            };
+            if is_running {
+                return Err(format!("{:?} is running! Stop it first", params.instance).into());
+            }
+            let path = instance.get_instance_path();
+            if !path.is_dir() {
+                return Err(format!("{:?} doesn't exist", params.instance).into());
+            }
+            // Make sure it's really an instance (not eg: a symlink elsewhere)
+            let parent = path.parent().map(Path::to_owned).unwrap_or_default();
+            let (real_path, real_parent) = (
+                tokio::fs::canonicalize(&path).await.path(&path)?,
+                tokio::fs::canonicalize(&parent).await.path(&parent)?,
+            );
+            if real_path.parent() != Some(real_parent.as_path()) {
+                return Err(
+                    format!("{:?} isn't inside the instances folder", params.instance).into(),
+                );
+            }
+            tokio::fs::remove_dir_all(&path).await?;
+            reply.result(Value::Null);
+        }
+        "launch" => launch(parse(params)?, reply, running).await?,
+        "running" => {
+            let mut names: Vec<String> = running.borrow().keys().cloned().collect();
+            names.sort();
+            reply.result(names.into());
+        }
+        "kill" => {
+            let params: InstanceParams = parse(params)?;
+            let child = running.borrow().get(&params.instance).cloned();
+            let Some(child) = child else {
+                return Err(format!("{:?} isn't running", params.instance).into());
+            };
+            child.lock().await.start_kill()?;
+            reply.result(Value::Null);
+        }
+        "mods_list" => {
+            let params: InstanceParams = parse(params)?;
+            let instance = params.selection()?;
+            let index = ModIndex::load(&instance).await?;
+            let mut mods: Vec<_> = index.mods.iter().collect();
+            mods.sort_by_key(|n| n.1.name.to_lowercase());
+            reply.result(
+                mods.into_iter()
+                    .map(|(id, config)| installed_mod_json(id, config))
+                    .collect(),
+            );
+        }
+        "mods_install" => {
+            let params: ModsInstallParams = parse(params)?;
+            let instance = params.target.selection()?;
+            let mut ids = Vec::new();
+            for project in &params.projects {
+                ids.push(store::resolve_mod_id(project, params.backend.into()).await?);
+            }
+            let not_allowed =
+                store::download_mods_bulk(ids, instance, Some(reply.progress())).await?;
+            // These have to be downloaded manually
+            reply.result(json!({
+                "not_allowed": not_allowed
+                    .iter()
+                    .map(|n| json!({ "name": n.name, "url": not_allowed_url(n) }))
+                    .collect::<Vec<_>>(),
+            }));
+        }
+        "mods_remove" => {
+            let params: ModsRemoveParams = parse(params)?;
+            let instance = params.target.selection()?;
+            let ids = find_installed_mods(&instance, &params.mods).await?;
+            store::delete_mods(ids, instance).await?;
+            reply.result(Value::Null);
+        }
+        _ => return Err(format!("Unknown method: {method:?}").into()),
+    }
+    Ok(())
+}
+
+async fn launch(mut args: QLaunch, reply: &Reply, running: Running) -> Res {
+    if args.instance_name.is_empty() {
+        return Err("Missing param: instance_name".into());
+    }
+    check_name(&args.instance_name)?;
+    if args.java.is_some() || !args.jvm_args.is_empty() {
+        return Err("`java` and `jvm_args` can't be set through the daemon".into());
+    }
+    if running.borrow().contains_key(&args.instance_name) {
+        return Err(format!("{:?} is already running", args.instance_name).into());
+    }
+    // The log is needed for the `log` events
+    args.detach = false;
+    let name = args.instance_name.clone();
+
+    let (process, censors) = start_game(args, true).await?;
+    let pid = process.child.lock().await.id();
+    running
+        .borrow_mut()
+        .insert(name.clone(), process.child.clone());
+    reply.result(json!({ "pid": pid }));
+
+    let sender = reply.forward(|n: LogLine| Some(log_event(&n)));
+    let result = process.read_logs(censors, Some(sender)).await;
+    running.borrow_mut().remove(&name);
+
+    match result {
+        Some(Ok((status, _, diag))) => reply.send(json!({
+            "type": "exit",
+            "exit_code": status.code(),
+            "diagnostic": diag.map(|n| n.to_string()),
+        })),
+        Some(Err(err)) => Err(err)?,
+        None => reply.send(json!({ "type": "exit", "exit_code": null, "diagnostic": null })),
+    }
+    Ok(())
+}
+
+fn parse<T: DeserializeOwned>(params: Value) -> Res<T> {
+    // Missing `params` is the same as `{}`
+    let params = if params.is_null() { json!({}) } else { params };
+    let params: T =
+        serde_json::from_value(params).map_err(|err| format!("Invalid params: {err}"))?;
+    Ok(params)
+}
+
+/// Instance names must stay inside the instances folder
+fn check_name(name: &str) -> Res {
+    if name.trim().is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+        return Err(format!("Invalid instance name: {name:?}").into());
+    }
+    Ok(())
+}
+
+/// Compares without stopping at the first difference,
+/// so the token can't be guessed from response times
+fn tokens_match(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
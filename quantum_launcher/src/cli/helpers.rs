@@ -1,6 +1,14 @@
 use owo_colors::{OwoColorize, Style};
-use ql_core::print::strip_ansi_codes;
-use std::{fmt::Write, io::IsTerminal};
+use ql_core::{
+    err,
+    print::{strip_ansi_codes, LogConfig},
+};
+use serde_json::json;
+use std::{
+    fmt::{Display, Write},
+    io::IsTerminal,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 #[must_use]
 pub fn render_row(
@@ -127,3 +135,48 @@ fn pad_line(line: Option<&str>, width: usize) -> String {
         line.to_owned()
     }
 }
+
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Switches to machine-readable output (`--json`).
+///
+/// Log messages are kept out of the terminal
+/// (they're still saved to the log file),
+/// so they don't mix with the JSON.
+pub fn enable_json_output() {
+    JSON_OUTPUT.store(true, Ordering::Relaxed);
+    ql_core::print::set_config(LogConfig {
+        terminal: false,
+        file: true,
+    });
+}
+
+#[must_use]
+pub fn is_json() -> bool {
+    JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Prints a line of JSON output. Every line is
+/// an object with a `type` field:
+///
+/// - `result`: the output of the command, in `data`
+/// - `progress`: `done`, `total` and `message`
+/// - `log`: a line of game/server output
+/// - `error`: a `message`, the command then exits with code 1
+pub fn print_event(event: &serde_json::Value) {
+    println!("{event}");
+}
+
+pub fn print_result(data: serde_json::Value) {
+    print_event(&json!({ "type": "result", "data": data }));
+}
+
+/// Shows an error and exits with code 1
+pub fn fail(message: impl Display) -> ! {
+    if is_json() {
+        print_event(&json!({ "type": "error", "message": message.to_string() }));
+    } else {
+        err!("{message}");
+    }
+    std::process::exit(1);
+}
@@ -1,17 +1,28 @@
-use std::sync::{LazyLock, RwLock};
+use std::{
+    ffi::OsString,
+    path::PathBuf,
+    sync::{LazyLock, RwLock},
+};
 
 use clap::{Parser, Subcommand};
+use clap_complete::ArgValueCandidates;
 use owo_colors::{OwoColorize, Style};
-use ql_core::{err, LAUNCHER_VERSION_NAME, REDACT_SENSITIVE_INFO, WEBSITE};
+use ql_core::{LAUNCHER_VERSION_NAME, REDACT_SENSITIVE_INFO, WEBSITE};
 
 use crate::{
     cli::helpers::render_row,
     menu_renderer::{DISCORD, GITHUB},
+    shortcut,
 };
 
+mod apply;
 mod command;
+mod complete;
+mod daemon;
 mod helpers;
 
+pub use complete::handle_env as handle_completions;
+
 #[derive(Parser)]
 #[cfg_attr(target_os = "windows", command(name = ".\\quantum_launcher.exe"))]
 #[cfg_attr(not(target_os = "windows"), command(name = "./quantum_launcher"))]
@@ -29,13 +40,27 @@ struct Cli {
     #[arg(long)]
     #[arg(help = "Enable experimental server manager (create, delete and host local servers)")]
     enable_server_manager: bool,
-    #[arg(long)]
-    #[arg(help = "Enable experimental MultiMC import feature (in create instance screen)")]
+    /// MultiMC import is no longer experimental. Kept for compatibility.
+    #[arg(long, hide = true)]
     enable_mmc_import: bool,
     #[arg(short, long)]
     #[arg(help = "Operate on servers, not instances")]
     #[arg(hide = true)]
     server: bool,
+    #[arg(long, global = true)]
+    #[arg(
+        help = "Print machine-readable JSON (one object per line) instead of text",
+        long_help = r#"Print machine-readable JSON instead of text.
+
+Each line is a JSON object with a "type" field:
+- "result": the output of the command, in "data"
+- "progress": download/install progress ("done", "total", "message")
+- "log": a line of game or server output
+- "error": what went wrong ("message"), exit code is non-zero
+
+Commands that don't output anything print nothing on success."#
+    )]
+    json: bool,
 }
 
 #[derive(Subcommand)]
@@ -43,6 +68,7 @@ enum QSubCommand {
     #[command(about = "Creates a new Minecraft instance")]
     Create {
         #[arg(help = "Version of Minecraft to download")]
+        #[arg(add = ArgValueCandidates::new(complete::versions))]
         version: String,
         instance_name: String,
         #[arg(short, long)]
@@ -50,19 +76,13 @@ enum QSubCommand {
         skip_assets: bool,
     },
     #[command(about = "Launches an instance")]
-    Launch {
-        instance_name: String,
-        #[arg(help = "Username to play with")]
-        username: String,
-        #[arg(short, long, short_alias = 'a')]
-        #[arg(help = "Whether to use a logged in account of the given username (if any)")]
-        use_account: bool,
-    },
+    Launch(QLaunch),
     #[command(aliases = ["list", "list-instances"], short_flag = 'l')]
     #[command(about = "Lists installed instances")]
     ListInstalled { properties: Option<Vec<String>> },
     #[command(about = "Deletes the specified instance")]
     Delete {
+        #[arg(add = ArgValueCandidates::new(complete::instances))]
         instance_name: String,
         #[arg(short, long)]
         #[arg(help = "Forces deletion without confirmation. DANGEROUS")]
@@ -73,6 +93,425 @@ enum QSubCommand {
     Loader(QLoader),
     #[command(about = "Lists downloadable versions", short_flag = 'a')]
     ListAvailableVersions,
+    #[clap(subcommand)]
+    Java(QJava),
+    #[command(about = "Manages the mods, resource packs and shaders of an instance")]
+    Mods {
+        #[arg(add = ArgValueCandidates::new(complete::instances))]
+        instance: String,
+        #[clap(subcommand)]
+        cmd: QMods,
+    },
+    #[clap(subcommand)]
+    Preset(QPreset),
+    #[clap(subcommand)]
+    Modpack(QModpack),
+    #[clap(subcommand)]
+    Instance(QInstance),
+    #[clap(subcommand)]
+    Server(QServer),
+    #[command(about = "Creates or updates an instance from a definition file")]
+    #[command(long_about = r#"Creates or updates an instance from a definition file
+
+The file (TOML, or JSON if it ends in `.json`) describes the
+instance, so it can be shared or kept in git. If the instance
+exists, it's changed to match: the loader is swapped, mods are
+added/removed and pinned versions are installed.
+
+Example:
+    name = "Team SMP"
+    version = "1.21.1"
+    memory = 4096                  # MB
+    java_args = ["-XX:+UseZGC"]
+    game_args = []
+
+    [loader]
+    kind = "fabric"
+    version = "0.16.5"             # optional
+
+    [[mods]]
+    id = "sodium"                  # Modrinth ID/slug, or `CF:ID`
+    version = "mc1.21.1-0.6.0-fabric"  # optional, CurseForge: file ID
+
+    [[resource_packs]]             # also: [[shaders]]
+    id = "CF:12345"
+
+    [settings]
+    window_width = 1280"#)]
+    Apply {
+        file: PathBuf,
+        #[arg(short = 'n', long)]
+        #[arg(help = "Only show what would change (same as `diff`)")]
+        dry_run: bool,
+    },
+    #[command(about = "Shows what `apply` would change for a definition file")]
+    Diff { file: PathBuf },
+    #[command(about = "Prints a shell completion script")]
+    #[command(long_about = r"Prints a shell completion script
+
+Completes commands, options, instance/server names and versions.
+To enable, add this to your shell's startup file:
+- bash: `source <(quantum_launcher completions bash)`
+- zsh: `source <(quantum_launcher completions zsh)`
+- fish: `quantum_launcher completions fish | source`
+- PowerShell: `quantum_launcher completions powershell | Out-String | Invoke-Expression`")]
+    Completions {
+        #[arg(value_enum)]
+        shell: CliShell,
+    },
+    #[command(about = "Runs in the background, controlled through a local API")]
+    #[command(long_about = r#"Runs in the background, controlled through a local API
+
+Other programs can connect to 127.0.0.1:PORT and send
+requests as lines of JSON, getting replies like `--json`:
+    {"id": 1, "token": "...", "method": "list_instances"}
+    {"id": 2, "token": "...", "method": "launch", "params": {"instance_name": "SMP"}}
+
+The token is in the `daemon_token` file in the launcher
+folder, and changes every time the daemon starts.
+
+Methods: version, list_instances, list_versions, create_instance,
+delete_instance, launch, running, kill, mods_list, mods_install, mods_remove"#)]
+    Daemon {
+        #[arg(short, long, default_value_t = daemon::DEFAULT_PORT)]
+        port: u16,
+    },
+}
+
+#[derive(Subcommand)]
+#[command(
+    about = "Manages Minecraft servers",
+    long_about = r"Create, run and configure Minecraft servers.
+
+Servers started with `start --detach` keep running in the background,
+use `stop` to shut them down."
+)]
+enum QServer {
+    #[command(about = "Creates a new server")]
+    Create {
+        #[arg(help = "Version of Minecraft to download")]
+        #[arg(add = ArgValueCandidates::new(complete::versions))]
+        version: String,
+        server_name: String,
+    },
+    #[command(about = "Deletes the specified server")]
+    Delete {
+        #[arg(add = ArgValueCandidates::new(complete::servers))]
+        server_name: String,
+        #[arg(short, long)]
+        #[arg(help = "Forces deletion without confirmation. DANGEROUS")]
+        force: bool,
+    },
+    #[command(about = "Starts a server")]
+    #[command(long_about = r"Starts a server
+
+By default the server runs in this terminal, and anything
+typed is sent to the server console (eg: `op Steve`).")]
+    Start {
+        #[arg(add = ArgValueCandidates::new(complete::servers))]
+        server_name: String,
+        #[arg(short, long)]
+        #[arg(help = "Runs the server in the background, without a console")]
+        detach: bool,
+    },
+    #[command(about = "Stops a running server")]
+    Stop {
+        #[arg(add = ArgValueCandidates::new(complete::servers))]
+        server_name: String,
+    },
+    #[command(about = "Lists created servers")]
+    List { properties: Option<Vec<String>> },
+    #[clap(subcommand)]
+    Properties(QServerProperties),
+    #[clap(subcommand)]
+    #[clap(alias = "loaders")]
+    Loader(QLoader),
+}
+
+/// Also the `params` of the daemon's `launch` request
+#[derive(clap::Args, Default, serde::Deserialize)]
+#[serde(default)]
+#[command(long_about = r"Launches an instance
+
+The options here only apply to this launch, they aren't saved.
+By default the game's log is shown here, and the launcher
+exits with the game's exit code once it closes.")]
+struct QLaunch {
+    #[arg(add = ArgValueCandidates::new(complete::instances))]
+    instance_name: String,
+    #[arg(
+        help = "Username to play with (or the name of an offline profile).\nIf not given, the instance's default account is used"
+    )]
+    username: Option<String>,
+    #[arg(short, long, short_alias = 'a')]
+    #[arg(help = "Whether to use a logged in account of the given username (if any)")]
+    use_account: bool,
+    #[arg(long, conflicts_with_all = ["username", "use_account", "offline"])]
+    #[arg(help = "Logged in account to play with (username or ID)")]
+    account: Option<String>,
+    #[arg(long, conflicts_with = "use_account")]
+    #[arg(help = "Play offline, even if the default account is logged in")]
+    offline: bool,
+    #[arg(long)]
+    #[arg(help = "Path to the `java` binary to use")]
+    java: Option<PathBuf>,
+    #[arg(short, long)]
+    #[arg(help = "Memory to allocate (in MB)")]
+    memory: Option<usize>,
+    #[arg(long = "jvm-arg", allow_hyphen_values = true)]
+    #[arg(help = "Extra Java argument (can be repeated)")]
+    jvm_args: Vec<String>,
+    #[arg(long = "game-arg", allow_hyphen_values = true)]
+    #[arg(help = "Extra game argument (can be repeated)")]
+    game_args: Vec<String>,
+    #[arg(long, conflicts_with = "world")]
+    #[arg(help = "Join this server on startup (`host` or `host:port`)")]
+    server: Option<String>,
+    #[arg(long)]
+    #[arg(help = "Open this singleplayer world on startup (1.20+)")]
+    world: Option<String>,
+    #[arg(short, long, conflicts_with = "wait")]
+    #[arg(help = "Exit right after the game starts, leaving it running")]
+    detach: bool,
+    #[arg(short, long)]
+    #[arg(help = "Show the game's log and exit with its exit code (default)")]
+    wait: bool,
+}
+
+#[derive(Subcommand)]
+#[command(about = "Views and edits `server.properties`")]
+enum QServerProperties {
+    #[command(about = "Lists all properties")]
+    List {
+        #[arg(add = ArgValueCandidates::new(complete::servers))]
+        server_name: String,
+    },
+    #[command(about = "Prints the value of a property")]
+    Get {
+        #[arg(add = ArgValueCandidates::new(complete::servers))]
+        server_name: String,
+        key: String,
+    },
+    #[command(about = "Changes the value of a property")]
+    Set {
+        #[arg(add = ArgValueCandidates::new(complete::servers))]
+        server_name: String,
+        key: String,
+        value: String,
+    },
+}
+
+#[derive(Subcommand)]
+#[command(
+    about = "Creates and applies mod presets (.qmp)",
+    long_about = r"Create and apply mod presets (.qmp files).
+
+Presets are QuantumLauncher's own bundles of mods and their config.
+Mods from the store are saved as references and redownloaded,
+other mods are saved inside the preset."
+)]
+enum QPreset {
+    #[command(about = "Creates a preset from the mods of an instance")]
+    Create {
+        #[arg(add = ArgValueCandidates::new(complete::instances))]
+        instance: String,
+        #[arg(help = "Where to save the preset (eg: `my_mods.qmp`)")]
+        output: PathBuf,
+        #[arg(short, long)]
+        #[arg(help = "Only include these mods (by ID, name or file name). Defaults to all")]
+        mods: Vec<String>,
+        #[arg(short = 'c', long)]
+        #[arg(help = "Include the `config/` folder")]
+        include_config: bool,
+    },
+    #[command(about = "Applies a preset (or modpack file) to an instance")]
+    Apply {
+        #[arg(add = ArgValueCandidates::new(complete::instances))]
+        instance: String,
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+#[command(about = "Installs Modrinth and CurseForge modpacks")]
+enum QModpack {
+    #[command(about = "Installs a modpack into an existing instance")]
+    #[command(long_about = r"Installs a modpack into an existing instance
+
+The pack can be a file (`.mrpack` or CurseForge `.zip`),
+or a project from the store, entered by ID (or slug for Modrinth).
+The instance should have the pack's Minecraft version and loader.")]
+    Install {
+        #[arg(add = ArgValueCandidates::new(complete::instances))]
+        instance: String,
+        pack: String,
+        #[arg(short, long, value_enum, default_value_t = CliBackend::Modrinth)]
+        #[arg(help = "The store to look up the pack in, if it isn't a file")]
+        backend: CliBackend,
+    },
+}
+
+#[derive(Subcommand)]
+#[command(about = "Exports and imports instances")]
+enum QInstance {
+    #[command(about = "Exports an instance to a file")]
+    Export {
+        #[arg(add = ArgValueCandidates::new(complete::instances))]
+        instance: String,
+        #[arg(help = "Where to save the instance (eg: `my_instance.zip`)")]
+        output: PathBuf,
+        #[arg(short, long, value_enum, default_value_t = CliExportFormat::Quantumlauncher)]
+        format: CliExportFormat,
+        #[arg(short, long)]
+        #[arg(help = "Files or folders in `.minecraft` to leave out (eg: `saves`)")]
+        exclude: Vec<String>,
+    },
+    #[command(about = "Imports a QuantumLauncher, MultiMC or PrismLauncher instance")]
+    Import {
+        file: PathBuf,
+        #[arg(short, long)]
+        #[arg(help = "Skips downloading game assets (sound/music) to speed up downloads")]
+        skip_assets: bool,
+    },
+    #[command(about = "Creates a desktop shortcut that launches an instance")]
+    #[command(long_about = r"Creates a desktop shortcut that launches an instance
+
+Linux: adds it to the app menu (and the desktop),
+Windows/macOS: puts a script on the desktop.
+Also makes `quantumlauncher://launch/INSTANCE` links work.")]
+    Shortcut {
+        #[arg(add = ArgValueCandidates::new(complete::instances))]
+        instance: String,
+        #[arg(
+            help = "Username to play with (or the name of an offline profile).\nIf not given, the instance's default account is used"
+        )]
+        username: Option<String>,
+        #[arg(long, conflicts_with = "username")]
+        #[arg(help = "Logged in account to play with (username or ID)")]
+        account: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum CliExportFormat {
+    Quantumlauncher,
+    #[value(alias = "prism")]
+    Multimc,
+    Modrinth,
+    Curseforge,
+}
+
+#[derive(Subcommand)]
+#[command(
+    long_about = r"Search, install, remove, enable, disable and update mods.
+
+Projects can be entered by ID (as shown by `search`),
+or by slug for Modrinth (eg: `sodium`).
+Installed mods can also be entered by name."
+)]
+enum QMods {
+    #[command(about = "Searches the store for mods compatible with the instance")]
+    Search {
+        query: Vec<String>,
+        #[arg(short, long, value_enum, default_value_t = CliBackend::Modrinth)]
+        backend: CliBackend,
+        #[arg(short = 't', long = "type", value_enum, default_value_t = CliQueryType::Mod)]
+        kind: CliQueryType,
+        #[arg(short, long, default_value_t = 10)]
+        #[arg(help = "Maximum number of results")]
+        limit: usize,
+    },
+    #[command(about = "Installs projects from the store, with their dependencies")]
+    Install {
+        #[arg(required = true)]
+        projects: Vec<String>,
+        #[arg(short, long, value_enum, default_value_t = CliBackend::Modrinth)]
+        backend: CliBackend,
+    },
+    #[command(about = "Removes installed projects (and unused dependencies)")]
+    Remove {
+        #[arg(required = true)]
+        mods: Vec<String>,
+    },
+    #[command(about = "Enables disabled projects")]
+    Enable {
+        #[arg(required = true)]
+        mods: Vec<String>,
+    },
+    #[command(about = "Disables projects without removing them")]
+    Disable {
+        #[arg(required = true)]
+        mods: Vec<String>,
+    },
+    #[command(about = "Lists projects installed from the store")]
+    List {
+        #[arg(short = 't', long = "type", value_enum)]
+        #[arg(help = "Only list projects of this type")]
+        kind: Option<CliQueryType>,
+    },
+    #[command(about = "Updates installed projects to their latest versions")]
+    Update {
+        #[arg(short, long)]
+        #[arg(help = "Only check for updates, without installing them")]
+        check: bool,
+    },
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum CliShell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+impl CliShell {
+    fn name(self) -> &'static str {
+        match self {
+            CliShell::Bash => "bash",
+            CliShell::Zsh => "zsh",
+            CliShell::Fish => "fish",
+            CliShell::Powershell => "powershell",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum CliBackend {
+    #[default]
+    Modrinth,
+    Curseforge,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum CliQueryType {
+    Mod,
+    Resourcepack,
+    Shader,
+}
+
+#[derive(Subcommand)]
+#[command(
+    about = "Manages Java installed by the launcher",
+    long_about = r"List, update and delete the Java versions installed by the launcher.
+
+Versions can be written as `17` or `java_17`.
+Deleted Java gets redownloaded automatically when needed."
+)]
+enum QJava {
+    #[command(about = "Lists installed Java, with size and the instances using it")]
+    List,
+    #[command(about = "Checks for newer builds of installed Java")]
+    CheckUpdates,
+    #[command(about = "Reinstalls the latest build of a Java version")]
+    Reinstall { version: String },
+    #[command(about = "Checks the files of a Java version, redownloading damaged ones")]
+    Repair { version: String },
+    #[command(about = "Deletes a Java version")]
+    Delete { version: String },
+    #[command(about = "Deletes Java not used by any instance or server")]
+    Clean,
 }
 
 #[derive(Subcommand)]
@@ -90,23 +529,26 @@ enum QLoader {
 Supported loaders: Fabric, Forge, Quilt, NeoForge, Paper, OptiFine
 (case-insensitive)")]
     Install {
+        #[arg(add = ArgValueCandidates::new(complete::loaders))]
         loader: String,
+        #[arg(add = ArgValueCandidates::new(complete::instances))]
         instance: String,
         more: Option<String>,
         #[arg(long)]
         version: Option<String>,
     },
     Uninstall {
+        #[arg(add = ArgValueCandidates::new(complete::instances))]
         instance: String,
     },
     #[command(about = "Info about the currently-installed loader")]
     Info {
+        #[arg(add = ArgValueCandidates::new(complete::instances))]
         instance: String,
     },
 }
 
 pub static EXPERIMENTAL_SERVERS: LazyLock<RwLock<bool>> = LazyLock::new(|| RwLock::new(false));
-pub static EXPERIMENTAL_MMC_IMPORT: LazyLock<RwLock<bool>> = LazyLock::new(|| RwLock::new(false));
 
 fn long_about() -> String {
     format!(
@@ -187,12 +629,17 @@ fn get_right_text() -> String {
 }
 
 pub fn start_cli(is_dir_err: bool) {
-    let cli = Cli::parse();
+    let cli = Cli::parse_from(get_args());
     *REDACT_SENSITIVE_INFO.lock().unwrap() = !cli.no_redact_info;
     *EXPERIMENTAL_SERVERS.write().unwrap() = cli.enable_server_manager;
-    *EXPERIMENTAL_MMC_IMPORT.write().unwrap() = cli.enable_mmc_import;
     if let Some(subcommand) = cli.command {
+        if cli.json {
+            helpers::enable_json_output();
+        }
         if is_dir_err {
+            if cli.json {
+                helpers::fail("Couldn't get launcher dir");
+            }
             std::process::exit(1);
         }
         let runtime = tokio::runtime::Runtime::new().unwrap();
@@ -210,17 +657,8 @@ pub fn start_cli(is_dir_err: bool) {
                     cli.server,
                 )));
             }
-            QSubCommand::Launch {
-                instance_name,
-                username,
-                use_account,
-            } => {
-                quit(runtime.block_on(command::launch_instance(
-                    instance_name,
-                    username,
-                    use_account,
-                    cli.server,
-                )));
+            QSubCommand::Launch(args) => {
+                quit(runtime.block_on(command::launch_instance(args, cli.server)));
             }
             QSubCommand::ListAvailableVersions => {
                 command::list_available_versions();
@@ -229,24 +667,74 @@ pub fn start_cli(is_dir_err: bool) {
             QSubCommand::Delete {
                 instance_name,
                 force,
-            } => quit(command::delete_instance(instance_name, force)),
+            } => quit(command::delete_instance(instance_name, force, cli.server)),
             QSubCommand::ListInstalled { properties } => {
                 quit(command::list_instances(properties.as_deref(), cli.server))
             }
             QSubCommand::Loader(cmd) => {
                 quit(runtime.block_on(command::loader(cmd, cli.server)));
             }
+            QSubCommand::Java(cmd) => {
+                quit(runtime.block_on(command::java(cmd)));
+            }
+            QSubCommand::Mods { instance, cmd } => {
+                quit(runtime.block_on(command::mods(instance, cmd, cli.server)));
+            }
+            QSubCommand::Preset(cmd) => {
+                quit(runtime.block_on(command::preset(cmd, cli.server)));
+            }
+            QSubCommand::Modpack(cmd) => {
+                quit(runtime.block_on(command::modpack(cmd, cli.server)));
+            }
+            QSubCommand::Instance(cmd) => {
+                quit(runtime.block_on(command::instance(cmd, cli.server)));
+            }
+            QSubCommand::Server(QServer::List { properties }) => {
+                quit(command::list_instances(properties.as_deref(), true));
+            }
+            QSubCommand::Server(cmd) => {
+                quit(runtime.block_on(command::server(cmd)));
+            }
+            QSubCommand::Apply { file, dry_run } => {
+                quit(runtime.block_on(apply::apply(&file, dry_run)));
+            }
+            QSubCommand::Diff { file } => {
+                quit(runtime.block_on(apply::apply(&file, true)));
+            }
+            QSubCommand::Completions { shell } => {
+                quit(complete::print_registration(shell.name()));
+            }
+            QSubCommand::Daemon { port } => {
+                let local = tokio::task::LocalSet::new();
+                quit(local.block_on(&runtime, daemon::run(port)));
+            }
         }
     } else {
         print_intro();
     }
 }
 
+/// The command line, with `quantumlauncher://` links
+/// (opened from a browser or shortcut) turned into commands
+fn get_args() -> Vec<OsString> {
+    let mut args: Vec<OsString> = std::env::args_os().collect();
+    let Some(url) = args.get(1).and_then(|n| n.to_str()).filter(|n| {
+        n.get(..shortcut::URL_SCHEME.len() + 1)
+            .is_some_and(|n| n.eq_ignore_ascii_case(&format!("{}:", shortcut::URL_SCHEME)))
+    }) else {
+        return args;
+    };
+    let Some(url_args) = shortcut::url_to_args(url) else {
+        helpers::fail(format!("Invalid link: {url}"))
+    };
+    args.truncate(1);
+    args.extend(url_args.into_iter().map(OsString::from));
+    args
+}
+
 fn quit(res: Result<(), Box<dyn std::error::Error + 'static>>) {
-    std::process::exit(if let Err(err) = res {
-        err!("{err}");
-        1
-    } else {
-        0
-    });
+    if let Err(err) = res {
+        helpers::fail(err);
+    }
+    std::process::exit(0);
 }
@@ -5,7 +5,11 @@ use ql_core::ListEntryKind;
 use ql_core::{
     err, IntoIoError, IntoJsonError, JsonFileError, LAUNCHER_DIR, LAUNCHER_VERSION_NAME,
 };
-use serde::{Deserialize, Serialize};
+use ql_instances::{
+    auth::{offline::OfflineProfile, AccountType, CredentialBackend, DEFAULT_CUSTOM_SERVER},
+    JavaInfo,
+};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashSet;
 use std::{collections::HashMap, path::Path};
 
@@ -29,11 +33,14 @@ pub struct LauncherConfig {
     /// The offline username set by the player when playing Minecraft.
     pub username: String,
 
-    #[deprecated(
-        since = "0.2.0",
-        note = "removed feature, field left here for backwards compatibility"
-    )]
-    pub java_installs: Option<Vec<String>>,
+    /// Java installations found on the system
+    /// (see [`ql_instances::find_system_java`]),
+    /// offered as a Java override in instance settings.
+    ///
+    /// `None` if they haven't been looked for yet.
+    // Since: v0.5.0 (unused from v0.2.0)
+    #[serde(default, deserialize_with = "deserialize_java_installs")]
+    pub java_installs: Option<Vec<JavaInfo>>,
 
     /// UI mode (Light/Dark/Auto) set by the user.
     // Since: v0.3
@@ -94,11 +101,40 @@ pub struct LauncherConfig {
     pub ui: Option<UiSettings>,
     // Since: v0.5.0
     pub persistent: Option<PersistentSettings>,
+
+    /// Where account logins (refresh tokens) are stored.
+    /// See [`CredentialBackend`] for more info.
+    ///
+    /// Default: `Auto`
+    // Since: v0.5.0
+    pub credential_backend: Option<CredentialBackend>,
+    /// Named offline accounts, each with a stable UUID
+    /// and optionally a skin. Shown as `NAME (offline)`
+    /// in the account list.
+    // Since: v0.5.0
+    pub offline_profiles: Option<Vec<OfflineProfile>>,
+}
+
+/// Versions before v0.2.0 stored paths (strings) in
+/// `java_installs`, so anything that isn't a [`JavaInfo`]
+/// is dropped instead of failing to load the config.
+///
+/// Versions after that always saved an empty list, so an
+/// empty list counts as "not looked for yet" (`None`).
+fn deserialize_java_installs<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<JavaInfo>>, D::Error> {
+    let list: Option<Vec<serde_json::Value>> = Option::deserialize(deserializer)?;
+    let list: Vec<JavaInfo> = list
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|n| serde_json::from_value(n).ok())
+        .collect();
+    Ok((!list.is_empty()).then_some(list))
 }
 
 impl Default for LauncherConfig {
     fn default() -> Self {
-        #[allow(deprecated)]
         Self {
             username: String::new(),
             ui_mode: None,
@@ -106,7 +142,7 @@ impl Default for LauncherConfig {
             version: Some(LAUNCHER_VERSION_NAME.to_owned()),
             accounts: None,
             ui_scale: None,
-            java_installs: Some(Vec::new()),
+            java_installs: None,
             ui_antialiasing: Some(true),
             account_selected: None,
             window: None,
@@ -114,6 +150,8 @@ impl Default for LauncherConfig {
             extra_java_args: None,
             ui: None,
             persistent: None,
+            credential_backend: None,
+            offline_profiles: None,
         }
     }
 }
@@ -183,11 +221,9 @@ impl LauncherConfig {
             }
         }
 
-        #[allow(deprecated)]
-        {
-            if self.java_installs.is_none() {
-                self.java_installs = Some(Vec::new());
-            }
+        // Java may have been uninstalled since
+        if let Some(java_installs) = &mut self.java_installs {
+            java_installs.retain(|n| n.path.is_file());
         }
     }
 
@@ -245,8 +281,38 @@ impl LauncherConfig {
         self.persistent
             .get_or_insert_with(PersistentSettings::default)
     }
+
+    /// The authlib-injector servers of all logged-in
+    /// custom accounts, without duplicates.
+    #[must_use]
+    pub fn get_custom_auth_servers(&self) -> Vec<String> {
+        let mut servers: Vec<String> = Vec::new();
+        for (username, account) in self.accounts.iter().flatten() {
+            if let AccountType::Custom(server) = account.get_account_type(username) {
+                if !servers.contains(&server) {
+                    servers.push(server);
+                }
+            }
+        }
+        servers
+    }
+
+    /// Finds an offline profile by its name
+    /// in the account list (`NAME (offline)`).
+    #[must_use]
+    pub fn get_offline_profile(&self, account: &str) -> Option<&OfflineProfile> {
+        let name = account.strip_suffix(OFFLINE_PROFILE_SUFFIX)?;
+        self.offline_profiles
+            .iter()
+            .flatten()
+            .find(|n| n.name == name)
+    }
 }
 
+/// Added to the names of offline profiles
+/// in the account list
+pub const OFFLINE_PROFILE_SUFFIX: &str = " (offline)";
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ConfigAccount {
     /// UUID of the Minecraft account. Stored as a string without dashes.
@@ -260,15 +326,24 @@ pub struct ConfigAccount {
     /// You can find someone's UUID through many online services where you
     /// input their username.
     pub uuid: String,
-    /// Currently unimplemented, does nothing.
-    pub skin: Option<String>, // TODO: Add skin visualization?
+    /// Unused. Skins are fetched from the account's
+    /// server instead (see `ql_instances::auth::skin`)
+    pub skin: Option<String>,
 
     /// Type of account:
     ///
     /// - `"Microsoft"`
     /// - `"ElyBy"`
     /// - `"LittleSkin"`
+    /// - `"Custom"` (see `auth_server`)
     pub account_type: Option<String>,
+    /// For `"Custom"` accounts, the API root of the
+    /// authlib-injector server the account is on.
+    ///
+    /// If missing, it's the old hardcoded
+    /// `https://drasl.unmojang.org/authlib-injector`
+    // Since: v0.5.0
+    pub auth_server: Option<String>,
 
     /// The original login identifier used for keyring operations.
     /// This is the email address or username that was used during login.
@@ -286,6 +361,45 @@ pub struct ConfigAccount {
     /// username while the regular "username"
     /// would be an email.
     pub username_nice: Option<String>,
+
+    /// For non-Microsoft accounts, the authlib-injector
+    /// server metadata fetched at login, so it doesn't
+    /// have to be fetched again on every launch.
+    // Since: v0.5.0
+    pub authlib_metadata: Option<String>,
+}
+
+impl ConfigAccount {
+    /// The name the account's token is stored under
+    #[must_use]
+    pub fn get_keyring_identifier(&self, username: &str) -> String {
+        // Fallback to old behavior for backwards compatibility
+        self.keyring_identifier.clone().unwrap_or_else(|| {
+            self.get_account_type(username)
+                .strip_name(username)
+                .to_owned()
+        })
+    }
+
+    /// Gets the account type, falling back to the
+    /// username suffix (eg: `" (elyby)"`) for old configs.
+    #[must_use]
+    pub fn get_account_type(&self, username: &str) -> AccountType {
+        let kind = self.account_type.as_deref();
+        if kind == Some("ElyBy") || username.ends_with(" (elyby)") {
+            AccountType::ElyBy
+        } else if kind == Some("Custom") || username.ends_with(" (custom)") {
+            AccountType::Custom(
+                self.auth_server
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_CUSTOM_SERVER.to_owned()),
+            )
+        } else if kind == Some("LittleSkin") || username.ends_with(" (littleskin)") {
+            AccountType::LittleSkin
+        } else {
+            AccountType::Microsoft
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -390,3 +504,29 @@ impl PersistentSettings {
             .unwrap_or_else(ListEntryKind::default_selected)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn old_java_installs() {
+        let parse = |n: &str| {
+            let config: LauncherConfig =
+                serde_json::from_str(&format!(r#"{{"username":"","java_installs":{n}}}"#)).unwrap();
+            config.java_installs
+        };
+        assert_eq!(parse("[]"), None);
+        assert_eq!(parse(r#"["/usr/bin/java"]"#), None);
+
+        let java = JavaInfo {
+            path: "/usr/bin/java".into(),
+            vendor: "Eclipse Adoptium".to_owned(),
+            version: "21.0.5".to_owned(),
+            major: 21,
+            arch: "amd64".to_owned(),
+        };
+        let list = serde_json::to_string(&[&java]).unwrap();
+        assert_eq!(parse(&list), Some(vec![java]));
+    }
+}
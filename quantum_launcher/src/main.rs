@@ -79,6 +79,8 @@ mod message_handler;
 mod message_update;
 /// Handles mod store
 mod mods_store;
+/// Desktop shortcuts and `quantumlauncher://` links
+mod shortcut;
 /// Stylesheet definitions (launcher themes)
 mod stylesheet;
 /// Code to tick every frame
@@ -156,6 +158,7 @@ const WINDOW_WIDTH: f32 = 600.0;
 fn main() {
     #[cfg(target_os = "windows")]
     attach_to_console();
+    cli::handle_completions();
     #[cfg(any(target_os = "linux", target_os = "freebsd"))]
     if should_migrate() {
         do_migration();
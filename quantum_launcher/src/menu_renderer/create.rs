@@ -5,12 +5,13 @@ use iced::{
     Alignment, Length,
 };
 use ql_core::ListEntryKind;
+use ql_packager::migrate::MigrationEntry;
 
 use crate::{
-    cli::EXPERIMENTAL_MMC_IMPORT,
     icons,
     menu_renderer::{
-        button_with_icon, ctxbox, dots, shortcut_ctrl, sidebar_button, tooltip, tsubtitle, Element,
+        back_button, button_with_icon, ctxbox, dots, shortcut_ctrl, sidebar_button, tooltip,
+        tsubtitle, Element,
     },
     state::{CreateInstanceMessage, MenuCreateInstance, MenuCreateInstanceChoosing, Message},
     stylesheet::{
@@ -38,10 +39,62 @@ impl MenuCreateInstance {
             .padding(10)
             .spacing(5)
             .into(),
+            MenuCreateInstance::Migrating { entries } => view_migrate(entries.as_deref(), timer),
         }
     }
 }
 
+fn view_migrate(entries: Option<&[(MigrationEntry, bool)]>, timer: usize) -> Element<'_> {
+    let list: Element = match entries {
+        None => widget::text!("Looking for other launchers{}", dots(timer)).into(),
+        Some([]) => widget::text("No instances of other launchers were found on this computer")
+            .style(tsubtitle)
+            .into(),
+        Some(entries) => widget::scrollable(
+            widget::column(entries.iter().enumerate().map(|(i, (entry, enabled))| {
+                let loader = entry.loader.map(|n| format!(" {n}")).unwrap_or_default();
+                widget::checkbox(
+                    format!(
+                        "{} ({}, {}{loader})",
+                        entry.name, entry.launcher, entry.mc_version
+                    ),
+                    *enabled,
+                )
+                .on_toggle(move |t| {
+                    Message::CreateInstance(CreateInstanceMessage::MigrateToggle(i, t))
+                })
+                .into()
+            }))
+            .padding(5)
+            .spacing(5),
+        )
+        .height(Length::Fill)
+        .width(Length::Fill)
+        .into(),
+    };
+
+    let any_selected = entries.is_some_and(|n| n.iter().any(|(_, b)| *b));
+
+    column![
+        back_button().on_press(Message::CreateInstance(CreateInstanceMessage::ScreenOpen {
+            is_server: false
+        })),
+        widget::text("Migrate from other launchers").size(20),
+        widget::text(
+            "Select the instances you want to import. Saves, mods and configs will be copied over."
+        )
+        .size(12)
+        .style(tsubtitle),
+        list,
+        button_with_icon(icons::download(), "Migrate", 16).on_press_maybe(
+            any_selected.then_some(Message::CreateInstance(CreateInstanceMessage::MigrateStart))
+        ),
+    ]
+    .padding(10)
+    .spacing(10)
+    .into()
+}
+
 impl MenuCreateInstanceChoosing {
     pub fn view(&self, existing_instances: Option<&[String]>, timer: usize) -> Element<'_> {
         let view = widget::pane_grid(&self.sidebar_grid_state, |_, is_sidebar, _| {
@@ -235,14 +288,20 @@ impl MenuCreateInstanceChoosing {
         let menu = column![
             widget::column![main_part, widget::vertical_space()],
             row![widget::horizontal_space()]
-                .push_maybe(
-                    EXPERIMENTAL_MMC_IMPORT.read().unwrap().then_some(tooltip(
-                        button_with_icon(icons::upload(), "Import from MultiMC...", 16)
-                            .on_press(Message::CreateInstance(CreateInstanceMessage::Import)),
-                        widget::text("Import Instance... (VERY EXPERIMENTAL right now)").size(14),
-                        Position::Top
-                    ))
-                )
+                .push_maybe((!self.is_server).then(|| tooltip(
+                    button_with_icon(icons::folder(), "Migrate...", 16)
+                        .on_press(Message::CreateInstance(CreateInstanceMessage::MigrateOpen)),
+                    widget::text("Import instances from other launchers installed on this computer\n(Minecraft Launcher, PrismLauncher, MultiMC, ATLauncher, GDLauncher, CurseForge)")
+                        .size(14),
+                    Position::Top
+                )))
+                .push(tooltip(
+                    button_with_icon(icons::upload(), "Import Instance...", 16)
+                        .on_press(Message::CreateInstance(CreateInstanceMessage::Import)),
+                    widget::text("Import from QuantumLauncher, MultiMC or PrismLauncher (.zip)")
+                        .size(14),
+                    Position::Top
+                ))
                 .push(get_create_button(already_exists))
                 .spacing(5)
         ]
@@ -4,7 +4,8 @@ use crate::{
         button_with_icon, checkered_list, settings::PREFIX_EXPLANATION, tsubtitle, FONT_MONO,
     },
     state::{
-        CustomJarState, EditInstanceMessage, ListMessage, MenuEditInstance, Message, NONE_JAR_NAME,
+        CustomJarState, EditInstanceMessage, ListMessage, MenuEditInstance, Message,
+        NEW_ACCOUNT_NAME, NONE_JAR_NAME,
     },
     stylesheet::{color::Color, styles::LauncherTheme, widgets::StyleButton},
 };
@@ -15,19 +16,31 @@ use iced::{
 use ql_core::InstanceSelection;
 use ql_core::{
     json::{
-        instance_config::{MainClassMode, PreLaunchPrefixMode},
-        GlobalSettings,
+        instance_config::{JvmPreset, MainClassMode, PreLaunchPrefixMode},
+        GlobalSettings, QuickPlay,
     },
     JavaVersion,
 };
+use ql_instances::JavaInfo;
 
 use super::Element;
 
+const KEEP_ACCOUNT_NAME: &str = "(Don't change)";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum QuickPlayKind {
+    Off,
+    Server,
+    World,
+}
+
 impl MenuEditInstance {
     pub fn view<'a>(
         &'a self,
         selected_instance: &InstanceSelection,
         jar_choices: Option<&'a CustomJarState>,
+        system_java: Option<&'a [JavaInfo]>,
+        accounts: &'a [String],
     ) -> Element<'a> {
         widget::scrollable(
             checkered_list([
@@ -60,8 +73,19 @@ impl MenuEditInstance {
                 )
                 .spacing(10),
 
+                if selected_instance.is_server() {
+                    column![]
+                } else {
+                    self.item_account(accounts)
+                },
+                if selected_instance.is_server() {
+                    column![]
+                } else {
+                    self.item_quick_play()
+                },
+
                 self.item_args(),
-                self.item_java_override(),
+                self.item_java_override(system_java),
                 self.item_custom_jar(jar_choices),
 
                 item_footer(selected_instance)
@@ -120,6 +144,89 @@ impl MenuEditInstance {
         )
     }
 
+    fn item_account<'a>(
+        &'a self,
+        accounts: &'a [String],
+    ) -> widget::Column<'a, Message, LauncherTheme> {
+        let accounts: Vec<String> = std::iter::once(KEEP_ACCOUNT_NAME.to_owned())
+            .chain(accounts.iter().filter(|n| *n != NEW_ACCOUNT_NAME).cloned())
+            .collect();
+
+        column![
+            row![
+                "Account:",
+                widget::pick_list(
+                    accounts,
+                    Some(
+                        self.config
+                            .account
+                            .clone()
+                            .unwrap_or_else(|| KEEP_ACCOUNT_NAME.to_owned())
+                    ),
+                    |n| Message::EditInstance(EditInstanceMessage::AccountChanged(
+                        (n != KEEP_ACCOUNT_NAME).then_some(n)
+                    ))
+                )
+                .text_size(14),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            widget::text("Switches to this account when the instance is selected")
+                .size(12)
+                .style(tsubtitle),
+        ]
+        .spacing(5)
+    }
+
+    fn item_quick_play(&self) -> widget::Column<'_, Message, LauncherTheme> {
+        let selected = match &self.config.quick_play {
+            None => QuickPlayKind::Off,
+            Some(QuickPlay::Server(_)) => QuickPlayKind::Server,
+            Some(QuickPlay::World(_)) => QuickPlayKind::World,
+        };
+        let radio = |label, kind| {
+            widget::radio(label, kind, Some(selected), |kind| {
+                Message::EditInstance(EditInstanceMessage::QuickPlayChanged(match kind {
+                    QuickPlayKind::Off => None,
+                    QuickPlayKind::Server => Some(QuickPlay::Server(String::new())),
+                    QuickPlayKind::World => Some(QuickPlay::World(String::new())),
+                }))
+            })
+            .size(14)
+            .text_size(13)
+        };
+
+        column![
+            "Join on Startup:",
+            row![
+                radio("Off", QuickPlayKind::Off),
+                radio("Server", QuickPlayKind::Server),
+                radio("World", QuickPlayKind::World),
+            ]
+            .spacing(10),
+        ]
+        .push_maybe(self.config.quick_play.as_ref().map(|quick_play| {
+            let (placeholder, make): (_, fn(String) -> QuickPlay) = match quick_play {
+                QuickPlay::Server(_) => (
+                    "Server address (eg: mc.example.com:25565)",
+                    QuickPlay::Server,
+                ),
+                QuickPlay::World(_) => ("World folder name (in saves)", QuickPlay::World),
+            };
+            widget::text_input(placeholder, quick_play.target())
+                .on_input(move |n| {
+                    Message::EditInstance(EditInstanceMessage::QuickPlayChanged(Some(make(n))))
+                })
+                .size(14)
+        }))
+        .push(
+            widget::text("Skips the title screen. Worlds only work in 1.20 and above")
+                .size(12)
+                .style(tsubtitle),
+        )
+        .spacing(5)
+    }
+
     fn item_args(&self) -> widget::Column<'_, Message, LauncherTheme> {
         let current_mode = self.config.global_java_args_enable.unwrap_or(true);
         let prefix_mode = self.config.pre_launch_prefix_mode.unwrap_or_default();
@@ -127,6 +234,24 @@ impl MenuEditInstance {
         let sp = || widget::Space::with_height(5);
 
         column![
+            row![
+                "JVM preset:",
+                widget::pick_list(
+                    JvmPreset::ALL,
+                    Some(self.config.jvm_preset.unwrap_or_default()),
+                    |n| Message::EditInstance(EditInstanceMessage::JvmPresetChanged(n))
+                )
+                .text_size(14),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            widget::text(self.config.jvm_preset.unwrap_or_default().get_description())
+                .size(12)
+                .style(tsubtitle),
+            widget::text("Flags not supported by the instance's Java version are skipped")
+                .size(12)
+                .style(tsubtitle),
+            sp(),
             row![
                 "Java arguments:",
                 widget::horizontal_space(),
@@ -219,15 +344,36 @@ impl MenuEditInstance {
 
         const RAM_16_GB_TO_MB: usize = 16384;
 
-        column![
-            "Allocated memory",
-            widget::text(
+        let auto_ram = self.config.auto_ram.unwrap_or(false);
+
+        let header = column![
+            row![
+                "Allocated memory",
+                widget::horizontal_space(),
+                widget::checkbox("Automatic", auto_ram)
+                    .on_toggle(|t| Message::EditInstance(EditInstanceMessage::AutoRamToggle(t)))
+                    .style(|t: &LauncherTheme, s| t.style_checkbox(s, Some(Color::SecondLight)))
+                    .size(12)
+                    .text_size(12)
+            ]
+            .align_y(Alignment::Center),
+            widget::text(if auto_ram {
+                "Picked when launching, based on your system's RAM and the number of installed mods"
+            } else {
                 r"Normal Minecraft: 2-3 GB
 Old versions: 512 MB - 1 GB
 Heavy modpacks / High settings: 4-8 GB+"
-            )
+            })
             .size(12)
             .style(tsubtitle),
+        ]
+        .spacing(5);
+        if auto_ram {
+            return header;
+        }
+
+        column![
+            header,
             widget::Space::with_height(5),
             row![
                 widget::text(&self.slider_text),
@@ -262,7 +408,10 @@ Heavy modpacks / High settings: 4-8 GB+"
         .spacing(5)
     }
 
-    fn item_java_override(&self) -> widget::Column<'_, Message, LauncherTheme> {
+    fn item_java_override<'a>(
+        &'a self,
+        system_java: Option<&'a [JavaInfo]>,
+    ) -> widget::Column<'a, Message, LauncherTheme> {
         fn radio(
             l: &str,
             a: bool,
@@ -337,9 +486,57 @@ Heavy modpacks / High settings: 4-8 GB+"
                         EditInstanceMessage::BrowseJavaOverride
                     ))
             )
+            .spacing(5),
+            row![
+                widget::Space::with_width(16),
+                Self::item_system_java(system_java, java_override)
+            ]
+        ]
+        .spacing(5)
+    }
+
+    fn item_system_java<'a>(
+        system_java: Option<&'a [JavaInfo]>,
+        java_override: &str,
+    ) -> Element<'a> {
+        let Some(system_java) = system_java else {
+            return widget::button(widget::text("Detect installed Java...").size(13))
+                .padding([4, 8])
+                .on_press(Message::EditInstance(EditInstanceMessage::JavaDetect))
+                .into();
+        };
+        // Detected installs are saved in the launcher config
+        let rescan = widget::button(widget::text("Rescan").size(13))
+            .padding([4, 8])
+            .on_press(Message::EditInstance(EditInstanceMessage::JavaDetect));
+        if system_java.is_empty() {
+            return row![
+                widget::text("No Java installations found on this computer")
+                    .size(12)
+                    .style(tsubtitle),
+                rescan
+            ]
             .spacing(5)
+            .align_y(Alignment::Center)
+            .into();
+        }
+
+        let selected = system_java
+            .iter()
+            .find(|n| n.path.to_str() == Some(java_override.trim()))
+            .cloned();
+        row![
+            widget::pick_list(system_java, selected, |n| {
+                Message::EditInstance(EditInstanceMessage::JavaOverride(
+                    n.path.to_string_lossy().to_string(),
+                ))
+            })
+            .placeholder("Detected Java installations...")
+            .text_size(13),
+            rescan
         ]
         .spacing(5)
+        .into()
     }
 
     fn item_custom_jar<'a>(
@@ -432,6 +629,9 @@ fn item_footer(
                 button_with_icon(icons::version_download_s(14), "Update Assets", 13)
                     .padding([4, 8])
                     .on_press(Message::EditInstance(EditInstanceMessage::UpdateAssets)),
+                button_with_icon(icons::play_s(14), "Create Desktop Shortcut", 13)
+                    .padding([4, 8])
+                    .on_press(Message::EditInstance(EditInstanceMessage::CreateShortcut)),
             ]
             .spacing(5)
             .wrap(),
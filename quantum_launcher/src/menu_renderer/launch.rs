@@ -14,9 +14,9 @@ use crate::{
     icons,
     menu_renderer::DISCORD,
     state::{
-        AccountMessage, CreateInstanceMessage, InstanceLog, LaunchTab, Launcher,
-        LauncherSettingsMessage, ManageModsMessage, MenuLaunch, Message, State, NEW_ACCOUNT_NAME,
-        OFFLINE_ACCOUNT_NAME,
+        AccountMessage, CreateInstanceMessage, EditInstanceMessage, InstanceLog, LaunchTab,
+        Launcher, LauncherSettingsMessage, ManageModsMessage, MenuLaunch, Message,
+        OfflineProfileMessage, SkinMessage, State, NEW_ACCOUNT_NAME, OFFLINE_ACCOUNT_NAME,
     },
     stylesheet::{color::Color, styles::LauncherTheme, widgets::StyleButton},
 };
@@ -66,7 +66,12 @@ impl Launcher {
                 LaunchTab::Log => self.get_tab_logs(menu).into(),
                 LaunchTab::Edit => {
                     if let Some(menu) = &menu.edit_instance {
-                        menu.view(selected, self.custom_jar.as_ref())
+                        menu.view(
+                            selected,
+                            self.custom_jar.as_ref(),
+                            self.config.java_installs.as_deref(),
+                            &self.accounts_dropdown,
+                        )
                     } else {
                         widget::column!(
                             "Error: This instance is corrupted/invalid!\n(Couldn't read config.json)",
@@ -191,8 +196,11 @@ impl Launcher {
                 .spacing(16)
                 .align_y(Alignment::Center),
             main_buttons,
-            // widget::button("Export Instance").on_press(Message::ExportInstanceOpen),
-            notes,
+        )
+        .push_maybe((!menu.is_viewing_server).then(|| self.get_account_switcher(menu)))
+        // widget::button("Export Instance").on_press(Message::ExportInstanceOpen),
+        .push(notes)
+        .push(
             widget::row![
                 widget::Column::new()
                     .push_maybe(get_view_servers(menu.is_viewing_server))
@@ -217,13 +225,77 @@ impl Launcher {
                     .spacing(5),
                 get_footer_text(),
             ]
-            .align_y(Alignment::End)
+            .align_y(Alignment::End),
         )
         .padding(16)
         .spacing(10)
         .into()
     }
 
+    /// Quickly switch accounts, or make the
+    /// selected one the instance's default
+    fn get_account_switcher<'a>(&'a self, menu: &'a MenuLaunch) -> Element<'a> {
+        let something_is_happening = self.java_recv.is_some() || menu.login_progress.is_some();
+        let pinned = menu
+            .edit_instance
+            .as_ref()
+            .and_then(|n| n.config.account.as_deref());
+        let is_pinned = pinned.is_some() && pinned == self.accounts_selected.as_deref();
+
+        let dropdown: Element = if something_is_happening {
+            widget::text(self.accounts_selected.as_deref().unwrap_or_default())
+                .size(14)
+                .into()
+        } else {
+            widget::pick_list(
+                self.accounts_dropdown.clone(),
+                self.accounts_selected.clone(),
+                |n| Message::Account(AccountMessage::Selected(n)),
+            )
+            .text_size(14)
+            .into()
+        };
+
+        let pin_button =
+            widget::button(widget::text(if is_pinned { "Unpin" } else { "Pin" }).size(12))
+                .padding([4, 8])
+                .on_press_maybe(
+                    (menu.edit_instance.is_some()
+                        && self
+                            .accounts_selected
+                            .as_deref()
+                            .is_some_and(|n| n != NEW_ACCOUNT_NAME))
+                    .then_some(Message::EditInstance(EditInstanceMessage::AccountPin(
+                        !is_pinned,
+                    ))),
+                );
+
+        // The instance's default account is used at launch,
+        // whatever is selected here
+        let pinned_note = pinned
+            .filter(|n| !is_pinned && self.accounts_dropdown.iter().any(|a| a == n))
+            .map(|n| {
+                widget::text!("(this instance uses {n})")
+                    .size(12)
+                    .style(tsubtitle)
+            });
+
+        widget::row![widget::text("Play as").size(14).style(tsubtitle), dropdown,]
+            .push_maybe(pinned_note)
+            .push(tooltip(
+                pin_button,
+                if is_pinned {
+                    "Stop using this account by default for this instance"
+                } else {
+                    "Always use this account for this instance"
+                },
+                Position::Bottom,
+            ))
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .into()
+    }
+
     fn get_mods_button(&self) -> widget::Button<'_, Message, LauncherTheme> {
         button_with_icon(icons::download(), "Mods", 15)
             .on_press(if self.modifiers_pressed.contains(Modifiers::SHIFT) {
@@ -407,6 +479,18 @@ impl Launcher {
 
     fn get_accounts_bar(&self, menu: &MenuLaunch) -> Element<'_> {
         let something_is_happening = self.java_recv.is_some() || menu.login_progress.is_some();
+        let (edit_label, edit_message, logout_label) = match self
+            .accounts_selected
+            .as_deref()
+            .and_then(|n| self.config.get_offline_profile(n))
+        {
+            Some(profile) => (
+                "Edit",
+                Message::OfflineProfile(OfflineProfileMessage::Open(Some(profile.name.clone()))),
+                "Delete",
+            ),
+            None => ("Skin", Message::Skin(SkinMessage::Open), "Logout"),
+        };
 
         let dropdown: Element = if something_is_happening {
             widget::text_input("", self.accounts_selected.as_deref().unwrap_or_default())
@@ -425,11 +509,19 @@ impl Launcher {
         widget::column![
             widget::row![widget::text(" Accounts:").size(14), horizontal_space()].push_maybe(
                 self.is_account_selected().then_some(
-                    widget::button(widget::text("Logout").size(11))
-                        .padding(3)
-                        .on_press(Message::Account(AccountMessage::LogoutCheck))
-                        .style(|n: &LauncherTheme, status| n
-                            .style_button(status, StyleButton::FlatExtraDark))
+                    widget::row![
+                        widget::button(widget::text(edit_label).size(11))
+                            .padding(3)
+                            .on_press(edit_message)
+                            .style(|n: &LauncherTheme, status| n
+                                .style_button(status, StyleButton::FlatExtraDark)),
+                        widget::button(widget::text(logout_label).size(11))
+                            .padding(3)
+                            .on_press(Message::Account(AccountMessage::LogoutCheck))
+                            .style(|n: &LauncherTheme, status| n
+                                .style_button(status, StyleButton::FlatExtraDark))
+                    ]
+                    .spacing(2)
                 )
             ),
             dropdown
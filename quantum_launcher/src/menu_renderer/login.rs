@@ -65,6 +65,7 @@ impl MenuLoginAlternate {
                 })
                 .size(20),
                 widget::vertical_space(),
+                self.view_custom_server(padding),
                 widget::text("Username/Email:").size(12),
                 center_x(
                     widget::text_input("Enter Username/Email...", &self.username)
@@ -96,6 +97,8 @@ impl MenuLoginAlternate {
                     ),
                 status,
                 widget::Space::with_height(5),
+            ]
+            .push_maybe((!self.is_custom).then(|| {
                 widget::row![
                     widget::text("Or").size(14),
                     widget::button(widget::text("Create an account").size(14)).on_press(
@@ -111,9 +114,9 @@ impl MenuLoginAlternate {
                 ]
                 .align_y(Alignment::Center)
                 .spacing(5)
-                .wrap(),
-                widget::vertical_space(),
-            ]
+                .wrap()
+            }))
+            .push(widget::vertical_space())
             .width(Length::Fill)
             .align_x(Alignment::Center)
             .spacing(5)
@@ -122,6 +125,31 @@ impl MenuLoginAlternate {
         .into()
     }
 
+    fn view_custom_server(&'_ self, padding: iced::Padding) -> Element<'_> {
+        if !self.is_custom {
+            return widget::Column::new().into();
+        }
+        widget::column![
+            widget::text("Server URL:").size(12),
+            center_x(
+                widget::text_input("Eg: drasl.example.com", &self.custom_auth_url)
+                    .padding(padding)
+                    .on_input(|n| Message::Account(AccountMessage::AltServerInput(n)))
+            ),
+            widget::Row::with_children(self.custom_auth_servers.iter().map(|server| {
+                widget::button(widget::text(server).size(12))
+                    .on_press(Message::Account(AccountMessage::AltServerInput(
+                        server.clone(),
+                    )))
+                    .into()
+            }))
+            .spacing(5)
+            .wrap(),
+        ]
+        .spacing(5)
+        .into()
+    }
+
     fn view_oauth(&'_ self, oauth: &crate::state::LittleSkinOauth) -> Element<'_> {
         let time_left = {
             let now = std::time::Instant::now();
@@ -9,7 +9,8 @@ use crate::{
     icons,
     state::{
         AccountMessage, InstallModsMessage, LauncherSettingsMessage, LicenseTab, ManageModsMessage,
-        MenuCurseforgeManualDownload, MenuLauncherUpdate, MenuLicense, Message, ProgressBar,
+        MenuCurseforgeManualDownload, MenuLauncherUpdate, MenuLicense, Message,
+        OfflineProfileMessage, ProgressBar,
     },
     stylesheet::{color::Color, styles::LauncherTheme, widgets::StyleButton},
 };
@@ -22,6 +23,7 @@ mod login;
 mod mods;
 mod onboarding;
 mod settings;
+mod skins;
 
 pub use onboarding::changelog;
 
@@ -435,13 +437,16 @@ pub fn view_account_login<'a>() -> Element<'a> {
                         kind: AccountType::LittleSkin
                     }
                 )),
-                 widget::button("Login with Drasl").on_press(Message::Account(
+                widget::button("Login with custom server").on_press(Message::Account(
                     AccountMessage::OpenMenu {
                         is_from_welcome_screen: false,
-                        kind: AccountType::Custom
+                        kind: AccountType::Custom(String::new())
                     }
                 )),
-                ]
+                widget::Space::with_height(5),
+                widget::button("Add offline profile")
+                    .on_press(Message::OfflineProfile(OfflineProfileMessage::Open(None))),
+            ]
             .align_x(Alignment::Center)
             .spacing(5),
             widget::horizontal_space(),
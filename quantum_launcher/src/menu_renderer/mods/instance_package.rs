@@ -1,4 +1,5 @@
 use iced::{widget, Length};
+use ql_packager::ExportFormat;
 
 use crate::{
     icons,
@@ -32,9 +33,11 @@ impl MenuExportInstance {
             widget::column![
                 widget::text("Format:").size(12),
                 widget::row![
-                    widget::pick_list(["QuantumLauncher"], Some("QuantumLauncher"), |_| {
-                        Message::Nothing
-                    })
+                    widget::pick_list(
+                        ExportFormat::ALL,
+                        Some(self.format),
+                        Message::ExportInstanceChangeFormat
+                    )
                     .text_line_height(1.68),
                     button_with_icon(icons::floppydisk(), "Export", 16)
                         .on_press(Message::ExportInstanceStart),
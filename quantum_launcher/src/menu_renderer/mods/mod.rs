@@ -2,6 +2,7 @@ mod export_mods;
 mod install_loader;
 mod instance_package;
 mod jarmods;
+mod modpack_options;
 mod mods_manage;
 mod mods_store;
 mod presets;
@@ -0,0 +1,56 @@
+use iced::{widget, Length};
+
+use crate::{
+    icons,
+    menu_renderer::{back_button, button_with_icon, tsubtitle, Element},
+    state::{InstallModsMessage, ManageModsMessage, MenuModpackOptions, Message, ModpackSource},
+};
+
+impl MenuModpackOptions {
+    pub fn view(&'_ self) -> Element<'_> {
+        let (back_msg, action) = match &self.source {
+            ModpackSource::File { .. } => (
+                Message::ManageMods(ManageModsMessage::ScreenOpenWithoutUpdate),
+                "Install",
+            ),
+            ModpackSource::Store(..) => (Message::InstallMods(InstallModsMessage::Open), "Install"),
+            ModpackSource::Update(_) => (
+                Message::ManageMods(ManageModsMessage::ScreenOpenWithoutUpdate),
+                "Update",
+            ),
+        };
+        widget::column![
+            back_button().on_press(back_msg),
+            widget::text!("{action}ing modpack: {}", self.name).size(20),
+            widget::text("This modpack has optional files. Select the ones you want:")
+                .size(12)
+                .style(tsubtitle),
+            widget::scrollable(
+                widget::column(
+                    self.optional
+                        .iter()
+                        .enumerate()
+                        .map(|(i, (path, enabled))| {
+                            widget::checkbox(path.as_str(), *enabled)
+                                .on_toggle(move |t| {
+                                    Message::ManageMods(ManageModsMessage::ModpackOptionsToggle(
+                                        i, t,
+                                    ))
+                                })
+                                .into()
+                        })
+                )
+                .padding(5)
+                .spacing(5)
+            )
+            .width(Length::Fill)
+            .height(Length::Fill),
+            button_with_icon(icons::download(), action, 16).on_press(Message::ManageMods(
+                ManageModsMessage::ModpackOptionsInstall
+            )),
+        ]
+        .padding(10)
+        .spacing(10)
+        .into()
+    }
+}
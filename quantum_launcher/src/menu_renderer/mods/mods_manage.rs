@@ -160,6 +160,36 @@ impl MenuEditMods {
     }
 
     fn get_mod_update_pane(&'_ self, tick_timer: usize) -> Element<'_> {
+        widget::column![
+            self.get_modpack_update_pane(),
+            self.get_mod_update_list(tick_timer)
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    fn get_modpack_update_pane(&'_ self) -> Element<'_> {
+        let (Some(update), Some(pack)) = (&self.modpack_update, &self.config.modpack) else {
+            return widget::column!().into();
+        };
+        widget::container(
+            widget::column!(
+                widget::text("Modpack Update Available!").size(15),
+                widget::text!("{} - {update}", pack.name).size(12),
+                widget::text!("Installed: {}", pack.installed_version)
+                    .size(12)
+                    .style(tsubtitle),
+                button_with_icon(icons::version_download(), "Update Modpack", 16)
+                    .on_press(Message::ManageMods(ManageModsMessage::ModpackUpdate)),
+            )
+            .padding(10)
+            .spacing(10)
+            .width(MODS_SIDEBAR_WIDTH),
+        )
+        .into()
+    }
+
+    fn get_mod_update_list(&'_ self, tick_timer: usize) -> Element<'_> {
         if self.update_check_handle.is_some() {
             widget::text!("Checking for mod updates{}", dots(tick_timer))
                 .size(12)
@@ -1,7 +1,10 @@
 use std::sync::LazyLock;
 
 use iced::{widget, Alignment, Length};
-use ql_core::{LAUNCHER_DIR, WEBSITE};
+use ql_core::{InstanceSelection, LAUNCHER_DIR, WEBSITE};
+use ql_instances::auth::{
+    credentials::file::PASSPHRASE_ENV_VAR, CredentialBackend, CredentialFileStatus,
+};
 
 use super::{
     back_button, button_with_icon, get_mode_selector, sidebar_button, underline, Element, DISCORD,
@@ -12,7 +15,10 @@ use crate::menu_renderer::{back_to_launch_screen, checkered_list, sidebar, tsubt
 use crate::{
     config::LauncherConfig,
     icons,
-    state::{LauncherSettingsMessage, LauncherSettingsTab, MenuLauncherSettings, Message},
+    state::{
+        JavaInstallsState, LauncherSettingsMessage, LauncherSettingsTab, MenuLauncherSettings,
+        Message,
+    },
     stylesheet::{
         color::Color,
         styles::{LauncherTheme, LauncherThemeColor},
@@ -162,6 +168,256 @@ Only increase if progress bars stutter or "not responding" dialogs show"#).size(
         ])
         .into()
     }
+
+    fn view_accounts_tab<'a>(&'a self, config: &'a LauncherConfig) -> Element<'a> {
+        let credentials = &self.credentials;
+        let selected = config.credential_backend.unwrap_or_default();
+
+        let backends = widget::column(CredentialBackend::ALL.iter().map(|backend| {
+            widget::column![
+                widget::radio(backend.to_string(), *backend, Some(selected), |n| {
+                    Message::LauncherSettings(LauncherSettingsMessage::CredentialBackendPicked(n))
+                })
+                .size(14)
+                .text_size(14),
+                widget::row![
+                    widget::Space::with_width(22),
+                    widget::text(backend.get_description())
+                        .size(12)
+                        .style(tsubtitle)
+                ],
+            ]
+            .spacing(2)
+            .into()
+        }))
+        .spacing(8);
+
+        let passphrase_input = widget::text_input("Passphrase", &credentials.passphrase)
+            .secure(true)
+            .size(14)
+            .width(SETTING_WIDTH * 2)
+            .on_input(|n| {
+                Message::LauncherSettings(LauncherSettingsMessage::CredentialPassphraseInput(n))
+            });
+        let has_input = !credentials.passphrase.is_empty() && !credentials.is_busy;
+        let set_passphrase = |label| {
+            widget::button(widget::text(label).size(14)).on_press_maybe(has_input.then_some(
+                Message::LauncherSettings(LauncherSettingsMessage::CredentialSetPassphrase(true)),
+            ))
+        };
+
+        let file_status: Element = match credentials.file_status {
+            None => widget::text("Loading...").into(),
+            Some(CredentialFileStatus::Missing) => widget::column![
+                widget::text("Not created yet (no logins saved in it).").size(14),
+                widget::text("It will be protected with this computer's machine key,\nunless you set a passphrase:")
+                    .size(12)
+                    .style(tsubtitle),
+                widget::row![passphrase_input, set_passphrase("Set passphrase")].spacing(5),
+            ]
+            .spacing(5)
+            .into(),
+            Some(CredentialFileStatus::Locked) => widget::column![
+                widget::text("Locked. Enter your passphrase to use the accounts saved in it.")
+                    .size(14),
+                widget::row![
+                    passphrase_input.on_submit_maybe(
+                        has_input.then_some(Message::LauncherSettings(
+                            LauncherSettingsMessage::CredentialUnlock
+                        ))
+                    ),
+                    widget::button(widget::text("Unlock").size(14)).on_press_maybe(
+                        has_input.then_some(Message::LauncherSettings(
+                            LauncherSettingsMessage::CredentialUnlock
+                        ))
+                    ),
+                ]
+                .spacing(5),
+            ]
+            .spacing(5)
+            .into(),
+            Some(CredentialFileStatus::Unlocked { passphrase: false }) => widget::column![
+                widget::text("Protected with this computer's machine key.").size(14),
+                widget::text("Anyone using your user account can read it. Set a passphrase for more security:")
+                    .size(12)
+                    .style(tsubtitle),
+                widget::row![passphrase_input, set_passphrase("Set passphrase")].spacing(5),
+            ]
+            .spacing(5)
+            .into(),
+            Some(CredentialFileStatus::Unlocked { passphrase: true }) => widget::column![
+                widget::text("Protected with a passphrase (unlocked).").size(14),
+                widget::row![passphrase_input, set_passphrase("Change passphrase")].spacing(5),
+                widget::button(widget::text("Remove passphrase").size(14)).on_press_maybe(
+                    (!credentials.is_busy).then_some(Message::LauncherSettings(
+                        LauncherSettingsMessage::CredentialSetPassphrase(false)
+                    ))
+                ),
+            ]
+            .spacing(5)
+            .into(),
+        };
+
+        widget::column![
+            widget::column![
+                widget::text("Accounts").size(20),
+                widget::text("Where account logins are saved. Switching moves the existing logins.")
+                    .size(12)
+                    .style(tsubtitle),
+            ]
+            .spacing(5),
+            backends,
+            widget::horizontal_rule(1),
+            widget::text("Encrypted file").size(16),
+            file_status,
+            widget::text!(
+                "On headless systems, the passphrase can also be set\nthrough the {PASSPHRASE_ENV_VAR} environment variable."
+            )
+            .size(12)
+            .style(tsubtitle),
+        ]
+        .push_maybe(credentials.is_busy.then_some(widget::text("Working...")))
+        .spacing(SETTINGS_SPACING)
+        .padding(16)
+        .into()
+    }
+
+    fn view_java_tab(&self) -> Element<'_> {
+        let java = &self.java;
+        let is_idle = java.busy.is_none();
+
+        let list: Element = match &java.installs {
+            None => widget::text("Loading...").into(),
+            Some(installs) if installs.is_empty() => {
+                widget::text("No Java installed by the launcher yet")
+                    .style(tsubtitle)
+                    .into()
+            }
+            Some(installs) => checkered_list(
+                installs
+                    .iter()
+                    .map(|install| view_java_install(install, java)),
+            )
+            .into(),
+        };
+
+        widget::column![
+            widget::column![
+                widget::text("Java").size(20),
+                widget::text(
+                    "Java installed automatically by the launcher.\nDeleted Java gets redownloaded when needed."
+                )
+                .size(12)
+                .style(tsubtitle),
+            ]
+            .spacing(5),
+            widget::row![
+                button_with_icon(icons::refresh(), "Check for updates", 16).on_press_maybe(
+                    (is_idle && !java.is_checking_updates && java.installs.is_some()).then_some(
+                        Message::LauncherSettings(LauncherSettingsMessage::JavaCheckUpdates)
+                    )
+                ),
+                button_with_icon(icons::bin(), "Delete unused", 16).on_press_maybe(
+                    is_idle.then_some(Message::LauncherSettings(
+                        LauncherSettingsMessage::JavaDeleteUnused
+                    ))
+                ),
+            ]
+            .spacing(5)
+            .wrap(),
+        ]
+        .push_maybe(java.busy.as_deref().map(widget::text))
+        .push_maybe(
+            java.is_checking_updates
+                .then_some(widget::text("Checking for updates...")),
+        )
+        .push(list)
+        .push(widget::horizontal_rule(1))
+        .push(
+            widget::row![
+                button_with_icon(icons::bin(), "Clear Java installs", 16).on_press_maybe(
+                    is_idle.then_some(Message::LauncherSettings(
+                        LauncherSettingsMessage::ClearJavaInstalls
+                    ))
+                ),
+                widget::text(
+                    "Might fix some Java problems.\nPerfectly safe, will be redownloaded."
+                )
+                .style(tsubtitle)
+                .size(12),
+            ]
+            .spacing(10)
+            .wrap(),
+        )
+        .spacing(SETTINGS_SPACING)
+        .padding(16)
+        .into()
+    }
+}
+
+fn view_java_install<'a>(
+    install: &'a ql_instances::JavaInstall,
+    java: &'a JavaInstallsState,
+) -> Element<'a> {
+    let version = install.version;
+    let is_idle = java.busy.is_none();
+
+    let status = if install.is_incomplete {
+        "Incomplete install (will be redone when needed)".to_owned()
+    } else if let Some(info) = &install.info {
+        info.to_string()
+    } else {
+        "Broken install".to_owned()
+    };
+    let used_by = if install.used_by.is_empty() {
+        "Not used by any instance".to_owned()
+    } else {
+        format!(
+            "Used by: {}",
+            install
+                .used_by
+                .iter()
+                .map(InstanceSelection::get_name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+    let update = match java.updates.get(&version) {
+        Some(Some(true)) => Some("Update available"),
+        Some(Some(false)) => Some("Up to date"),
+        Some(None) => Some("Unknown build (reinstall to enable update checks)"),
+        None => None,
+    };
+
+    widget::column![
+        widget::row![
+            widget::text!("Java {}", version as usize).size(16),
+            widget::text!("{:.1} MB", install.size_mb())
+                .size(12)
+                .style(tsubtitle),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center),
+        widget::text(status).size(12),
+        widget::text(used_by).size(12).style(tsubtitle),
+    ]
+    .push_maybe(update.map(|n| widget::text(n).size(12)))
+    .push(
+        widget::row![
+            button_with_icon(icons::download(), "Reinstall", 14).on_press_maybe(is_idle.then_some(
+                Message::LauncherSettings(LauncherSettingsMessage::JavaReinstall(version))
+            )),
+            button_with_icon(icons::tweak(), "Repair", 14).on_press_maybe(is_idle.then_some(
+                Message::LauncherSettings(LauncherSettingsMessage::JavaRepair(version))
+            )),
+            button_with_icon(icons::bin(), "Delete", 14).on_press_maybe(is_idle.then_some(
+                Message::LauncherSettings(LauncherSettingsMessage::JavaDelete(version))
+            )),
+        ]
+        .spacing(5),
+    )
+    .spacing(5)
+    .into()
 }
 
 fn get_ui_opacity(config: &LauncherConfig) -> widget::Column<'static, Message, LauncherTheme> {
@@ -245,23 +501,12 @@ impl LauncherSettingsTab {
                     )),
                 ),
                 args_split_by_space(menu.arg_split_by_space),
-                widget::horizontal_rule(1),
-                widget::row![
-                    button_with_icon(icons::bin(), "Clear Java installs", 16).on_press(
-                        Message::LauncherSettings(LauncherSettingsMessage::ClearJavaInstalls)
-                    ),
-                    widget::text(
-                        "Might fix some Java problems.\nPerfectly safe, will be redownloaded."
-                    )
-                    .style(tsubtitle)
-                    .size(12),
-                ]
-                .spacing(10)
-                .wrap(),
             ]
             .spacing(SETTINGS_SPACING)
             .padding(16)
             .into(),
+            LauncherSettingsTab::Accounts => menu.view_accounts_tab(config),
+            LauncherSettingsTab::Java => menu.view_java_tab(),
             LauncherSettingsTab::About => view_about_tab(),
         }
     }
@@ -0,0 +1,259 @@
+use iced::{widget, Alignment, Length};
+use ql_instances::auth::{offline, skin::SkinVariant};
+
+use crate::{
+    menu_renderer::{back_button, back_to_launch_screen, tsubtitle, Element},
+    state::{MenuAccountSkins, MenuOfflineProfile, Message, OfflineProfileMessage, SkinMessage},
+    stylesheet::{color::Color, styles::LauncherTheme},
+};
+
+/// Skin previews are 16x32, scaled up by this
+const PREVIEW_SCALE: f32 = 8.0;
+
+fn view_preview<'a>(preview: Option<&widget::image::Handle>, scale: f32) -> Element<'a> {
+    let (width, height) = (16.0 * scale, 32.0 * scale);
+    match preview {
+        Some(handle) => widget::image(handle.clone())
+            .filter_method(widget::image::FilterMethod::Nearest)
+            .width(width)
+            .height(height)
+            .into(),
+        None => widget::container(widget::text("Default skin").size(12).style(tsubtitle))
+            .center(Length::Fixed(width))
+            .height(height)
+            .into(),
+    }
+}
+
+impl MenuAccountSkins {
+    pub fn view(&'_ self, tick_timer: usize) -> Element<'_> {
+        let current: Element = if self.is_loading {
+            let dots = ".".repeat((tick_timer % 3) + 1);
+            widget::text!("Loading{dots}").into()
+        } else {
+            view_preview(self.preview.as_ref(), PREVIEW_SCALE)
+        };
+
+        widget::scrollable(
+            widget::column![
+                back_button().on_press(back_to_launch_screen(None, None)),
+                widget::text!("Skin: {}", self.account).size(20),
+                widget::row![
+                    widget::container(current).padding(10).style(
+                        |n: &LauncherTheme| n.style_container_sharp_box(0.0, Color::ExtraDark)
+                    ),
+                    self.view_controls(),
+                ]
+                .spacing(10),
+                self.view_library(),
+            ]
+            .padding(10)
+            .spacing(10),
+        )
+        .style(LauncherTheme::style_scrollable_flat_extra_dark)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn view_controls(&'_ self) -> Element<'_> {
+        let is_busy = self.is_loading;
+        let capes: Element = match &self.profile {
+            Some(profile) if profile.capes.iter().any(|n| n.id.is_some()) => {
+                let no_cape = widget::button(widget::text("No cape").size(14)).on_press_maybe(
+                    (profile.get_active_cape().is_some() && !is_busy)
+                        .then_some(Message::Skin(SkinMessage::CapePicked(None))),
+                );
+                widget::column(
+                    std::iter::once(no_cape.into()).chain(profile.capes.iter().map(|cape| {
+                        widget::button(
+                            widget::text(if cape.is_active {
+                                format!("{} (active)", cape.name)
+                            } else {
+                                cape.name.clone()
+                            })
+                            .size(14),
+                        )
+                        .on_press_maybe(
+                            (!cape.is_active && !is_busy)
+                                .then(|| Message::Skin(SkinMessage::CapePicked(cape.id.clone()))),
+                        )
+                        .into()
+                    })),
+                )
+                .spacing(5)
+                .into()
+            }
+            Some(profile) => widget::text(match profile.get_active_cape() {
+                Some(cape) => format!("{} (change it on the account's website)", cape.name),
+                None => "None".to_owned(),
+            })
+            .size(14)
+            .into(),
+            None => widget::Column::new().into(),
+        };
+
+        widget::column![
+            widget::text("Upload a new skin").size(16),
+            widget::pick_list(SkinVariant::ALL, Some(self.variant), |n| {
+                Message::Skin(SkinMessage::VariantPicked(n))
+            })
+            .text_size(14),
+            widget::button(widget::text("Choose PNG...").size(14))
+                .on_press_maybe((!is_busy).then_some(Message::Skin(SkinMessage::UploadFile))),
+            widget::Space::with_height(5),
+            widget::text("Cape").size(16),
+            capes,
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    fn view_library(&'_ self) -> Element<'_> {
+        let has_skin = self.profile.as_ref().is_some_and(|n| n.skin.is_some());
+
+        let skins = widget::Row::with_children(self.library.iter().enumerate().map(
+            |(i, (skin, preview))| {
+                widget::container(
+                    widget::column![
+                        view_preview(preview.as_ref(), 3.0),
+                        widget::text(&skin.name).size(12),
+                        widget::row![
+                            widget::button(widget::text("Use").size(12)).on_press_maybe(
+                                (!self.is_loading)
+                                    .then_some(Message::Skin(SkinMessage::UploadLibrary(i)))
+                            ),
+                            widget::button(widget::text("Delete").size(12)).on_press(
+                                Message::Skin(SkinMessage::LibraryRemove(skin.name.clone()))
+                            ),
+                        ]
+                        .spacing(5),
+                    ]
+                    .align_x(Alignment::Center)
+                    .spacing(5),
+                )
+                .padding(5)
+                .style(|n: &LauncherTheme| n.style_container_sharp_box(0.0, Color::ExtraDark))
+                .into()
+            },
+        ))
+        .spacing(5)
+        .wrap();
+
+        widget::column![
+            widget::text("Saved skins").size(16),
+            widget::text("Skins saved on this computer, to switch between them")
+                .size(12)
+                .style(tsubtitle),
+            widget::row![
+                widget::text_input("Name...", &self.library_name)
+                    .size(14)
+                    .width(200)
+                    .on_input(|n| Message::Skin(SkinMessage::LibraryNameInput(n))),
+                widget::button(widget::text("Save current skin").size(14)).on_press_maybe(
+                    has_skin.then_some(Message::Skin(SkinMessage::LibrarySaveCurrent))
+                ),
+                widget::button(widget::text("Add PNG...").size(14))
+                    .on_press(Message::Skin(SkinMessage::LibraryAddFile)),
+            ]
+            .spacing(5)
+            .align_y(Alignment::Center),
+            skins,
+        ]
+        .spacing(5)
+        .into()
+    }
+}
+
+impl MenuOfflineProfile {
+    pub fn view(&'_ self) -> Element<'_> {
+        let name = self.name.trim();
+        let error = if name.is_empty() {
+            Some("Enter a username")
+        } else if name.contains(' ') {
+            Some("Username can't have spaces")
+        } else if offline::parse_uuid(&self.uuid).is_none() {
+            Some("Invalid UUID")
+        } else {
+            None
+        };
+
+        widget::scrollable(
+            widget::column![
+                back_button().on_press(back_to_launch_screen(None, None)),
+                widget::text(if self.original_name.is_some() {
+                    "Edit offline profile"
+                } else {
+                    "New offline profile"
+                })
+                .size(20),
+                widget::row![
+                    widget::container(view_preview(self.preview.as_ref(), PREVIEW_SCALE))
+                        .padding(10)
+                        .style(
+                            |n: &LauncherTheme| n.style_container_sharp_box(0.0, Color::ExtraDark)
+                        ),
+                    self.view_fields(),
+                ]
+                .spacing(10),
+                widget::button("Save").on_press_maybe(
+                    error
+                        .is_none()
+                        .then_some(Message::OfflineProfile(OfflineProfileMessage::Save))
+                ),
+            ]
+            .push_maybe(error.map(|n| widget::text(n).size(12).style(tsubtitle)))
+            .padding(10)
+            .spacing(10),
+        )
+        .style(LauncherTheme::style_scrollable_flat_extra_dark)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn view_fields(&'_ self) -> Element<'_> {
+        widget::column![
+            widget::text("Username").size(16),
+            widget::text_input("Enter username...", &self.name)
+                .size(14)
+                .width(300)
+                .on_input(|n| Message::OfflineProfile(OfflineProfileMessage::NameInput(n))),
+            widget::Space::with_height(5),
+            widget::text("UUID").size(16),
+            widget::text("Same as vanilla offline mode by default, so your identity\nstays the same in LAN worlds on other computers")
+                .size(12)
+                .style(tsubtitle),
+            widget::row![
+                widget::text_input("UUID...", &self.uuid)
+                    .size(14)
+                    .width(300)
+                    .on_input(|n| Message::OfflineProfile(OfflineProfileMessage::UuidInput(n))),
+                widget::button(widget::text("Reset").size(14))
+                    .on_press(Message::OfflineProfile(OfflineProfileMessage::UuidReset)),
+            ]
+            .spacing(5),
+            widget::Space::with_height(5),
+            widget::text("Skin").size(16),
+            widget::pick_list(SkinVariant::ALL, Some(self.variant), |n| {
+                Message::OfflineProfile(OfflineProfileMessage::VariantPicked(n))
+            })
+            .text_size(14),
+            widget::row![
+                widget::button(widget::text("Choose PNG...").size(14))
+                    .on_press(Message::OfflineProfile(OfflineProfileMessage::SkinPick)),
+                widget::button(widget::text("Remove").size(14)).on_press_maybe(
+                    self.skin
+                        .is_some()
+                        .then_some(Message::OfflineProfile(OfflineProfileMessage::SkinClear))
+                ),
+            ]
+            .spacing(5),
+        ]
+        .push_maybe(self.skin.as_ref().map(|path| {
+            widget::text(path.to_string_lossy())
+                .size(12)
+                .style(tsubtitle)
+        }))
+        .spacing(5)
+        .into()
+    }
+}
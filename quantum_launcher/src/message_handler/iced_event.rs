@@ -1,10 +1,10 @@
 use crate::message_update::MSG_RESIZE;
 use crate::state::{
-    AutoSaveKind, CreateInstanceMessage, LaunchTab, Launcher, LauncherSettingsMessage,
-    LauncherSettingsTab, MenuCreateInstance, MenuCreateInstanceChoosing, MenuEditMods,
-    MenuEditPresets, MenuExportInstance, MenuInstallFabric, MenuInstallOptifine, MenuInstallPaper,
-    MenuLauncherSettings, MenuLauncherUpdate, MenuLoginAlternate, MenuLoginMS, MenuRecommendedMods,
-    Message, State,
+    AutoSaveKind, CreateInstanceMessage, CredentialsState, JavaInstallsState, LaunchTab, Launcher,
+    LauncherSettingsMessage, LauncherSettingsTab, MenuAccountSkins, MenuCreateInstance,
+    MenuCreateInstanceChoosing, MenuEditMods, MenuEditPresets, MenuExportInstance,
+    MenuInstallFabric, MenuInstallOptifine, MenuInstallPaper, MenuLauncherSettings,
+    MenuLauncherUpdate, MenuLoginAlternate, MenuLoginMS, MenuRecommendedMods, Message, State,
 };
 use iced::{
     keyboard::{self, key::Named, Key},
@@ -305,7 +305,9 @@ impl Launcher {
                 mod_update_progress: None,
                 ..
             })
-            | State::Create(MenuCreateInstance::Choosing { .. })
+            | State::Create(
+                MenuCreateInstance::Choosing { .. } | MenuCreateInstance::Migrating { .. },
+            )
             | State::Error { .. }
             | State::UpdateFound(MenuLauncherUpdate { progress: None, .. })
             | State::LauncherSettings(_)
@@ -315,6 +317,10 @@ impl Launcher {
             | State::LoginAlternate(MenuLoginAlternate {
                 is_loading: false, ..
             })
+            | State::AccountSkins(MenuAccountSkins {
+                is_loading: false, ..
+            })
+            | State::OfflineProfile(_)
             | State::Welcome(_) => {
                 ret_to_main_screen = true;
             }
@@ -326,6 +332,8 @@ impl Launcher {
                             temp_scale: self.config.ui_scale.unwrap_or(1.0),
                             selected_tab: LauncherSettingsTab::About,
                             arg_split_by_space: true,
+                            java: JavaInstallsState::default(),
+                            credentials: CredentialsState::default(),
                         });
                     }
                 }
@@ -341,6 +349,7 @@ impl Launcher {
             | State::InstallFabric(MenuInstallFabric::Loaded { progress: None, .. })
             | State::EditJarMods(_)
             | State::ExportMods(_)
+            | State::ModpackOptions(_)
             | State::ManagePresets(MenuEditPresets {
                 is_building: false,
                 progress: None,
@@ -378,6 +387,7 @@ impl Launcher {
             | State::ImportModpack(_)
             | State::CurseforgeManualDownload(_)
             | State::LoginAlternate(_)
+            | State::AccountSkins(_)
             | State::LogUploadResult { .. }
             | State::RecommendedMods(MenuRecommendedMods::Loading { .. })
             | State::Launch(_) => {}
@@ -22,7 +22,7 @@ use ql_core::{
     err, json::instance_config::InstanceConfigJson, GenericProgress, InstanceSelection,
     IntoIoError, IntoJsonError, IntoStringError, JsonFileError,
 };
-use ql_core::{info, pt, LaunchedProcess};
+use ql_core::{info, pt, LaunchedProcess, ModId};
 use ql_instances::auth::AccountData;
 use ql_mod_manager::{loaders, store::ModIndex};
 use std::{
@@ -81,9 +81,17 @@ impl Launcher {
     }
 
     pub fn launch_game(&mut self, account_data: Option<AccountData>) -> Task<Message> {
+        let offline_profile = self
+            .get_launch_account()
+            .and_then(|n| self.config.get_offline_profile(n))
+            .filter(|_| account_data.is_none())
+            .cloned();
+
         let username = if let Some(account_data) = &account_data {
             // Logged in account
             account_data.nice_username.clone()
+        } else if let Some(profile) = &offline_profile {
+            profile.name.clone()
         } else {
             // Offline username
             self.config.username.clone()
@@ -93,7 +101,10 @@ impl Launcher {
         self.java_recv = Some(ProgressBar::with_recv(receiver));
 
         let global_settings = self.config.global_settings.clone();
-        let extra_java_args = self.config.extra_java_args.clone().unwrap_or_default();
+        let overrides = ql_instances::LaunchOverrides {
+            java_args: self.config.extra_java_args.clone().unwrap_or_default(),
+            ..Default::default()
+        };
 
         let instance_name = self.instance().get_name().to_owned();
         Task::perform(
@@ -104,7 +115,8 @@ impl Launcher {
                     Some(sender),
                     account_data,
                     global_settings,
-                    extra_java_args,
+                    overrides,
+                    offline_profile,
                 )
                 .await
                 .strerr()
@@ -258,6 +270,17 @@ impl Launcher {
                 (a, Some(b.abort_on_drop()))
             };
 
+            let modpack_update_cmd = if check_updates && config_json.modpack.is_some() {
+                Task::perform(
+                    ql_mod_manager::store::check_for_modpack_update(instance.clone()),
+                    |n| {
+                        Message::ManageMods(ManageModsMessage::ModpackUpdateCheckResult(n.strerr()))
+                    },
+                )
+            } else {
+                Task::none()
+            };
+
             let available_updates = if let Some(updates) = this.mod_updates_checked.get(instance) {
                 updates.clone()
             } else {
@@ -272,6 +295,7 @@ impl Launcher {
                 sorted_mods_list,
                 selected_state: SelectedState::None,
                 available_updates,
+                modpack_update: None,
                 mod_update_progress: None,
                 locally_installed_mods,
                 drag_and_drop_hovered: false,
@@ -284,7 +308,11 @@ impl Launcher {
                 list_scroll: AbsoluteOffset::default(),
             });
 
-            Ok(Task::batch([update_local_mods_task, update_cmd]))
+            Ok(Task::batch([
+                update_local_mods_task,
+                update_cmd,
+                modpack_update_cmd,
+            ]))
         }
         match block_on(inner(self, check_updates)) {
             Ok(n) => n,
@@ -356,6 +384,33 @@ impl Launcher {
         }
     }
 
+    pub fn update_modpack(&mut self) -> Task<Message> {
+        let State::EditMods(menu) = &self.state else {
+            return Task::none();
+        };
+        let Some(pack) = &menu.config.modpack else {
+            return Task::none();
+        };
+        let id = ModId::from_index_str(&pack.project_id);
+        let selected_instance = self.selected_instance.clone().unwrap();
+        self.state = State::GenericMessage("Downloading modpack...".to_owned());
+
+        // The user can change the optional files
+        // (picked last time) before it's installed
+        Task::perform(
+            async move {
+                let pack = ql_mod_manager::store::download_modpack(&id, &selected_instance)
+                    .await
+                    .strerr()?;
+                let chosen = ql_mod_manager::store::get_modpack_optional(&selected_instance)
+                    .await
+                    .strerr()?;
+                Ok((pack, chosen))
+            },
+            |n| Message::ManageMods(ManageModsMessage::ModpackUpdateDownloaded(n)),
+        )
+    }
+
     pub fn go_to_server_manage_menu(&mut self, message: Option<String>) -> Task<Message> {
         if let State::Launch(menu) = &mut self.state {
             menu.is_viewing_server = true;
@@ -468,18 +523,7 @@ impl Launcher {
     }
 
     fn load_modpack_from_path(&mut self, path: PathBuf) -> Task<Message> {
-        let (sender, receiver) = std::sync::mpsc::channel();
-
-        self.state = State::ImportModpack(ProgressBar::with_recv(receiver));
-
-        Task::perform(
-            ql_mod_manager::add_files(
-                self.selected_instance.clone().unwrap(),
-                vec![path],
-                Some(sender),
-            ),
-            |n| Message::ManageMods(ManageModsMessage::AddFileDone(n.strerr())),
-        )
+        self.start_adding_files(vec![path], false)
     }
 
     fn load_jar_from_path(&mut self, path: &Path, filename: &str) {
@@ -638,7 +682,7 @@ impl Launcher {
 
         match selected_instance {
             InstanceSelection::Instance(_) => {
-                if let Some(account) = &self.accounts_selected {
+                if let Some(account) = self.get_launch_account() {
                     if account == OFFLINE_ACCOUNT_NAME
                         && (self.config.username.is_empty() || self.config.username.contains(' '))
                     {
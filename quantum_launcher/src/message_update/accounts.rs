@@ -11,8 +11,8 @@ use ql_instances::auth::{self, AccountType};
 use crate::{
     config::ConfigAccount,
     state::{
-        AccountMessage, AutoSaveKind, Launcher, LittleSkinOauth, MenuLoginAlternate, MenuLoginMS,
-        Message, ProgressBar, State, NEW_ACCOUNT_NAME, OFFLINE_ACCOUNT_NAME,
+        AccountMessage, AutoSaveKind, Launcher, LittleSkinOauth, MenuLaunch, MenuLoginAlternate,
+        MenuLoginMS, Message, ProgressBar, State, NEW_ACCOUNT_NAME, OFFLINE_ACCOUNT_NAME,
     },
 };
 
@@ -50,9 +50,18 @@ impl Launcher {
             }
             AccountMessage::LogoutCheck => {
                 let username = self.accounts_selected.as_ref().unwrap();
+                let is_offline = self.config.get_offline_profile(username).is_some();
                 self.state = State::ConfirmAction {
-                    msg1: format!("log out of your account: {username}"),
-                    msg2: "You can always log in later".to_owned(),
+                    msg1: if is_offline {
+                        format!("delete the offline profile: {username}")
+                    } else {
+                        format!("log out of your account: {username}")
+                    },
+                    msg2: if is_offline {
+                        "Its skin file won't be deleted".to_owned()
+                    } else {
+                        "You can always log in later".to_owned()
+                    },
                     yes: Message::Account(AccountMessage::LogoutConfirm),
                     no: Message::MScreenOpen {
                         message: None,
@@ -105,13 +114,22 @@ impl Launcher {
             AccountMessage::LogoutConfirm => {
                 self.autosave.remove(&AutoSaveKind::LauncherConfig);
                 let username = self.accounts_selected.clone().unwrap();
-                let account_type = self
-                    .accounts
-                    .get(&username)
-                    .map_or(AccountType::Microsoft, |n| n.account_type);
+                if let Some(profile) = self.config.get_offline_profile(&username) {
+                    let name = profile.name.clone();
+                    if let Some(profiles) = &mut self.config.offline_profiles {
+                        profiles.retain(|n| n.name != name);
+                    }
+                } else {
+                    let account_type = self
+                        .accounts
+                        .get(&username)
+                        .map_or(AccountType::Microsoft, |n| n.account_type.clone());
 
-                if let Err(err) = auth::logout(account_type.strip_name(&username), account_type) {
-                    self.set_error(err);
+                    if let Err(err) =
+                        auth::logout(account_type.strip_name(&username), &account_type)
+                    {
+                        self.set_error(err);
+                    }
                 }
                 if let Some(accounts) = &mut self.config.accounts {
                     accounts.remove(&username);
@@ -135,7 +153,7 @@ impl Launcher {
                 return self.go_to_launch_screen(Option::<String>::None);
             }
             AccountMessage::RefreshComplete(Ok(data)) => {
-                self.accounts.insert(data.get_username_modified(), data);
+                self.insert_refreshed_account(data.get_username_modified(), data);
 
                 let account_data = self.get_selected_account_data();
 
@@ -158,7 +176,7 @@ impl Launcher {
                         })
                     });
                 }
-                AccountType::ElyBy | AccountType::LittleSkin | AccountType::Custom => {
+                AccountType::ElyBy | AccountType::LittleSkin | AccountType::Custom(_) => {
                     self.state = State::LoginAlternate(MenuLoginAlternate {
                         username: String::new(),
                         password: String::new(),
@@ -170,7 +188,13 @@ impl Launcher {
 
                         is_littleskin: matches!(kind, AccountType::LittleSkin),
                         is_elyby: matches!(kind, AccountType::ElyBy),
-                        is_custom: matches!(kind, AccountType::Custom),
+                        is_custom: matches!(kind, AccountType::Custom(_)),
+                        custom_auth_servers: self.config.get_custom_auth_servers(),
+                        custom_auth_url: if let AccountType::Custom(server) = kind {
+                            server
+                        } else {
+                            String::new()
+                        },
                         device_code_error: None,
                         oauth: None,
                     });
@@ -197,6 +221,11 @@ impl Launcher {
                     menu.show_password = t;
                 }
             }
+            AccountMessage::AltServerInput(url) => {
+                if let State::LoginAlternate(menu) = &mut self.state {
+                    menu.custom_auth_url = url;
+                }
+            }
 
             AccountMessage::AltLogin => {
                 if let State::LoginAlternate(menu) = &mut self.state {
@@ -208,18 +237,26 @@ impl Launcher {
                     }
                     menu.is_loading = true;
 
+                    let username = menu.username.clone();
+                    let server_url = menu.custom_auth_url.clone();
+                    let kind = if menu.is_littleskin {
+                        Some(AccountType::LittleSkin)
+                    } else if menu.is_elyby {
+                        Some(AccountType::ElyBy)
+                    } else {
+                        None
+                    };
+
                     return Task::perform(
-                        auth::yggdrasil::login_new(
-                            menu.username.clone(),
-                            password,
-                            if menu.is_littleskin {
-                                AccountType::LittleSkin
-                            } else if menu.is_elyby {
-                                AccountType::ElyBy
-                            } else {
-                                AccountType::Custom
-                            },
-                        ),
+                        async move {
+                            let kind = match kind {
+                                Some(kind) => kind,
+                                None => AccountType::Custom(
+                                    auth::AuthServer::discover(&server_url).await?.api_root,
+                                ),
+                            };
+                            auth::yggdrasil::login_new(username, password, kind).await
+                        },
                         |n| Message::Account(AccountMessage::AltLoginResponse(n.strerr())),
                     );
                 }
@@ -288,11 +325,11 @@ impl Launcher {
                     |n| Message::Account(AccountMessage::RefreshComplete(n.strerr())),
                 )
             }
-            AccountType::ElyBy | AccountType::LittleSkin | AccountType::Custom => Task::perform(
+            AccountType::ElyBy | AccountType::LittleSkin | AccountType::Custom(_) => Task::perform(
                 auth::yggdrasil::login_refresh(
                     account.username.clone(),
                     account.refresh_token.clone(),
-                    account.account_type,
+                    account.account_type.clone(),
                 ),
                 |n| Message::Account(AccountMessage::RefreshComplete(n.strerr())),
             ),
@@ -319,8 +356,14 @@ impl Launcher {
                 uuid: data.uuid.clone(),
                 skin: None,
                 account_type: Some(data.account_type.to_string()),
+                auth_server: if let AccountType::Custom(server) = &data.account_type {
+                    Some(server.clone())
+                } else {
+                    None
+                },
                 keyring_identifier: Some(data.username.clone()),
                 username_nice: Some(data.nice_username.clone()),
+                authlib_metadata: data.authlib_metadata.clone(),
             },
         );
 
@@ -358,8 +401,36 @@ impl Launcher {
         task
     }
 
+    /// The account to launch the selected instance with:
+    /// its default account if it has one, otherwise the
+    /// one selected in the launcher (which is left as is)
+    pub fn get_launch_account(&self) -> Option<&str> {
+        let pinned = match &self.state {
+            State::Launch(MenuLaunch {
+                edit_instance: Some(menu),
+                ..
+            }) => menu.config.account.as_deref(),
+            _ => None,
+        };
+        pinned
+            .filter(|n| self.accounts_dropdown.iter().any(|a| a == n))
+            .or(self.accounts_selected.as_deref())
+    }
+
+    /// Stores a refreshed account, keeping the authlib-injector
+    /// metadata that was cached at login.
+    pub fn insert_refreshed_account(&mut self, username: String, mut data: AccountData) {
+        if data.authlib_metadata.is_none() {
+            if let Some(old) = self.accounts.get(&username) {
+                data.authlib_metadata.clone_from(&old.authlib_metadata);
+            }
+        }
+        self.accounts.insert(username, data);
+    }
+
+    /// Data of the account to launch with, see [`Self::get_launch_account`]
     pub fn get_selected_account_data(&self) -> Option<AccountData> {
-        if let Some(account) = &self.accounts_selected {
+        if let Some(account) = self.get_launch_account() {
             if account == NEW_ACCOUNT_NAME || account == OFFLINE_ACCOUNT_NAME {
                 None
             } else {
@@ -24,7 +24,8 @@ impl Launcher {
         match message {
             CreateInstanceMessage::End(Err(err))
             | CreateInstanceMessage::VersionsLoaded(Err(err))
-            | CreateInstanceMessage::ImportResult(Err(err)) => {
+            | CreateInstanceMessage::ImportResult(Err(err))
+            | CreateInstanceMessage::MigrateResult(Err(err)) => {
                 self.set_error(err);
             }
             CreateInstanceMessage::ScreenOpen { is_server } => {
@@ -133,13 +134,80 @@ impl Launcher {
                     return self.go_to_main_menu_with_message(None::<String>);
                 }
                 self.set_error(
-                    r#"the file you imported isn't a valid QuantumLauncher/MultiMC instance.
+                    r#"the file you imported isn't a valid QuantumLauncher/MultiMC/PrismLauncher instance.
 
 If you meant to import a Modrinth/Curseforge/Preset pack,
 create a instance with the matching version,
 then go to "Mods->Add File""#,
                 );
             }
+
+            CreateInstanceMessage::MigrateOpen => {
+                self.state = State::Create(MenuCreateInstance::Migrating { entries: None });
+                return Task::perform(ql_packager::migrate::find_other_instances(), |n| {
+                    Message::CreateInstance(CreateInstanceMessage::MigrateLoaded(n))
+                });
+            }
+            CreateInstanceMessage::MigrateLoaded(list) => {
+                if let State::Create(MenuCreateInstance::Migrating { entries }) = &mut self.state {
+                    *entries = Some(list.into_iter().map(|n| (n, false)).collect());
+                }
+            }
+            CreateInstanceMessage::MigrateToggle(idx, t) => {
+                if let State::Create(MenuCreateInstance::Migrating {
+                    entries: Some(entries),
+                }) = &mut self.state
+                {
+                    if let Some((_, b)) = entries.get_mut(idx) {
+                        *b = t;
+                    }
+                }
+            }
+            CreateInstanceMessage::MigrateStart => {
+                if let State::Create(MenuCreateInstance::Migrating {
+                    entries: Some(entries),
+                }) = &self.state
+                {
+                    let selected: Vec<_> = entries
+                        .iter()
+                        .filter(|(_, b)| *b)
+                        .map(|(n, _)| n.clone())
+                        .collect();
+
+                    let (send, recv) = std::sync::mpsc::channel();
+                    let progress = ProgressBar::with_recv(recv);
+                    self.state = State::Create(MenuCreateInstance::ImportingInstance(progress));
+
+                    return Task::perform(
+                        async move {
+                            let mut instances = Vec::new();
+                            for entry in selected {
+                                instances.push(
+                                    ql_packager::migrate::migrate_instance(
+                                        entry,
+                                        true,
+                                        Some(send.clone()),
+                                    )
+                                    .await?,
+                                );
+                            }
+                            Ok::<_, ql_packager::InstancePackageError>(instances)
+                        },
+                        |n| {
+                            Message::CreateInstance(CreateInstanceMessage::MigrateResult(
+                                n.strerr(),
+                            ))
+                        },
+                    );
+                }
+            }
+            CreateInstanceMessage::MigrateResult(Ok(instances)) => {
+                self.selected_instance = instances.last().cloned();
+                return self.go_to_main_menu_with_message(Some(format!(
+                    "Migrated {} instance(s)",
+                    instances.len()
+                )));
+            }
         }
         Task::none()
     }
@@ -10,9 +10,10 @@ use ql_core::{
 
 use crate::{
     message_handler::format_memory,
+    shortcut,
     state::{
-        dir_watch, get_entries, CustomJarState, EditInstanceMessage, LaunchTab, Launcher,
-        MenuCreateInstance, MenuEditInstance, MenuLaunch, Message, ProgressBar, State,
+        dir_watch, get_entries, AutoSaveKind, CustomJarState, EditInstanceMessage, LaunchTab,
+        Launcher, MenuCreateInstance, MenuEditInstance, MenuLaunch, Message, ProgressBar, State,
         ADD_JAR_NAME, NONE_JAR_NAME, OPEN_FOLDER_JAR_NAME, REMOVE_JAR_NAME,
     },
 };
@@ -85,6 +86,15 @@ impl Launcher {
                     config.java_override_version = Some(n);
                 })
             }
+            EditInstanceMessage::JavaDetect => {
+                return Ok(Task::perform(ql_instances::find_system_java(), |n| {
+                    Message::EditInstance(EditInstanceMessage::JavaDetected(n))
+                }));
+            }
+            EditInstanceMessage::JavaDetected(list) => {
+                self.config.java_installs = Some(list);
+                self.autosave.remove(&AutoSaveKind::LauncherConfig);
+            }
             EditInstanceMessage::BrowseJavaOverride => {
                 if let Some(file) = rfd::FileDialog::new()
                     .set_title("Select Java Executable (./bin/java)")
@@ -126,6 +136,45 @@ impl Launcher {
             EditInstanceMessage::LoggingToggle(t) => iflet_config!(&mut self.state, config <- {
                 config.enable_logger = Some(t);
             }),
+            EditInstanceMessage::AutoRamToggle(t) => {
+                iflet_config!(&mut self.state, auto_ram, {
+                    *auto_ram = Some(t);
+                });
+            }
+            EditInstanceMessage::AccountChanged(account) => {
+                iflet_config!(&mut self.state, config <- {
+                    config.account = account;
+                });
+            }
+            EditInstanceMessage::AccountPin(pin) => {
+                let account = self.accounts_selected.clone().filter(|_| pin);
+                let (
+                    Some(instance),
+                    State::Launch(MenuLaunch {
+                        edit_instance: Some(menu),
+                        ..
+                    }),
+                ) = (self.selected_instance.clone(), &mut self.state)
+                else {
+                    return Ok(Task::none());
+                };
+                menu.config.account = account;
+                let config = menu.config.clone();
+                return Ok(Task::perform(
+                    async move { config.save(&instance).await.strerr() },
+                    |n| Message::EditInstance(EditInstanceMessage::ConfigSaved(n)),
+                ));
+            }
+            EditInstanceMessage::QuickPlayChanged(n) => {
+                iflet_config!(&mut self.state, quick_play, {
+                    *quick_play = n;
+                });
+            }
+            EditInstanceMessage::JvmPresetChanged(preset) => {
+                iflet_config!(&mut self.state, jvm_preset, {
+                    *jvm_preset = Some(preset);
+                });
+            }
             EditInstanceMessage::CloseLauncherToggle(t) => {
                 if let State::Launch(MenuLaunch {
                     edit_instance: Some(menu),
@@ -278,10 +327,46 @@ impl Launcher {
                     },
                 ));
             }
+            EditInstanceMessage::CreateShortcut => return Ok(self.create_shortcut()),
         }
         Ok(Task::none())
     }
 
+    fn create_shortcut(&self) -> Task<Message> {
+        let instance = self.instance().get_name().to_owned();
+        let has_default_account = matches!(
+            &self.state,
+            State::Launch(MenuLaunch {
+                edit_instance: Some(MenuEditInstance {
+                    config: InstanceConfigJson {
+                        account: Some(_),
+                        ..
+                    },
+                    ..
+                }),
+                ..
+            })
+        );
+        // Without a default account, play as the selected one
+        let account = match (&self.accounts_selected, has_default_account) {
+            (Some(account), false) => Some((&self.config, account.as_str())),
+            _ => None,
+        };
+        let args = shortcut::launch_args(&instance, account);
+
+        Task::perform(
+            async move { shortcut::create(&instance, &args).await },
+            |n| match n {
+                // Show where it was saved
+                Ok(paths) => paths
+                    .last()
+                    .and_then(|n| n.parent())
+                    .map_or(Message::Nothing, |n| Message::CoreOpenPath(n.to_owned())),
+                Err(err) => Message::Error(err),
+            },
+        )
+    }
+
     fn instance_redownload_stage(&mut self, stage: ql_core::DownloadProgress) -> Task<Message> {
         let (sender, receiver) = std::sync::mpsc::channel();
         let bar = ProgressBar::with_recv(receiver);
@@ -3,13 +3,17 @@ use iced::{widget, Task};
 use ql_core::{
     err, jarmod::JarMods, InstanceSelection, IntoIoError, IntoStringError, ModId, SelectedMod,
 };
-use ql_mod_manager::store::ModIndex;
-use std::{collections::HashSet, path::PathBuf};
+use ql_mod_manager::store::{ModIndex, PackPlan};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
 
 use crate::state::{
-    AutoSaveKind, ExportModsMessage, Launcher, ManageJarModsMessage, ManageModsMessage,
-    MenuCurseforgeManualDownload, MenuEditJarMods, MenuEditMods, MenuEditModsModal, Message,
-    ProgressBar, SelectedState, State,
+    AutoSaveKind, ExportModsMessage, InstallModsMessage, Launcher, ManageJarModsMessage,
+    ManageModsMessage, MenuCurseforgeManualDownload, MenuEditJarMods, MenuEditMods,
+    MenuEditModsModal, MenuModpackOptions, Message, ModpackSource, ProgressBar, SelectedState,
+    State,
 };
 
 impl Launcher {
@@ -24,7 +28,10 @@ impl Launcher {
             | ManageModsMessage::DeleteFinished(Err(err))
             | ManageModsMessage::LocalDeleteFinished(Err(err))
             | ManageModsMessage::ToggleFinished(Err(err))
-            | ManageModsMessage::UpdateModsFinished(Err(err)) => self.set_error(err),
+            | ManageModsMessage::UpdateModsFinished(Err(err))
+            | ManageModsMessage::ModpackDownloaded(Err(err))
+            | ManageModsMessage::ModpackUpdateDownloaded(Err(err))
+            | ManageModsMessage::ModpackUpdateFinished(Err(err)) => self.set_error(err),
 
             ManageModsMessage::ListScrolled(offset) => {
                 if let State::EditMods(menu) = &mut self.state {
@@ -167,6 +174,90 @@ impl Launcher {
                     }
                 }
             }
+            ManageModsMessage::ModpackOptionsToggle(idx, t) => {
+                if let State::ModpackOptions(menu) = &mut self.state {
+                    if let Some((_, b)) = menu.optional.get_mut(idx) {
+                        *b = t;
+                    }
+                }
+            }
+            ManageModsMessage::ModpackOptionsInstall => {
+                let state =
+                    std::mem::replace(&mut self.state, State::GenericMessage(String::new()));
+                if let State::ModpackOptions(menu) = state {
+                    let optional = menu
+                        .optional
+                        .into_iter()
+                        .filter_map(|(n, b)| b.then_some(n))
+                        .collect();
+                    return self.install_modpack_from(menu.source, optional);
+                }
+                self.state = state;
+            }
+            ManageModsMessage::ModpackFileRead {
+                path,
+                plan,
+                delete_file,
+            } => {
+                return match plan {
+                    Some(plan) => self.open_modpack_options(
+                        plan,
+                        HashSet::new(),
+                        ModpackSource::File { path, delete_file },
+                    ),
+                    None => self.add_files(vec![path], HashSet::new(), delete_file),
+                };
+            }
+            ManageModsMessage::ModpackDownloaded(Ok((id, pack))) => {
+                return self.open_modpack_options(
+                    pack.plan.clone(),
+                    HashSet::new(),
+                    ModpackSource::Store(id, pack),
+                );
+            }
+            ManageModsMessage::ModpackUpdateDownloaded(Ok((pack, chosen))) => {
+                return self.open_modpack_options(
+                    pack.plan.clone(),
+                    chosen,
+                    ModpackSource::Update(pack),
+                );
+            }
+            ManageModsMessage::ModpackUpdateCheckResult(update) => match update {
+                Ok(update) => {
+                    if let State::EditMods(menu) = &mut self.state {
+                        menu.modpack_update = update;
+                    }
+                }
+                Err(err) => err!(no_log, "Could not check for modpack updates: {err}"),
+            },
+            ManageModsMessage::ModpackUpdate => return self.update_modpack(),
+            ManageModsMessage::ModpackUpdateFinished(Ok(report)) => {
+                if !report.not_allowed.is_empty() {
+                    self.state = State::CurseforgeManualDownload(MenuCurseforgeManualDownload {
+                        unsupported: report.not_allowed,
+                        is_store: true,
+                        delete_mods: true,
+                    });
+                    return Task::none();
+                }
+
+                let mut message = format!(
+                    "Updated modpack!\n{} files added, {} removed, {} updated",
+                    report.added.len(),
+                    report.removed.len(),
+                    report.updated.len()
+                );
+                if !report.conflicts.is_empty() {
+                    message.push_str(
+                        "\n\nKept your changes to these files\n(the modpack's version is saved as <file>.new):",
+                    );
+                    for file in &report.conflicts {
+                        message.push_str("\n- ");
+                        message.push_str(file);
+                    }
+                }
+                return self.go_to_launch_screen(Some(message));
+            }
             ManageModsMessage::UpdateCheckToggle(idx, t) => {
                 if let State::EditMods(MenuEditMods {
                     available_updates, ..
@@ -324,25 +415,119 @@ impl Launcher {
             return Task::none();
         };
 
+        self.start_adding_files(paths, delete_file)
+    }
+
+    /// Adds mod/modpack/preset files to the selected instance.
+    ///
+    /// If a single modpack with optional files is being added,
+    /// the user gets to pick those first.
+    pub fn start_adding_files(&mut self, paths: Vec<PathBuf>, delete_file: bool) -> Task<Message> {
+        if let [path] = paths.as_slice() {
+            let is_modpack = path
+                .extension()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.eq_ignore_ascii_case("zip") || n.eq_ignore_ascii_case("mrpack"));
+            if is_modpack {
+                let path = path.clone();
+                let instance = self.instance().clone();
+                return Task::perform(
+                    async move {
+                        let plan = read_modpack_plan(&path, &instance).await;
+                        (path, plan)
+                    },
+                    move |(path, plan)| {
+                        Message::ManageMods(ManageModsMessage::ModpackFileRead {
+                            path,
+                            plan,
+                            delete_file,
+                        })
+                    },
+                );
+            }
+        }
+        self.add_files(paths, HashSet::new(), delete_file)
+    }
+
+    /// Lets the user pick the optional files of a modpack
+    /// (if it has any), with `chosen` ones ticked, before
+    /// installing it.
+    pub fn open_modpack_options(
+        &mut self,
+        plan: PackPlan,
+        chosen: HashSet<String>,
+        source: ModpackSource,
+    ) -> Task<Message> {
+        if plan.optional.is_empty() {
+            return self.install_modpack_from(source, chosen);
+        }
+        self.state = State::ModpackOptions(MenuModpackOptions {
+            name: plan.name,
+            optional: plan
+                .optional
+                .into_iter()
+                .map(|n| {
+                    let enabled = chosen.contains(&n);
+                    (n, enabled)
+                })
+                .collect(),
+            source,
+        });
+        Task::none()
+    }
+
+    fn install_modpack_from(
+        &mut self,
+        source: ModpackSource,
+        optional: HashSet<String>,
+    ) -> Task<Message> {
+        let instance = self.instance().clone();
+        match source {
+            ModpackSource::File { path, delete_file } => {
+                self.add_files(vec![path], optional, delete_file)
+            }
+            ModpackSource::Store(id, pack) => {
+                let (sender, receiver) = std::sync::mpsc::channel();
+                self.state = State::ImportModpack(ProgressBar::with_recv(receiver));
+                self.mod_updates_checked.remove(&instance);
+                Task::perform(
+                    async move {
+                        pack.install(&instance, &optional, Some(&sender))
+                            .await
+                            .map(|not_allowed| (id, not_allowed))
+                    },
+                    |n| Message::InstallMods(InstallModsMessage::DownloadComplete(n.strerr())),
+                )
+            }
+            ModpackSource::Update(pack) => {
+                let (sender, receiver) = std::sync::mpsc::channel();
+                self.state = State::ImportModpack(ProgressBar::with_recv(receiver));
+                self.mod_updates_checked.remove(&instance);
+                Task::perform(
+                    ql_mod_manager::store::update_modpack(instance, pack, optional, Some(sender)),
+                    |n| Message::ManageMods(ManageModsMessage::ModpackUpdateFinished(n.strerr())),
+                )
+            }
+        }
+    }
+
+    fn add_files(
+        &mut self,
+        paths: Vec<PathBuf>,
+        optional: HashSet<String>,
+        delete_file: bool,
+    ) -> Task<Message> {
         let (sender, receiver) = std::sync::mpsc::channel();
         let selected_instance = self.selected_instance.as_ref().unwrap();
 
         self.state = State::ImportModpack(ProgressBar::with_recv(receiver));
         self.mod_updates_checked.remove(selected_instance);
 
-        // Modpacks being imported
-        if paths
-            .iter()
-            .filter_map(|n| n.extension())
-            .any(|n| !n.eq_ignore_ascii_case("jar"))
-        {
-            self.mod_updates_checked.remove(selected_instance);
-        }
-
         let files_task = Task::perform(
             ql_mod_manager::add_files(
                 self.selected_instance.clone().unwrap(),
                 paths.clone(),
+                optional,
                 Some(sender),
             ),
             move |n| Message::ManageMods(ManageModsMessage::AddFileDone(n.strerr())),
@@ -765,3 +950,22 @@ impl MenuEditMods {
         widget::scrollable::scroll_to(id, self.list_scroll)
     }
 }
+
+/// Reads the optional files of a modpack file.
+/// Returns `None` if it isn't a modpack.
+async fn read_modpack_plan(path: &Path, instance: &InstanceSelection) -> Option<PackPlan> {
+    let file = match tokio::fs::read(path).await.path(path) {
+        Ok(n) => n,
+        Err(err) => {
+            err!("Couldn't read modpack: {err}");
+            return None;
+        }
+    };
+    match ql_mod_manager::store::get_modpack_plan(&file, instance) {
+        Ok(plan) => plan,
+        Err(err) => {
+            err!("Couldn't read modpack {path:?}: {err}");
+            None
+        }
+    }
+}
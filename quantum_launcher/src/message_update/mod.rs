@@ -4,7 +4,10 @@ use frostmark::MarkState;
 use iced::futures::executor::block_on;
 use iced::widget::text_editor;
 use iced::{widget::scrollable::AbsoluteOffset, Task};
-use ql_core::{err, InstanceSelection, IntoStringError, Loader, ModId, OptifineUniqueVersion};
+use ql_core::{
+    err, InstanceSelection, IntoStringError, JavaVersion, Loader, ModId, OptifineUniqueVersion,
+};
+use ql_instances::auth::{credentials, AccountType};
 use ql_mod_manager::{
     loaders,
     store::{get_description, QueryType},
@@ -16,9 +19,12 @@ mod edit_instance;
 mod manage_mods;
 mod presets;
 mod recommended;
+mod skins;
 
 use crate::config::UiWindowDecorations;
-use crate::state::{GameLogMessage, InstanceNotes, MenuLaunch, ModOperation, NotesMessage};
+use crate::state::{
+    GameLogMessage, InstanceNotes, ManageModsMessage, MenuLaunch, ModOperation, NotesMessage,
+};
 use crate::{
     config::UiSettings,
     state::{
@@ -31,6 +37,19 @@ use crate::{
 
 pub const MSG_RESIZE: &str = "Resize your window to apply the changes.";
 
+fn load_java_installs() -> Task<Message> {
+    Task::perform(
+        async move { ql_instances::list_java_installs().await.strerr() },
+        |n| Message::LauncherSettings(LauncherSettingsMessage::JavaLoaded(n)),
+    )
+}
+
+fn load_credential_status() -> Task<Message> {
+    Task::perform(async move { credentials::file::get_status() }, |n| {
+        Message::LauncherSettings(LauncherSettingsMessage::CredentialsLoaded(n))
+    })
+}
+
 impl Launcher {
     pub fn update_install_fabric(&mut self, message: InstallFabricMessage) -> Task<Message> {
         match message {
@@ -289,19 +308,17 @@ impl Launcher {
                 }
             }
             InstallModsMessage::InstallModpack(id) => {
-                let (sender, receiver) = std::sync::mpsc::channel();
-                self.state = State::ImportModpack(ProgressBar::with_recv(receiver));
-
+                self.state = State::GenericMessage("Downloading modpack...".to_owned());
                 let selected_instance = self.selected_instance.clone().unwrap();
-                self.mod_updates_checked.remove(&selected_instance);
 
+                // The user picks its optional files before it's installed
                 return Task::perform(
                     async move {
-                        ql_mod_manager::store::download_mod(&id, &selected_instance, Some(sender))
+                        ql_mod_manager::store::download_modpack(&id, &selected_instance)
                             .await
-                            .map(|not_allowed| (id, not_allowed))
+                            .map(|pack| (id, pack))
                     },
-                    |n| Message::InstallMods(InstallModsMessage::DownloadComplete(n.strerr())),
+                    |n| Message::ManageMods(ManageModsMessage::ModpackDownloaded(n.strerr())),
                 );
             }
             InstallModsMessage::Uninstall(index) => {
@@ -550,14 +567,198 @@ impl Launcher {
                 self.confirm_clear_java_installs();
             }
             LauncherSettingsMessage::ClearJavaInstallsConfirm => {
-                return Task::perform(ql_instances::delete_java_installs(), |()| Message::Nothing);
+                return Task::perform(ql_instances::delete_java_installs(), |()| {
+                    Message::LauncherSettings(LauncherSettingsMessage::ChangeTab(
+                        state::LauncherSettingsTab::Java,
+                    ))
+                });
             }
             LauncherSettingsMessage::ChangeTab(tab) => {
                 self.go_to_launcher_settings();
                 if let State::LauncherSettings(menu) = &mut self.state {
                     menu.selected_tab = tab;
+                    if tab == state::LauncherSettingsTab::Java && menu.java.installs.is_none() {
+                        return load_java_installs();
+                    }
+                    if tab == state::LauncherSettingsTab::Accounts
+                        && menu.credentials.file_status.is_none()
+                    {
+                        return load_credential_status();
+                    }
                 }
             }
+            LauncherSettingsMessage::JavaLoaded(res) => match res {
+                Ok(installs) => {
+                    if let State::LauncherSettings(menu) = &mut self.state {
+                        menu.java.installs = Some(installs);
+                        menu.java.busy = None;
+                    }
+                }
+                Err(err) => self.set_error(err),
+            },
+            LauncherSettingsMessage::JavaCheckUpdates => {
+                if let State::LauncherSettings(menu) = &mut self.state {
+                    let versions: Vec<JavaVersion> = menu
+                        .java
+                        .installs
+                        .iter()
+                        .flatten()
+                        .map(|n| n.version)
+                        .collect();
+                    menu.java.is_checking_updates = true;
+                    return Task::perform(
+                        async move {
+                            let mut updates = Vec::new();
+                            for version in versions {
+                                let update =
+                                    ql_instances::check_java_update(version).await.strerr()?;
+                                updates.push((version, update));
+                            }
+                            Ok(updates)
+                        },
+                        |n| {
+                            Message::LauncherSettings(LauncherSettingsMessage::JavaUpdatesChecked(
+                                n,
+                            ))
+                        },
+                    );
+                }
+            }
+            LauncherSettingsMessage::JavaUpdatesChecked(res) => match res {
+                Ok(updates) => {
+                    if let State::LauncherSettings(menu) = &mut self.state {
+                        menu.java.updates = updates.into_iter().collect();
+                        menu.java.is_checking_updates = false;
+                    }
+                }
+                Err(err) => self.set_error(err),
+            },
+            LauncherSettingsMessage::JavaReinstall(version) => {
+                self.set_java_busy(format!("Updating Java {}...", version as usize));
+                return Task::perform(
+                    async move { ql_instances::reinstall_java(version, None).await.strerr() },
+                    |n| Message::LauncherSettings(LauncherSettingsMessage::JavaDone(n)),
+                );
+            }
+            LauncherSettingsMessage::JavaRepair(version) => {
+                self.set_java_busy(format!("Repairing Java {}...", version as usize));
+                return Task::perform(
+                    async move { ql_instances::repair_java(version, None).await.strerr() },
+                    |n| Message::LauncherSettings(LauncherSettingsMessage::JavaDone(n)),
+                );
+            }
+            LauncherSettingsMessage::JavaDelete(version) => {
+                self.set_java_busy(format!("Deleting Java {}...", version as usize));
+                return Task::perform(
+                    async move { ql_instances::delete_java_install(version).await.strerr() },
+                    |n| Message::LauncherSettings(LauncherSettingsMessage::JavaDone(n)),
+                );
+            }
+            LauncherSettingsMessage::JavaDeleteUnused => {
+                self.set_java_busy("Deleting unused Java...".to_owned());
+                let mut running: Vec<InstanceSelection> = self.processes.keys().cloned().collect();
+                return Task::perform(
+                    async move {
+                        running.extend(
+                            ql_servers::get_running_servers()
+                                .await
+                                .iter()
+                                .map(|n| InstanceSelection::new(n, true)),
+                        );
+                        ql_instances::delete_unused_java_installs(&running)
+                            .await
+                            .map(|_| ())
+                            .strerr()
+                    },
+                    |n| Message::LauncherSettings(LauncherSettingsMessage::JavaDone(n)),
+                );
+            }
+            LauncherSettingsMessage::JavaDone(res) => match res {
+                Ok(()) => {
+                    if let State::LauncherSettings(menu) = &mut self.state {
+                        menu.java.updates.clear();
+                    }
+                    return load_java_installs();
+                }
+                Err(err) => self.set_error(err),
+            },
+            LauncherSettingsMessage::CredentialsLoaded(status) => {
+                if let State::LauncherSettings(menu) = &mut self.state {
+                    menu.credentials.file_status = Some(status);
+                    menu.credentials.is_busy = false;
+                }
+            }
+            LauncherSettingsMessage::CredentialBackendPicked(backend) => {
+                // Not just the loaded accounts, locked
+                // ones (eg: passphrase not entered) too
+                let accounts: Vec<(String, AccountType)> = self
+                    .config
+                    .accounts
+                    .iter()
+                    .flatten()
+                    .map(|(username, account)| {
+                        (
+                            account.get_keyring_identifier(username),
+                            account.get_account_type(username),
+                        )
+                    })
+                    .collect();
+                self.set_credentials_busy();
+                return Task::perform(
+                    async move {
+                        ql_instances::auth::migrate_credentials(&accounts, backend)
+                            .strerr()
+                            .map(|()| backend)
+                    },
+                    |n| {
+                        Message::LauncherSettings(LauncherSettingsMessage::CredentialBackendMoved(
+                            n,
+                        ))
+                    },
+                );
+            }
+            LauncherSettingsMessage::CredentialBackendMoved(res) => match res {
+                Ok(backend) => {
+                    self.config.credential_backend = Some(backend);
+                    return load_credential_status();
+                }
+                Err(err) => self.set_error(err),
+            },
+            LauncherSettingsMessage::CredentialPassphraseInput(input) => {
+                if let State::LauncherSettings(menu) = &mut self.state {
+                    menu.credentials.passphrase = input;
+                }
+            }
+            LauncherSettingsMessage::CredentialUnlock => {
+                if let State::LauncherSettings(menu) = &mut self.state {
+                    let passphrase = std::mem::take(&mut menu.credentials.passphrase);
+                    menu.credentials.is_busy = true;
+                    return Task::perform(
+                        async move { credentials::file::unlock(&passphrase).strerr() },
+                        |n| Message::LauncherSettings(LauncherSettingsMessage::CredentialDone(n)),
+                    );
+                }
+            }
+            LauncherSettingsMessage::CredentialSetPassphrase(set) => {
+                if let State::LauncherSettings(menu) = &mut self.state {
+                    let passphrase = std::mem::take(&mut menu.credentials.passphrase);
+                    menu.credentials.is_busy = true;
+                    return Task::perform(
+                        async move {
+                            credentials::file::set_passphrase(set.then_some(passphrase.as_str()))
+                                .strerr()
+                        },
+                        |n| Message::LauncherSettings(LauncherSettingsMessage::CredentialDone(n)),
+                    );
+                }
+            }
+            LauncherSettingsMessage::CredentialDone(res) => match res {
+                Ok(()) => {
+                    self.reload_accounts();
+                    return load_credential_status();
+                }
+                Err(err) => self.set_error(err),
+            },
             LauncherSettingsMessage::ToggleAntialiasing(t) => {
                 self.config.ui_antialiasing = Some(t);
             }
@@ -635,13 +836,25 @@ impl Launcher {
         }
     }
 
+    fn set_credentials_busy(&mut self) {
+        if let State::LauncherSettings(menu) = &mut self.state {
+            menu.credentials.is_busy = true;
+        }
+    }
+
+    fn set_java_busy(&mut self, msg: String) {
+        if let State::LauncherSettings(menu) = &mut self.state {
+            menu.java.busy = Some(msg);
+        }
+    }
+
     fn confirm_clear_java_installs(&mut self) {
         self.state = State::ConfirmAction {
             msg1: "delete auto-installed Java files".to_owned(),
             msg2: "They will get reinstalled automatically as needed".to_owned(),
             yes: Message::LauncherSettings(LauncherSettingsMessage::ClearJavaInstallsConfirm),
             no: Message::LauncherSettings(LauncherSettingsMessage::ChangeTab(
-                state::LauncherSettingsTab::Internal,
+                state::LauncherSettingsTab::Java,
             )),
         }
     }
@@ -654,6 +867,8 @@ impl Launcher {
             temp_scale: self.config.ui_scale.unwrap_or(1.0),
             selected_tab: state::LauncherSettingsTab::UserInterface,
             arg_split_by_space: true,
+            java: state::JavaInstallsState::default(),
+            credentials: state::CredentialsState::default(),
         });
     }
 
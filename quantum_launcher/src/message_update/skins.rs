@@ -0,0 +1,388 @@
+use std::path::PathBuf;
+
+use iced::{widget, Task};
+use ql_core::{IntoIoError, IntoStringError};
+use ql_instances::auth::{
+    self,
+    offline::{self, OfflineProfile},
+    skin::{self, library, SkinVariant},
+    AccountData,
+};
+
+use crate::{
+    config::OFFLINE_PROFILE_SUFFIX,
+    state::{
+        AutoSaveKind, Launcher, MenuAccountSkins, MenuOfflineProfile, Message,
+        OfflineProfileMessage, SkinMessage, State, OFFLINE_ACCOUNT_NAME,
+    },
+};
+
+fn get_preview(png: &[u8], variant: SkinVariant) -> Option<widget::image::Handle> {
+    let preview = skin::render_preview(png, variant).ok()?;
+    Some(widget::image::Handle::from_rgba(
+        preview.width,
+        preview.height,
+        preview.rgba,
+    ))
+}
+
+fn pick_png_path() -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .add_filter("Skin", &["png"])
+        .set_title("Select a skin")
+        .pick_file()
+}
+
+fn pick_png() -> Option<(String, Vec<u8>)> {
+    let path = pick_png_path()?;
+    let name = path.file_stem()?.to_string_lossy().to_string();
+    Some((name, std::fs::read(&path).path(&path).ok()?))
+}
+
+fn get_file_preview(path: Option<&PathBuf>, variant: SkinVariant) -> Option<widget::image::Handle> {
+    get_preview(&std::fs::read(path?).ok()?, variant)
+}
+
+fn load_profile(account: AccountData) -> Task<Message> {
+    Task::perform(
+        async move { skin::get_profile(&account).await.strerr() },
+        |n| Message::Skin(SkinMessage::ProfileLoaded(n)),
+    )
+}
+
+fn load_library() -> Task<Message> {
+    Task::perform(async move { library::list().await.strerr() }, |n| {
+        Message::Skin(SkinMessage::LibraryLoaded(n))
+    })
+}
+
+impl Launcher {
+    pub fn update_skins(&mut self, msg: SkinMessage) -> Task<Message> {
+        match msg {
+            SkinMessage::Open => {
+                let Some((key, account)) = self
+                    .accounts_selected
+                    .clone()
+                    .and_then(|key| self.accounts.get(&key).cloned().map(|n| (key, n)))
+                else {
+                    return Task::none();
+                };
+                self.state = State::AccountSkins(MenuAccountSkins {
+                    account: key,
+                    profile: None,
+                    preview: None,
+                    library: Vec::new(),
+                    variant: SkinVariant::Classic,
+                    library_name: String::new(),
+                    is_loading: true,
+                });
+
+                // Uploading needs a fresh access token
+                if account.needs_refresh {
+                    return Task::perform(
+                        async move {
+                            if account.is_microsoft() {
+                                auth::ms::login_refresh(
+                                    account.username,
+                                    account.refresh_token,
+                                    None,
+                                )
+                                .await
+                                .strerr()
+                            } else {
+                                auth::yggdrasil::login_refresh(
+                                    account.username,
+                                    account.refresh_token,
+                                    account.account_type,
+                                )
+                                .await
+                                .strerr()
+                            }
+                        },
+                        |n| Message::Skin(SkinMessage::AccountRefreshed(n)),
+                    );
+                }
+                return Task::batch([load_profile(account), load_library()]);
+            }
+            SkinMessage::AccountRefreshed(Ok(account)) => {
+                if let State::AccountSkins(menu) = &self.state {
+                    self.insert_refreshed_account(menu.account.clone(), account.clone());
+                }
+                return Task::batch([load_profile(account), load_library()]);
+            }
+            SkinMessage::ProfileLoaded(Ok(profile)) => {
+                if let State::AccountSkins(menu) = &mut self.state {
+                    if let Some(skin) = &profile.skin {
+                        menu.preview = get_preview(&skin.image, skin.variant);
+                        menu.variant = skin.variant;
+                    } else {
+                        menu.preview = None;
+                    }
+                    menu.profile = Some(profile);
+                    menu.is_loading = false;
+                }
+            }
+            SkinMessage::LibraryLoaded(Ok(skins)) => {
+                if let State::AccountSkins(menu) = &mut self.state {
+                    menu.library = skins
+                        .into_iter()
+                        .map(|n| {
+                            let preview = get_preview(&n.image, n.variant);
+                            (n, preview)
+                        })
+                        .collect();
+                }
+            }
+            SkinMessage::VariantPicked(variant) => {
+                if let State::AccountSkins(menu) = &mut self.state {
+                    menu.variant = variant;
+                }
+            }
+            SkinMessage::UploadFile => {
+                if let State::AccountSkins(menu) = &self.state {
+                    let variant = menu.variant;
+                    if let Some((_, image)) = pick_png() {
+                        return self.upload_skin(image, variant);
+                    }
+                }
+            }
+            SkinMessage::UploadLibrary(i) => {
+                if let State::AccountSkins(menu) = &self.state {
+                    if let Some((skin, _)) = menu.library.get(i) {
+                        return self.upload_skin(skin.image.clone(), skin.variant);
+                    }
+                }
+            }
+            SkinMessage::Uploaded(Ok(())) | SkinMessage::CapeChanged(Ok(())) => {
+                if let Some(account) = self.get_skins_account() {
+                    return load_profile(account);
+                }
+            }
+            SkinMessage::CapePicked(cape_id) => {
+                if let Some(account) = self.get_skins_account() {
+                    self.set_skins_loading();
+                    return Task::perform(
+                        async move { skin::set_cape(&account, cape_id.as_deref()).await.strerr() },
+                        |n| Message::Skin(SkinMessage::CapeChanged(n)),
+                    );
+                }
+            }
+            SkinMessage::LibraryNameInput(name) => {
+                if let State::AccountSkins(menu) = &mut self.state {
+                    menu.library_name = name;
+                }
+            }
+            SkinMessage::LibrarySaveCurrent => {
+                if let State::AccountSkins(MenuAccountSkins {
+                    profile: Some(profile),
+                    library_name,
+                    account,
+                    ..
+                }) = &self.state
+                {
+                    if let Some(skin) = profile.skin.clone() {
+                        let name = if library_name.trim().is_empty() {
+                            self.accounts
+                                .get(account)
+                                .map_or_else(|| account.clone(), |n| n.nice_username.clone())
+                        } else {
+                            library_name.clone()
+                        };
+                        return Task::perform(
+                            async move {
+                                library::add(&name, &skin.image, skin.variant)
+                                    .await
+                                    .strerr()
+                                    .map(|_| ())
+                            },
+                            |n| Message::Skin(SkinMessage::LibraryChanged(n)),
+                        );
+                    }
+                }
+            }
+            SkinMessage::LibraryAddFile => {
+                if let State::AccountSkins(menu) = &self.state {
+                    let variant = menu.variant;
+                    if let Some((name, image)) = pick_png() {
+                        return Task::perform(
+                            async move {
+                                library::add(&name, &image, variant)
+                                    .await
+                                    .strerr()
+                                    .map(|_| ())
+                            },
+                            |n| Message::Skin(SkinMessage::LibraryChanged(n)),
+                        );
+                    }
+                }
+            }
+            SkinMessage::LibraryRemove(name) => {
+                return Task::perform(async move { library::remove(&name).await.strerr() }, |n| {
+                    Message::Skin(SkinMessage::LibraryChanged(n))
+                });
+            }
+            SkinMessage::LibraryChanged(Ok(())) => {
+                if let State::AccountSkins(menu) = &mut self.state {
+                    menu.library_name.clear();
+                }
+                return load_library();
+            }
+
+            SkinMessage::AccountRefreshed(Err(err))
+            | SkinMessage::ProfileLoaded(Err(err))
+            | SkinMessage::LibraryLoaded(Err(err))
+            | SkinMessage::Uploaded(Err(err))
+            | SkinMessage::CapeChanged(Err(err))
+            | SkinMessage::LibraryChanged(Err(err)) => self.set_error(err),
+        }
+        Task::none()
+    }
+
+    fn get_skins_account(&self) -> Option<AccountData> {
+        if let State::AccountSkins(menu) = &self.state {
+            self.accounts.get(&menu.account).cloned()
+        } else {
+            None
+        }
+    }
+
+    fn set_skins_loading(&mut self) {
+        if let State::AccountSkins(menu) = &mut self.state {
+            menu.is_loading = true;
+        }
+    }
+
+    fn upload_skin(&mut self, image: Vec<u8>, variant: SkinVariant) -> Task<Message> {
+        let Some(account) = self.get_skins_account() else {
+            return Task::none();
+        };
+        self.set_skins_loading();
+        Task::perform(
+            async move { skin::upload_skin(&account, image, variant).await.strerr() },
+            |n| Message::Skin(SkinMessage::Uploaded(n)),
+        )
+    }
+
+    pub fn update_offline_profile(&mut self, msg: OfflineProfileMessage) -> Task<Message> {
+        if let OfflineProfileMessage::Open(name) = msg {
+            let profile = name.and_then(|name| {
+                self.config
+                    .offline_profiles
+                    .iter()
+                    .flatten()
+                    .find(|n| n.name == name)
+                    .cloned()
+            });
+            self.state = State::OfflineProfile(match profile {
+                Some(profile) => MenuOfflineProfile {
+                    preview: get_file_preview(profile.skin.as_ref(), profile.skin_variant),
+                    original_name: Some(profile.name.clone()),
+                    name: profile.name,
+                    uuid: profile.uuid,
+                    skin: profile.skin,
+                    variant: profile.skin_variant,
+                },
+                None => MenuOfflineProfile {
+                    original_name: None,
+                    name: String::new(),
+                    uuid: String::new(),
+                    skin: None,
+                    variant: SkinVariant::Classic,
+                    preview: None,
+                },
+            });
+            return Task::none();
+        }
+
+        let State::OfflineProfile(menu) = &mut self.state else {
+            return Task::none();
+        };
+        match msg {
+            OfflineProfileMessage::Open(_) => {}
+            OfflineProfileMessage::NameInput(name) => {
+                // Keep following the name, unless a custom UUID was entered
+                if menu.uuid.is_empty() || menu.uuid == offline::get_offline_uuid(&menu.name) {
+                    menu.uuid = offline::get_offline_uuid(&name);
+                }
+                menu.name = name;
+            }
+            OfflineProfileMessage::UuidInput(uuid) => menu.uuid = uuid,
+            OfflineProfileMessage::UuidReset => menu.uuid = offline::get_offline_uuid(&menu.name),
+            OfflineProfileMessage::SkinPick => {
+                let Some(path) = pick_png_path() else {
+                    return Task::none();
+                };
+                match std::fs::read(&path)
+                    .path(&path)
+                    .strerr()
+                    .and_then(|n| skin::check_skin(&n).strerr())
+                {
+                    Ok(()) => {
+                        menu.preview = get_file_preview(Some(&path), menu.variant);
+                        menu.skin = Some(path);
+                    }
+                    Err(err) => self.set_error(err),
+                }
+            }
+            OfflineProfileMessage::SkinClear => {
+                menu.skin = None;
+                menu.preview = None;
+            }
+            OfflineProfileMessage::VariantPicked(variant) => {
+                menu.variant = variant;
+                menu.preview = get_file_preview(menu.skin.as_ref(), variant);
+            }
+            OfflineProfileMessage::Save => {
+                let Some(uuid) = offline::parse_uuid(&menu.uuid) else {
+                    return Task::none();
+                };
+                let profile = OfflineProfile {
+                    name: menu.name.trim().to_owned(),
+                    uuid,
+                    skin: menu.skin.clone(),
+                    skin_variant: menu.variant,
+                };
+                let original_name = menu.original_name.clone();
+                return self.save_offline_profile(profile, original_name);
+            }
+        }
+        Task::none()
+    }
+
+    fn save_offline_profile(
+        &mut self,
+        profile: OfflineProfile,
+        original_name: Option<String>,
+    ) -> Task<Message> {
+        let profiles = self.config.offline_profiles.get_or_insert_with(Vec::new);
+        if original_name.as_ref() != Some(&profile.name)
+            && profiles.iter().any(|n| n.name == profile.name)
+        {
+            self.set_error(format!(
+                "An offline profile named {} already exists",
+                profile.name
+            ));
+            return Task::none();
+        }
+
+        if let Some(original_name) = &original_name {
+            profiles.retain(|n| n.name != *original_name);
+            let old_key = format!("{original_name}{OFFLINE_PROFILE_SUFFIX}");
+            self.accounts_dropdown.retain(|n| *n != old_key);
+        }
+        let key = format!("{}{OFFLINE_PROFILE_SUFFIX}", profile.name);
+        profiles.push(profile);
+
+        let idx = self
+            .accounts_dropdown
+            .iter()
+            .position(|n| n == OFFLINE_ACCOUNT_NAME)
+            .unwrap_or(self.accounts_dropdown.len());
+        self.accounts_dropdown.insert(idx, key.clone());
+        self.accounts_selected = Some(key.clone());
+        self.config.account_selected = Some(key);
+        self.autosave.remove(&AutoSaveKind::LauncherConfig);
+
+        self.go_to_launch_screen(Option::<String>::None)
+    }
+}
@@ -0,0 +1,387 @@
+//! Shortcuts that launch an instance directly,
+//! without opening the launcher window.
+//!
+//! - Linux/BSD: a `.desktop` entry (in the app menu, and on the desktop)
+//! - Windows: a `.bat` script on the desktop
+//! - macOS: a `.command` script on the desktop
+//!
+//! These run `quantum_launcher launch INSTANCE ...`. Creating one
+//! also registers `quantumlauncher://launch/INSTANCE` links
+//! (Linux/BSD and Windows), which are handled by [`url_to_args`].
+
+use std::path::{Path, PathBuf};
+
+use ql_core::{
+    err, info, json::InstanceConfigJson, InstanceSelection, IntoIoError, IntoStringError,
+};
+
+use crate::{config::LauncherConfig, state::OFFLINE_ACCOUNT_NAME};
+
+pub const URL_SCHEME: &str = "quantumlauncher";
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const LOGO: &[u8] = include_bytes!("../../assets/icon/ql_logo.png");
+
+/// Creates shortcuts that launch `instance` (running the
+/// launcher with `args`, from [`launch_args`]),
+/// returning the created files.
+pub async fn create(instance: &str, args: &[String]) -> Result<Vec<PathBuf>, String> {
+    let exe = std::env::current_exe().strerr()?;
+    let mut command = vec![exe.to_string_lossy().to_string()];
+    command.extend(args.iter().cloned());
+
+    let file_name = format!("quantumlauncher-{}", sanitize(instance));
+    let paths = write_shortcuts(instance, &file_name, &command).await?;
+    for path in &paths {
+        info!("Created shortcut: {path:?}");
+    }
+
+    if let Err(err) = register_url_scheme(&exe).await {
+        err!("Couldn't register {URL_SCHEME}:// links: {err}");
+    }
+    Ok(paths)
+}
+
+/// Arguments to run `quantum_launcher launch` for `instance`,
+/// playing as `account` (an entry of the GUI's account list)
+/// or with the instance's default account if `None`.
+///
+/// The instance (and username) come after `--`, so they
+/// can't be mistaken for options.
+pub fn launch_args(instance: &str, account: Option<(&LauncherConfig, &str)>) -> Vec<String> {
+    let mut args = vec!["launch".to_owned()];
+    let mut username = None;
+    match account {
+        Some((config, account)) if account == OFFLINE_ACCOUNT_NAME => {
+            if config.username.is_empty() {
+                args.push("--offline".to_owned());
+            } else {
+                username = Some(config.username.clone());
+            }
+        }
+        Some((config, account)) => {
+            if let Some(profile) = config.get_offline_profile(account) {
+                username = Some(profile.name.clone());
+            } else {
+                args.push(format!("--account={account}"));
+            }
+        }
+        None => {}
+    }
+    args.push("--".to_owned());
+    args.push(instance.to_owned());
+    args.extend(username);
+    args
+}
+
+/// Turns a `quantumlauncher://launch/INSTANCE` link into the
+/// command line to run. Returns `None` for anything else.
+///
+/// Links can come from any website, so only the instance
+/// can be picked: it's played with its default account,
+/// or the account selected in the launcher.
+pub fn url_to_args(url: &str) -> Option<Vec<String>> {
+    let instance = url_instance(url)?;
+
+    let has_default_account = tokio::runtime::Runtime::new().ok().is_some_and(|runtime| {
+        runtime
+            .block_on(InstanceConfigJson::read(&InstanceSelection::new(
+                &instance, false,
+            )))
+            .is_ok_and(|n| n.account.is_some())
+    });
+    let config = (!has_default_account)
+        .then(LauncherConfig::load_s)
+        .and_then(Result::ok);
+    let account = config.as_ref().map(|config| {
+        let account = config
+            .account_selected
+            .as_deref()
+            .unwrap_or(OFFLINE_ACCOUNT_NAME);
+        (config, account)
+    });
+    Some(launch_args(&instance, account))
+}
+
+/// The instance name in a `quantumlauncher://launch/INSTANCE` link
+fn url_instance(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    if !scheme.eq_ignore_ascii_case(URL_SCHEME) {
+        return None;
+    }
+    let rest = rest.strip_prefix("launch/")?;
+    let rest = rest.split(['?', '#']).next()?.trim_end_matches('/');
+    let instance = urlencoding::decode(rest).ok()?.into_owned();
+    if instance.is_empty() || instance.contains(['/', '\\']) || instance.starts_with(['.', '-']) {
+        return None;
+    }
+    Some(instance)
+}
+
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+async fn write_shortcuts(
+    instance: &str,
+    file_name: &str,
+    command: &[String],
+) -> Result<Vec<PathBuf>, String> {
+    let icon = get_icon(instance).await?;
+    let exec = command
+        .iter()
+        .map(|n| desktop_quote(n))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let contents = format!(
+        "[Desktop Entry]
+Type=Application
+Version=1.5
+
+Name={instance}
+Comment=Play {instance} with Quantum Launcher
+Categories=Game;
+Icon={}
+Exec={exec}
+PrefersNonDefaultGPU=true
+",
+        icon.to_string_lossy()
+    );
+    let file_name = format!("{file_name}.desktop");
+
+    let mut paths = Vec::new();
+    let apps_dir = dirs::data_dir()
+        .ok_or("Couldn't find the .local/share directory")?
+        .join("applications");
+    tokio::fs::create_dir_all(&apps_dir)
+        .await
+        .path(&apps_dir)
+        .strerr()?;
+    let path = apps_dir.join(&file_name);
+    write_executable(&path, &contents).await?;
+    paths.push(path);
+
+    if let Some(desktop) = dirs::desktop_dir().filter(|n| n.is_dir()) {
+        let path = desktop.join(&file_name);
+        write_executable(&path, &contents).await?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+#[cfg(target_os = "windows")]
+async fn write_shortcuts(
+    _instance: &str,
+    file_name: &str,
+    command: &[String],
+) -> Result<Vec<PathBuf>, String> {
+    let command = command
+        .iter()
+        .map(|n| format!("\"{}\"", n.replace('%', "%%").replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let path = desktop_dir()?.join(format!("{file_name}.bat"));
+    write_executable(&path, &format!("@echo off\r\n{command}\r\n")).await?;
+    Ok(vec![path])
+}
+
+#[cfg(target_os = "macos")]
+async fn write_shortcuts(
+    _instance: &str,
+    file_name: &str,
+    command: &[String],
+) -> Result<Vec<PathBuf>, String> {
+    let command = command
+        .iter()
+        .map(|n| format!("'{}'", n.replace('\'', r"'\''")))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let path = desktop_dir()?.join(format!("{file_name}.command"));
+    write_executable(&path, &format!("#!/bin/sh\nexec {command}\n")).await?;
+    Ok(vec![path])
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+fn desktop_dir() -> Result<PathBuf, String> {
+    dirs::desktop_dir().ok_or_else(|| "Couldn't find the Desktop folder".to_owned())
+}
+
+async fn write_executable(path: &Path, contents: &str) -> Result<(), String> {
+    tokio::fs::write(path, contents).await.path(path).strerr()?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+            .await
+            .path(path)
+            .strerr()?;
+    }
+    Ok(())
+}
+
+/// The instance's `icon.png` (in its folder) if it
+/// has one, otherwise the launcher logo
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+async fn get_icon(instance: &str) -> Result<PathBuf, String> {
+    let custom = InstanceSelection::new(instance, false)
+        .get_instance_path()
+        .join("icon.png");
+    if custom.is_file() {
+        return Ok(custom);
+    }
+    let path = ql_core::LAUNCHER_DIR.join("logo.png");
+    if !path.is_file() {
+        tokio::fs::write(&path, LOGO).await.path(&path).strerr()?;
+    }
+    Ok(path)
+}
+
+/// Quotes an argument for the `Exec` key of a `.desktop` file
+/// (escaped once for the command line, once for the file)
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn desktop_quote(arg: &str) -> String {
+    let mut out = String::from("\"");
+    for c in arg.chars() {
+        match c {
+            '"' | '`' | '$' => {
+                out.push_str(r"\\");
+                out.push(c);
+            }
+            '\\' => out.push_str(r"\\\\"),
+            '%' => out.push_str("%%"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Makes `quantumlauncher://` links open this launcher
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+async fn register_url_scheme(exe: &Path) -> Result<(), String> {
+    const FILE_NAME: &str = "quantumlauncher-url.desktop";
+
+    let apps_dir = dirs::data_dir()
+        .ok_or("Couldn't find the .local/share directory")?
+        .join("applications");
+    let path = apps_dir.join(FILE_NAME);
+    let contents = format!(
+        "[Desktop Entry]
+Type=Application
+Name=Quantum Launcher
+NoDisplay=true
+MimeType=x-scheme-handler/{URL_SCHEME};
+Exec={} %u
+",
+        desktop_quote(&exe.to_string_lossy())
+    );
+    tokio::fs::write(&path, contents)
+        .await
+        .path(&path)
+        .strerr()?;
+
+    let status = tokio::process::Command::new("xdg-mime")
+        .args(["default", FILE_NAME])
+        .arg(format!("x-scheme-handler/{URL_SCHEME}"))
+        .status()
+        .await
+        .map_err(|err| format!("couldn't run xdg-mime: {err}"))?;
+    if !status.success() {
+        return Err(format!("xdg-mime exited with {status}"));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+async fn register_url_scheme(exe: &Path) -> Result<(), String> {
+    let key = format!(r"HKCU\Software\Classes\{URL_SCHEME}");
+    let command = format!("\"{}\" \"%1\"", exe.to_string_lossy());
+    let values: [(String, Option<&str>, &str); 3] = [
+        (key.clone(), None, "URL:Quantum Launcher"),
+        (key.clone(), Some("URL Protocol"), ""),
+        (format!(r"{key}\shell\open\command"), None, &command),
+    ];
+    for (key, name, data) in values {
+        let mut cmd = tokio::process::Command::new("reg");
+        cmd.args(["add", &key]);
+        if let Some(name) = name {
+            cmd.args(["/v", name]);
+        } else {
+            cmd.arg("/ve");
+        }
+        cmd.args(["/d", data, "/f"]);
+        ql_core::no_window!(cmd);
+        let status = cmd
+            .status()
+            .await
+            .map_err(|err| format!("couldn't run reg: {err}"))?;
+        if !status.success() {
+            return Err(format!("reg exited with {status}"));
+        }
+    }
+    Ok(())
+}
+
+/// Links need the launcher to be registered in the app's
+/// `Info.plist`, which isn't possible from here
+#[cfg(target_os = "macos")]
+#[allow(clippy::unused_async)]
+async fn register_url_scheme(_exe: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url() {
+        let instance = |url| url_instance(url);
+        assert_eq!(
+            instance("quantumlauncher://launch/SMP").as_deref(),
+            Some("SMP")
+        );
+        assert_eq!(
+            instance("QuantumLauncher://launch/My%20Pack/?x=1").as_deref(),
+            Some("My Pack")
+        );
+        assert_eq!(instance("quantumlauncher://launch/"), None);
+        assert_eq!(instance("quantumlauncher://open/SMP"), None);
+        assert_eq!(instance("https://launch/SMP"), None);
+        assert_eq!(instance("quantumlauncher://launch/..%2Fx"), None);
+        assert_eq!(instance("quantumlauncher://launch/a%5Cb"), None);
+        assert_eq!(instance("quantumlauncher://launch/--help"), None);
+    }
+
+    #[test]
+    fn args() {
+        let mut config = LauncherConfig::default();
+        assert_eq!(launch_args("SMP", None), ["launch", "--", "SMP"]);
+        assert_eq!(
+            launch_args("SMP", Some((&config, OFFLINE_ACCOUNT_NAME))),
+            ["launch", "--offline", "--", "SMP"]
+        );
+        assert_eq!(
+            launch_args("SMP", Some((&config, "Steve"))),
+            ["launch", "--account=Steve", "--", "SMP"]
+        );
+        config.username = "-Alex".to_owned();
+        assert_eq!(
+            launch_args("SMP", Some((&config, OFFLINE_ACCOUNT_NAME))),
+            ["launch", "--", "SMP", "-Alex"]
+        );
+    }
+
+    #[test]
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    fn quote() {
+        assert_eq!(desktop_quote("SMP"), r#""SMP""#);
+        assert_eq!(desktop_quote("100%"), r#""100%%""#);
+        assert_eq!(desktop_quote(r#"a "b""#), r#""a \\"b\\"""#);
+        assert_eq!(desktop_quote("$HOME`x`"), r#""\\$HOME\\`x\\`""#);
+        assert_eq!(desktop_quote(r"C:\x"), r#""C:\\\\x""#);
+    }
+}
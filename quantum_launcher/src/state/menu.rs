@@ -1,5 +1,6 @@
 use std::{
     collections::{HashMap, HashSet},
+    path::PathBuf,
     time::Instant,
 };
 
@@ -15,14 +16,22 @@ use ql_core::{
     file_utils::DirItem,
     jarmod::JarMods,
     json::{instance_config::MainClassMode, InstanceConfigJson, VersionDetails},
-    DownloadProgress, GenericProgress, InstanceSelection, IntoStringError, ListEntry, ModId,
-    OptifineUniqueVersion, SelectedMod, StoreBackendType,
+    DownloadProgress, GenericProgress, InstanceSelection, IntoStringError, JavaVersion, ListEntry,
+    ModId, OptifineUniqueVersion, SelectedMod, StoreBackendType,
+};
+use ql_instances::auth::{
+    skin::{library::LibrarySkin, SkinProfile, SkinVariant},
+    CredentialFileStatus,
 };
 use ql_mod_manager::loaders::paper::PaperVersion;
 use ql_mod_manager::{
     loaders::{self, forge::ForgeInstallProgress, optifine::OptifineInstallProgress},
-    store::{CurseforgeNotAllowed, ModConfig, ModIndex, QueryType, RecommendedMod, SearchResult},
+    store::{
+        CurseforgeNotAllowed, ModConfig, ModIndex, QueryType, RecommendedMod, SearchResult,
+        StoreModpack,
+    },
 };
+use ql_packager::{migrate::MigrationEntry, ExportFormat};
 
 use crate::state::ImageState;
 
@@ -236,6 +245,9 @@ pub struct MenuEditMods {
 
     pub update_check_handle: Option<iced::task::Handle>,
     pub available_updates: Vec<(ModId, String, bool)>,
+    /// Name of the new version, if the modpack
+    /// this instance was installed from has an update
+    pub modpack_update: Option<String>,
 
     pub list_scroll: AbsoluteOffset,
     /// Index of the item selected before pressing shift
@@ -339,6 +351,11 @@ pub enum MenuCreateInstance {
     Choosing(MenuCreateInstanceChoosing),
     DownloadingInstance(ProgressBar<DownloadProgress>),
     ImportingInstance(ProgressBar<GenericProgress>),
+    /// Picking instances of other launchers
+    /// (on this computer) to import
+    Migrating {
+        entries: Option<Vec<(MigrationEntry, bool)>>,
+    },
 }
 
 pub struct MenuCreateInstanceChoosing {
@@ -466,12 +483,38 @@ pub struct MenuLauncherSettings {
     pub temp_scale: f64,
     pub selected_tab: LauncherSettingsTab,
     pub arg_split_by_space: bool,
+    pub java: JavaInstallsState,
+    pub credentials: CredentialsState,
+}
+
+/// Where account logins are stored, shown in the Accounts settings tab
+#[derive(Default)]
+pub struct CredentialsState {
+    /// `None` if still loading
+    pub file_status: Option<CredentialFileStatus>,
+    pub passphrase: String,
+    pub is_busy: bool,
+}
+
+/// Launcher-installed Java runtimes, shown in the Java settings tab
+#[derive(Default)]
+pub struct JavaInstallsState {
+    /// `None` if still loading
+    pub installs: Option<Vec<ql_instances::JavaInstall>>,
+    /// `None` (inside) if it's unknown where
+    /// the runtime was installed from
+    pub updates: HashMap<JavaVersion, Option<bool>>,
+    pub is_checking_updates: bool,
+    /// What's currently being done (reinstalling, deleting, ...)
+    pub busy: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum LauncherSettingsTab {
     UserInterface,
     Internal,
+    Accounts,
+    Java,
     About,
 }
 
@@ -483,6 +526,8 @@ impl std::fmt::Display for LauncherSettingsTab {
             match self {
                 LauncherSettingsTab::UserInterface => "Appearance",
                 LauncherSettingsTab::Internal => "Game",
+                LauncherSettingsTab::Accounts => "Accounts",
+                LauncherSettingsTab::Java => "Java",
                 LauncherSettingsTab::About => "About",
             }
         )
@@ -490,19 +535,29 @@ impl std::fmt::Display for LauncherSettingsTab {
 }
 
 impl LauncherSettingsTab {
-    pub const ALL: &'static [Self] = &[Self::UserInterface, Self::Internal, Self::About];
+    pub const ALL: &'static [Self] = &[
+        Self::UserInterface,
+        Self::Internal,
+        Self::Accounts,
+        Self::Java,
+        Self::About,
+    ];
 
     pub const fn next(self) -> Self {
         match self {
             Self::UserInterface => Self::Internal,
-            Self::Internal | Self::About => Self::About,
+            Self::Internal => Self::Accounts,
+            Self::Accounts => Self::Java,
+            Self::Java | Self::About => Self::About,
         }
     }
 
     pub const fn prev(self) -> Self {
         match self {
             Self::UserInterface | Self::Internal => Self::UserInterface,
-            Self::About => Self::Internal,
+            Self::Accounts => Self::Internal,
+            Self::Java => Self::Accounts,
+            Self::About => Self::Java,
         }
     }
 }
@@ -543,8 +598,27 @@ pub struct MenuCurseforgeManualDownload {
     pub delete_mods: bool,
 }
 
+/// Picking which optional files of a
+/// Modrinth modpack to install
+pub struct MenuModpackOptions {
+    pub name: String,
+    pub optional: Vec<(String, bool)>,
+    pub source: ModpackSource,
+}
+
+/// Where the modpack in [`MenuModpackOptions`] comes from
+pub enum ModpackSource {
+    /// A file being added to the instance
+    File { path: PathBuf, delete_file: bool },
+    /// Being installed from the mod store
+    Store(ModId, StoreModpack),
+    /// A new version of the instance's modpack
+    Update(StoreModpack),
+}
+
 pub struct MenuExportInstance {
     pub entries: Option<Vec<(DirItem, bool)>>,
+    pub format: ExportFormat,
     pub progress: Option<ProgressBar<GenericProgress>>,
 }
 
@@ -561,15 +635,44 @@ pub struct MenuLoginAlternate {
     pub is_from_welcome_screen: bool,
 
     pub is_elyby: bool,
-    
+
     pub is_custom: bool,
-    //pub custom_auth_url: Option<String>,
-    
+    /// Server URL entered by the user, for custom accounts
+    pub custom_auth_url: String,
+    /// Servers of already logged-in custom accounts
+    pub custom_auth_servers: Vec<String>,
     pub is_littleskin: bool,
     pub oauth: Option<LittleSkinOauth>,
     pub device_code_error: Option<String>,
 }
 
+/// Viewing/changing the skin and cape of an account
+pub struct MenuAccountSkins {
+    /// Entry of `Launcher::accounts`
+    pub account: String,
+    pub profile: Option<SkinProfile>,
+    pub preview: Option<widget::image::Handle>,
+    /// Saved skins, with their previews
+    pub library: Vec<(LibrarySkin, Option<widget::image::Handle>)>,
+    /// Variant used when uploading/saving a new skin
+    pub variant: SkinVariant,
+    /// Name to save the current skin as
+    pub library_name: String,
+    pub is_loading: bool,
+}
+
+/// Creating/editing an offline profile
+pub struct MenuOfflineProfile {
+    /// Name of the profile being edited,
+    /// `None` if creating a new one
+    pub original_name: Option<String>,
+    pub name: String,
+    pub uuid: String,
+    pub skin: Option<PathBuf>,
+    pub variant: SkinVariant,
+    pub preview: Option<widget::image::Handle>,
+}
+
 pub struct LittleSkinOauth {
     // pub device_code: String,
     pub user_code: String,
@@ -598,6 +701,7 @@ pub enum State {
     ExportMods(MenuExportMods),
     EditJarMods(MenuEditJarMods),
     ImportModpack(ProgressBar<GenericProgress>),
+    ModpackOptions(MenuModpackOptions),
     CurseforgeManualDownload(MenuCurseforgeManualDownload),
     ExportInstance(MenuExportInstance),
 
@@ -622,6 +726,8 @@ pub enum State {
     AccountLogin,
     LoginMS(MenuLoginMS),
     LoginAlternate(MenuLoginAlternate),
+    AccountSkins(MenuAccountSkins),
+    OfflineProfile(MenuOfflineProfile),
 
     InstallPaper(MenuInstallPaper),
     InstallFabric(MenuInstallFabric),
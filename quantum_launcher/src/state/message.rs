@@ -9,21 +9,26 @@ use iced::widget::{self, scrollable::AbsoluteOffset};
 use ql_core::{
     file_utils::DirItem,
     jarmod::JarMods,
-    json::instance_config::{MainClassMode, PreLaunchPrefixMode},
+    json::instance_config::{JvmPreset, MainClassMode, PreLaunchPrefixMode, QuickPlay},
     read_log::Diagnostic,
-    InstanceSelection, LaunchedProcess, ListEntry, Loader, ModId, StoreBackendType,
+    InstanceSelection, JavaVersion, LaunchedProcess, ListEntry, Loader, ModId, StoreBackendType,
 };
 use ql_instances::{
     auth::{
         ms::{AuthCodeResponse, AuthTokenResponse},
-        AccountData, AccountType,
+        skin::{library::LibrarySkin, SkinProfile, SkinVariant},
+        AccountData, AccountType, CredentialBackend, CredentialFileStatus,
     },
-    UpdateCheckInfo,
+    JavaInfo, UpdateCheckInfo,
 };
 use ql_mod_manager::{
     loaders::{fabric, paper::PaperVersion},
-    store::{CurseforgeNotAllowed, ImageResult, ModIndex, QueryType, RecommendedMod, SearchResult},
+    store::{
+        CurseforgeNotAllowed, ImageResult, ModIndex, PackPlan, PackUpdateReport, QueryType,
+        RecommendedMod, SearchResult, StoreModpack,
+    },
 };
+use ql_packager::{migrate::MigrationEntry, ExportFormat};
 
 use super::{LaunchTab, LauncherSettingsTab, LicenseTab, Res};
 
@@ -69,6 +74,12 @@ pub enum CreateInstanceMessage {
     #[allow(unused)]
     Import,
     ImportResult(Res<Option<InstanceSelection>>),
+
+    MigrateOpen,
+    MigrateLoaded(Vec<MigrationEntry>),
+    MigrateToggle(usize, bool),
+    MigrateStart,
+    MigrateResult(Res<Vec<InstanceSelection>>),
 }
 
 #[derive(Debug, Clone)]
@@ -76,14 +87,26 @@ pub enum EditInstanceMessage {
     ConfigSaved(Res),
     ReinstallLibraries,
     UpdateAssets,
+    /// Creates a desktop shortcut that launches the instance
+    CreateShortcut,
     BrowseJavaOverride,
+    JavaDetect,
+    JavaDetected(Vec<JavaInfo>),
 
     JavaOverride(String),
     JavaOverrideVersion(usize),
     MemoryChanged(f32),
     MemoryInputChanged(String),
+    AutoRamToggle(bool),
+    JvmPresetChanged(JvmPreset),
     LoggingToggle(bool),
     CloseLauncherToggle(bool),
+    AccountChanged(Option<String>),
+    /// Makes the selected account the instance's
+    /// default (`true`) or clears it (`false`), saving immediately
+    AccountPin(bool),
+    /// Server/world to join on startup (or `None` for the title screen)
+    QuickPlayChanged(Option<QuickPlay>),
     SetMainClass(Option<MainClassMode>, Option<String>),
 
     JavaArgs(ListMessage),
@@ -130,6 +153,23 @@ pub enum ManageModsMessage {
     UpdateCheckResult(Res<Vec<(ModId, String)>>),
     UpdateCheckToggle(usize, bool),
 
+    /// A modpack file being added was read
+    /// (`plan` is `None` if it isn't a modpack)
+    ModpackFileRead {
+        path: PathBuf,
+        plan: Option<PackPlan>,
+        delete_file: bool,
+    },
+    ModpackDownloaded(Res<(ModId, StoreModpack)>),
+    ModpackOptionsToggle(usize, bool),
+    ModpackOptionsInstall,
+
+    ModpackUpdateCheckResult(Res<Option<String>>),
+    ModpackUpdate,
+    /// The new version, and the optional files picked last time
+    ModpackUpdateDownloaded(Res<(StoreModpack, HashSet<String>)>),
+    ModpackUpdateFinished(Res<PackUpdateReport>),
+
     /// Add a mod, preset or modpack to the current instance.
     /// The field represents whether to delete the file after importing it.
     AddFile(bool),
@@ -250,6 +290,7 @@ pub enum AccountMessage {
     AltPasswordInput(String),
     AltOtpInput(String),
     AltShowPassword(bool),
+    AltServerInput(String),
     AltLogin,
     AltLoginResponse(Res<ql_instances::auth::yggdrasil::Account>),
 
@@ -264,6 +305,43 @@ pub enum AccountMessage {
     LittleSkinDeviceCodeError(String),
 }
 
+#[derive(Debug, Clone)]
+pub enum SkinMessage {
+    Open,
+    AccountRefreshed(Res<AccountData>),
+    ProfileLoaded(Res<SkinProfile>),
+    LibraryLoaded(Res<Vec<LibrarySkin>>),
+    VariantPicked(SkinVariant),
+    /// Upload a PNG file
+    UploadFile,
+    /// Upload a skin from the library
+    UploadLibrary(usize),
+    Uploaded(Res),
+    CapePicked(Option<String>),
+    CapeChanged(Res),
+
+    LibraryNameInput(String),
+    LibrarySaveCurrent,
+    LibraryAddFile,
+    LibraryRemove(String),
+    LibraryChanged(Res),
+}
+
+#[derive(Debug, Clone)]
+pub enum OfflineProfileMessage {
+    /// Edit an offline profile (by name),
+    /// or create a new one if `None`
+    Open(Option<String>),
+    NameInput(String),
+    UuidInput(String),
+    /// Go back to the vanilla UUID for the name
+    UuidReset,
+    SkinPick,
+    SkinClear,
+    VariantPicked(SkinVariant),
+    Save,
+}
+
 #[derive(Debug, Clone)]
 pub enum LauncherSettingsMessage {
     Open,
@@ -276,6 +354,22 @@ pub enum LauncherSettingsMessage {
     UiIdleFps(f64),
     ClearJavaInstalls,
     ClearJavaInstallsConfirm,
+    JavaLoaded(Res<Vec<ql_instances::JavaInstall>>),
+    JavaCheckUpdates,
+    JavaUpdatesChecked(Res<Vec<(JavaVersion, Option<bool>)>>),
+    JavaReinstall(JavaVersion),
+    JavaRepair(JavaVersion),
+    JavaDelete(JavaVersion),
+    JavaDeleteUnused,
+    JavaDone(Res<()>),
+    CredentialsLoaded(CredentialFileStatus),
+    CredentialBackendPicked(CredentialBackend),
+    CredentialBackendMoved(Res<CredentialBackend>),
+    CredentialPassphraseInput(String),
+    CredentialUnlock,
+    /// `true`: set the entered passphrase, `false`: remove it
+    CredentialSetPassphrase(bool),
+    CredentialDone(Res<()>),
     ChangeTab(LauncherSettingsTab),
     DefaultMinecraftWidthChanged(String),
     DefaultMinecraftHeightChanged(String),
@@ -364,6 +458,8 @@ pub enum Message {
     WelcomeContinueToAuth,
 
     Account(AccountMessage),
+    Skin(SkinMessage),
+    OfflineProfile(OfflineProfileMessage),
     CreateInstance(CreateInstanceMessage),
     EditInstance(EditInstanceMessage),
     LauncherSettings(LauncherSettingsMessage),
@@ -412,6 +508,7 @@ pub enum Message {
     #[allow(unused)]
     ExportInstanceOpen,
     ExportInstanceToggleItem(usize, bool),
+    ExportInstanceChangeFormat(ExportFormat),
     ExportInstanceStart,
     ExportInstanceFinished(Res<Vec<u8>>),
     ExportInstanceLoaded(Res<Vec<DirItem>>),
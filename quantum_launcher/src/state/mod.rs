@@ -12,11 +12,11 @@ use ql_core::{
     IntoStringError, IoError, JsonFileError, LaunchedProcess, ModId, Progress, LAUNCHER_DIR,
     LAUNCHER_VERSION_NAME,
 };
-use ql_instances::auth::{ms::CLIENT_ID, AccountData, AccountType};
+use ql_instances::auth::{ms::CLIENT_ID, AccountData};
 use tokio::process::ChildStdin;
 
 use crate::{
-    config::{LauncherConfig, SIDEBAR_WIDTH},
+    config::{LauncherConfig, OFFLINE_PROFILE_SUFFIX, SIDEBAR_WIDTH},
     stylesheet::styles::LauncherTheme,
 };
 
@@ -159,6 +159,7 @@ impl Launcher {
             State::ChangeLog
         };
 
+        ql_instances::auth::set_credential_backend(config.credential_backend.unwrap_or_default());
         let (accounts, accounts_dropdown, selected_account) = load_accounts(&mut config);
 
         let persistent = config.c_persistent();
@@ -268,6 +269,21 @@ impl Launcher {
         }
     }
 
+    /// Loads the accounts again, eg: after unlocking
+    /// the encrypted credential file.
+    pub fn reload_accounts(&mut self) {
+        let (accounts, accounts_dropdown, selected_account) = load_accounts(&mut self.config);
+        self.accounts = accounts;
+        self.accounts_dropdown = accounts_dropdown;
+        if self
+            .accounts_selected
+            .as_ref()
+            .is_none_or(|n| !self.accounts_dropdown.contains(n))
+        {
+            self.accounts_selected = Some(selected_account);
+        }
+    }
+
     pub fn instance(&self) -> &InstanceSelection {
         self.selected_instance.as_ref().unwrap()
     }
@@ -326,6 +342,15 @@ fn load_accounts(
         }
     }
 
+    // Listed after logged-in accounts, before "(Offline)"
+    let offline_idx = accounts_dropdown.len() - 2;
+    for profile in config.offline_profiles.iter().flatten().rev() {
+        accounts_dropdown.insert(
+            offline_idx,
+            format!("{}{OFFLINE_PROFILE_SUFFIX}", profile.name),
+        );
+    }
+
     let selected_account = config.account_selected.clone().unwrap_or(
         accounts_dropdown
             .first()
@@ -342,90 +367,9 @@ fn load_account(
     username: &str,
     account: &mut crate::config::ConfigAccount,
 ) {
-    fn get_refresh_token_for_account_type(
-        account_type: AccountType,
-        username: &str,
-        keyring_identifier: Option<&str>,
-    ) -> Result<String, String> {
-        let keyring_username = if let Some(keyring_id) = keyring_identifier {
-            keyring_id
-        } else {
-            // Fallback to old behavior for backwards compatibility
-            match account_type {
-                AccountType::ElyBy => username.strip_suffix(" (elyby)").unwrap_or(username),
-                AccountType::LittleSkin => {
-                    username.strip_suffix(" (littleskin)").unwrap_or(username)
-                }
-                AccountType::Microsoft => username,
-                AccountType::Custom => username.strip_suffix(" (custom)").unwrap_or(username),
-            }
-        };
-        ql_instances::auth::read_refresh_token(keyring_username, account_type).strerr()
-    }
-
-    let (account_type, refresh_token) =
-        if account.account_type.as_deref() == Some("ElyBy") || username.ends_with(" (elyby)") {
-            (
-                AccountType::ElyBy,
-                get_refresh_token_for_account_type(
-                    AccountType::ElyBy,
-                    username,
-                    account.keyring_identifier.as_deref(),
-                ),
-            )
-        } else if account.account_type.as_deref() == Some("Custom")
-            || username.ends_with(" (custom)")
-        {
-            (
-                AccountType::Custom,
-                get_refresh_token_for_account_type(
-                    AccountType::Custom,
-                    username,
-                    account.keyring_identifier.as_deref(),
-                ),
-            )
-        } else if account.account_type.as_deref() == Some("LittleSkin")
-            || username.ends_with(" (littleskin)")
-        {
-            (
-                AccountType::LittleSkin,
-                get_refresh_token_for_account_type(
-                    AccountType::LittleSkin,
-                    username,
-                    account.keyring_identifier.as_deref(),
-                ),
-            )
-        } else {
-            (
-                AccountType::Microsoft,
-                get_refresh_token_for_account_type(
-                    AccountType::Microsoft,
-                    username,
-                    account.keyring_identifier.as_deref(),
-                ),
-            )
-        };
-
-    let keyring_username = if let Some(keyring_id) = &account.keyring_identifier {
-        keyring_id.clone()
-    } else {
-        // Fallback to old behavior for backwards compatibility
-        match account_type {
-            AccountType::ElyBy => username
-                .strip_suffix(" (elyby)")
-                .unwrap_or(username)
-                .to_owned(),
-            AccountType::LittleSkin => username
-                .strip_suffix(" (littleskin)")
-                .unwrap_or(username)
-                .to_owned(),
-            AccountType::Custom => username
-                .strip_suffix(" (custom)")
-                .unwrap_or(username)
-                .to_owned(),
-            AccountType::Microsoft => username.to_owned(),
-        }
-    };
+    let account_type = account.get_account_type(username);
+    let keyring_username = account.get_keyring_identifier(username);
+    let refresh_token = ql_instances::auth::read_refresh_token(&keyring_username, &account_type);
 
     match refresh_token {
         Ok(refresh_token) => {
@@ -438,6 +382,7 @@ fn load_account(
                     refresh_token,
                     needs_refresh: true,
                     account_type,
+                    authlib_metadata: account.authlib_metadata.clone(),
 
                     username: keyring_username.clone(),
                     nice_username: account
@@ -447,6 +392,10 @@ fn load_account(
                 },
             );
         }
+        // Still logged in, just waiting for the passphrase
+        Err(err) if err.is_locked() => {
+            err!("Could not load account {keyring_username}: {err}");
+        }
         Err(err) => {
             err!(
                 "Could not load account: {err}\nUsername: {keyring_username}, Account Type: {}",
@@ -152,6 +152,8 @@ impl Launcher {
             // These menus don't require background ticking
             State::Error { .. }
             | State::LoginAlternate(_)
+            | State::AccountSkins(_)
+            | State::OfflineProfile(_)
             | State::AccountLogin
             | State::ExportInstance(_)
             | State::ConfirmAction { .. }
@@ -161,6 +163,7 @@ impl Launcher {
             | State::LoginMS(MenuLoginMS { .. })
             | State::GenericMessage(_)
             | State::CurseforgeManualDownload(_)
+            | State::ModpackOptions(_)
             | State::LogUploadResult { .. }
             | State::InstallPaper(_)
             | State::ExportMods(_) => {}
@@ -343,7 +346,7 @@ impl MenuEditMods {
 impl MenuCreateInstance {
     pub fn tick(&mut self) {
         match self {
-            MenuCreateInstance::Choosing { .. } => {}
+            MenuCreateInstance::Choosing { .. } | MenuCreateInstance::Migrating { .. } => {}
             MenuCreateInstance::DownloadingInstance(progress) => {
                 progress.tick();
             }
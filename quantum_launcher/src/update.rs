@@ -1,5 +1,6 @@
 use iced::{futures::executor::block_on, Task};
 use ql_core::{err, file_utils::DirItem, info, InstanceSelection, IntoIoError, IntoStringError};
+use ql_packager::ExportFormat;
 use std::fmt::Write;
 use tokio::io::AsyncWriteExt;
 
@@ -62,6 +63,8 @@ impl Launcher {
             }
 
             Message::Account(msg) => return self.update_account(msg),
+            Message::Skin(msg) => return self.update_skins(msg),
+            Message::OfflineProfile(msg) => return self.update_offline_profile(msg),
             Message::ManageMods(msg) => return self.update_manage_mods(msg),
             Message::ExportMods(msg) => return self.update_export_mods(msg),
             Message::ManageJarMods(msg) => return self.update_manage_jar_mods(msg),
@@ -303,6 +306,7 @@ impl Launcher {
             Message::ExportInstanceOpen => {
                 self.state = State::ExportInstance(MenuExportInstance {
                     entries: None,
+                    format: ExportFormat::default(),
                     progress: None,
                 });
                 return Task::perform(
@@ -361,10 +365,16 @@ impl Launcher {
                     }
                 }
             }
+            Message::ExportInstanceChangeFormat(format) => {
+                if let State::ExportInstance(menu) = &mut self.state {
+                    menu.format = format;
+                }
+            }
             Message::ExportInstanceStart => {
                 if let State::ExportInstance(MenuExportInstance {
                     entries: Some(entries),
                     progress,
+                    format,
                 }) = &mut self.state
                 {
                     let (send, recv) = std::sync::mpsc::channel();
@@ -379,6 +389,7 @@ impl Launcher {
                         ql_packager::export_instance(
                             self.selected_instance.clone().unwrap(),
                             exceptions,
+                            *format,
                             Some(send),
                         ),
                         |n| Message::ExportInstanceFinished(n.strerr()),
@@ -387,7 +398,16 @@ impl Launcher {
             }
             Message::ExportInstanceFinished(res) => match res {
                 Ok(bytes) => {
-                    if let Some(path) = rfd::FileDialog::new().save_file() {
+                    let mut dialog = rfd::FileDialog::new();
+                    if let (State::ExportInstance(menu), Some(instance)) =
+                        (&self.state, &self.selected_instance)
+                    {
+                        let extension = menu.format.extension();
+                        dialog = dialog
+                            .add_filter(menu.format.to_string(), &[extension])
+                            .set_file_name(format!("{}.{extension}", instance.get_name()));
+                    }
+                    if let Some(path) = dialog.save_file() {
                         if let Err(err) = std::fs::write(&path, bytes).path(path) {
                             self.set_error(err);
                         } else {
@@ -153,10 +153,13 @@ impl Launcher {
             }
 
             State::LoginAlternate(menu) => menu.view(self.tick_timer),
+            State::AccountSkins(menu) => menu.view(self.tick_timer),
+            State::OfflineProfile(menu) => menu.view(),
             State::ExportInstance(menu) => menu.view(self.tick_timer),
 
             State::LoginMS(menu) => menu.view(),
             State::CurseforgeManualDownload(menu) => menu.view(),
+            State::ModpackOptions(menu) => menu.view(),
             State::License(menu) => menu.view(),
             State::ExportMods(menu) => menu.view(),
             State::InstallForge(menu) => menu.view(),
@@ -16,7 +16,8 @@ pub async fn launch(name: String, timeout: f32, cli: &Cli) -> bool {
             None,
             None,
             None,
-            Vec::new(),
+            ql_instances::LaunchOverrides::default(),
+            None,
         )
         .await,
     );